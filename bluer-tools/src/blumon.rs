@@ -250,7 +250,9 @@ async fn main() -> Result<()> {
 
     let opts = Opts::parse();
 
-    let session = bluer::Session::new().await?;
+    let session =
+        bluer::Session::new_with_options(bluer::SessionOptions { cache_properties: true, ..Default::default() })
+            .await?;
     let adapter = session.default_adapter().await?;
 
     let logger = match opts.advertisement_log {
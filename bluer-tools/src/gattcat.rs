@@ -1309,6 +1309,7 @@ impl ServeOpts {
             loop {
                 match events.next().await {
                     Some(SessionEvent::AdapterRemoved(name)) if name == adapter_name => break,
+                    Some(SessionEvent::DaemonStopped) => break,
                     None => break,
                     _ => (),
                 }
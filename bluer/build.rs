@@ -4,7 +4,7 @@ use std::{
     env,
     error::Error,
     fmt,
-    fs::File,
+    fs::{File, OpenOptions},
     io::Write,
     path::Path,
     str::FromStr,
@@ -183,10 +183,15 @@ fn convert_ids(src: &str, dest: &str, name: &str, doc_name: &str) -> Result<(),
 
     let input = File::open(src)?;
     let mut entries: Vec<CodeEntry> = serde_json::from_reader(input)?;
+    write_ids(&mut entries, dest, name, doc_name)
+}
+
+/// Writes an enum of the entries, whose names are made unique first.
+fn write_ids(entries: &mut [CodeEntry], dest: &str, name: &str, doc_name: &str) -> Result<(), Box<dyn Error>> {
     let mut out = File::create(Path::new(&env::var("OUT_DIR")?).join(dest))?;
 
     let mut seen_names: HashMap<String, usize> = HashMap::new();
-    for entry in &mut entries {
+    for entry in entries.iter_mut() {
         let s = seen_names.entry(entry.rust_id()).or_default();
         if *s > 0 {
             entry.name = format!("{} ({})", &entry.name, s);
@@ -201,7 +206,7 @@ fn convert_ids(src: &str, dest: &str, name: &str, doc_name: &str) -> Result<(),
     writeln!(out, "#[cfg_attr(feature = \"serde\", derive(serde::Serialize, serde::Deserialize))]")?;
     writeln!(out, "#[non_exhaustive]")?;
     writeln!(out, "pub enum {name} {{")?;
-    for service in &entries {
+    for service in entries.iter() {
         writeln!(out, "    /// {}", &service.name)?;
         writeln!(out, "    #[strum(serialize = \"{}\")]", &service.name.replace('"', "\\\""))?;
         writeln!(out, "    {},", service.rust_id())?;
@@ -211,7 +216,7 @@ fn convert_ids(src: &str, dest: &str, name: &str, doc_name: &str) -> Result<(),
     writeln!(out, "impl From<{name}> for u16 {{")?;
     writeln!(out, "    fn from(s: {name}) -> u16 {{")?;
     writeln!(out, "        match s {{")?;
-    for entry in &entries {
+    for entry in entries.iter() {
         writeln!(out, "            {}::{} => {},", name, entry.rust_id(), entry.code)?;
     }
     writeln!(out, "        }}")?;
@@ -224,7 +229,7 @@ fn convert_ids(src: &str, dest: &str, name: &str, doc_name: &str) -> Result<(),
     writeln!(out, "    fn try_from(code: u16) -> Result<Self, u16> {{")?;
     writeln!(out, "        #[allow(unreachable_patterns)]")?;
     writeln!(out, "        match code {{")?;
-    for entry in entries {
+    for entry in entries.iter() {
         writeln!(out, "            {} => Ok(Self::{}),", entry.code, entry.rust_id())?;
     }
     writeln!(out, "            _ => Err(code),")?;
@@ -236,6 +241,78 @@ fn convert_ids(src: &str, dest: &str, name: &str, doc_name: &str) -> Result<(),
     Ok(())
 }
 
+#[derive(Deserialize)]
+struct AppearanceCategoryEntry {
+    category: u16,
+    name: String,
+    #[serde(default)]
+    subcategory: Vec<AppearanceSubcategoryEntry>,
+}
+
+#[derive(Deserialize)]
+struct AppearanceSubcategoryEntry {
+    value: u16,
+    name: String,
+}
+
+fn convert_appearances(src: &str) -> Result<(), Box<dyn Error>> {
+    println!("cargo:rerun-if-changed={src}");
+
+    let input = File::open(src)?;
+    let categories: Vec<AppearanceCategoryEntry> = serde_json::from_reader(input)?;
+
+    let mut name_counts: HashMap<String, usize> = HashMap::new();
+    for sub in categories.iter().flat_map(|category| &category.subcategory) {
+        *name_counts.entry(sub.name.clone()).or_default() += 1;
+    }
+
+    let mut category_entries = Vec::new();
+    let mut appearance_entries = Vec::new();
+    let mut generic_entries = Vec::new();
+    for category in categories {
+        generic_entries.push(appearance_entries.len());
+        appearance_entries.push(CodeEntry {
+            code: category.category << 6,
+            name: match category.category {
+                0 => category.name.clone(),
+                _ => format!("Generic {}", &category.name),
+            },
+        });
+        for sub in category.subcategory {
+            let name = match name_counts[&sub.name] {
+                1 => sub.name,
+                _ => format!("{} ({})", &sub.name, &category.name),
+            };
+            appearance_entries.push(CodeEntry { code: (category.category << 6) | sub.value, name });
+        }
+        category_entries.push(CodeEntry { code: category.category, name: category.name });
+    }
+
+    write_ids(&mut category_entries, "appearance_category.inc", "AppearanceCategory", "appearance categories")?;
+    write_ids(&mut appearance_entries, "appearance.inc", "Appearance", "appearance values")?;
+
+    let mut out =
+        OpenOptions::new().append(true).open(Path::new(&env::var("OUT_DIR")?).join("appearance.inc"))?;
+    writeln!(out, "impl From<AppearanceCategory> for Appearance {{")?;
+    writeln!(out, "    /// The generic appearance value of the category.")?;
+    writeln!(out, "    fn from(category: AppearanceCategory) -> Self {{")?;
+    writeln!(out, "        match category {{")?;
+    for (category, generic) in category_entries.iter().zip(generic_entries) {
+        writeln!(
+            out,
+            "            AppearanceCategory::{} => Self::{},",
+            category.rust_id(),
+            appearance_entries[generic].rust_id()
+        )?;
+    }
+    writeln!(out, "        }}")?;
+    writeln!(out, "    }}")?;
+    writeln!(out, "}}")?;
+    writeln!(out)?;
+
+    Ok(())
+}
+
 fn build_ids() -> Result<(), Box<dyn Error>> {
     convert_uuids(
         "service_class_uuids.json",
@@ -276,6 +353,14 @@ fn build_ids() -> Result<(), Box<dyn Error>> {
     convert_ids("bluetooth-numbers-database/v1/company_ids.json", "company.inc", "Manufacturer", "manufacturers")
         .expect("companys");
 
+    // The appearance values are not part of the Bluetooth numbers database.
+    // appearance_values.json is a conversion to JSON of the Appearance Values section
+    // of the Bluetooth SIG Assigned Numbers document, published as
+    // assigned_numbers/core/appearance_values.yaml in the Bluetooth SIG public
+    // repository at https://bitbucket.org/bluetooth-SIG/public.
+    // The converted file is distributed under the license of this crate.
+    convert_appearances("appearance_values.json").expect("appearances");
+
     Ok(())
 }
 
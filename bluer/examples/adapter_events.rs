@@ -23,6 +23,13 @@ async fn main() -> bluer::Result<()> {
             SessionEvent::AdapterRemoved(name) => {
                 println!("Adapter removed: {}", name);
             }
+            SessionEvent::DaemonStopped => {
+                println!("Bluetooth daemon stopped");
+            }
+            SessionEvent::DaemonStarted => {
+                println!("Bluetooth daemon started");
+            }
+            _ => (),
         }
     }
 
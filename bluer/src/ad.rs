@@ -0,0 +1,695 @@
+//! Advertising and extended inquiry response data.
+//!
+//! Bluetooth LE advertising data (AD), scan response data and
+//! classic Bluetooth extended inquiry response (EIR) data
+//! consist of a sequence of AD structures, each made of a length byte,
+//! an AD type and type-specific data.
+//!
+//! This parses such a payload into typed [Structure]s using [AdvertisingData::parse]
+//! and serializes them back using [AdvertisingData::to_bytes].
+//! Structures that are not understood or not well-formed are preserved as
+//! [Structure::Other], so that serializing parsed data reproduces the original payload.
+//!
+//! Parsed data can be converted into an [Advertisement](crate::adv::Advertisement)
+//! for advertising it using BlueZ.
+
+#[cfg(feature = "bluetoothd")]
+use std::collections::HashMap;
+use std::{fmt, time::Duration};
+use uuid::Uuid;
+
+use crate::{class::ClassOfDevice, Address, UuidExt};
+
+/// AD type of flags.
+pub const TYPE_FLAGS: u8 = 0x01;
+/// AD type of incomplete list of 16-bit service class UUIDs.
+pub const TYPE_INCOMPLETE_UUID16: u8 = 0x02;
+/// AD type of complete list of 16-bit service class UUIDs.
+pub const TYPE_COMPLETE_UUID16: u8 = 0x03;
+/// AD type of incomplete list of 32-bit service class UUIDs.
+pub const TYPE_INCOMPLETE_UUID32: u8 = 0x04;
+/// AD type of complete list of 32-bit service class UUIDs.
+pub const TYPE_COMPLETE_UUID32: u8 = 0x05;
+/// AD type of incomplete list of 128-bit service class UUIDs.
+pub const TYPE_INCOMPLETE_UUID128: u8 = 0x06;
+/// AD type of complete list of 128-bit service class UUIDs.
+pub const TYPE_COMPLETE_UUID128: u8 = 0x07;
+/// AD type of shortened local name.
+pub const TYPE_SHORT_NAME: u8 = 0x08;
+/// AD type of complete local name.
+pub const TYPE_COMPLETE_NAME: u8 = 0x09;
+/// AD type of TX power level.
+pub const TYPE_TX_POWER: u8 = 0x0a;
+/// AD type of class of device.
+pub const TYPE_CLASS_OF_DEVICE: u8 = 0x0d;
+/// AD type of peripheral connection interval range.
+pub const TYPE_CONNECTION_INTERVAL_RANGE: u8 = 0x12;
+/// AD type of list of 16-bit service solicitation UUIDs.
+pub const TYPE_SOLICIT_UUID16: u8 = 0x14;
+/// AD type of list of 128-bit service solicitation UUIDs.
+pub const TYPE_SOLICIT_UUID128: u8 = 0x15;
+/// AD type of service data with 16-bit UUID.
+pub const TYPE_SERVICE_DATA16: u8 = 0x16;
+/// AD type of public target address.
+pub const TYPE_PUBLIC_TARGET_ADDRESS: u8 = 0x17;
+/// AD type of random target address.
+pub const TYPE_RANDOM_TARGET_ADDRESS: u8 = 0x18;
+/// AD type of appearance.
+pub const TYPE_APPEARANCE: u8 = 0x19;
+/// AD type of advertising interval.
+pub const TYPE_ADVERTISING_INTERVAL: u8 = 0x1a;
+/// AD type of LE Bluetooth device address.
+pub const TYPE_LE_ADDRESS: u8 = 0x1b;
+/// AD type of LE role.
+pub const TYPE_LE_ROLE: u8 = 0x1c;
+/// AD type of list of 32-bit service solicitation UUIDs.
+pub const TYPE_SOLICIT_UUID32: u8 = 0x1f;
+/// AD type of service data with 32-bit UUID.
+pub const TYPE_SERVICE_DATA32: u8 = 0x20;
+/// AD type of service data with 128-bit UUID.
+pub const TYPE_SERVICE_DATA128: u8 = 0x21;
+/// AD type of URI.
+pub const TYPE_URI: u8 = 0x24;
+/// AD type of LE supported features.
+pub const TYPE_LE_SUPPORTED_FEATURES: u8 = 0x27;
+/// AD type of long advertising interval.
+pub const TYPE_LONG_ADVERTISING_INTERVAL: u8 = 0x2f;
+/// AD type of broadcast name.
+pub const TYPE_BROADCAST_NAME: u8 = 0x30;
+/// AD type of manufacturer specific data.
+pub const TYPE_MANUFACTURER_DATA: u8 = 0xff;
+
+/// Unit of advertising intervals.
+const INTERVAL_UNIT: Duration = Duration::from_micros(625);
+
+/// Unit of connection intervals.
+const CONNECTION_INTERVAL_UNIT: Duration = Duration::from_micros(1250);
+
+/// URI scheme name string codes of schemes commonly used in advertisements.
+const URI_SCHEMES: &[(char, &str)] = &[('\u{16}', "http:"), ('\u{17}', "https:")];
+
+/// Code for URIs with a scheme not having a URI scheme name string code.
+const URI_SCHEME_EMPTY: char = '\u{01}';
+
+/// Invalid advertising data.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum InvalidData {
+    /// The AD structure at the specified offset extends beyond the end of the data.
+    Truncated(usize),
+    /// The AD structure with the specified AD type is too long to be serialized.
+    TooLong(u8),
+}
+
+impl fmt::Display for InvalidData {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Truncated(offset) => write!(f, "truncated AD structure at offset {offset}"),
+            Self::TooLong(ad_type) => write!(f, "AD structure of type {ad_type:#04x} is too long"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidData {}
+
+/// Advertising flags.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Flags {
+    /// LE limited discoverable mode.
+    pub le_limited_discoverable: bool,
+    /// LE general discoverable mode.
+    pub le_general_discoverable: bool,
+    /// BR/EDR not supported.
+    pub br_edr_not_supported: bool,
+    /// Simultaneous LE and BR/EDR to same device capable (controller).
+    pub le_br_edr_controller: bool,
+    /// Previously used bit 4 of the flags.
+    pub le_br_edr_host: bool,
+}
+
+impl From<u8> for Flags {
+    fn from(value: u8) -> Self {
+        Self {
+            le_limited_discoverable: value & 0x01 != 0,
+            le_general_discoverable: value & 0x02 != 0,
+            br_edr_not_supported: value & 0x04 != 0,
+            le_br_edr_controller: value & 0x08 != 0,
+            le_br_edr_host: value & 0x10 != 0,
+        }
+    }
+}
+
+impl From<Flags> for u8 {
+    fn from(flags: Flags) -> Self {
+        (flags.le_limited_discoverable as u8)
+            | (flags.le_general_discoverable as u8) << 1
+            | (flags.br_edr_not_supported as u8) << 2
+            | (flags.le_br_edr_controller as u8) << 3
+            | (flags.le_br_edr_host as u8) << 4
+    }
+}
+
+/// A typed AD structure.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Structure {
+    /// Flags.
+    Flags(Flags),
+    /// List of 16-bit service class UUIDs.
+    ServiceUuids16 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// 16-bit UUIDs.
+        uuids: Vec<u16>,
+    },
+    /// List of 32-bit service class UUIDs.
+    ServiceUuids32 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// 32-bit UUIDs.
+        uuids: Vec<u32>,
+    },
+    /// List of 128-bit service class UUIDs.
+    ServiceUuids128 {
+        /// Whether the list is complete.
+        complete: bool,
+        /// UUIDs.
+        uuids: Vec<Uuid>,
+    },
+    /// Local name.
+    LocalName {
+        /// Whether the name is complete or shortened.
+        complete: bool,
+        /// Name.
+        name: String,
+    },
+    /// TX power level in dBm.
+    TxPowerLevel(i8),
+    /// Class of device.
+    ClassOfDevice(ClassOfDevice),
+    /// Preferred connection interval range of the peripheral
+    /// in units of 1.25 ms.
+    ///
+    /// `0xffff` indicates no specific minimum or maximum.
+    ConnectionIntervalRange {
+        /// Minimum connection interval.
+        min: u16,
+        /// Maximum connection interval.
+        max: u16,
+    },
+    /// List of 16-bit service solicitation UUIDs.
+    SolicitUuids16(Vec<u16>),
+    /// List of 32-bit service solicitation UUIDs.
+    SolicitUuids32(Vec<u32>),
+    /// List of 128-bit service solicitation UUIDs.
+    SolicitUuids128(Vec<Uuid>),
+    /// Service data with 16-bit UUID.
+    ServiceData16 {
+        /// 16-bit UUID.
+        uuid: u16,
+        /// Service data.
+        data: Vec<u8>,
+    },
+    /// Service data with 32-bit UUID.
+    ServiceData32 {
+        /// 32-bit UUID.
+        uuid: u32,
+        /// Service data.
+        data: Vec<u8>,
+    },
+    /// Service data with 128-bit UUID.
+    ServiceData128 {
+        /// UUID.
+        uuid: Uuid,
+        /// Service data.
+        data: Vec<u8>,
+    },
+    /// Public addresses of devices the advertisement is directed to.
+    PublicTargetAddresses(Vec<Address>),
+    /// Random addresses of devices the advertisement is directed to.
+    RandomTargetAddresses(Vec<Address>),
+    /// Appearance.
+    ///
+    /// When the `id` feature is enabled, it can be converted into an `id::Appearance`.
+    Appearance(u16),
+    /// Advertising interval in units of 0.625 ms.
+    ///
+    /// This is also the interval of periodic advertising when it is advertised.
+    AdvertisingInterval(u16),
+    /// Long advertising interval in units of 0.625 ms.
+    ///
+    /// It is encoded using three bytes if possible and four bytes otherwise.
+    LongAdvertisingInterval(u32),
+    /// LE Bluetooth device address.
+    LeAddress {
+        /// Address.
+        address: Address,
+        /// Whether the address is a random address.
+        random: bool,
+    },
+    /// LE role.
+    ///
+    /// 0 = only peripheral, 1 = only central,
+    /// 2 = peripheral and central with peripheral preferred,
+    /// 3 = peripheral and central with central preferred.
+    LeRole(u8),
+    /// URI.
+    Uri(String),
+    /// LE supported features as bit mask in little endian byte order.
+    LeSupportedFeatures(Vec<u8>),
+    /// Broadcast name of an Auracast broadcast source.
+    BroadcastName(String),
+    /// Manufacturer specific data.
+    ManufacturerData {
+        /// Company identifier.
+        company: u16,
+        /// Manufacturer specific data.
+        data: Vec<u8>,
+    },
+    /// AD structure of other type or with data that could not be decoded.
+    Other {
+        /// AD type.
+        ad_type: u8,
+        /// Data.
+        data: Vec<u8>,
+    },
+}
+
+impl Structure {
+    /// Decodes an AD structure from its AD type and data.
+    ///
+    /// If the data cannot be decoded losslessly, [Structure::Other] is returned.
+    pub fn decode(ad_type: u8, data: &[u8]) -> Self {
+        match Self::decode_typed(ad_type, data) {
+            Some(s) if s.ad_type() == ad_type && s.data() == data => s,
+            _ => Self::Other { ad_type, data: data.to_vec() },
+        }
+    }
+
+    fn decode_typed(ad_type: u8, data: &[u8]) -> Option<Self> {
+        Some(match ad_type {
+            TYPE_FLAGS => match *data {
+                [flags] => Self::Flags(flags.into()),
+                _ => return None,
+            },
+            TYPE_INCOMPLETE_UUID16 | TYPE_COMPLETE_UUID16 => Self::ServiceUuids16 {
+                complete: ad_type == TYPE_COMPLETE_UUID16,
+                uuids: chunks(data, 2)?.map(le_u16).collect(),
+            },
+            TYPE_INCOMPLETE_UUID32 | TYPE_COMPLETE_UUID32 => Self::ServiceUuids32 {
+                complete: ad_type == TYPE_COMPLETE_UUID32,
+                uuids: chunks(data, 4)?.map(le_u32).collect(),
+            },
+            TYPE_INCOMPLETE_UUID128 | TYPE_COMPLETE_UUID128 => Self::ServiceUuids128 {
+                complete: ad_type == TYPE_COMPLETE_UUID128,
+                uuids: chunks(data, 16)?.map(le_uuid).collect(),
+            },
+            TYPE_SHORT_NAME | TYPE_COMPLETE_NAME => Self::LocalName {
+                complete: ad_type == TYPE_COMPLETE_NAME,
+                name: String::from_utf8(data.to_vec()).ok()?,
+            },
+            TYPE_TX_POWER => match *data {
+                [power] => Self::TxPowerLevel(power as i8),
+                _ => return None,
+            },
+            TYPE_CLASS_OF_DEVICE => match *data {
+                [a, b, c] => Self::ClassOfDevice(u32::from_le_bytes([a, b, c, 0]).into()),
+                _ => return None,
+            },
+            TYPE_CONNECTION_INTERVAL_RANGE => match *data {
+                [a, b, c, d] => Self::ConnectionIntervalRange {
+                    min: u16::from_le_bytes([a, b]),
+                    max: u16::from_le_bytes([c, d]),
+                },
+                _ => return None,
+            },
+            TYPE_SOLICIT_UUID16 => Self::SolicitUuids16(chunks(data, 2)?.map(le_u16).collect()),
+            TYPE_SOLICIT_UUID32 => Self::SolicitUuids32(chunks(data, 4)?.map(le_u32).collect()),
+            TYPE_SOLICIT_UUID128 => Self::SolicitUuids128(chunks(data, 16)?.map(le_uuid).collect()),
+            TYPE_SERVICE_DATA16 if data.len() >= 2 => {
+                Self::ServiceData16 { uuid: le_u16(&data[..2]), data: data[2..].to_vec() }
+            }
+            TYPE_SERVICE_DATA32 if data.len() >= 4 => {
+                Self::ServiceData32 { uuid: le_u32(&data[..4]), data: data[4..].to_vec() }
+            }
+            TYPE_SERVICE_DATA128 if data.len() >= 16 => {
+                Self::ServiceData128 { uuid: le_uuid(&data[..16]), data: data[16..].to_vec() }
+            }
+            TYPE_PUBLIC_TARGET_ADDRESS => Self::PublicTargetAddresses(chunks(data, 6)?.map(le_address).collect()),
+            TYPE_RANDOM_TARGET_ADDRESS => Self::RandomTargetAddresses(chunks(data, 6)?.map(le_address).collect()),
+            TYPE_APPEARANCE if data.len() == 2 => Self::Appearance(le_u16(data)),
+            TYPE_ADVERTISING_INTERVAL if data.len() == 2 => Self::AdvertisingInterval(le_u16(data)),
+            TYPE_LONG_ADVERTISING_INTERVAL => match *data {
+                [a, b, c] => Self::LongAdvertisingInterval(u32::from_le_bytes([a, b, c, 0])),
+                [a, b, c, d] => Self::LongAdvertisingInterval(u32::from_le_bytes([a, b, c, d])),
+                _ => return None,
+            },
+            TYPE_LE_ADDRESS => match *data {
+                [a, b, c, d, e, f, random] if random <= 1 => {
+                    Self::LeAddress { address: le_address(&[a, b, c, d, e, f]), random: random == 1 }
+                }
+                _ => return None,
+            },
+            TYPE_LE_ROLE => match *data {
+                [role] => Self::LeRole(role),
+                _ => return None,
+            },
+            TYPE_URI => {
+                let uri = std::str::from_utf8(data).ok()?;
+                let mut chars = uri.chars();
+                let scheme = chars.next()?;
+                if scheme == URI_SCHEME_EMPTY {
+                    Self::Uri(chars.as_str().to_string())
+                } else {
+                    let (_, prefix) = URI_SCHEMES.iter().find(|(code, _)| *code == scheme)?;
+                    Self::Uri(format!("{prefix}{}", chars.as_str()))
+                }
+            }
+            TYPE_LE_SUPPORTED_FEATURES => Self::LeSupportedFeatures(data.to_vec()),
+            TYPE_BROADCAST_NAME => Self::BroadcastName(String::from_utf8(data.to_vec()).ok()?),
+            TYPE_MANUFACTURER_DATA if data.len() >= 2 => {
+                Self::ManufacturerData { company: le_u16(&data[..2]), data: data[2..].to_vec() }
+            }
+            _ => return None,
+        })
+    }
+
+    /// AD type.
+    pub fn ad_type(&self) -> u8 {
+        match self {
+            Self::Flags(_) => TYPE_FLAGS,
+            Self::ServiceUuids16 { complete: false, .. } => TYPE_INCOMPLETE_UUID16,
+            Self::ServiceUuids16 { complete: true, .. } => TYPE_COMPLETE_UUID16,
+            Self::ServiceUuids32 { complete: false, .. } => TYPE_INCOMPLETE_UUID32,
+            Self::ServiceUuids32 { complete: true, .. } => TYPE_COMPLETE_UUID32,
+            Self::ServiceUuids128 { complete: false, .. } => TYPE_INCOMPLETE_UUID128,
+            Self::ServiceUuids128 { complete: true, .. } => TYPE_COMPLETE_UUID128,
+            Self::LocalName { complete: false, .. } => TYPE_SHORT_NAME,
+            Self::LocalName { complete: true, .. } => TYPE_COMPLETE_NAME,
+            Self::TxPowerLevel(_) => TYPE_TX_POWER,
+            Self::ClassOfDevice(_) => TYPE_CLASS_OF_DEVICE,
+            Self::ConnectionIntervalRange { .. } => TYPE_CONNECTION_INTERVAL_RANGE,
+            Self::SolicitUuids16(_) => TYPE_SOLICIT_UUID16,
+            Self::SolicitUuids32(_) => TYPE_SOLICIT_UUID32,
+            Self::SolicitUuids128(_) => TYPE_SOLICIT_UUID128,
+            Self::ServiceData16 { .. } => TYPE_SERVICE_DATA16,
+            Self::ServiceData32 { .. } => TYPE_SERVICE_DATA32,
+            Self::ServiceData128 { .. } => TYPE_SERVICE_DATA128,
+            Self::PublicTargetAddresses(_) => TYPE_PUBLIC_TARGET_ADDRESS,
+            Self::RandomTargetAddresses(_) => TYPE_RANDOM_TARGET_ADDRESS,
+            Self::Appearance(_) => TYPE_APPEARANCE,
+            Self::AdvertisingInterval(_) => TYPE_ADVERTISING_INTERVAL,
+            Self::LongAdvertisingInterval(_) => TYPE_LONG_ADVERTISING_INTERVAL,
+            Self::LeAddress { .. } => TYPE_LE_ADDRESS,
+            Self::LeRole(_) => TYPE_LE_ROLE,
+            Self::Uri(_) => TYPE_URI,
+            Self::LeSupportedFeatures(_) => TYPE_LE_SUPPORTED_FEATURES,
+            Self::BroadcastName(_) => TYPE_BROADCAST_NAME,
+            Self::ManufacturerData { .. } => TYPE_MANUFACTURER_DATA,
+            Self::Other { ad_type, .. } => *ad_type,
+        }
+    }
+
+    /// Encoded data of the AD structure, excluding length and AD type.
+    pub fn data(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        match self {
+            Self::Flags(flags) => buf.push((*flags).into()),
+            Self::ServiceUuids16 { uuids, .. } | Self::SolicitUuids16(uuids) => {
+                for uuid in uuids {
+                    buf.extend_from_slice(&uuid.to_le_bytes());
+                }
+            }
+            Self::ServiceUuids32 { uuids, .. } | Self::SolicitUuids32(uuids) => {
+                for uuid in uuids {
+                    buf.extend_from_slice(&uuid.to_le_bytes());
+                }
+            }
+            Self::ServiceUuids128 { uuids, .. } | Self::SolicitUuids128(uuids) => {
+                for uuid in uuids {
+                    buf.extend_from_slice(&uuid.as_u128().to_le_bytes());
+                }
+            }
+            Self::LocalName { name, .. } | Self::BroadcastName(name) => buf.extend_from_slice(name.as_bytes()),
+            Self::TxPowerLevel(power) => buf.push(*power as u8),
+            Self::ClassOfDevice(class) => buf.extend_from_slice(&u32::from(class.clone()).to_le_bytes()[..3]),
+            Self::ConnectionIntervalRange { min, max } => {
+                buf.extend_from_slice(&min.to_le_bytes());
+                buf.extend_from_slice(&max.to_le_bytes());
+            }
+            Self::ServiceData16 { uuid, data } => {
+                buf.extend_from_slice(&uuid.to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::ServiceData32 { uuid, data } => {
+                buf.extend_from_slice(&uuid.to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::ServiceData128 { uuid, data } => {
+                buf.extend_from_slice(&uuid.as_u128().to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+            Self::PublicTargetAddresses(addresses) | Self::RandomTargetAddresses(addresses) => {
+                for address in addresses {
+                    buf.extend(address.0.iter().rev());
+                }
+            }
+            Self::Appearance(v) | Self::AdvertisingInterval(v) => buf.extend_from_slice(&v.to_le_bytes()),
+            Self::LongAdvertisingInterval(v) => {
+                let bytes = v.to_le_bytes();
+                match *v {
+                    0..=0xff_ffff => buf.extend_from_slice(&bytes[..3]),
+                    _ => buf.extend_from_slice(&bytes),
+                }
+            }
+            Self::LeAddress { address, random } => {
+                buf.extend(address.0.iter().rev());
+                buf.push(*random as u8);
+            }
+            Self::LeRole(role) => buf.push(*role),
+            Self::Uri(uri) => match URI_SCHEMES.iter().find(|(_, prefix)| uri.starts_with(prefix)) {
+                Some((code, prefix)) => {
+                    buf.extend_from_slice(code.encode_utf8(&mut [0; 4]).as_bytes());
+                    buf.extend_from_slice(&uri.as_bytes()[prefix.len()..]);
+                }
+                None => {
+                    buf.extend_from_slice(URI_SCHEME_EMPTY.encode_utf8(&mut [0; 4]).as_bytes());
+                    buf.extend_from_slice(uri.as_bytes());
+                }
+            },
+            Self::LeSupportedFeatures(data) | Self::Other { data, .. } => buf.extend_from_slice(data),
+            Self::ManufacturerData { company, data } => {
+                buf.extend_from_slice(&company.to_le_bytes());
+                buf.extend_from_slice(data);
+            }
+        }
+        buf
+    }
+
+    /// Advertising interval, if this is an advertising interval structure.
+    pub fn advertising_interval(&self) -> Option<Duration> {
+        match self {
+            Self::AdvertisingInterval(v) => Some(INTERVAL_UNIT * *v as u32),
+            Self::LongAdvertisingInterval(v) => Some(INTERVAL_UNIT * *v),
+            _ => None,
+        }
+    }
+
+    /// Minimum and maximum connection interval, if this is a connection interval range
+    /// structure that specifies them.
+    pub fn connection_interval_range(&self) -> Option<(Option<Duration>, Option<Duration>)> {
+        let interval = |v: u16| match v {
+            0xffff => None,
+            v => Some(CONNECTION_INTERVAL_UNIT * v as u32),
+        };
+        match self {
+            Self::ConnectionIntervalRange { min, max } => Some((interval(*min), interval(*max))),
+            _ => None,
+        }
+    }
+}
+
+fn chunks(data: &[u8], size: usize) -> Option<std::slice::Chunks<'_, u8>> {
+    if data.len() % size == 0 {
+        Some(data.chunks(size))
+    } else {
+        None
+    }
+}
+
+fn le_u16(data: &[u8]) -> u16 {
+    u16::from_le_bytes([data[0], data[1]])
+}
+
+fn le_u32(data: &[u8]) -> u32 {
+    u32::from_le_bytes([data[0], data[1], data[2], data[3]])
+}
+
+fn le_uuid(data: &[u8]) -> Uuid {
+    let mut bytes = [0; 16];
+    bytes.copy_from_slice(data);
+    Uuid::from_u128(u128::from_le_bytes(bytes))
+}
+
+fn le_address(data: &[u8]) -> Address {
+    let mut addr = [0; 6];
+    addr.copy_from_slice(data);
+    addr.reverse();
+    Address(addr)
+}
+
+/// Advertising or extended inquiry response data consisting of AD structures.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdvertisingData(pub Vec<Structure>);
+
+impl AdvertisingData {
+    /// Parses advertising or extended inquiry response data.
+    ///
+    /// Parsing stops at the first AD structure of zero length,
+    /// which marks the start of padding in extended inquiry response data.
+    pub fn parse(mut data: &[u8]) -> Result<Self, InvalidData> {
+        let total = data.len();
+        let mut structures = Vec::new();
+        while let Some((&len, rest)) = data.split_first() {
+            let len = len as usize;
+            if len == 0 {
+                break;
+            }
+            if rest.len() < len {
+                return Err(InvalidData::Truncated(total - data.len()));
+            }
+            structures.push(Structure::decode(rest[0], &rest[1..len]));
+            data = &rest[len..];
+        }
+        Ok(Self(structures))
+    }
+
+    /// Serializes the AD structures.
+    pub fn to_bytes(&self) -> Result<Vec<u8>, InvalidData> {
+        let mut buf = Vec::new();
+        for structure in &self.0 {
+            let data = structure.data();
+            let len = u8::try_from(data.len() + 1).map_err(|_| InvalidData::TooLong(structure.ad_type()))?;
+            buf.push(len);
+            buf.push(structure.ad_type());
+            buf.extend(data);
+        }
+        Ok(buf)
+    }
+
+    /// Service class UUIDs from all lists of service class UUIDs.
+    pub fn service_uuids(&self) -> Vec<Uuid> {
+        let mut uuids = Vec::new();
+        for structure in &self.0 {
+            match structure {
+                Structure::ServiceUuids16 { uuids: u, .. } => uuids.extend(u.iter().map(|u| Uuid::from_u16(*u))),
+                Structure::ServiceUuids32 { uuids: u, .. } => uuids.extend(u.iter().map(|u| Uuid::from_u32(*u))),
+                Structure::ServiceUuids128 { uuids: u, .. } => uuids.extend(u.iter().cloned()),
+                _ => (),
+            }
+        }
+        uuids
+    }
+
+    /// Local name, preferring the complete over the shortened name.
+    pub fn local_name(&self) -> Option<&str> {
+        let mut local_name = None;
+        for structure in &self.0 {
+            if let Structure::LocalName { complete, name } = structure {
+                if *complete {
+                    return Some(name);
+                }
+                local_name = Some(name.as_str());
+            }
+        }
+        local_name
+    }
+
+    /// Builds advertising data from AD types and data, as provided by
+    /// [Device::advertising_data](crate::Device::advertising_data).
+    ///
+    /// The structures are ordered by AD type.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub fn from_map(map: &HashMap<u8, Vec<u8>>) -> Self {
+        let mut types: Vec<_> = map.keys().copied().collect();
+        types.sort_unstable();
+        Self(types.into_iter().map(|ad_type| Structure::decode(ad_type, &map[&ad_type])).collect())
+    }
+}
+
+impl FromIterator<Structure> for AdvertisingData {
+    fn from_iter<T: IntoIterator<Item = Structure>>(iter: T) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
+#[cfg(feature = "bluetoothd")]
+impl From<AdvertisingData> for crate::adv::Advertisement {
+    /// Converts advertising data into an advertisement.
+    ///
+    /// Service UUIDs, solicitation UUIDs, service data, manufacturer data,
+    /// local name, appearance and TX power level are mapped onto the corresponding
+    /// fields of the advertisement, while flags are mapped onto the
+    /// [discoverable](crate::adv::Advertisement::discoverable) field.
+    /// All other structures are included as raw
+    /// [advertising data](crate::adv::Advertisement::advertising_data).
+    fn from(data: AdvertisingData) -> Self {
+        use crate::adv::Feature;
+
+        let mut adv = Self::default();
+        for structure in data.0 {
+            match structure {
+                Structure::Flags(flags) => {
+                    if flags.le_general_discoverable || flags.le_limited_discoverable {
+                        adv.discoverable = Some(true);
+                    }
+                }
+                Structure::ServiceUuids16 { uuids, .. } => {
+                    adv.service_uuids.extend(uuids.into_iter().map(Uuid::from_u16))
+                }
+                Structure::ServiceUuids32 { uuids, .. } => {
+                    adv.service_uuids.extend(uuids.into_iter().map(Uuid::from_u32))
+                }
+                Structure::ServiceUuids128 { uuids, .. } => adv.service_uuids.extend(uuids),
+                Structure::SolicitUuids16(uuids) => {
+                    adv.solicit_uuids.extend(uuids.into_iter().map(Uuid::from_u16))
+                }
+                Structure::SolicitUuids32(uuids) => {
+                    adv.solicit_uuids.extend(uuids.into_iter().map(Uuid::from_u32))
+                }
+                Structure::SolicitUuids128(uuids) => adv.solicit_uuids.extend(uuids),
+                Structure::ServiceData16 { uuid, data } => {
+                    adv.service_data.insert(Uuid::from_u16(uuid), data);
+                }
+                Structure::ServiceData32 { uuid, data } => {
+                    adv.service_data.insert(Uuid::from_u32(uuid), data);
+                }
+                Structure::ServiceData128 { uuid, data } => {
+                    adv.service_data.insert(uuid, data);
+                }
+                Structure::ManufacturerData { company, data } => {
+                    adv.manufacturer_data.insert(company, data);
+                }
+                Structure::LocalName { complete, name } => {
+                    if complete || adv.local_name.is_none() {
+                        adv.local_name = Some(name);
+                    }
+                }
+                Structure::Appearance(appearance) => adv.appearance = Some(appearance),
+                Structure::TxPowerLevel(power) => {
+                    adv.tx_power = Some(power.into());
+                    adv.system_includes.insert(Feature::TxPower);
+                }
+                other => {
+                    adv.advertising_data.insert(other.ad_type(), other.data());
+                }
+            }
+        }
+        adv
+    }
+}
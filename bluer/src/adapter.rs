@@ -23,15 +23,23 @@ use uuid::Uuid;
 use crate::{
     adv,
     adv::{Advertisement, AdvertisementHandle, Capabilities, Feature, PlatformFeature, SecondaryChannel},
-    all_dbus_objects, device,
+    all_dbus_objects, battery,
+    class::ClassOfDevice,
+    device,
     device::Device,
+    device_set::{self, DeviceSet},
     gatt,
     monitor::MonitorManager,
-    Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
-    SingleSessionToken, SERVICE_NAME, TIMEOUT,
+    network, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
+    SingleSessionToken,
 };
 
+#[cfg(feature = "media")]
+use crate::media;
+
 pub(crate) const INTERFACE: &str = "org.bluez.Adapter1";
+pub(crate) const ADMIN_POLICY_SET_INTERFACE: &str = "org.bluez.AdminPolicySet1";
+pub(crate) const ADMIN_POLICY_STATUS_INTERFACE: &str = "org.bluez.AdminPolicyStatus1";
 pub(crate) const PATH: &str = "/org/bluez";
 pub(crate) const PREFIX: &str = "/org/bluez/";
 
@@ -65,7 +73,7 @@ impl Adapter {
     }
 
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
-        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
     }
 
     pub(crate) fn dbus_path(adapter_name: &str) -> Result<Path<'static>> {
@@ -100,7 +108,7 @@ impl Adapter {
     /// Bluetooth addresses of discovered Bluetooth devices.
     pub async fn device_addresses(&self) -> Result<Vec<Address>> {
         let mut addrs = Vec::new();
-        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
             match Device::parse_dbus_path(&path) {
                 Some((adapter, addr)) if adapter == *self.name && interfaces.contains_key(device::INTERFACE) => {
                     addrs.push(addr)
@@ -111,6 +119,39 @@ impl Adapter {
         Ok(addrs)
     }
 
+    /// The decoded Bluetooth class of device.
+    ///
+    /// This is a typed representation of [Adapter::class].
+    pub async fn class_of_device(&self) -> Result<ClassOfDevice> {
+        Ok(self.class().await?.into())
+    }
+
+    /// Identifiers of known coordinated sets of Bluetooth devices.
+    pub async fn device_set_ids(&self) -> Result<Vec<String>> {
+        let mut ids = Vec::new();
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
+            match DeviceSet::parse_dbus_path(&path) {
+                Some((adapter, id))
+                    if adapter == *self.name && interfaces.contains_key(device_set::INTERFACE) =>
+                {
+                    ids.push(id.to_string())
+                }
+                _ => (),
+            }
+        }
+        Ok(ids)
+    }
+
+    /// Get interface to coordinated set of Bluetooth devices with specified identifier.
+    pub fn device_set(&self, id: &str) -> Result<DeviceSet> {
+        DeviceSet::new(self.inner.clone(), self.name.clone(), id)
+    }
+
+    /// Interfaces to known coordinated sets of Bluetooth devices.
+    pub async fn device_sets(&self) -> Result<Vec<DeviceSet>> {
+        self.device_set_ids().await?.iter().map(|id| self.device_set(id)).collect()
+    }
+
     /// Starts monitoring of advertisements.
     ///
     /// Once a monitoring job is activated by BlueZ, the client can expect to get
@@ -242,6 +283,8 @@ impl Adapter {
     async fn discovery_session(&self) -> Result<SingleSessionToken> {
         let dbus_path = self.dbus_path.clone();
         let connection = self.inner.connection.clone();
+        let service_name = self.inner.service_name.clone();
+        let timeout = self.inner.timeout;
         let token = self
             .inner
             .single_session(
@@ -254,18 +297,18 @@ impl Adapter {
                     Ok(())
                 },
                 async move {
-                    log::trace!("{}: {}.StopDiscovery ()", &dbus_path, SERVICE_NAME);
-                    let proxy = Proxy::new(SERVICE_NAME, &dbus_path, TIMEOUT, &*connection);
+                    log::trace!("{}: {}.StopDiscovery ()", &dbus_path, &service_name);
+                    let proxy = Proxy::new(service_name.clone(), &dbus_path, timeout, &*connection);
                     let result: std::result::Result<(), dbus::Error> =
                         proxy.method_call(INTERFACE, "StopDiscovery", ()).await;
-                    log::trace!("{}: {}.StopDiscovery () -> {:?}", &dbus_path, SERVICE_NAME, &result);
+                    log::trace!("{}: {}.StopDiscovery () -> {:?}", &dbus_path, &service_name, &result);
                 },
             )
             .await?;
         Ok(token)
     }
 
-    dbus_interface!();
+    dbus_interface!(cached);
     dbus_default_interface!(INTERFACE);
 
     /// Streams adapter property and device changes.
@@ -273,24 +316,44 @@ impl Adapter {
     /// The stream ends when the adapter is removed.
     pub async fn events(&self) -> Result<impl Stream<Item = AdapterEvent>> {
         let name = self.name.clone();
+        let dbus_path = self.dbus_path.clone();
         let events = self.inner.events(self.dbus_path.clone(), true).await?;
         let stream = events.flat_map(move |event| match event {
-            Event::ObjectAdded { object, .. } => match Device::parse_dbus_path(&object) {
-                Some((adapter, address)) if adapter == *name => {
+            Event::ObjectAdded { object, interfaces } => match Device::parse_dbus_path(&object) {
+                Some((adapter, address)) if adapter == *name && interfaces.contains(device::INTERFACE) => {
                     stream::once(async move { AdapterEvent::DeviceAdded(address) }).boxed()
                 }
                 _ => stream::empty().boxed(),
             },
-            Event::ObjectRemoved { object, .. } => match Device::parse_dbus_path(&object) {
-                Some((adapter, address)) if adapter == *name => {
+            Event::ObjectRemoved { object, interfaces } => match Device::parse_dbus_path(&object) {
+                Some((adapter, address)) if adapter == *name && interfaces.contains(device::INTERFACE) => {
                     stream::once(async move { AdapterEvent::DeviceRemoved(address) }).boxed()
                 }
+                _ if object == dbus_path => stream::iter(
+                    interfaces
+                        .iter()
+                        .flat_map(|interface| AdapterPropertyKind::of_interface(interface))
+                        .map(AdapterEvent::PropertyInvalidated)
+                        .collect::<Vec<_>>(),
+                )
+                .boxed(),
                 _ => stream::empty().boxed(),
             },
-            Event::PropertiesChanged { changed, .. } => stream::iter(
-                AdapterProperty::from_prop_map(changed).into_iter().map(AdapterEvent::PropertyChanged),
-            )
-            .boxed(),
+            Event::PropertiesChanged { interface, changed, invalidated, .. } => {
+                let invalidated = invalidated
+                    .iter()
+                    .filter_map(|name| AdapterPropertyKind::from_dbus_name(&interface, name))
+                    .map(AdapterEvent::PropertyInvalidated);
+                stream::iter(
+                    AdapterProperty::from_prop_map(changed)
+                        .into_iter()
+                        .map(AdapterEvent::PropertyChanged)
+                        .chain(invalidated)
+                        .collect::<Vec<_>>(),
+                )
+                .boxed()
+            }
+            Event::ServiceOwnerChanged { .. } => stream::empty().boxed(),
         });
         Ok(stream)
     }
@@ -344,6 +407,58 @@ impl Adapter {
         gatt_profile.register(self.inner.clone(), self.name.clone()).await
     }
 
+    /// Registers a battery provider.
+    ///
+    /// The provider publishes the battery levels of remote devices to BlueZ.
+    /// Use the [BatteryControl](battery::BatteryControl) of each battery to update its level.
+    ///
+    /// Drop the returned [BatteryProviderHandle](battery::BatteryProviderHandle) to unregister the provider.
+    pub async fn register_battery_provider(
+        &self, provider: battery::BatteryProvider,
+    ) -> Result<battery::BatteryProviderHandle> {
+        provider.register(self.inner.clone(), self.name.clone()).await
+    }
+
+    /// Registers a network server providing the specified Personal Area Networking role.
+    ///
+    /// Incoming connections are attached to the specified network bridge,
+    /// which must already exist.
+    ///
+    /// Drop the returned [NetworkServerHandle](network::NetworkServerHandle) to unregister the server.
+    pub async fn register_network_server(
+        &self, role: network::Role, bridge: &str,
+    ) -> Result<network::NetworkServerHandle> {
+        network::Network::register_server(self.inner.clone(), self.name.clone(), role, bridge.to_string()).await
+    }
+
+    /// Registers a local media endpoint.
+    ///
+    /// The endpoint announces support for a codec of an audio profile and
+    /// is called by BlueZ to negotiate the codec configuration when a
+    /// remote device connects using that profile.
+    ///
+    /// Drop the returned [MediaEndpointHandle](media::MediaEndpointHandle) to unregister the endpoint.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn register_media_endpoint(
+        &self, endpoint: media::MediaEndpoint,
+    ) -> Result<media::MediaEndpointHandle> {
+        endpoint.register(self.inner.clone(), self.name.clone()).await
+    }
+
+    /// Registers a local media player.
+    ///
+    /// The player is exposed to remote devices using the Audio/Video Remote
+    /// Control Profile (AVRCP), allowing them to display track information
+    /// and control playback.
+    ///
+    /// Drop the returned [LocalPlayerHandle](media::LocalPlayerHandle) to unregister the player.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn register_media_player(&self, player: media::LocalPlayer) -> Result<media::LocalPlayerHandle> {
+        player.register(self.inner.clone(), self.name.clone()).await
+    }
+
     // ===========================================================================================
     // Methods
     // ===========================================================================================
@@ -395,12 +510,33 @@ impl Adapter {
 
         self.device(address)
     }
+
+    /// Sets the service allowlist of the admin policy.
+    ///
+    /// Only profiles whose service UUIDs are contained in the allowlist
+    /// can be used by remote devices; connections to other services are rejected.
+    /// An empty allowlist allows all services.
+    ///
+    /// Devices that are affected by the policy can be identified using
+    /// [Device::is_affected_by_policy].
+    pub async fn set_service_allow_list(&self, uuids: &HashSet<Uuid>) -> Result<()> {
+        let uuids: Vec<String> = uuids.iter().map(|uuid| uuid.to_string()).collect();
+        self.call_method_with_interface("SetServiceAllowList", (uuids,), ADMIN_POLICY_SET_INTERFACE).await
+    }
 }
 
 define_properties!(
     Adapter,
     /// Bluetooth adapter property.
-    pub AdapterProperty => {
+    pub AdapterProperty,
+    /// Kind of a Bluetooth adapter property.
+    pub AdapterPropertyKind,
+    /// Snapshot of all properties of a Bluetooth adapter.
+    ///
+    /// Obtain it using [Adapter::info] and keep it up to date by applying
+    /// the properties received from [AdapterEvent::PropertyChanged]
+    /// and invalidating those received from [AdapterEvent::PropertyInvalidated].
+    pub AdapterInfo => {
 
         // ===========================================================================================
         // Adapter properties
@@ -654,6 +790,29 @@ define_properties!(
                 v.iter().filter_map(|s| s.parse().ok()).collect()
             }),
         );
+
+        // ===========================================================================================
+        // Admin policy properties
+        // ===========================================================================================
+
+        /// Service UUIDs that remote devices are allowed to use
+        /// according to the admin policy.
+        ///
+        /// An empty set means that all services are allowed.
+        /// Use [Adapter::set_service_allow_list] to change the allowlist.
+        property(
+            ServiceAllowList, HashSet<Uuid>,
+            dbus: (ADMIN_POLICY_STATUS_INTERFACE, "ServiceAllowList", Vec<String>, OPTIONAL),
+            get: (service_allow_list, v => {
+                v
+                .iter()
+                .map(|uuid| {
+                    uuid.parse()
+                        .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(uuid.to_string()))))
+                })
+                .collect::<Result<HashSet<Uuid>>>()?
+            }),
+        );
     }
 );
 
@@ -661,6 +820,7 @@ define_properties!(
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Clone, Debug)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum AdapterEvent {
     /// Bluetooth device with specified address was added.
     DeviceAdded(Address),
@@ -668,6 +828,8 @@ pub enum AdapterEvent {
     DeviceRemoved(Address),
     /// Bluetooth adapter property changed.
     PropertyChanged(AdapterProperty),
+    /// Bluetooth adapter property invalidated, i.e. its value became unknown or it was removed.
+    PropertyInvalidated(AdapterPropertyKind),
 }
 
 /// Transport parameter determines the type of scan.
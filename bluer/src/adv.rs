@@ -15,7 +15,7 @@ use std::{
 use strum::{Display, EnumString};
 use uuid::Uuid;
 
-use crate::{read_dict, Adapter, Result, SessionInner, SERVICE_NAME, TIMEOUT};
+use crate::{read_dict, Adapter, Result, SessionInner};
 
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.LEAdvertisingManager1";
 pub(crate) const ADVERTISEMENT_INTERFACE: &str = "org.bluez.LEAdvertisement1";
@@ -192,6 +192,9 @@ pub struct Advertisement {
     /// truncated.
     pub local_name: Option<String>,
     /// Appearance to be used in the advertising report.
+    ///
+    /// When the `id` feature is enabled, an assigned appearance value can be set
+    /// using `Advertisement::set_appearance`.
     pub appearance: Option<u16>,
     /// Duration of the advertisement in seconds.
     ///
@@ -236,6 +239,22 @@ pub struct Advertisement {
     pub _non_exhaustive: (),
 }
 
+#[cfg(feature = "id")]
+#[cfg_attr(docsrs, doc(cfg(feature = "id")))]
+impl Advertisement {
+    /// Sets the appearance to be used in the advertising report to an assigned appearance value.
+    pub fn set_appearance(&mut self, appearance: crate::id::Appearance) {
+        self.appearance = Some(appearance.into());
+    }
+
+    /// The appearance to be used in the advertising report as an assigned appearance value.
+    ///
+    /// `None` is returned if no appearance is set or its value is not assigned.
+    pub fn known_appearance(&self) -> Option<crate::id::Appearance> {
+        self.appearance.and_then(|v| crate::id::Appearance::try_from(v).ok())
+    }
+}
+
 impl Advertisement {
     pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Self> {
         cr.register(ADVERTISEMENT_INTERFACE, |ib: &mut IfaceBuilder<Self>| {
@@ -305,15 +324,20 @@ impl Advertisement {
         }
 
         log::trace!("Registering advertisement at {}", &name);
-        let proxy =
-            Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
-        let () =
-            proxy.method_call(MANAGER_INTERFACE, "RegisterAdvertisement", (name.clone(), PropMap::new())).await?;
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let reg_name = name.clone();
+        inner
+            .register(&name, adapter_path.clone(), MANAGER_INTERFACE, "RegisterAdvertisement", move || {
+                (reg_name.clone(), PropMap::new())
+            })
+            .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let unreg_name = name.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            inner.forget_registrations(&unreg_name).await;
 
             log::trace!("Unregistering advertisement at {}", &unreg_name);
             let _: std::result::Result<(), dbus::Error> =
@@ -11,7 +11,7 @@ use tokio::{
 };
 use uuid::Uuid;
 
-use crate::{method_call, Address, Device, Result, SessionInner, ERR_PREFIX, SERVICE_NAME, TIMEOUT};
+use crate::{method_call, Address, Device, Result, SessionInner, ERR_PREFIX};
 
 pub(crate) const INTERFACE: &str = "org.bluez.Agent1";
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.AgentManager1";
@@ -465,28 +465,38 @@ impl RegisteredAgent {
         }
 
         log::trace!("Registering agent at {}", &name);
-        let proxy = Proxy::new(SERVICE_NAME, MANAGER_PATH, TIMEOUT, inner.connection.clone());
-        let () = proxy.method_call(MANAGER_INTERFACE, "RegisterAgent", (name.clone(), capability)).await?;
-        let connection = inner.connection.clone();
+        let reg_name = name.clone();
+        inner
+            .register(&name, MANAGER_PATH.into(), MANAGER_INTERFACE, "RegisterAgent", move || {
+                (reg_name.clone(), capability)
+            })
+            .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), MANAGER_PATH, inner.timeout, inner.connection.clone());
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let unreg_name = name.clone();
+        let unreg_inner = inner.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            unreg_inner.forget_registrations(&unreg_name).await;
 
             log::trace!("Unregistering agent at {}", &unreg_name);
             let _: std::result::Result<(), dbus::Error> =
                 proxy.method_call(MANAGER_INTERFACE, "UnregisterAgent", (unreg_name.clone(),)).await;
 
             log::trace!("Unpublishing agent at {}", &unreg_name);
-            let mut cr = inner.crossroads.lock().await;
+            let mut cr = unreg_inner.crossroads.lock().await;
             let _: Option<Self> = cr.remove(&unreg_name);
         });
 
         if request_default {
             log::trace!("Requesting default agent for {}", &name);
-            let proxy = Proxy::new(SERVICE_NAME, MANAGER_PATH, TIMEOUT, connection);
-            let () = proxy.method_call(MANAGER_INTERFACE, "RequestDefaultAgent", (name.clone(),)).await?;
+            let reg_name = name.clone();
+            inner
+                .register(&name, MANAGER_PATH.into(), MANAGER_INTERFACE, "RequestDefaultAgent", move || {
+                    (reg_name.clone(),)
+                })
+                .await?;
         }
 
         Ok(AgentHandle { name, _drop_tx: drop_tx })
@@ -0,0 +1,290 @@
+//! Battery level publishing.
+//!
+//! Applications that learn the battery level of a remote device through
+//! other means than the GATT Battery Service, for example through a
+//! vendor-specific protocol, can publish it to BlueZ using a [BatteryProvider].
+//! BlueZ then exposes the battery level through the
+//! [battery percentage](crate::Device::battery_percentage) property of the device.
+
+use dbus::{
+    arg::{PropMap, Variant},
+    channel::Sender,
+    message::SignalArgs,
+    nonblock::{stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged, Proxy, SyncConnection},
+    Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use std::{
+    fmt,
+    mem::take,
+    sync::{Arc, Mutex, Weak},
+};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{Adapter, Address, Device, Error, ErrorKind, Result, SessionInner};
+
+pub(crate) const INTERFACE: &str = "org.bluez.BatteryProvider1";
+pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.BatteryProviderManager1";
+pub(crate) const PROVIDER_PREFIX: &str = publish_path!("battery/");
+
+/// Definition of a battery provider.
+///
+/// Use [Adapter::register_battery_provider] to register the provider.
+#[derive(Debug, Default)]
+pub struct BatteryProvider {
+    /// Batteries to publish.
+    pub batteries: Vec<Battery>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Battery of a remote device.
+#[derive(Debug, Default)]
+pub struct Battery {
+    /// Address of the remote device the battery belongs to.
+    ///
+    /// The device must be known to the adapter the provider is registered on.
+    pub device: Address,
+    /// Initial battery level in percent, between 0 and 100.
+    pub percentage: u8,
+    /// Initial description of the source providing the battery level,
+    /// for example `HFP 1.7`.
+    pub source: Option<String>,
+    /// Control handle for battery once it has been registered.
+    ///
+    /// Use [battery_control] to obtain it.
+    pub control_handle: BatteryControlHandle,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Battery state shared between a registered battery and its control.
+#[derive(Default)]
+struct BatteryShared {
+    percentage: u8,
+    source: Option<String>,
+    registration: Option<(Weak<SyncConnection>, Path<'static>)>,
+}
+
+/// An object to update the battery level once it has been registered.
+///
+/// Use [battery_control] to obtain controller and associated handle.
+pub struct BatteryControl {
+    shared: Arc<Mutex<BatteryShared>>,
+}
+
+impl fmt::Debug for BatteryControl {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let shared = self.shared.lock().unwrap();
+        write!(f, "BatteryControl {{ percentage: {} }}", shared.percentage)
+    }
+}
+
+impl BatteryControl {
+    /// Current battery level in percent.
+    pub fn percentage(&self) -> u8 {
+        self.shared.lock().unwrap().percentage
+    }
+
+    /// Current description of the source providing the battery level.
+    pub fn source(&self) -> Option<String> {
+        self.shared.lock().unwrap().source.clone()
+    }
+
+    /// Updates the battery level in percent.
+    ///
+    /// The percentage must be between 0 and 100.
+    pub fn set_percentage(&self, percentage: u8) -> Result<()> {
+        if percentage > 100 {
+            return Err(Error::new(ErrorKind::InvalidArguments));
+        }
+        self.update(|shared| {
+            shared.percentage = percentage;
+            ("Percentage", Variant(Box::new(percentage)))
+        })
+    }
+
+    /// Updates the description of the source providing the battery level.
+    pub fn set_source(&self, source: impl Into<String>) -> Result<()> {
+        let source = source.into();
+        self.update(|shared| {
+            shared.source = Some(source.clone());
+            ("Source", Variant(Box::new(source)))
+        })
+    }
+
+    fn update(
+        &self, f: impl FnOnce(&mut BatteryShared) -> (&'static str, Variant<Box<dyn dbus::arg::RefArg>>),
+    ) -> Result<()> {
+        let mut shared = self.shared.lock().unwrap();
+        let (connection, path) = match &shared.registration {
+            Some((connection, path)) => match connection.upgrade() {
+                Some(connection) => (connection, path.clone()),
+                None => return Err(Error::new(ErrorKind::NotRegistered)),
+            },
+            None => return Err(Error::new(ErrorKind::NotRegistered)),
+        };
+        let (name, value) = f(&mut shared);
+        drop(shared);
+
+        let mut changed_properties = PropMap::new();
+        changed_properties.insert(name.to_string(), value);
+        let ppc = PropertiesPropertiesChanged {
+            interface_name: INTERFACE.to_string(),
+            changed_properties,
+            invalidated_properties: Vec::new(),
+        };
+        connection.send(ppc.to_emit_message(&path)).map_err(|_| Error::new(ErrorKind::Failed))?;
+        Ok(())
+    }
+}
+
+/// A handle to store inside a battery definition to make it controllable
+/// once it has been registered.
+///
+/// Use [battery_control] to obtain controller and associated handle.
+#[derive(Default)]
+pub struct BatteryControlHandle {
+    shared: Arc<Mutex<BatteryShared>>,
+}
+
+impl fmt::Debug for BatteryControlHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BatteryControlHandle")
+    }
+}
+
+/// Creates a [BatteryControl] and its associated [BatteryControlHandle].
+///
+/// Keep the [BatteryControl] and store the [BatteryControlHandle] in [Battery::control_handle].
+pub fn battery_control() -> (BatteryControl, BatteryControlHandle) {
+    let shared = Arc::new(Mutex::new(BatteryShared::default()));
+    (BatteryControl { shared: shared.clone() }, BatteryControlHandle { shared })
+}
+
+/// A battery exposed over D-Bus to bluez.
+pub(crate) struct RegisteredBattery {
+    device: Path<'static>,
+    shared: Arc<Mutex<BatteryShared>>,
+}
+
+impl RegisteredBattery {
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            cr_property!(ib, "Device", reg => {
+                Some(reg.device.clone())
+            });
+            cr_property!(ib, "Percentage", reg => {
+                Some(reg.shared.lock().unwrap().percentage)
+            });
+            cr_property!(ib, "Source", reg => {
+                reg.shared.lock().unwrap().source.clone()
+            });
+        })
+    }
+}
+
+impl BatteryProvider {
+    pub(crate) async fn register(
+        mut self, inner: Arc<SessionInner>, adapter_name: Arc<String>,
+    ) -> Result<BatteryProviderHandle> {
+        let mut reg_paths = Vec::new();
+        let provider_path = Path::new(format!("{}{}", PROVIDER_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        log::trace!("Publishing battery provider at {}", &provider_path);
+
+        let batteries = take(&mut self.batteries);
+        let mut registered = Vec::new();
+        for battery in batteries {
+            if battery.percentage > 100 {
+                return Err(Error::new(ErrorKind::InvalidArguments));
+            }
+            registered.push((Device::dbus_path(&adapter_name, battery.device)?, battery));
+        }
+
+        {
+            let mut cr = inner.crossroads.lock().await;
+
+            reg_paths.push(provider_path.clone());
+            let om = cr.object_manager::<Self>();
+            cr.insert(provider_path.clone(), &[om], self);
+
+            for (idx, (device, battery)) in registered.into_iter().enumerate() {
+                let battery_path = Path::new(format!("{}/battery{}", &provider_path, idx)).unwrap();
+                log::trace!("Publishing battery at {}", &battery_path);
+
+                let shared = battery.control_handle.shared;
+                {
+                    let mut shared = shared.lock().unwrap();
+                    shared.percentage = battery.percentage;
+                    shared.source = battery.source;
+                    shared.registration = Some((Arc::downgrade(&inner.connection), battery_path.clone()));
+                }
+
+                reg_paths.push(battery_path.clone());
+                cr.insert(battery_path, &[inner.battery_token], Arc::new(RegisteredBattery { device, shared }));
+            }
+        }
+
+        log::trace!("Registering battery provider at {}", &provider_path);
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let provider_path_reg = provider_path.clone();
+        let res = inner
+            .register(
+                &provider_path,
+                adapter_path.clone(),
+                MANAGER_INTERFACE,
+                "RegisterBatteryProvider",
+                move || (provider_path_reg.clone(),),
+            )
+            .await;
+        let registered = res.is_ok();
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let provider_path_unreg = provider_path.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+            inner.forget_registrations(&provider_path_unreg).await;
+
+            if registered {
+                log::trace!("Unregistering battery provider at {}", &provider_path_unreg);
+                let _: std::result::Result<(), dbus::Error> = proxy
+                    .method_call(MANAGER_INTERFACE, "UnregisterBatteryProvider", (provider_path_unreg,))
+                    .await;
+            }
+
+            let mut cr = inner.crossroads.lock().await;
+            for reg_path in reg_paths.into_iter().rev() {
+                log::trace!("Unpublishing {}", &reg_path);
+                if let Some(reg) = cr.remove::<Arc<RegisteredBattery>>(&reg_path) {
+                    reg.shared.lock().unwrap().registration = None;
+                }
+            }
+        });
+
+        res?;
+        Ok(BatteryProviderHandle { name: provider_path, _drop_tx: drop_tx })
+    }
+}
+
+/// Handle to registered battery provider.
+///
+/// Drop to unregister battery provider.
+#[must_use = "BatteryProviderHandle must be held for battery provider to be registered"]
+pub struct BatteryProviderHandle {
+    name: Path<'static>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl Drop for BatteryProviderHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for BatteryProviderHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "BatteryProviderHandle {{ {} }}", &self.name)
+    }
+}
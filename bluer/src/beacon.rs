@@ -0,0 +1,468 @@
+//! Beacon formats.
+//!
+//! This decodes and encodes the following beacon formats,
+//! which are carried in the manufacturer specific data or service data
+//! of Bluetooth LE advertisements:
+//!
+//! * [iBeacon](IBeacon),
+//! * [Eddystone] with its UID, URL, TLM and EID frames,
+//! * [AltBeacon].
+//!
+//! Beacons can be decoded from the [properties of a device](Beacon::from_device),
+//! from [raw advertising data](Beacon::from_advertising_data)
+//! or from the manufacturer and service data directly.
+//! Use [Beacon::to_advertisement] to obtain an advertisement for sending a beacon.
+
+#[cfg(feature = "bluetoothd")]
+use std::collections::BTreeSet;
+use std::{fmt, time::Duration};
+use uuid::Uuid;
+
+use crate::{
+    ad::{AdvertisingData, Flags, Structure},
+    UuidExt,
+};
+#[cfg(feature = "bluetoothd")]
+use crate::{
+    adv::{Advertisement, Type},
+    monitor::MonitorEvent,
+    Device, Result, Session,
+};
+
+/// Company identifier of Apple, Inc. used by iBeacons.
+pub const IBEACON_COMPANY: u16 = 0x004c;
+
+/// 16-bit UUID of the Eddystone service.
+pub const EDDYSTONE_UUID16: u16 = 0xfeaa;
+
+/// iBeacon type and length preceding the iBeacon payload.
+const IBEACON_PREFIX: [u8; 2] = [0x02, 0x15];
+
+/// AltBeacon beacon code.
+const ALTBEACON_CODE: [u8; 2] = [0xbe, 0xac];
+
+/// Eddystone frame types.
+const EDDYSTONE_UID: u8 = 0x00;
+const EDDYSTONE_URL: u8 = 0x10;
+const EDDYSTONE_TLM: u8 = 0x20;
+const EDDYSTONE_EID: u8 = 0x30;
+
+/// Version of unencrypted Eddystone TLM frames.
+const EDDYSTONE_TLM_VERSION: u8 = 0x00;
+
+/// Eddystone TLM temperature indicating that no temperature is available.
+const EDDYSTONE_TLM_NO_TEMPERATURE: i16 = i16::MIN;
+
+/// Maximum length of an encoded Eddystone URL excluding the scheme prefix.
+const EDDYSTONE_URL_MAX_LEN: usize = 17;
+
+/// Eddystone URL scheme prefixes.
+const EDDYSTONE_URL_SCHEMES: [&str; 4] = ["http://www.", "https://www.", "http://", "https://"];
+
+/// Eddystone URL expansion codes.
+const EDDYSTONE_URL_EXPANSIONS: [&str; 14] = [
+    ".com/", ".org/", ".edu/", ".net/", ".info/", ".biz/", ".gov/", ".com", ".org", ".edu", ".net", ".info",
+    ".biz", ".gov",
+];
+
+/// Beacon that cannot be encoded.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvalidBeacon(pub String);
+
+impl fmt::Display for InvalidBeacon {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid beacon: {}", &self.0)
+    }
+}
+
+impl std::error::Error for InvalidBeacon {}
+
+/// iBeacon.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IBeacon {
+    /// Proximity UUID.
+    pub uuid: Uuid,
+    /// Major value.
+    pub major: u16,
+    /// Minor value.
+    pub minor: u16,
+    /// Calibrated received signal strength at 1 m in dBm.
+    pub measured_power: i8,
+}
+
+impl IBeacon {
+    /// Decodes an iBeacon from manufacturer specific data of Apple, Inc.
+    pub fn from_manufacturer_data(data: &[u8]) -> Option<Self> {
+        let payload = data.strip_prefix(&IBEACON_PREFIX)?;
+        if payload.len() != 21 {
+            return None;
+        }
+        Some(Self {
+            uuid: Uuid::from_slice(&payload[..16]).ok()?,
+            major: u16::from_be_bytes([payload[16], payload[17]]),
+            minor: u16::from_be_bytes([payload[18], payload[19]]),
+            measured_power: payload[20] as i8,
+        })
+    }
+
+    /// Encodes the iBeacon as manufacturer specific data of [Apple, Inc.](IBEACON_COMPANY).
+    pub fn to_manufacturer_data(&self) -> Vec<u8> {
+        let mut data = IBEACON_PREFIX.to_vec();
+        data.extend_from_slice(self.uuid.as_bytes());
+        data.extend_from_slice(&self.major.to_be_bytes());
+        data.extend_from_slice(&self.minor.to_be_bytes());
+        data.push(self.measured_power as u8);
+        data
+    }
+}
+
+/// AltBeacon.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AltBeacon {
+    /// Company identifier of the beacon manufacturer.
+    pub manufacturer: u16,
+    /// Beacon identifier.
+    ///
+    /// It is commonly split into a 16 byte organizational unit
+    /// followed by two 2 byte values.
+    pub id: [u8; 20],
+    /// Calibrated received signal strength at 1 m in dBm.
+    pub reference_rssi: i8,
+    /// Reserved for use by the manufacturer.
+    pub manufacturer_reserved: u8,
+}
+
+impl AltBeacon {
+    /// Decodes an AltBeacon from manufacturer specific data of the specified company.
+    pub fn from_manufacturer_data(manufacturer: u16, data: &[u8]) -> Option<Self> {
+        let payload = data.strip_prefix(&ALTBEACON_CODE)?;
+        if payload.len() != 22 {
+            return None;
+        }
+        Some(Self {
+            manufacturer,
+            id: payload[..20].try_into().ok()?,
+            reference_rssi: payload[20] as i8,
+            manufacturer_reserved: payload[21],
+        })
+    }
+
+    /// Encodes the AltBeacon as manufacturer specific data of [its manufacturer](Self::manufacturer).
+    pub fn to_manufacturer_data(&self) -> Vec<u8> {
+        let mut data = ALTBEACON_CODE.to_vec();
+        data.extend_from_slice(&self.id);
+        data.push(self.reference_rssi as u8);
+        data.push(self.manufacturer_reserved);
+        data
+    }
+}
+
+/// Eddystone frame.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Eddystone {
+    /// Unique identifier.
+    Uid {
+        /// Calibrated TX power at 0 m in dBm.
+        tx_power: i8,
+        /// Namespace.
+        namespace: [u8; 10],
+        /// Instance.
+        instance: [u8; 6],
+    },
+    /// URL.
+    Url {
+        /// Calibrated TX power at 0 m in dBm.
+        tx_power: i8,
+        /// URL.
+        ///
+        /// It must start with `http://` or `https://` and
+        /// its encoded form must not exceed 17 bytes.
+        url: String,
+    },
+    /// Unencrypted telemetry.
+    Tlm {
+        /// Battery voltage in mV.
+        ///
+        /// Zero if not supported.
+        battery_voltage: u16,
+        /// Temperature in degrees Celsius in signed 8.8 fixed point notation.
+        ///
+        /// `-128.0` (`0x8000`) if not supported.
+        /// Use [Eddystone::temperature] to obtain it as floating point value.
+        temperature: i16,
+        /// Number of advertising frames sent since power-on or reboot.
+        advertising_count: u32,
+        /// Time since power-on or reboot with a resolution of 0.1 s.
+        uptime: Duration,
+    },
+    /// Ephemeral identifier.
+    Eid {
+        /// Calibrated TX power at 0 m in dBm.
+        tx_power: i8,
+        /// Ephemeral identifier.
+        eid: [u8; 8],
+    },
+}
+
+impl Eddystone {
+    /// Eddystone service UUID.
+    pub fn uuid() -> Uuid {
+        Uuid::from_u16(EDDYSTONE_UUID16)
+    }
+
+    /// Decodes an Eddystone frame from service data of the Eddystone service.
+    pub fn from_service_data(data: &[u8]) -> Option<Self> {
+        let (&frame_type, data) = data.split_first()?;
+        match frame_type {
+            EDDYSTONE_UID if data.len() == 17 || data.len() == 19 => Some(Self::Uid {
+                tx_power: data[0] as i8,
+                namespace: data[1..11].try_into().ok()?,
+                instance: data[11..17].try_into().ok()?,
+            }),
+            EDDYSTONE_URL if data.len() >= 2 => {
+                Some(Self::Url { tx_power: data[0] as i8, url: decode_eddystone_url(&data[1..])? })
+            }
+            EDDYSTONE_TLM if data.len() == 13 && data[0] == EDDYSTONE_TLM_VERSION => Some(Self::Tlm {
+                battery_voltage: u16::from_be_bytes([data[1], data[2]]),
+                temperature: i16::from_be_bytes([data[3], data[4]]),
+                advertising_count: u32::from_be_bytes([data[5], data[6], data[7], data[8]]),
+                uptime: Duration::from_millis(
+                    u32::from_be_bytes([data[9], data[10], data[11], data[12]]) as u64 * 100,
+                ),
+            }),
+            EDDYSTONE_EID if data.len() == 9 => {
+                Some(Self::Eid { tx_power: data[0] as i8, eid: data[1..9].try_into().ok()? })
+            }
+            _ => None,
+        }
+    }
+
+    /// Encodes the Eddystone frame as service data of the [Eddystone service](Self::uuid).
+    pub fn to_service_data(&self) -> std::result::Result<Vec<u8>, InvalidBeacon> {
+        let mut data = Vec::new();
+        match self {
+            Self::Uid { tx_power, namespace, instance } => {
+                data.push(EDDYSTONE_UID);
+                data.push(*tx_power as u8);
+                data.extend_from_slice(namespace);
+                data.extend_from_slice(instance);
+                data.extend_from_slice(&[0, 0]);
+            }
+            Self::Url { tx_power, url } => {
+                data.push(EDDYSTONE_URL);
+                data.push(*tx_power as u8);
+                data.extend(encode_eddystone_url(url)?);
+            }
+            Self::Tlm { battery_voltage, temperature, advertising_count, uptime } => {
+                let uptime = u32::try_from(uptime.as_millis() / 100)
+                    .map_err(|_| InvalidBeacon(format!("uptime {uptime:?} is too long")))?;
+                data.push(EDDYSTONE_TLM);
+                data.push(EDDYSTONE_TLM_VERSION);
+                data.extend_from_slice(&battery_voltage.to_be_bytes());
+                data.extend_from_slice(&temperature.to_be_bytes());
+                data.extend_from_slice(&advertising_count.to_be_bytes());
+                data.extend_from_slice(&uptime.to_be_bytes());
+            }
+            Self::Eid { tx_power, eid } => {
+                data.push(EDDYSTONE_EID);
+                data.push(*tx_power as u8);
+                data.extend_from_slice(eid);
+            }
+        }
+        Ok(data)
+    }
+
+    /// Temperature of a telemetry frame in degrees Celsius, if available.
+    pub fn temperature(&self) -> Option<f32> {
+        match self {
+            Self::Tlm { temperature, .. } if *temperature != EDDYSTONE_TLM_NO_TEMPERATURE => {
+                Some(*temperature as f32 / 256.)
+            }
+            _ => None,
+        }
+    }
+}
+
+fn decode_eddystone_url(data: &[u8]) -> Option<String> {
+    let (&scheme, data) = data.split_first()?;
+    let mut url = EDDYSTONE_URL_SCHEMES.get(scheme as usize)?.to_string();
+    for &b in data {
+        match b {
+            0x00..=0x0d => url.push_str(EDDYSTONE_URL_EXPANSIONS[b as usize]),
+            0x21..=0x7e => url.push(b as char),
+            _ => return None,
+        }
+    }
+    Some(url)
+}
+
+fn encode_eddystone_url(url: &str) -> std::result::Result<Vec<u8>, InvalidBeacon> {
+    let (scheme, prefix) = EDDYSTONE_URL_SCHEMES
+        .iter()
+        .enumerate()
+        .filter(|(_, prefix)| url.starts_with(*prefix))
+        .max_by_key(|(_, prefix)| prefix.len())
+        .ok_or_else(|| InvalidBeacon(format!("URL {url} has unsupported scheme")))?;
+
+    let mut data = vec![scheme as u8];
+    let mut rest = &url[prefix.len()..];
+    'outer: while !rest.is_empty() {
+        for (code, expansion) in EDDYSTONE_URL_EXPANSIONS.iter().enumerate() {
+            if let Some(r) = rest.strip_prefix(expansion) {
+                data.push(code as u8);
+                rest = r;
+                continue 'outer;
+            }
+        }
+        match rest.as_bytes()[0] {
+            b @ 0x21..=0x7e => data.push(b),
+            _ => return Err(InvalidBeacon(format!("URL {url} contains unsupported characters"))),
+        }
+        rest = &rest[1..];
+    }
+
+    if data.len() - 1 > EDDYSTONE_URL_MAX_LEN {
+        return Err(InvalidBeacon(format!("URL {url} is too long")));
+    }
+    Ok(data)
+}
+
+/// A beacon.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Beacon {
+    /// iBeacon.
+    IBeacon(IBeacon),
+    /// Eddystone frame.
+    Eddystone(Eddystone),
+    /// AltBeacon.
+    AltBeacon(AltBeacon),
+}
+
+impl From<IBeacon> for Beacon {
+    fn from(beacon: IBeacon) -> Self {
+        Self::IBeacon(beacon)
+    }
+}
+
+impl From<Eddystone> for Beacon {
+    fn from(beacon: Eddystone) -> Self {
+        Self::Eddystone(beacon)
+    }
+}
+
+impl From<AltBeacon> for Beacon {
+    fn from(beacon: AltBeacon) -> Self {
+        Self::AltBeacon(beacon)
+    }
+}
+
+impl Beacon {
+    /// Decodes a beacon from manufacturer specific data of the specified company.
+    pub fn from_manufacturer_data(company: u16, data: &[u8]) -> Option<Self> {
+        if company == IBEACON_COMPANY {
+            if let Some(beacon) = IBeacon::from_manufacturer_data(data) {
+                return Some(Self::IBeacon(beacon));
+            }
+        }
+        AltBeacon::from_manufacturer_data(company, data).map(Self::AltBeacon)
+    }
+
+    /// Decodes a beacon from service data of the specified service.
+    pub fn from_service_data(uuid: &Uuid, data: &[u8]) -> Option<Self> {
+        if *uuid == Eddystone::uuid() {
+            Eddystone::from_service_data(data).map(Self::Eddystone)
+        } else {
+            None
+        }
+    }
+
+    /// Decodes all beacons contained in raw advertising data.
+    pub fn from_advertising_data(data: &AdvertisingData) -> Vec<Self> {
+        data.0
+            .iter()
+            .filter_map(|structure| match structure {
+                Structure::ManufacturerData { company, data } => Self::from_manufacturer_data(*company, data),
+                Structure::ServiceData16 { uuid, data } => Self::from_service_data(&Uuid::from_u16(*uuid), data),
+                Structure::ServiceData128 { uuid, data } => Self::from_service_data(uuid, data),
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Decodes all beacons contained in the advertised manufacturer and service data
+    /// of a device.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub async fn from_device(device: &Device) -> Result<Vec<Self>> {
+        let mut beacons = Vec::new();
+        for (company, data) in device.manufacturer_data().await?.unwrap_or_default() {
+            beacons.extend(Self::from_manufacturer_data(company, &data));
+        }
+        for (uuid, data) in device.service_data().await?.unwrap_or_default() {
+            beacons.extend(Self::from_service_data(&uuid, &data));
+        }
+        Ok(beacons)
+    }
+
+    /// Decodes all beacons advertised by the device an advertisement monitor event refers to.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub async fn from_monitor_event(session: &Session, event: &MonitorEvent) -> Result<Vec<Self>> {
+        let id = match event {
+            MonitorEvent::DeviceFound(id) | MonitorEvent::DeviceLost(id) => id,
+        };
+        let device = session.adapter(&id.adapter)?.device(id.device)?;
+        Self::from_device(&device).await
+    }
+
+    /// Raw advertising data consisting of flags and the beacon.
+    pub fn to_advertising_data(&self) -> std::result::Result<AdvertisingData, InvalidBeacon> {
+        let flags = Flags { le_general_discoverable: true, br_edr_not_supported: true, ..Default::default() };
+        let mut structures = vec![Structure::Flags(flags)];
+        match self {
+            Self::IBeacon(beacon) => structures.push(Structure::ManufacturerData {
+                company: IBEACON_COMPANY,
+                data: beacon.to_manufacturer_data(),
+            }),
+            Self::Eddystone(beacon) => {
+                structures.push(Structure::ServiceUuids16 { complete: true, uuids: vec![EDDYSTONE_UUID16] });
+                structures
+                    .push(Structure::ServiceData16 { uuid: EDDYSTONE_UUID16, data: beacon.to_service_data()? });
+            }
+            Self::AltBeacon(beacon) => structures.push(Structure::ManufacturerData {
+                company: beacon.manufacturer,
+                data: beacon.to_manufacturer_data(),
+            }),
+        }
+        Ok(AdvertisingData(structures))
+    }
+
+    /// Broadcast advertisement sending the beacon.
+    ///
+    /// Use [Adapter::advertise](crate::Adapter::advertise) to send it.
+    #[cfg(feature = "bluetoothd")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+    pub fn to_advertisement(&self) -> std::result::Result<Advertisement, InvalidBeacon> {
+        let mut adv = Advertisement { advertisement_type: Type::Broadcast, ..Default::default() };
+        match self {
+            Self::IBeacon(beacon) => {
+                adv.manufacturer_data.insert(IBEACON_COMPANY, beacon.to_manufacturer_data());
+            }
+            Self::Eddystone(beacon) => {
+                adv.service_uuids = BTreeSet::from([Eddystone::uuid()]);
+                adv.service_data.insert(Eddystone::uuid(), beacon.to_service_data()?);
+            }
+            Self::AltBeacon(beacon) => {
+                adv.manufacturer_data.insert(beacon.manufacturer, beacon.to_manufacturer_data());
+            }
+        }
+        Ok(adv)
+    }
+}
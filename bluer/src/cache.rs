@@ -0,0 +1,268 @@
+//! Local mirror of the objects and properties of the Bluetooth daemon.
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::{stdintf::org_freedesktop_dbus::ObjectManager, Proxy, SyncConnection},
+    strings::BusName,
+    Path,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::RwLock,
+    time::Duration,
+};
+
+/// Cached properties of an interface of an object.
+#[derive(Default)]
+struct CachedInterface {
+    /// Known property values.
+    props: PropMap,
+    /// Properties whose values are unknown and must be queried from the daemon.
+    invalidated: HashSet<String>,
+}
+
+/// Cached objects with their interfaces.
+type Objects = HashMap<Path<'static>, HashMap<String, CachedInterface>>;
+
+/// Change received from the daemon while the cache is being refreshed.
+enum Change {
+    AddInterfaces(Path<'static>, HashMap<String, PropMap>),
+    RemoveInterfaces(Path<'static>, Vec<String>),
+    Properties(Path<'static>, String, PropMap, Vec<String>),
+}
+
+/// Contents of the cache.
+#[derive(Default)]
+struct State {
+    /// Cached objects, `None` if the cache is not valid.
+    objects: Option<Objects>,
+    /// Changes received since a refresh was started, `None` if no refresh is in progress.
+    pending: Option<Vec<Change>>,
+    /// Incremented whenever the cache is cleared or a refresh is started.
+    generation: u64,
+}
+
+/// Mirror of the objects and properties of the Bluetooth daemon.
+///
+/// It is filled using `GetManagedObjects` and kept up to date by the event loop
+/// of the session from the object manager and property change signals.
+/// Thus it is updated before any event is delivered to subscribers.
+///
+/// While the cache is not valid, for example because the daemon is not running
+/// or a refresh is in progress, all queries must be answered by the daemon.
+/// Changes received during a refresh are recorded and applied once the objects
+/// have been fetched.
+pub(crate) struct ObjectCache {
+    service_name: BusName<'static>,
+    timeout: Duration,
+    state: RwLock<State>,
+}
+
+impl ObjectCache {
+    /// Creates an invalid cache for the specified D-Bus service.
+    pub fn new(service_name: BusName<'static>, timeout: Duration) -> Self {
+        Self { service_name, timeout, state: RwLock::new(State::default()) }
+    }
+
+    /// Replaces the contents by all objects of the Bluetooth daemon.
+    ///
+    /// If they cannot be fetched, the cache becomes invalid.
+    pub async fn refresh(&self, connection: &SyncConnection) {
+        let generation = self.start_refresh();
+        self.finish_refresh(generation, connection).await;
+    }
+
+    /// Invalidates the cache and starts recording changes for a refresh.
+    ///
+    /// Returns the generation to pass to [ObjectCache::finish_refresh].
+    pub fn start_refresh(&self) -> u64 {
+        let mut state = self.state.write().unwrap();
+        state.generation += 1;
+        state.objects = None;
+        state.pending = Some(Vec::new());
+        state.generation
+    }
+
+    /// Fetches all objects of the Bluetooth daemon and applies the changes
+    /// recorded since the refresh was started.
+    ///
+    /// Nothing is done if the cache has been cleared or another refresh has been
+    /// started in the meantime.
+    pub async fn finish_refresh(&self, generation: u64, connection: &SyncConnection) {
+        let proxy = Proxy::new(self.service_name.clone(), "/", self.timeout, connection);
+        let objects = match proxy.get_managed_objects().await {
+            Ok(objects) => Some(
+                objects
+                    .into_iter()
+                    .map(|(path, interfaces)| {
+                        let interfaces = interfaces
+                            .into_iter()
+                            .map(|(name, props)| (name, CachedInterface { props, invalidated: HashSet::new() }))
+                            .collect();
+                        (path, interfaces)
+                    })
+                    .collect(),
+            ),
+            Err(err) => {
+                log::debug!("Cannot fetch objects of {} for cache: {}", &self.service_name, &err);
+                None
+            }
+        };
+
+        let mut state = self.state.write().unwrap();
+        if state.generation != generation {
+            return;
+        }
+        let pending = state.pending.take().unwrap_or_default();
+        state.objects = objects;
+        for change in pending {
+            state.apply(change);
+        }
+    }
+
+    /// Invalidates the whole cache.
+    pub fn clear(&self) {
+        let mut state = self.state.write().unwrap();
+        state.generation += 1;
+        state.objects = None;
+        state.pending = None;
+    }
+
+    /// Adds interfaces with their properties to an object.
+    pub fn add_interfaces(&self, object: &Path<'static>, interfaces: &HashMap<String, PropMap>) {
+        let interfaces = interfaces.iter().map(|(name, props)| (name.clone(), clone_prop_map(props))).collect();
+        self.state.write().unwrap().apply(Change::AddInterfaces(object.clone(), interfaces));
+    }
+
+    /// Removes interfaces from an object.
+    ///
+    /// The object is removed when it has no interfaces left.
+    pub fn remove_interfaces(&self, object: &Path<'static>, interfaces: &[String]) {
+        self.state.write().unwrap().apply(Change::RemoveInterfaces(object.clone(), interfaces.to_vec()));
+    }
+
+    /// Applies changed and invalidated properties of an interface of an object.
+    pub fn change_properties(
+        &self, object: &Path<'static>, interface: &str, changed: &PropMap, invalidated: &[String],
+    ) {
+        self.state.write().unwrap().apply(Change::Properties(
+            object.clone(),
+            interface.to_string(),
+            clone_prop_map(changed),
+            invalidated.to_vec(),
+        ));
+    }
+
+    /// Marks a property as unknown, so that it is queried from the daemon until its
+    /// next change is received.
+    pub fn invalidate(&self, object: &Path<'static>, interface: &str, name: &str) {
+        self.change_properties(object, interface, &PropMap::new(), &[name.to_string()]);
+    }
+
+    /// Whether an object is cached.
+    ///
+    /// `None` is returned if the cache is not valid.
+    pub fn contains(&self, object: &Path<'static>) -> Option<bool> {
+        Some(self.state.read().unwrap().objects.as_ref()?.contains_key(object))
+    }
+
+    /// Converts the cached value of a property using `f`.
+    ///
+    /// `Some(None)` is returned if the interface of the object is cached, but does
+    /// not have the property.
+    /// `None` is returned if the value is not cached or cannot be converted by `f`
+    /// and thus must be queried from the daemon.
+    pub fn property<T>(
+        &self, object: &Path<'static>, interface: &str, name: &str,
+        f: impl FnOnce(&(dyn RefArg + 'static)) -> Option<T>,
+    ) -> Option<Option<T>> {
+        let state = self.state.read().unwrap();
+        let cached = state.objects.as_ref()?.get(object)?.get(interface)?;
+        match cached.props.get(name) {
+            Some(value) => f(&*value.0).map(Some),
+            None if cached.invalidated.contains(name) => None,
+            None => Some(None),
+        }
+    }
+
+    /// Returns all cached properties of an interface of an object.
+    ///
+    /// `Some(None)` is returned if the object is cached, but does not have the interface.
+    /// `None` is returned if some values are not cached and must be queried from the daemon.
+    pub fn properties(&self, object: &Path<'static>, interface: &str) -> Option<Option<PropMap>> {
+        let state = self.state.read().unwrap();
+        match state.objects.as_ref()?.get(object)?.get(interface) {
+            Some(cached) if cached.invalidated.is_empty() => Some(Some(clone_prop_map(&cached.props))),
+            Some(_) => None,
+            None => Some(None),
+        }
+    }
+
+    /// Returns all cached objects in the format of `GetManagedObjects`.
+    ///
+    /// `None` is returned if the cache is not valid.
+    pub fn managed_objects(&self) -> Option<HashMap<Path<'static>, HashMap<String, PropMap>>> {
+        let state = self.state.read().unwrap();
+        let objects = state
+            .objects
+            .as_ref()?
+            .iter()
+            .map(|(path, interfaces)| {
+                let interfaces = interfaces
+                    .iter()
+                    .map(|(name, cached)| (name.clone(), clone_prop_map(&cached.props)))
+                    .collect();
+                (path.clone(), interfaces)
+            })
+            .collect();
+        Some(objects)
+    }
+}
+
+impl State {
+    /// Applies a change to the cached objects or records it, if a refresh is in progress.
+    fn apply(&mut self, change: Change) {
+        let Some(objects) = self.objects.as_mut() else {
+            if let Some(pending) = self.pending.as_mut() {
+                pending.push(change);
+            }
+            return;
+        };
+
+        match change {
+            Change::AddInterfaces(object, interfaces) => {
+                let cached = objects.entry(object).or_default();
+                for (name, props) in interfaces {
+                    cached.insert(name, CachedInterface { props, invalidated: HashSet::new() });
+                }
+            }
+            Change::RemoveInterfaces(object, interfaces) => {
+                if let Some(cached) = objects.get_mut(&object) {
+                    for name in &interfaces {
+                        cached.remove(name);
+                    }
+                    if cached.is_empty() {
+                        objects.remove(&object);
+                    }
+                }
+            }
+            Change::Properties(object, interface, changed, invalidated) => {
+                if let Some(cached) = objects.get_mut(&object).and_then(|o| o.get_mut(&interface)) {
+                    for (name, value) in changed {
+                        cached.invalidated.remove(&name);
+                        cached.props.insert(name, value);
+                    }
+                    for name in invalidated {
+                        cached.props.remove(&name);
+                        cached.invalidated.insert(name);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Clones a property map.
+fn clone_prop_map(props: &PropMap) -> PropMap {
+    props.iter().map(|(name, value)| (name.clone(), Variant(value.0.box_clone()))).collect()
+}
@@ -0,0 +1,242 @@
+//! btsnoop capture files.
+//!
+//! This is the format written by `btmon -w` and the Android Bluetooth stack.
+//! All numbers are stored in big-endian byte order.
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    io::{Read, Result, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{invalid_data, read_exact_or_eof, ReadRecord, Record, WriteRecord, MAX_RECORD_LEN};
+use crate::sys::{HCI_MON_COMMAND_PKT, HCI_MON_EVENT_PKT};
+
+/// Identification pattern at the start of a btsnoop file.
+pub const MAGIC: [u8; 8] = *b"btsnoop\0";
+
+/// Version of the btsnoop format.
+pub const VERSION: u32 = 1;
+
+/// Microseconds between midnight January 1st, 0 AD and the UNIX epoch.
+const EPOCH_DELTA_US: u64 = 0x00dc_ddb3_0f2f_8000;
+
+const RECORD_HEADER_SIZE: usize = 24;
+
+/// Flag set when the packet was received from the controller.
+const FLAG_RECEIVED: u32 = 1 << 0;
+
+/// Flag set when the packet is a command or event.
+const FLAG_COMMAND_EVENT: u32 = 1 << 1;
+
+/// Type of data link contained in a btsnoop file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Datalink {
+    /// Unencapsulated HCI (H1).
+    ///
+    /// Packets carry no packet type indicator; only commands, events and ACL data
+    /// can be represented.
+    H1 = 1001,
+    /// HCI UART (H4).
+    H4 = 1002,
+    /// HCI monitor, as written by `btmon`.
+    Monitor = 2001,
+}
+
+fn timestamp_to_btsnoop(ts: SystemTime) -> i64 {
+    let us = match ts.duration_since(UNIX_EPOCH) {
+        Ok(d) => d.as_micros() as i64,
+        Err(err) => -(err.duration().as_micros() as i64),
+    };
+    us.saturating_add(EPOCH_DELTA_US as i64)
+}
+
+fn timestamp_from_btsnoop(ts: i64) -> SystemTime {
+    let us = ts.saturating_sub(EPOCH_DELTA_US as i64);
+    if us >= 0 {
+        UNIX_EPOCH + Duration::from_micros(us as u64)
+    } else {
+        UNIX_EPOCH - Duration::from_micros(us.unsigned_abs())
+    }
+}
+
+/// Reader of btsnoop capture files.
+#[derive(Debug)]
+pub struct Reader<R> {
+    reader: R,
+    datalink: Datalink,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Opens a btsnoop file for reading by reading its header.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut hdr = [0; 16];
+        reader.read_exact(&mut hdr)?;
+        if hdr[..8] != MAGIC {
+            return Err(invalid_data("not a btsnoop file"));
+        }
+        let version = u32::from_be_bytes(hdr[8..12].try_into().unwrap());
+        if version != VERSION {
+            return Err(invalid_data("unsupported btsnoop version"));
+        }
+        let datalink = Datalink::from_u32(u32::from_be_bytes(hdr[12..16].try_into().unwrap()))
+            .ok_or_else(|| invalid_data("unsupported btsnoop datalink type"))?;
+        Ok(Self { reader, datalink })
+    }
+
+    /// Data link type of the file.
+    pub fn datalink(&self) -> Datalink {
+        self.datalink
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn read_raw(&mut self) -> Result<Option<(u32, SystemTime, Vec<u8>)>> {
+        let mut hdr = [0; RECORD_HEADER_SIZE];
+        if !read_exact_or_eof(&mut self.reader, &mut hdr)? {
+            return Ok(None);
+        }
+        let included_len = u32::from_be_bytes(hdr[4..8].try_into().unwrap());
+        let flags = u32::from_be_bytes(hdr[8..12].try_into().unwrap());
+        let timestamp = timestamp_from_btsnoop(i64::from_be_bytes(hdr[16..24].try_into().unwrap()));
+
+        if included_len > MAX_RECORD_LEN {
+            return Err(invalid_data("btsnoop record too long"));
+        }
+
+        let mut data = vec![0; included_len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some((flags, timestamp, data)))
+    }
+}
+
+impl<R> ReadRecord for Reader<R>
+where
+    R: Read,
+{
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        loop {
+            let Some((flags, timestamp, data)) = self.read_raw()? else { return Ok(None) };
+            let received = flags & FLAG_RECEIVED != 0;
+            let record = match self.datalink {
+                Datalink::H1 => {
+                    let mut h4 = Vec::with_capacity(data.len() + 1);
+                    h4.push(match (flags & FLAG_COMMAND_EVENT != 0, received) {
+                        (true, false) => crate::sys::HCI_COMMAND_PKT,
+                        (true, true) => crate::sys::HCI_EVENT_PKT,
+                        (false, _) => crate::sys::HCI_ACLDATA_PKT,
+                    });
+                    h4.extend(data);
+                    Record::from_h4(0, timestamp, received, &h4)
+                }
+                Datalink::H4 => Record::from_h4(0, timestamp, received, &data),
+                Datalink::Monitor => {
+                    Some(Record { index: (flags >> 16) as u16, opcode: flags as u16, timestamp, payload: data })
+                }
+            };
+            if let Some(record) = record {
+                return Ok(Some(record));
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: Read,
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Writer of btsnoop capture files.
+#[derive(Debug)]
+pub struct Writer<W>
+where
+    W: Write,
+{
+    writer: W,
+    datalink: Datalink,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    /// Creates a new btsnoop file of the specified data link type by writing its header.
+    pub fn new(mut writer: W, datalink: Datalink) -> Result<Self> {
+        writer.write_all(&MAGIC)?;
+        writer.write_all(&VERSION.to_be_bytes())?;
+        writer.write_all(&(datalink as u32).to_be_bytes())?;
+        Ok(Self { writer, datalink })
+    }
+
+    /// Data link type of the file.
+    pub fn datalink(&self) -> Datalink {
+        self.datalink
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_raw(&mut self, flags: u32, timestamp: SystemTime, data: &[u8]) -> Result<()> {
+        let len: u32 = data.len().try_into().map_err(|_| invalid_data("btsnoop record too long"))?;
+        self.writer.write_all(&len.to_be_bytes())?;
+        self.writer.write_all(&len.to_be_bytes())?;
+        self.writer.write_all(&flags.to_be_bytes())?;
+        self.writer.write_all(&0u32.to_be_bytes())?;
+        self.writer.write_all(&timestamp_to_btsnoop(timestamp).to_be_bytes())?;
+        self.writer.write_all(data)
+    }
+}
+
+impl<W> WriteRecord for Writer<W>
+where
+    W: Write,
+{
+    fn write_record(&mut self, record: &Record) -> Result<bool> {
+        match self.datalink {
+            Datalink::H1 | Datalink::H4 => {
+                let Some((received, h4)) = record.to_h4() else { return Ok(false) };
+                let command_event = matches!(record.opcode, HCI_MON_COMMAND_PKT | HCI_MON_EVENT_PKT);
+                let mut flags = 0;
+                if received {
+                    flags |= FLAG_RECEIVED;
+                }
+                if command_event {
+                    flags |= FLAG_COMMAND_EVENT;
+                }
+                match self.datalink {
+                    Datalink::H1 if h4[0] == crate::sys::HCI_ACLDATA_PKT || command_event => {
+                        self.write_raw(flags, record.timestamp, &h4[1..])?
+                    }
+                    Datalink::H1 => return Ok(false),
+                    _ => self.write_raw(flags, record.timestamp, &h4)?,
+                }
+            }
+            Datalink::Monitor => {
+                let flags = (u32::from(record.index) << 16) | u32::from(record.opcode);
+                self.write_raw(flags, record.timestamp, &record.payload)?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
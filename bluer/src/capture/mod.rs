@@ -0,0 +1,244 @@
+//! Capture files of HCI traffic.
+//!
+//! This reads and writes HCI traffic in the following file formats:
+//!
+//! * [btsnoop], as used by `btmon` and Android,
+//! * [pcap] with the [H4 with pseudo-header](pcap::LinkType::BluetoothHciH4WithPhdr)
+//!   and [Linux monitor](pcap::LinkType::BluetoothLinuxMonitor) link types,
+//!   as used by Wireshark and `tcpdump`.
+//!
+//! All formats are read into and written from [Record]s, which follow the
+//! structure of records received on the HCI monitor channel.
+//! Thus captures can be converted between formats.
+//!
+//! Use [record] to capture traffic from an [HCI monitor](crate::hci::Monitor) into a file
+//! and [Replay] to play back a capture file with its original timing.
+//!
+//! No running Bluetooth daemon is required.
+//!
+
+#[cfg(feature = "hci")]
+use futures::{pin_mut, Future, FutureExt};
+use std::{
+    io::{Error, ErrorKind, Result},
+    time::{Duration, SystemTime},
+};
+
+use crate::sys::{
+    HCI_ACLDATA_PKT, HCI_COMMAND_PKT, HCI_EVENT_PKT, HCI_ISODATA_PKT, HCI_MON_ACL_RX_PKT, HCI_MON_ACL_TX_PKT,
+    HCI_MON_COMMAND_PKT, HCI_MON_EVENT_PKT, HCI_MON_ISO_RX_PKT, HCI_MON_ISO_TX_PKT, HCI_MON_SCO_RX_PKT,
+    HCI_MON_SCO_TX_PKT, HCI_SCODATA_PKT,
+};
+
+pub mod btsnoop;
+pub mod pcap;
+
+/// Maximum length of a record accepted when reading capture files.
+///
+/// Longer records are rejected as invalid data, since their length field is
+/// most likely corrupt.
+/// This is the maximum snapshot length accepted by libpcap.
+pub const MAX_RECORD_LEN: u32 = 262_144;
+
+/// A captured record of HCI traffic.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Record {
+    /// Controller index.
+    pub index: u16,
+    /// HCI monitor opcode.
+    ///
+    /// See [MonitorOpcode](crate::hci::MonitorOpcode) for possible values.
+    pub opcode: u16,
+    /// Time of capture.
+    pub timestamp: SystemTime,
+    /// Payload.
+    ///
+    /// For HCI packets this is the packet without UART (H4) packet type indicator.
+    pub payload: Vec<u8>,
+}
+
+impl Record {
+    /// Converts an HCI packet prefixed with the UART (H4) packet type indicator into a record.
+    ///
+    /// `received` specifies whether the packet was received from the controller.
+    /// Returns `None` if the packet type is unknown or empty.
+    pub fn from_h4(index: u16, timestamp: SystemTime, received: bool, data: &[u8]) -> Option<Self> {
+        let (&ty, payload) = data.split_first()?;
+        let opcode = match (ty, received) {
+            (HCI_COMMAND_PKT, _) => HCI_MON_COMMAND_PKT,
+            (HCI_EVENT_PKT, _) => HCI_MON_EVENT_PKT,
+            (HCI_ACLDATA_PKT, false) => HCI_MON_ACL_TX_PKT,
+            (HCI_ACLDATA_PKT, true) => HCI_MON_ACL_RX_PKT,
+            (HCI_SCODATA_PKT, false) => HCI_MON_SCO_TX_PKT,
+            (HCI_SCODATA_PKT, true) => HCI_MON_SCO_RX_PKT,
+            (HCI_ISODATA_PKT, false) => HCI_MON_ISO_TX_PKT,
+            (HCI_ISODATA_PKT, true) => HCI_MON_ISO_RX_PKT,
+            _ => return None,
+        };
+        Some(Self { index, opcode, timestamp, payload: payload.to_vec() })
+    }
+
+    /// Converts the record into an HCI packet prefixed with the UART (H4) packet type indicator.
+    ///
+    /// Returns whether the packet was received from the controller together with the packet.
+    /// Returns `None` if the record does not contain an HCI packet.
+    pub fn to_h4(&self) -> Option<(bool, Vec<u8>)> {
+        let (ty, received) = match self.opcode {
+            HCI_MON_COMMAND_PKT => (HCI_COMMAND_PKT, false),
+            HCI_MON_EVENT_PKT => (HCI_EVENT_PKT, true),
+            HCI_MON_ACL_TX_PKT => (HCI_ACLDATA_PKT, false),
+            HCI_MON_ACL_RX_PKT => (HCI_ACLDATA_PKT, true),
+            HCI_MON_SCO_TX_PKT => (HCI_SCODATA_PKT, false),
+            HCI_MON_SCO_RX_PKT => (HCI_SCODATA_PKT, true),
+            HCI_MON_ISO_TX_PKT => (HCI_ISODATA_PKT, false),
+            HCI_MON_ISO_RX_PKT => (HCI_ISODATA_PKT, true),
+            _ => return None,
+        };
+        let mut data = Vec::with_capacity(1 + self.payload.len());
+        data.push(ty);
+        data.extend_from_slice(&self.payload);
+        Some((received, data))
+    }
+}
+
+#[cfg(feature = "hci")]
+impl From<crate::hci::MonitorRecord> for Record {
+    /// Converts a monitor record into a capture record.
+    ///
+    /// If the monitor record has no timestamp, the current time is used.
+    fn from(record: crate::hci::MonitorRecord) -> Self {
+        Self {
+            index: record.index,
+            opcode: record.opcode.into(),
+            timestamp: record.timestamp.unwrap_or_else(SystemTime::now),
+            payload: record.payload,
+        }
+    }
+}
+
+#[cfg(feature = "hci")]
+impl From<Record> for crate::hci::MonitorRecord {
+    fn from(record: Record) -> Self {
+        Self {
+            index: record.index,
+            opcode: record.opcode.into(),
+            timestamp: Some(record.timestamp),
+            payload: record.payload,
+        }
+    }
+}
+
+/// Reads records from a capture file.
+pub trait ReadRecord {
+    /// Reads the next record.
+    ///
+    /// Returns `None` at the end of the file.
+    /// Records that cannot be represented as a [Record] are skipped.
+    fn read_record(&mut self) -> Result<Option<Record>>;
+}
+
+/// Writes records into a capture file.
+pub trait WriteRecord {
+    /// Writes a record.
+    ///
+    /// Returns whether the record was written.
+    /// Records that cannot be represented in the format of the file are skipped.
+    fn write_record(&mut self, record: &Record) -> Result<bool>;
+
+    /// Flushes buffered data to the underlying writer.
+    fn flush(&mut self) -> Result<()>;
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Reads exactly the size of the buffer or nothing at all.
+///
+/// Returns `false` if the end of file was reached before reading any byte.
+fn read_exact_or_eof(r: &mut impl std::io::Read, buf: &mut [u8]) -> Result<bool> {
+    let mut pos = 0;
+    while pos < buf.len() {
+        match r.read(&mut buf[pos..]) {
+            Ok(0) if pos == 0 => return Ok(false),
+            Ok(0) => return Err(Error::new(ErrorKind::UnexpectedEof, "capture record truncated")),
+            Ok(n) => pos += n,
+            Err(err) if err.kind() == ErrorKind::Interrupted => (),
+            Err(err) => return Err(err),
+        }
+    }
+    Ok(true)
+}
+
+/// Records HCI traffic received from the HCI monitor channel into a capture file
+/// until `stop` completes or an error occurs.
+///
+/// Returns the number of records written.
+#[cfg(feature = "hci")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hci")))]
+pub async fn record(
+    monitor: &mut crate::hci::Monitor, writer: &mut impl WriteRecord, stop: impl Future<Output = ()>,
+) -> Result<usize> {
+    let stop = stop.fuse();
+    pin_mut!(stop);
+
+    let mut count = 0;
+    loop {
+        let record = futures::select! {
+            record = monitor.recv().fuse() => record?,
+            () = stop => break,
+        };
+        if writer.write_record(&record.into())? {
+            count += 1;
+        }
+    }
+
+    writer.flush()?;
+    Ok(count)
+}
+
+/// Replays records from a capture file with their original timing.
+#[derive(Debug)]
+pub struct Replay<R> {
+    reader: R,
+    speed: f64,
+    last: Option<SystemTime>,
+}
+
+impl<R> Replay<R>
+where
+    R: ReadRecord,
+{
+    /// Creates a new replay reading from the specified capture file.
+    ///
+    /// `speed` specifies the replay speed relative to the original timing, i.e.
+    /// `2.0` replays twice as fast.
+    /// Specify [f64::INFINITY] to replay without delays.
+    pub fn new(reader: R, speed: f64) -> Self {
+        Self { reader, speed, last: None }
+    }
+
+    /// Returns the next record after the delay since the previous record
+    /// has elapsed.
+    ///
+    /// Returns `None` at the end of the file.
+    pub async fn next(&mut self) -> Result<Option<Record>> {
+        let Some(record) = self.reader.read_record()? else { return Ok(None) };
+
+        if let Some(last) = self.last {
+            let delay = record.timestamp.duration_since(last).unwrap_or_default();
+            if self.speed.is_finite() && self.speed > 0.0 && !delay.is_zero() {
+                tokio::time::sleep(Duration::from_secs_f64(delay.as_secs_f64() / self.speed)).await;
+            }
+        }
+        self.last = Some(record.timestamp);
+
+        Ok(Some(record))
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -0,0 +1,279 @@
+//! pcap capture files.
+//!
+//! This is the classic libpcap format as read by Wireshark and written by `tcpdump`.
+//! Files in either byte order and with microsecond or nanosecond timestamp resolution
+//! are read; files are written in little-endian byte order with microsecond resolution.
+
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    io::{Read, Result, Write},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use super::{invalid_data, read_exact_or_eof, ReadRecord, Record, WriteRecord, MAX_RECORD_LEN};
+
+/// Magic number of files with microsecond timestamp resolution.
+pub const MAGIC_US: u32 = 0xa1b2_c3d4;
+
+/// Magic number of files with nanosecond timestamp resolution.
+pub const MAGIC_NS: u32 = 0xa1b2_3c4d;
+
+/// Major version of the pcap format.
+pub const VERSION_MAJOR: u16 = 2;
+
+/// Minor version of the pcap format.
+pub const VERSION_MINOR: u16 = 4;
+
+/// Default maximum length of captured packets.
+pub const DEFAULT_SNAPLEN: u32 = 65535;
+
+const RECORD_HEADER_SIZE: usize = 16;
+
+/// Size of the direction pseudo-header of [LinkType::BluetoothHciH4WithPhdr].
+const PHDR_SIZE: usize = 4;
+
+/// Size of the header of [LinkType::BluetoothLinuxMonitor].
+const MONITOR_HDR_SIZE: usize = 4;
+
+/// Link-layer header type of a pcap file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum LinkType {
+    /// Bluetooth HCI UART (H4) packets preceded by a 4-byte direction pseudo-header.
+    ///
+    /// This corresponds to `LINKTYPE_BLUETOOTH_HCI_H4_WITH_PHDR`.
+    BluetoothHciH4WithPhdr = 201,
+    /// Bluetooth Linux monitor records.
+    ///
+    /// This corresponds to `LINKTYPE_BLUETOOTH_LINUX_MONITOR`.
+    BluetoothLinuxMonitor = 254,
+}
+
+/// Reader of pcap capture files.
+#[derive(Debug)]
+pub struct Reader<R> {
+    reader: R,
+    link_type: LinkType,
+    big_endian: bool,
+    nanos: bool,
+    snaplen: u32,
+}
+
+impl<R> Reader<R>
+where
+    R: Read,
+{
+    /// Opens a pcap file for reading by reading its header.
+    pub fn new(mut reader: R) -> Result<Self> {
+        let mut hdr = [0; 24];
+        reader.read_exact(&mut hdr)?;
+
+        let magic: [u8; 4] = hdr[0..4].try_into().unwrap();
+        let (big_endian, nanos) = if magic == MAGIC_US.to_le_bytes() {
+            (false, false)
+        } else if magic == MAGIC_US.to_be_bytes() {
+            (true, false)
+        } else if magic == MAGIC_NS.to_le_bytes() {
+            (false, true)
+        } else if magic == MAGIC_NS.to_be_bytes() {
+            (true, true)
+        } else {
+            return Err(invalid_data("not a pcap file"));
+        };
+
+        let mut this = Self { reader, link_type: LinkType::BluetoothLinuxMonitor, big_endian, nanos, snaplen: 0 };
+        if this.u16(&hdr[4..6]) != VERSION_MAJOR {
+            return Err(invalid_data("unsupported pcap version"));
+        }
+        this.snaplen = this.u32(&hdr[16..20]);
+        this.link_type = LinkType::from_u32(this.u32(&hdr[20..24]) & 0x03ff_ffff)
+            .ok_or_else(|| invalid_data("unsupported pcap link type"))?;
+        Ok(this)
+    }
+
+    /// Link-layer header type of the file.
+    pub fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    /// Maximum length of captured packets.
+    pub fn snaplen(&self) -> u32 {
+        self.snaplen
+    }
+
+    /// Returns the underlying reader.
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+
+    fn u16(&self, buf: &[u8]) -> u16 {
+        let buf = buf.try_into().unwrap();
+        if self.big_endian {
+            u16::from_be_bytes(buf)
+        } else {
+            u16::from_le_bytes(buf)
+        }
+    }
+
+    fn u32(&self, buf: &[u8]) -> u32 {
+        let buf = buf.try_into().unwrap();
+        if self.big_endian {
+            u32::from_be_bytes(buf)
+        } else {
+            u32::from_le_bytes(buf)
+        }
+    }
+
+    fn read_raw(&mut self) -> Result<Option<(SystemTime, Vec<u8>)>> {
+        let mut hdr = [0; RECORD_HEADER_SIZE];
+        if !read_exact_or_eof(&mut self.reader, &mut hdr)? {
+            return Ok(None);
+        }
+        let secs = self.u32(&hdr[0..4]);
+        let frac = self.u32(&hdr[4..8]);
+        let included_len = self.u32(&hdr[8..12]);
+
+        let frac =
+            if self.nanos { Duration::from_nanos(frac.into()) } else { Duration::from_micros(frac.into()) };
+        let timestamp = UNIX_EPOCH + Duration::from_secs(secs.into()) + frac;
+
+        if included_len > MAX_RECORD_LEN {
+            return Err(invalid_data("pcap record too long"));
+        }
+
+        let mut data = vec![0; included_len as usize];
+        self.reader.read_exact(&mut data)?;
+        Ok(Some((timestamp, data)))
+    }
+}
+
+impl<R> ReadRecord for Reader<R>
+where
+    R: Read,
+{
+    fn read_record(&mut self) -> Result<Option<Record>> {
+        loop {
+            let Some((timestamp, data)) = self.read_raw()? else { return Ok(None) };
+            let record = match self.link_type {
+                LinkType::BluetoothHciH4WithPhdr if data.len() >= PHDR_SIZE => {
+                    // The direction is always stored in network byte order.
+                    let received = u32::from_be_bytes(data[..PHDR_SIZE].try_into().unwrap()) & 1 != 0;
+                    Record::from_h4(0, timestamp, received, &data[PHDR_SIZE..])
+                }
+                LinkType::BluetoothLinuxMonitor if data.len() >= MONITOR_HDR_SIZE => Some(Record {
+                    index: u16::from_be_bytes([data[0], data[1]]),
+                    opcode: u16::from_be_bytes([data[2], data[3]]),
+                    timestamp,
+                    payload: data[MONITOR_HDR_SIZE..].to_vec(),
+                }),
+                _ => None,
+            };
+            if let Some(record) = record {
+                return Ok(Some(record));
+            }
+        }
+    }
+}
+
+impl<R> Iterator for Reader<R>
+where
+    R: Read,
+{
+    type Item = Result<Record>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_record().transpose()
+    }
+}
+
+/// Writer of pcap capture files.
+#[derive(Debug)]
+pub struct Writer<W>
+where
+    W: Write,
+{
+    writer: W,
+    link_type: LinkType,
+    snaplen: u32,
+}
+
+impl<W> Writer<W>
+where
+    W: Write,
+{
+    /// Creates a new pcap file of the specified link-layer header type by writing its header.
+    ///
+    /// The maximum packet length is set to [DEFAULT_SNAPLEN].
+    pub fn new(writer: W, link_type: LinkType) -> Result<Self> {
+        Self::with_snaplen(writer, link_type, DEFAULT_SNAPLEN)
+    }
+
+    /// Creates a new pcap file of the specified link-layer header type and maximum packet length
+    /// by writing its header.
+    ///
+    /// Packets exceeding the maximum length are truncated.
+    pub fn with_snaplen(mut writer: W, link_type: LinkType, snaplen: u32) -> Result<Self> {
+        writer.write_all(&MAGIC_US.to_le_bytes())?;
+        writer.write_all(&VERSION_MAJOR.to_le_bytes())?;
+        writer.write_all(&VERSION_MINOR.to_le_bytes())?;
+        writer.write_all(&0i32.to_le_bytes())?;
+        writer.write_all(&0u32.to_le_bytes())?;
+        writer.write_all(&snaplen.to_le_bytes())?;
+        writer.write_all(&(link_type as u32).to_le_bytes())?;
+        Ok(Self { writer, link_type, snaplen })
+    }
+
+    /// Link-layer header type of the file.
+    pub fn link_type(&self) -> LinkType {
+        self.link_type
+    }
+
+    /// Returns the underlying writer.
+    pub fn into_inner(self) -> W {
+        self.writer
+    }
+
+    fn write_raw(&mut self, timestamp: SystemTime, header: &[u8], data: &[u8]) -> Result<()> {
+        let since_epoch = timestamp.duration_since(UNIX_EPOCH).unwrap_or_default();
+        let secs: u32 =
+            since_epoch.as_secs().try_into().map_err(|_| invalid_data("timestamp not representable in pcap"))?;
+        let orig_len = header.len() + data.len();
+        let orig_len: u32 = orig_len.try_into().map_err(|_| invalid_data("pcap record too long"))?;
+        let incl_len = orig_len.min(self.snaplen);
+        let data_len = (incl_len as usize).saturating_sub(header.len()).min(data.len());
+
+        self.writer.write_all(&secs.to_le_bytes())?;
+        self.writer.write_all(&since_epoch.subsec_micros().to_le_bytes())?;
+        self.writer.write_all(&incl_len.to_le_bytes())?;
+        self.writer.write_all(&orig_len.to_le_bytes())?;
+        self.writer.write_all(&header[..header.len().min(incl_len as usize)])?;
+        self.writer.write_all(&data[..data_len])
+    }
+}
+
+impl<W> WriteRecord for Writer<W>
+where
+    W: Write,
+{
+    fn write_record(&mut self, record: &Record) -> Result<bool> {
+        match self.link_type {
+            LinkType::BluetoothHciH4WithPhdr => {
+                let Some((received, h4)) = record.to_h4() else { return Ok(false) };
+                self.write_raw(record.timestamp, &u32::from(received).to_be_bytes(), &h4)?;
+            }
+            LinkType::BluetoothLinuxMonitor => {
+                let mut hdr = [0; MONITOR_HDR_SIZE];
+                hdr[0..2].copy_from_slice(&record.index.to_be_bytes());
+                hdr[2..4].copy_from_slice(&record.opcode.to_be_bytes());
+                self.write_raw(record.timestamp, &hdr, &record.payload)?;
+            }
+        }
+        Ok(true)
+    }
+
+    fn flush(&mut self) -> Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,694 @@
+//! Class of Device of classic Bluetooth (BR/EDR) devices.
+//!
+//! The Class of Device is a 24-bit value advertised by classic Bluetooth devices.
+//! It describes the kind of the device through the major and minor device class
+//! and the services it provides through the major service classes.
+//!
+//! Use [ClassOfDevice::from] to decode the raw value returned by
+//! [Adapter::class](crate::Adapter::class) or [Device::class](crate::Device::class)
+//! and [u32::from] to encode it again.
+
+use std::{collections::BTreeSet, fmt};
+use strum::{Display, EnumIter, IntoEnumIterator};
+
+/// Major service class, indicating a general service provided by a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumIter)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MajorServiceClass {
+    /// Limited discoverable mode.
+    #[strum(serialize = "Limited Discoverable Mode")]
+    LimitedDiscoverableMode,
+    /// LE audio.
+    #[strum(serialize = "LE Audio")]
+    LeAudio,
+    /// Positioning (location identification).
+    Positioning,
+    /// Networking, for example LAN or ad hoc.
+    Networking,
+    /// Rendering, for example printing or speakers.
+    Rendering,
+    /// Capturing, for example scanning or microphones.
+    Capturing,
+    /// Object transfer, for example v-Inbox or v-Folder.
+    #[strum(serialize = "Object Transfer")]
+    ObjectTransfer,
+    /// Audio, for example speakers, microphones or headsets.
+    Audio,
+    /// Telephony, for example cordless telephony or modems.
+    Telephony,
+    /// Information, for example web servers.
+    Information,
+}
+
+impl MajorServiceClass {
+    /// Bit within the Class of Device.
+    pub const fn bit(&self) -> u32 {
+        match self {
+            Self::LimitedDiscoverableMode => 1 << 13,
+            Self::LeAudio => 1 << 14,
+            Self::Positioning => 1 << 16,
+            Self::Networking => 1 << 17,
+            Self::Rendering => 1 << 18,
+            Self::Capturing => 1 << 19,
+            Self::ObjectTransfer => 1 << 20,
+            Self::Audio => 1 << 21,
+            Self::Telephony => 1 << 22,
+            Self::Information => 1 << 23,
+        }
+    }
+}
+
+/// Major device class, indicating the kind of a device.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MajorDeviceClass {
+    /// Miscellaneous.
+    Miscellaneous,
+    /// Computer, for example desktop, notebook or PDA.
+    Computer,
+    /// Phone, for example cellular, cordless or modem.
+    Phone,
+    /// LAN or network access point.
+    #[strum(serialize = "Network Access Point")]
+    NetworkAccessPoint,
+    /// Audio/video, for example headset, speaker or TV.
+    #[strum(serialize = "Audio/Video")]
+    AudioVideo,
+    /// Peripheral, for example mouse, joystick or keyboard.
+    Peripheral,
+    /// Imaging, for example printer, scanner, camera or display.
+    Imaging,
+    /// Wearable.
+    Wearable,
+    /// Toy.
+    Toy,
+    /// Health.
+    Health,
+    /// Uncategorized, device code not specified.
+    Uncategorized,
+    /// Reserved value.
+    #[strum(to_string = "Reserved ({0:#04x})")]
+    Other(u8),
+}
+
+impl From<u8> for MajorDeviceClass {
+    fn from(value: u8) -> Self {
+        match value {
+            0x00 => Self::Miscellaneous,
+            0x01 => Self::Computer,
+            0x02 => Self::Phone,
+            0x03 => Self::NetworkAccessPoint,
+            0x04 => Self::AudioVideo,
+            0x05 => Self::Peripheral,
+            0x06 => Self::Imaging,
+            0x07 => Self::Wearable,
+            0x08 => Self::Toy,
+            0x09 => Self::Health,
+            0x1f => Self::Uncategorized,
+            other => Self::Other(other),
+        }
+    }
+}
+
+impl From<MajorDeviceClass> for u8 {
+    fn from(value: MajorDeviceClass) -> Self {
+        match value {
+            MajorDeviceClass::Miscellaneous => 0x00,
+            MajorDeviceClass::Computer => 0x01,
+            MajorDeviceClass::Phone => 0x02,
+            MajorDeviceClass::NetworkAccessPoint => 0x03,
+            MajorDeviceClass::AudioVideo => 0x04,
+            MajorDeviceClass::Peripheral => 0x05,
+            MajorDeviceClass::Imaging => 0x06,
+            MajorDeviceClass::Wearable => 0x07,
+            MajorDeviceClass::Toy => 0x08,
+            MajorDeviceClass::Health => 0x09,
+            MajorDeviceClass::Uncategorized => 0x1f,
+            MajorDeviceClass::Other(other) => other,
+        }
+    }
+}
+
+/// Defines a minor device class enum that is encoded as the 6-bit minor device class value.
+macro_rules! minor_device_class {
+    (
+        $(#[$outer:meta])*
+        $name:ident {
+            $(
+                $(#[$inner:meta])*
+                $variant:ident = $value:expr,
+            )*
+        }
+    ) => {
+        $(#[$outer])*
+        #[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[non_exhaustive]
+        pub enum $name {
+            $(
+                $(#[$inner])*
+                $variant,
+            )*
+            /// Reserved value.
+            #[strum(to_string = "Reserved ({0:#04x})")]
+            Other(u8),
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $( $value => Self::$variant, )*
+                    other => Self::Other(other),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $( $name::$variant => $value, )*
+                    $name::Other(other) => other,
+                }
+            }
+        }
+    };
+}
+
+minor_device_class! {
+    /// Minor device class of computers.
+    ComputerClass {
+        /// Uncategorized.
+        Uncategorized = 0x00,
+        /// Desktop workstation.
+        Desktop = 0x01,
+        /// Server-class computer.
+        Server = 0x02,
+        /// Laptop.
+        Laptop = 0x03,
+        /// Handheld PC or PDA (clamshell).
+        #[strum(serialize = "Handheld PC/PDA")]
+        HandheldPcPda = 0x04,
+        /// Palm-size PC or PDA.
+        #[strum(serialize = "Palm-size PC/PDA")]
+        PalmSizePcPda = 0x05,
+        /// Wearable computer (watch size).
+        Wearable = 0x06,
+        /// Tablet.
+        Tablet = 0x07,
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of phones.
+    PhoneClass {
+        /// Uncategorized.
+        Uncategorized = 0x00,
+        /// Cellular.
+        Cellular = 0x01,
+        /// Cordless.
+        Cordless = 0x02,
+        /// Smartphone.
+        Smartphone = 0x03,
+        /// Wired modem or voice gateway.
+        #[strum(serialize = "Wired Modem")]
+        WiredModem = 0x04,
+        /// Common ISDN access.
+        #[strum(serialize = "ISDN")]
+        Isdn = 0x05,
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of network access points, indicating their utilization.
+    NetworkAccessPointClass {
+        /// Fully available.
+        #[strum(serialize = "Fully Available")]
+        FullyAvailable = 0x00,
+        /// 1% to 17% utilized.
+        #[strum(serialize = "1% to 17% Utilized")]
+        Utilized1To17 = 0x08,
+        /// 17% to 33% utilized.
+        #[strum(serialize = "17% to 33% Utilized")]
+        Utilized17To33 = 0x10,
+        /// 33% to 50% utilized.
+        #[strum(serialize = "33% to 50% Utilized")]
+        Utilized33To50 = 0x18,
+        /// 50% to 67% utilized.
+        #[strum(serialize = "50% to 67% Utilized")]
+        Utilized50To67 = 0x20,
+        /// 67% to 83% utilized.
+        #[strum(serialize = "67% to 83% Utilized")]
+        Utilized67To83 = 0x28,
+        /// 83% to 99% utilized.
+        #[strum(serialize = "83% to 99% Utilized")]
+        Utilized83To99 = 0x30,
+        /// No service available.
+        #[strum(serialize = "No Service Available")]
+        NoServiceAvailable = 0x38,
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of audio/video devices.
+    AudioVideoClass {
+        /// Uncategorized.
+        Uncategorized = 0x00,
+        /// Wearable headset.
+        #[strum(serialize = "Wearable Headset")]
+        WearableHeadset = 0x01,
+        /// Hands-free device.
+        #[strum(serialize = "Hands-free")]
+        HandsFree = 0x02,
+        /// Microphone.
+        Microphone = 0x04,
+        /// Loudspeaker.
+        Loudspeaker = 0x05,
+        /// Headphones.
+        Headphones = 0x06,
+        /// Portable audio.
+        #[strum(serialize = "Portable Audio")]
+        PortableAudio = 0x07,
+        /// Car audio.
+        #[strum(serialize = "Car Audio")]
+        CarAudio = 0x08,
+        /// Set-top box.
+        #[strum(serialize = "Set-top Box")]
+        SetTopBox = 0x09,
+        /// HiFi audio device.
+        #[strum(serialize = "HiFi Audio")]
+        HifiAudio = 0x0a,
+        /// VCR.
+        #[strum(serialize = "VCR")]
+        Vcr = 0x0b,
+        /// Video camera.
+        #[strum(serialize = "Video Camera")]
+        VideoCamera = 0x0c,
+        /// Camcorder.
+        Camcorder = 0x0d,
+        /// Video monitor.
+        #[strum(serialize = "Video Monitor")]
+        VideoMonitor = 0x0e,
+        /// Video display and loudspeaker.
+        #[strum(serialize = "Video Display and Loudspeaker")]
+        VideoDisplayAndLoudspeaker = 0x0f,
+        /// Video conferencing.
+        #[strum(serialize = "Video Conferencing")]
+        VideoConferencing = 0x10,
+        /// Gaming or toy.
+        #[strum(serialize = "Gaming/Toy")]
+        GamingToy = 0x12,
+    }
+}
+
+minor_device_class! {
+    /// Kind of peripheral device, which is part of [PeripheralClass].
+    #[derive(Default)]
+    PeripheralKind {
+        /// Uncategorized.
+        #[default]
+        Uncategorized = 0x00,
+        /// Joystick.
+        Joystick = 0x01,
+        /// Gamepad.
+        Gamepad = 0x02,
+        /// Remote control.
+        #[strum(serialize = "Remote Control")]
+        RemoteControl = 0x03,
+        /// Sensing device.
+        #[strum(serialize = "Sensing Device")]
+        SensingDevice = 0x04,
+        /// Digitizer tablet.
+        #[strum(serialize = "Digitizer Tablet")]
+        DigitizerTablet = 0x05,
+        /// Card reader, for example SIM card reader.
+        #[strum(serialize = "Card Reader")]
+        CardReader = 0x06,
+        /// Digital pen.
+        #[strum(serialize = "Digital Pen")]
+        DigitalPen = 0x07,
+        /// Handheld scanner for bar codes, RFID, etc.
+        #[strum(serialize = "Handheld Scanner")]
+        HandheldScanner = 0x08,
+        /// Handheld gestural input device, for example a wand.
+        #[strum(serialize = "Handheld Gestural Input Device")]
+        HandheldGesturalInputDevice = 0x09,
+    }
+}
+
+/// Minor device class of peripheral devices.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PeripheralClass {
+    /// Device has a keyboard.
+    pub keyboard: bool,
+    /// Device is a pointing device.
+    pub pointing_device: bool,
+    /// Kind of device.
+    pub kind: PeripheralKind,
+}
+
+impl From<u8> for PeripheralClass {
+    fn from(value: u8) -> Self {
+        Self { keyboard: value & 0x10 != 0, pointing_device: value & 0x20 != 0, kind: (value & 0x0f).into() }
+    }
+}
+
+impl From<PeripheralClass> for u8 {
+    fn from(value: PeripheralClass) -> Self {
+        let mut v = u8::from(value.kind) & 0x0f;
+        if value.keyboard {
+            v |= 0x10;
+        }
+        if value.pointing_device {
+            v |= 0x20;
+        }
+        v
+    }
+}
+
+impl fmt::Display for PeripheralClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let input = match (self.keyboard, self.pointing_device) {
+            (true, true) => Some("Combo Keyboard/Pointing Device"),
+            (true, false) => Some("Keyboard"),
+            (false, true) => Some("Pointing Device"),
+            (false, false) => None,
+        };
+        match (input, self.kind) {
+            (Some(input), PeripheralKind::Uncategorized) => write!(f, "{input}"),
+            (Some(input), kind) => write!(f, "{input}, {kind}"),
+            (None, kind) => write!(f, "{kind}"),
+        }
+    }
+}
+
+/// Minor device class of imaging devices.
+///
+/// More than one of the flags may be set.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ImagingClass {
+    /// Display.
+    pub display: bool,
+    /// Camera.
+    pub camera: bool,
+    /// Scanner.
+    pub scanner: bool,
+    /// Printer.
+    pub printer: bool,
+}
+
+impl From<u8> for ImagingClass {
+    fn from(value: u8) -> Self {
+        Self {
+            display: value & 0x04 != 0,
+            camera: value & 0x08 != 0,
+            scanner: value & 0x10 != 0,
+            printer: value & 0x20 != 0,
+        }
+    }
+}
+
+impl From<ImagingClass> for u8 {
+    fn from(value: ImagingClass) -> Self {
+        let mut v = 0;
+        for (set, bit) in
+            [(value.display, 0x04), (value.camera, 0x08), (value.scanner, 0x10), (value.printer, 0x20)]
+        {
+            if set {
+                v |= bit;
+            }
+        }
+        v
+    }
+}
+
+impl fmt::Display for ImagingClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names: Vec<_> = [
+            (self.display, "Display"),
+            (self.camera, "Camera"),
+            (self.scanner, "Scanner"),
+            (self.printer, "Printer"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect();
+        if names.is_empty() {
+            write!(f, "Uncategorized")
+        } else {
+            write!(f, "{}", names.join("/"))
+        }
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of wearable devices.
+    WearableClass {
+        /// Wristwatch.
+        Wristwatch = 0x01,
+        /// Pager.
+        Pager = 0x02,
+        /// Jacket.
+        Jacket = 0x03,
+        /// Helmet.
+        Helmet = 0x04,
+        /// Glasses.
+        Glasses = 0x05,
+        /// Pin, for example lapel pin, broach or badge.
+        Pin = 0x06,
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of toys.
+    ToyClass {
+        /// Robot.
+        Robot = 0x01,
+        /// Vehicle.
+        Vehicle = 0x02,
+        /// Doll or action figure.
+        #[strum(serialize = "Doll/Action Figure")]
+        Doll = 0x03,
+        /// Controller.
+        Controller = 0x04,
+        /// Game.
+        Game = 0x05,
+    }
+}
+
+minor_device_class! {
+    /// Minor device class of health devices.
+    HealthClass {
+        /// Undefined.
+        Undefined = 0x00,
+        /// Blood pressure monitor.
+        #[strum(serialize = "Blood Pressure Monitor")]
+        BloodPressureMonitor = 0x01,
+        /// Thermometer.
+        Thermometer = 0x02,
+        /// Weighing scale.
+        #[strum(serialize = "Weighing Scale")]
+        WeighingScale = 0x03,
+        /// Glucose meter.
+        #[strum(serialize = "Glucose Meter")]
+        GlucoseMeter = 0x04,
+        /// Pulse oximeter.
+        #[strum(serialize = "Pulse Oximeter")]
+        PulseOximeter = 0x05,
+        /// Heart or pulse rate monitor.
+        #[strum(serialize = "Heart/Pulse Rate Monitor")]
+        HeartPulseRateMonitor = 0x06,
+        /// Health data display.
+        #[strum(serialize = "Health Data Display")]
+        HealthDataDisplay = 0x07,
+        /// Step counter.
+        #[strum(serialize = "Step Counter")]
+        StepCounter = 0x08,
+        /// Body composition analyzer.
+        #[strum(serialize = "Body Composition Analyzer")]
+        BodyCompositionAnalyzer = 0x09,
+        /// Peak flow monitor.
+        #[strum(serialize = "Peak Flow Monitor")]
+        PeakFlowMonitor = 0x0a,
+        /// Medication monitor.
+        #[strum(serialize = "Medication Monitor")]
+        MedicationMonitor = 0x0b,
+        /// Knee prosthesis.
+        #[strum(serialize = "Knee Prosthesis")]
+        KneeProsthesis = 0x0c,
+        /// Ankle prosthesis.
+        #[strum(serialize = "Ankle Prosthesis")]
+        AnkleProsthesis = 0x0d,
+        /// Generic health manager.
+        #[strum(serialize = "Generic Health Manager")]
+        GenericHealthManager = 0x0e,
+        /// Personal mobility device.
+        #[strum(serialize = "Personal Mobility Device")]
+        PersonalMobilityDevice = 0x0f,
+    }
+}
+
+/// Device class consisting of major device class and the corresponding minor device class.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum DeviceClass {
+    /// Miscellaneous device with raw minor device class.
+    Miscellaneous(u8),
+    /// Computer.
+    Computer(ComputerClass),
+    /// Phone.
+    Phone(PhoneClass),
+    /// LAN or network access point.
+    NetworkAccessPoint(NetworkAccessPointClass),
+    /// Audio/video device.
+    AudioVideo(AudioVideoClass),
+    /// Peripheral device.
+    Peripheral(PeripheralClass),
+    /// Imaging device.
+    Imaging(ImagingClass),
+    /// Wearable device.
+    Wearable(WearableClass),
+    /// Toy.
+    Toy(ToyClass),
+    /// Health device.
+    Health(HealthClass),
+    /// Uncategorized device with raw minor device class.
+    Uncategorized(u8),
+    /// Reserved major device class with raw minor device class.
+    Other {
+        /// Major device class.
+        major: u8,
+        /// Minor device class.
+        minor: u8,
+    },
+}
+
+impl Default for DeviceClass {
+    fn default() -> Self {
+        Self::Uncategorized(0)
+    }
+}
+
+impl DeviceClass {
+    /// Creates the device class from the 5-bit major and 6-bit minor device class.
+    pub fn from_major_minor(major: u8, minor: u8) -> Self {
+        let major = major & 0x1f;
+        let minor = minor & 0x3f;
+        match MajorDeviceClass::from(major) {
+            MajorDeviceClass::Miscellaneous => Self::Miscellaneous(minor),
+            MajorDeviceClass::Computer => Self::Computer(minor.into()),
+            MajorDeviceClass::Phone => Self::Phone(minor.into()),
+            MajorDeviceClass::NetworkAccessPoint => Self::NetworkAccessPoint(minor.into()),
+            MajorDeviceClass::AudioVideo => Self::AudioVideo(minor.into()),
+            MajorDeviceClass::Peripheral => Self::Peripheral(minor.into()),
+            MajorDeviceClass::Imaging => Self::Imaging(minor.into()),
+            MajorDeviceClass::Wearable => Self::Wearable(minor.into()),
+            MajorDeviceClass::Toy => Self::Toy(minor.into()),
+            MajorDeviceClass::Health => Self::Health(minor.into()),
+            MajorDeviceClass::Uncategorized => Self::Uncategorized(minor),
+            MajorDeviceClass::Other(major) => Self::Other { major, minor },
+        }
+    }
+
+    /// Major device class.
+    pub fn major(&self) -> MajorDeviceClass {
+        match self {
+            Self::Miscellaneous(_) => MajorDeviceClass::Miscellaneous,
+            Self::Computer(_) => MajorDeviceClass::Computer,
+            Self::Phone(_) => MajorDeviceClass::Phone,
+            Self::NetworkAccessPoint(_) => MajorDeviceClass::NetworkAccessPoint,
+            Self::AudioVideo(_) => MajorDeviceClass::AudioVideo,
+            Self::Peripheral(_) => MajorDeviceClass::Peripheral,
+            Self::Imaging(_) => MajorDeviceClass::Imaging,
+            Self::Wearable(_) => MajorDeviceClass::Wearable,
+            Self::Toy(_) => MajorDeviceClass::Toy,
+            Self::Health(_) => MajorDeviceClass::Health,
+            Self::Uncategorized(_) => MajorDeviceClass::Uncategorized,
+            Self::Other { major, .. } => MajorDeviceClass::Other(*major),
+        }
+    }
+
+    /// 6-bit minor device class.
+    pub fn minor(&self) -> u8 {
+        let minor = match *self {
+            Self::Miscellaneous(minor) => minor,
+            Self::Computer(minor) => minor.into(),
+            Self::Phone(minor) => minor.into(),
+            Self::NetworkAccessPoint(minor) => minor.into(),
+            Self::AudioVideo(minor) => minor.into(),
+            Self::Peripheral(minor) => minor.into(),
+            Self::Imaging(minor) => minor.into(),
+            Self::Wearable(minor) => minor.into(),
+            Self::Toy(minor) => minor.into(),
+            Self::Health(minor) => minor.into(),
+            Self::Uncategorized(minor) => minor,
+            Self::Other { minor, .. } => minor,
+        };
+        minor & 0x3f
+    }
+}
+
+impl fmt::Display for DeviceClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.major())?;
+        match self {
+            Self::Miscellaneous(_) | Self::Uncategorized(_) | Self::Other { .. } => Ok(()),
+            Self::Computer(minor) => write!(f, ": {minor}"),
+            Self::Phone(minor) => write!(f, ": {minor}"),
+            Self::NetworkAccessPoint(minor) => write!(f, ": {minor}"),
+            Self::AudioVideo(minor) => write!(f, ": {minor}"),
+            Self::Peripheral(minor) => write!(f, ": {minor}"),
+            Self::Imaging(minor) => write!(f, ": {minor}"),
+            Self::Wearable(minor) => write!(f, ": {minor}"),
+            Self::Toy(minor) => write!(f, ": {minor}"),
+            Self::Health(minor) => write!(f, ": {minor}"),
+        }
+    }
+}
+
+/// Class of Device of a classic Bluetooth (BR/EDR) device.
+///
+/// Converts from and into the raw 24-bit value using [From].
+#[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ClassOfDevice {
+    /// Major service classes.
+    pub service_classes: BTreeSet<MajorServiceClass>,
+    /// Major and minor device class.
+    pub device_class: DeviceClass,
+}
+
+impl From<u32> for ClassOfDevice {
+    fn from(value: u32) -> Self {
+        Self {
+            service_classes: MajorServiceClass::iter().filter(|sc| value & sc.bit() != 0).collect(),
+            device_class: DeviceClass::from_major_minor((value >> 8) as u8, (value >> 2) as u8),
+        }
+    }
+}
+
+impl From<ClassOfDevice> for u32 {
+    fn from(value: ClassOfDevice) -> Self {
+        let mut v =
+            (u8::from(value.device_class.major()) as u32 & 0x1f) << 8 | (value.device_class.minor() as u32) << 2;
+        for sc in &value.service_classes {
+            v |= sc.bit();
+        }
+        v
+    }
+}
+
+impl fmt::Display for ClassOfDevice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.device_class)?;
+        if !self.service_classes.is_empty() {
+            let names: Vec<_> = self.service_classes.iter().map(|sc| sc.to_string()).collect();
+            write!(f, " ({})", names.join(", "))?;
+        }
+        Ok(())
+    }
+}
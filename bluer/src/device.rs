@@ -11,18 +11,45 @@ use std::{
     fmt,
     sync::Arc,
 };
+use strum::{Display, EnumString};
 use tokio::{sync::oneshot, time::sleep};
 use uuid::Uuid;
 
+#[cfg(feature = "media")]
+use crate::media;
 use crate::{
     all_dbus_objects,
+    class::ClassOfDevice,
     gatt::{self, remote::Service, SERVICE_INTERFACE},
-    Adapter, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result, SessionInner,
-    SERVICE_NAME, TIMEOUT,
+    network, Adapter, Address, AddressType, Error, ErrorKind, Event, InternalErrorKind, Modalias, Result,
+    SessionInner,
 };
 
 pub(crate) const INTERFACE: &str = "org.bluez.Device1";
 pub(crate) const BATTERY_INTERFACE: &str = "org.bluez.Battery1";
+pub(crate) const ADMIN_POLICY_STATUS_INTERFACE: &str = "org.bluez.AdminPolicyStatus1";
+pub(crate) const INPUT_INTERFACE: &str = "org.bluez.Input1";
+
+/// Reconnect mode of a human interface device (HID).
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReconnectMode {
+    /// Device and host are not required to automatically restore the connection.
+    #[strum(serialize = "none")]
+    None,
+    /// Bluetooth HID host restores connection.
+    #[strum(serialize = "host")]
+    Host,
+    /// Bluetooth HID device restores connection.
+    #[strum(serialize = "device")]
+    Device,
+    /// Bluetooth HID device shall attempt to restore the lost connection,
+    /// but Bluetooth HID host may also restore the connection.
+    #[strum(serialize = "any")]
+    Any,
+}
 
 /// Interface to a Bluetooth device.
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
@@ -47,7 +74,7 @@ impl Device {
     }
 
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
-        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
     }
 
     pub(crate) fn dbus_path(adapter_name: &str, address: Address) -> Result<Path<'static>> {
@@ -94,10 +121,28 @@ impl Device {
     pub async fn events(&self) -> Result<impl Stream<Item = DeviceEvent>> {
         let events = self.inner.events(self.dbus_path.clone(), false).await?;
         let stream = events.flat_map(move |event| match event {
-            Event::PropertiesChanged { changed, .. } => {
-                stream::iter(DeviceProperty::from_prop_map(changed).into_iter().map(DeviceEvent::PropertyChanged))
-                    .boxed()
+            Event::PropertiesChanged { interface, changed, invalidated, .. } => {
+                let invalidated = invalidated
+                    .iter()
+                    .filter_map(|name| DevicePropertyKind::from_dbus_name(&interface, name))
+                    .map(DeviceEvent::PropertyInvalidated);
+                stream::iter(
+                    DeviceProperty::from_prop_map(changed)
+                        .into_iter()
+                        .map(DeviceEvent::PropertyChanged)
+                        .chain(invalidated)
+                        .collect::<Vec<_>>(),
+                )
+                .boxed()
             }
+            Event::ObjectRemoved { interfaces, .. } => stream::iter(
+                interfaces
+                    .iter()
+                    .flat_map(|interface| DevicePropertyKind::of_interface(interface))
+                    .map(DeviceEvent::PropertyInvalidated)
+                    .collect::<Vec<_>>(),
+            )
+            .boxed(),
             _ => stream::empty().boxed(),
         });
 
@@ -114,7 +159,7 @@ impl Device {
             return Err(Error::new(ErrorKind::ServicesUnresolved));
         }
 
-        let timeout = sleep(TIMEOUT).fuse();
+        let timeout = sleep(self.inner.timeout).fuse();
         pin_mut!(timeout);
 
         loop {
@@ -136,6 +181,24 @@ impl Device {
         Err(Error::new(ErrorKind::ServicesUnresolved))
     }
 
+    /// The decoded Bluetooth class of device of the remote device.
+    ///
+    /// This is a typed representation of [Device::class].
+    pub async fn class_of_device(&self) -> Result<Option<ClassOfDevice>> {
+        Ok(self.class().await?.map(ClassOfDevice::from))
+    }
+
+    /// The external appearance of the remote device as an assigned appearance value.
+    ///
+    /// This is a typed representation of [Device::appearance].
+    /// `None` is returned if the device does not provide an appearance
+    /// or its value is not assigned.
+    #[cfg(feature = "id")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "id")))]
+    pub async fn known_appearance(&self) -> Result<Option<crate::id::Appearance>> {
+        Ok(self.appearance().await?.and_then(|v| crate::id::Appearance::try_from(v).ok()))
+    }
+
     /// Remote GATT services.
     ///
     /// The device must be connected for GATT services to be resolved.
@@ -143,7 +206,7 @@ impl Device {
         self.wait_for_services_resolved().await?;
 
         let mut services = Vec::new();
-        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
             match Service::parse_dbus_path(&path) {
                 Some((adapter, device_address, id))
                     if adapter == *self.adapter_name
@@ -159,12 +222,42 @@ impl Device {
         Ok(services)
     }
 
+    /// Media players of the remote device.
+    ///
+    /// Media players are available while the Audio/Video Remote Control
+    /// Profile (AVRCP) is connected.
+    #[cfg(feature = "media")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+    pub async fn media_players(&self) -> Result<Vec<media::MediaPlayer>> {
+        let mut players = Vec::new();
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
+            match Self::parse_dbus_path_prefix(&path) {
+                Some(((adapter, device_address), rest))
+                    if adapter == *self.adapter_name
+                        && device_address == self.address
+                        && rest.starts_with("/player")
+                        && interfaces.contains_key(media::PLAYER_INTERFACE) =>
+                {
+                    players.push(media::MediaPlayer::new(self.inner.clone(), path.clone()));
+                }
+                _ => (),
+            }
+        }
+
+        Ok(players)
+    }
+
     /// Remote GATT service with specified id.
     pub async fn service(&self, service_id: u16) -> Result<gatt::remote::Service> {
         gatt::remote::Service::new(self.inner.clone(), self.adapter_name.clone(), self.address, service_id)
     }
 
-    dbus_interface!();
+    /// Network service of the remote device for Personal Area Networking (PAN).
+    pub fn network(&self) -> network::Network {
+        network::Network::new(self.inner.clone(), self.dbus_path.clone())
+    }
+
+    dbus_interface!(cached);
     dbus_default_interface!(INTERFACE);
 
     // ===========================================================================================
@@ -258,9 +351,11 @@ impl Device {
         let (done_tx, done_rx) = oneshot::channel();
         let dbus_path = self.dbus_path.clone();
         let connection = self.inner.connection.clone();
+        let service_name = self.inner.service_name.clone();
+        let timeout = self.inner.timeout;
         tokio::spawn(async move {
             if done_rx.await.is_err() {
-                let proxy = Proxy::new(SERVICE_NAME, dbus_path, TIMEOUT, &*connection);
+                let proxy = Proxy::new(service_name, dbus_path, timeout, &*connection);
                 let _: std::result::Result<(), dbus::Error> =
                     proxy.method_call(INTERFACE, "CancelPairing", ()).await;
             }
@@ -275,7 +370,15 @@ impl Device {
 define_properties!(
     Device,
     /// Bluetooth device property.
-    pub DeviceProperty => {
+    pub DeviceProperty,
+    /// Kind of a Bluetooth device property.
+    pub DevicePropertyKind,
+    /// Snapshot of all properties of a Bluetooth device.
+    ///
+    /// Obtain it using [Device::info] and keep it up to date by applying
+    /// the properties received from [DeviceEvent::PropertyChanged]
+    /// and invalidating those received from [DeviceEvent::PropertyInvalidated].
+    pub DeviceInfo => {
         /// The Bluetooth remote name.
         ///
         /// This value can not be
@@ -513,6 +616,8 @@ define_properties!(
         ///
         /// Note: Only types considered safe to be handled by
         /// application are exposed.
+        ///
+        /// Use [AdvertisingData::from_map](crate::ad::AdvertisingData::from_map) to decode it.
         property(
             AdvertisingData, HashMap<u8, Vec<u8>>,
             dbus: (INTERFACE, "AdvertisingData", HashMap<u8, Variant<Box<dyn RefArg  + 'static>>>, OPTIONAL),
@@ -533,14 +638,47 @@ define_properties!(
             dbus: (BATTERY_INTERFACE, "Percentage", u8, OPTIONAL),
             get: (battery_percentage, v => {v.to_owned()}),
         );
+
+        /// Indicates whether the remote device is affected by the admin policy,
+        /// i.e. whether it provides services that are not in the
+        /// [service allowlist](crate::Adapter::set_service_allow_list).
+        property(
+            AffectedByPolicy, bool,
+            dbus: (ADMIN_POLICY_STATUS_INTERFACE, "AffectedByPolicy", bool, OPTIONAL),
+            get: (is_affected_by_policy, v => {v.to_owned()}),
+        );
+
+        /// Reconnect mode of the remote human interface device (HID).
+        ///
+        /// Only available for devices connected as input devices.
+        property(
+            InputReconnectMode, ReconnectMode,
+            dbus: (INPUT_INTERFACE, "ReconnectMode", String, OPTIONAL),
+            get: (input_reconnect_mode, v => {v.parse()?}),
+        );
     }
 );
 
+#[cfg(feature = "id")]
+impl DeviceInfo {
+    /// The external appearance of the remote device as an assigned appearance value.
+    ///
+    /// `None` is returned if the device does not provide an appearance
+    /// or its value is not assigned.
+    #[cfg_attr(docsrs, doc(cfg(feature = "id")))]
+    pub fn known_appearance(&self) -> Option<crate::id::Appearance> {
+        self.appearance.and_then(|v| crate::id::Appearance::try_from(v).ok())
+    }
+}
+
 /// Bluetooth device event.
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Debug, Clone)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum DeviceEvent {
     /// Property changed.
     PropertyChanged(DeviceProperty),
+    /// Property invalidated, i.e. its value became unknown or it was removed.
+    PropertyInvalidated(DevicePropertyKind),
 }
@@ -0,0 +1,154 @@
+//! Coordinated set of remote Bluetooth devices.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{collections::HashSet, fmt, sync::Arc};
+
+use crate::{Adapter, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner};
+
+pub(crate) const INTERFACE: &str = "org.bluez.DeviceSet1";
+
+/// Interface to a coordinated set of Bluetooth devices.
+///
+/// A coordinated set groups devices that together form one product,
+/// for example the left and right earbud of a pair of LE Audio earbuds,
+/// as discovered using the Coordinated Set Identification Profile (CSIP).
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone)]
+pub struct DeviceSet {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+    adapter_name: Arc<String>,
+    id: String,
+}
+
+impl fmt::Debug for DeviceSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> std::fmt::Result {
+        write!(f, "DeviceSet {{ adapter_name: {}, id: {} }}", self.adapter_name(), self.id())
+    }
+}
+
+impl DeviceSet {
+    /// Create interface to the device set of specified id belonging to the specified adapter.
+    pub(crate) fn new(inner: Arc<SessionInner>, adapter_name: Arc<String>, id: &str) -> Result<Self> {
+        Ok(Self { inner, dbus_path: Self::dbus_path(&adapter_name, id)?, adapter_name, id: id.to_string() })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
+    }
+
+    pub(crate) fn dbus_path(adapter_name: &str, id: &str) -> Result<Path<'static>> {
+        let adapter_path = Adapter::dbus_path(adapter_name)?;
+        Path::new(format!("{}/set_{}", adapter_path, id))
+            .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))
+    }
+
+    pub(crate) fn parse_dbus_path<'a>(path: &'a Path) -> Option<(&'a str, &'a str)> {
+        match Adapter::parse_dbus_path_prefix(path) {
+            Some((adapter_name, p)) => match p.strip_prefix("/set_") {
+                Some(id) if !id.is_empty() && !id.contains('/') => Some((adapter_name, id)),
+                _ => None,
+            },
+            None => None,
+        }
+    }
+
+    /// The Bluetooth adapter name.
+    pub fn adapter_name(&self) -> &str {
+        &self.adapter_name
+    }
+
+    /// The identifier of the device set.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    /// Interfaces to the member devices of the set.
+    pub async fn devices(&self) -> Result<Vec<Device>> {
+        self.members()
+            .await?
+            .into_iter()
+            .map(|address| Device::new(self.inner.clone(), self.adapter_name.clone(), address))
+            .collect()
+    }
+
+    /// Streams device set property changes.
+    ///
+    /// Changes of set membership are delivered as [DeviceSetProperty::Members].
+    /// The stream ends when the device set is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = DeviceSetEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { changed, .. } => stream::iter(
+                DeviceSetProperty::from_prop_map(changed).into_iter().map(DeviceSetEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    dbus_interface!(cached);
+    dbus_default_interface!(INTERFACE);
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Connects all members of the set.
+    pub async fn connect(&self) -> Result<()> {
+        self.call_method("Connect", ()).await
+    }
+
+    /// Disconnects all members of the set.
+    pub async fn disconnect(&self) -> Result<()> {
+        self.call_method("Disconnect", ()).await
+    }
+}
+
+define_properties!(
+    DeviceSet,
+    /// Device set property.
+    pub DeviceSetProperty => {
+        /// Indicates whether the members of the set are connected
+        /// automatically once one member is connected.
+        property(
+            AutoConnect, bool,
+            dbus: (INTERFACE, "AutoConnect", bool, MANDATORY),
+            get: (is_auto_connect, v => {v.to_owned()}),
+            set: (set_auto_connect, v => {v}),
+        );
+
+        /// Addresses of the devices that are known members of the set.
+        property(
+            Members, HashSet<Address>,
+            dbus: (INTERFACE, "Devices", Vec<Path<'static>>, MANDATORY),
+            get: (members, v => {
+                v.iter().filter_map(|path| Device::parse_dbus_path(path).map(|(_, address)| address)).collect()
+            }),
+        );
+
+        /// Number of devices belonging to the set.
+        ///
+        /// This may be larger than the number of known members.
+        property(
+            Size, u8,
+            dbus: (INTERFACE, "Size", u8, MANDATORY),
+            get: (size, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// Device set event.
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DeviceSetEvent {
+    /// Property changed.
+    PropertyChanged(DeviceSetProperty),
+}
@@ -30,7 +30,7 @@ use super::{
 };
 use crate::{
     method_call, parent_path, Adapter, Address, DbusResult, Device, Error, ErrorKind, Result, SessionInner,
-    ERR_PREFIX, SERVICE_NAME, TIMEOUT,
+    ERR_PREFIX,
 };
 
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.GattManager1";
@@ -620,7 +620,7 @@ impl CharacteristicWriteIoRequest {
         let CharacteristicWriteIoRequest { adapter_name, device_address, mtu, tx, .. } = self;
         let (fd, socket) = make_socket_pair(false)?;
         let _ = tx.send(Ok(fd));
-        Ok(CharacteristicReader { adapter_name, device_address, mtu: mtu.into(), socket, buf: Vec::new() })
+        Ok(CharacteristicReader { adapter_name, device_address, mtu: mtu.into(), socket })
     }
 
     /// Reject the write request.
@@ -1348,16 +1348,20 @@ impl Application {
         }
 
         log::trace!("Registering application at {}", &app_path);
-        let proxy =
-            Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
-        let () = proxy
-            .method_call(MANAGER_INTERFACE, "RegisterApplication", (app_path.clone(), PropMap::new()))
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let reg_app_path = app_path.clone();
+        inner
+            .register(&app_path, adapter_path.clone(), MANAGER_INTERFACE, "RegisterApplication", move || {
+                (reg_app_path.clone(), PropMap::new())
+            })
             .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let app_path_unreg = app_path.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            inner.forget_registrations(&app_path_unreg).await;
 
             log::trace!("Unregistering application at {}", &app_path_unreg);
             let _: std::result::Result<(), dbus::Error> =
@@ -1437,16 +1441,20 @@ impl Profile {
         }
 
         log::trace!("Registering profile at {}", &profile_path);
-        let proxy =
-            Proxy::new(SERVICE_NAME, Adapter::dbus_path(&adapter_name)?, TIMEOUT, inner.connection.clone());
-        let () = proxy
-            .method_call(MANAGER_INTERFACE, "RegisterApplication", (profile_path.clone(), PropMap::new()))
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let profile_path_reg = profile_path.clone();
+        inner
+            .register(&profile_path, adapter_path.clone(), MANAGER_INTERFACE, "RegisterApplication", move || {
+                (profile_path_reg.clone(), PropMap::new())
+            })
             .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let profile_path_unreg = profile_path.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            inner.forget_registrations(&profile_path_unreg).await;
 
             log::trace!("Unregistering profile at {}", &profile_path_unreg);
             let _: std::result::Result<(), dbus::Error> = proxy
@@ -1,25 +1,20 @@
 //! Local and remote GATT services.
 
 use dbus::arg::OwnedFd;
-use futures::ready;
 use libc::{AF_LOCAL, SOCK_CLOEXEC, SOCK_NONBLOCK, SOCK_SEQPACKET};
-use pin_project::pin_project;
 use std::{
-    mem::MaybeUninit,
     os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
     pin::Pin,
     task::{Context, Poll},
 };
 use strum::{Display, EnumString};
-use tokio::{
-    io::{AsyncRead, AsyncWrite, ReadBuf},
-    net::UnixDatagram,
-};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
-use crate::Address;
+use crate::{seq_packet::SeqPacket, Address};
 
 pub mod local;
 pub mod remote;
+pub mod value;
 
 pub(crate) const SERVICE_INTERFACE: &str = "org.bluez.GattService1";
 pub(crate) const CHARACTERISTIC_INTERFACE: &str = "org.bluez.GattCharacteristic1";
@@ -105,15 +100,12 @@ impl Default for WriteOp {
 }
 
 /// Streams data from a characteristic with low overhead.
-#[pin_project]
 #[derive(Debug)]
 pub struct CharacteristicReader {
     adapter_name: String,
     device_address: Address,
     mtu: usize,
-    #[pin]
-    socket: UnixDatagram,
-    buf: Vec<u8>,
+    socket: SeqPacket,
 }
 
 impl CharacteristicReader {
@@ -141,28 +133,19 @@ impl CharacteristicReader {
     ///
     /// Does not wait for new data to arrive.
     pub fn try_recv(&self) -> std::io::Result<Vec<u8>> {
-        let mut buf = Vec::with_capacity(self.mtu);
-        let n = self.socket.try_recv_buf(&mut buf)?;
-        buf.truncate(n);
-        Ok(buf)
+        self.socket.try_recv(self.mtu)
     }
 
     /// Receive the characteristic value from a single notify or write operation.
     ///
     /// Waits for data to arrive.
     pub async fn recv(&self) -> std::io::Result<Vec<u8>> {
-        loop {
-            self.recvable().await?;
-            match self.try_recv() {
-                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
-                res => return res,
-            }
-        }
+        self.socket.recv(self.mtu).await
     }
 
     /// Consumes this object, returning the raw underlying file descriptor.
     pub fn into_raw_fd(self) -> std::io::Result<RawFd> {
-        Ok(self.socket.into_std()?.into_raw_fd())
+        Ok(self.socket.into_raw_fd())
     }
 }
 
@@ -174,35 +157,9 @@ impl AsyncRead for CharacteristicReader {
     /// Thus, for best efficiency, provide a buffer of at least [mtu] bytes.
     ///
     /// [mtu]: CharacteristicReader::mtu
-    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
-        let buf_space = buf.remaining();
-        if !self.buf.is_empty() {
-            // Return buffered data first, if any.
-            let to_read = buf_space.min(self.buf.len());
-            let remaining = self.buf.split_off(to_read);
-            buf.put_slice(&self.buf);
-            self.buf = remaining;
-            Poll::Ready(Ok(()))
-        } else if buf_space < self.mtu {
-            let this = self.project();
-
-            // If provided buffer is too small, read into temporary buffer.
-            let mut mtu_buf: Vec<MaybeUninit<u8>> = vec![MaybeUninit::uninit(); *this.mtu];
-            let mut mtu_read_buf = ReadBuf::uninit(&mut mtu_buf);
-            ready!(this.socket.poll_recv(cx, &mut mtu_read_buf))?;
-            let n = mtu_read_buf.filled().len();
-            mtu_buf.truncate(n);
-            let mut mtu_buf: Vec<u8> = mtu_buf.into_iter().map(|v| unsafe { v.assume_init() }).collect();
-
-            // Then fill provided buffer appropriately and keep the rest in
-            // our internal buffer.
-            *this.buf = mtu_buf.split_off(buf_space.min(n));
-            buf.put_slice(&mtu_buf);
-
-            Poll::Ready(Ok(()))
-        } else {
-            self.project().socket.poll_recv(cx, buf)
-        }
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        this.socket.poll_read(cx, buf, this.mtu)
     }
 }
 
@@ -219,14 +176,12 @@ impl IntoRawFd for CharacteristicReader {
 }
 
 /// Streams data to a characteristic with low overhead.
-#[pin_project]
 #[derive(Debug)]
 pub struct CharacteristicWriter {
     adapter_name: String,
     device_address: Address,
     mtu: usize,
-    #[pin]
-    socket: UnixDatagram,
+    socket: SeqPacket,
 }
 
 impl CharacteristicWriter {
@@ -253,7 +208,7 @@ impl CharacteristicWriter {
     /// Checks if the remote device has stopped the notification session.
     pub fn is_closed(&self) -> std::io::Result<bool> {
         let mut buf = [0u8];
-        match self.socket.try_recv(&mut buf) {
+        match self.socket.try_recv_into(&mut buf) {
             Ok(_) => Ok(true),
             Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => Ok(false),
             Err(err) => Err(err),
@@ -271,14 +226,7 @@ impl CharacteristicWriter {
     ///
     /// Does not wait for send space to become available.
     pub fn try_send(&self, buf: &[u8]) -> std::io::Result<()> {
-        if buf.len() > self.mtu {
-            return Err(std::io::Error::new(std::io::ErrorKind::WriteZero, "data length exceeds MTU"));
-        }
-        match self.socket.try_send(buf) {
-            Ok(n) if n == buf.len() => Ok(()),
-            Ok(_) => Err(std::io::Error::other("partial write occured")),
-            Err(err) => Err(err),
-        }
+        self.socket.try_send(buf, self.mtu)
     }
 
     /// Send the characteristic value using a single write or notify operation.
@@ -287,18 +235,12 @@ impl CharacteristicWriter {
     ///
     /// Waits for send space to become available.
     pub async fn send(&self, buf: &[u8]) -> std::io::Result<()> {
-        loop {
-            self.sendable().await?;
-            match self.try_send(buf) {
-                Err(err) if err.kind() == std::io::ErrorKind::WouldBlock => continue,
-                res => return res,
-            }
-        }
+        self.socket.send(buf, self.mtu).await
     }
 
     /// Consumes this object, returning the raw underlying file descriptor.
     pub fn into_raw_fd(self) -> std::io::Result<RawFd> {
-        Ok(self.socket.into_std()?.into_raw_fd())
+        Ok(self.socket.into_raw_fd())
     }
 }
 
@@ -308,9 +250,7 @@ impl AsyncWrite for CharacteristicWriter {
     /// A single write operation will send no more than [mtu](CharacteristicWriter::mtu) bytes.
     /// However, attempting to send a larger buffer will not result in an error but a partial send.
     fn poll_write(self: Pin<&mut Self>, cx: &mut std::task::Context, buf: &[u8]) -> Poll<std::io::Result<usize>> {
-        let max_len = buf.len().min(self.mtu);
-        let buf = &buf[..max_len];
-        self.project().socket.poll_send(cx, buf)
+        self.socket.poll_write(cx, buf, self.mtu)
     }
 
     fn poll_flush(self: Pin<&mut Self>, _cx: &mut std::task::Context) -> Poll<std::io::Result<()>> {
@@ -335,7 +275,7 @@ impl IntoRawFd for CharacteristicWriter {
 }
 
 /// Creates a UNIX socket pair for communication with bluetoothd.
-pub(crate) fn make_socket_pair(non_block: bool) -> std::io::Result<(OwnedFd, UnixDatagram)> {
+pub(crate) fn make_socket_pair(non_block: bool) -> std::io::Result<(OwnedFd, SeqPacket)> {
     let mut sv: [RawFd; 2] = [0; 2];
     let mut ty = SOCK_SEQPACKET | SOCK_CLOEXEC;
     if non_block {
@@ -347,12 +287,9 @@ pub(crate) fn make_socket_pair(non_block: bool) -> std::io::Result<(OwnedFd, Uni
     let [fd1, fd2] = sv;
 
     let fd1 = unsafe { OwnedFd::new(fd1) };
-    let us = unsafe { std::os::unix::net::UnixDatagram::from_raw_fd(fd2) };
-
-    us.set_nonblocking(true)?;
-    let us = UnixDatagram::from_std(us)?;
+    let sp = SeqPacket::new(unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd2) })?;
 
-    Ok((fd1, us))
+    Ok((fd1, sp))
 }
 
 /// Apply MTU workaround.
@@ -7,16 +7,15 @@ use dbus::{
 };
 use futures::{Stream, StreamExt};
 use std::{fmt, os::unix::prelude::FromRawFd, sync::Arc};
-use tokio::net::UnixDatagram;
 use uuid::Uuid;
 
 use super::{
-    mtu_workaround, CharacteristicFlags, CharacteristicReader, CharacteristicWriter, WriteOp,
-    CHARACTERISTIC_INTERFACE, DESCRIPTOR_INTERFACE, SERVICE_INTERFACE,
+    mtu_workaround, value::AssignedCharacteristic, CharacteristicFlags, CharacteristicReader,
+    CharacteristicWriter, WriteOp, CHARACTERISTIC_INTERFACE, DESCRIPTOR_INTERFACE, SERVICE_INTERFACE,
 };
 use crate::{
-    all_dbus_objects, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner,
-    SingleSessionToken, SERVICE_NAME, TIMEOUT,
+    all_dbus_objects, seq_packet::SeqPacket, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result,
+    SessionInner, SingleSessionToken,
 };
 
 // ===========================================================================================
@@ -59,7 +58,7 @@ impl Service {
     }
 
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
-        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
     }
 
     pub(crate) fn dbus_path(adapter_name: &str, device_address: Address, id: u16) -> Result<Path<'static>> {
@@ -110,7 +109,7 @@ impl Service {
     /// GATT characteristics belonging to this service.
     pub async fn characteristics(&self) -> Result<Vec<Characteristic>> {
         let mut chars = Vec::new();
-        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
             match Characteristic::parse_dbus_path(&path) {
                 Some((adapter, device_address, service_id, id))
                     if adapter == *self.adapter_name
@@ -137,7 +136,7 @@ impl Service {
         )
     }
 
-    dbus_interface!();
+    dbus_interface!(cached);
     dbus_default_interface!(SERVICE_INTERFACE);
 }
 
@@ -216,7 +215,7 @@ impl Characteristic {
     }
 
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
-        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
     }
 
     pub(crate) fn dbus_path(
@@ -275,7 +274,7 @@ impl Characteristic {
     /// GATT descriptors belonging to this characteristic.
     pub async fn descriptors(&self) -> Result<Vec<Descriptor>> {
         let mut chars = Vec::new();
-        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
             match Descriptor::parse_dbus_path(&path) {
                 Some((adapter, device_address, service_id, char_id, id))
                     if adapter == *self.adapter_name
@@ -334,6 +333,33 @@ impl Characteristic {
         Ok(())
     }
 
+    /// Issues a request to read the value of the characteristic
+    /// and decodes it as the assigned characteristic `T`.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the UUID of the characteristic
+    /// does not match the UUID of `T`.
+    pub async fn read_typed<T: AssignedCharacteristic>(&self) -> Result<T> {
+        self.check_uuid::<T>().await?;
+        Ok(T::decode(&self.read().await?)?)
+    }
+
+    /// Issues a request to write the value of the characteristic
+    /// encoded from the assigned characteristic `T`.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the UUID of the characteristic
+    /// does not match the UUID of `T`.
+    pub async fn write_typed<T: AssignedCharacteristic>(&self, value: &T) -> Result<()> {
+        self.check_uuid::<T>().await?;
+        self.write(&value.encode()).await
+    }
+
+    async fn check_uuid<T: AssignedCharacteristic>(&self) -> Result<()> {
+        if self.uuid().await? != T::UUID {
+            return Err(Error::new(ErrorKind::InvalidArguments));
+        }
+        Ok(())
+    }
+
     /// Acquire writer for writing with low overhead.
     ///
     /// It only works with characteristic that has
@@ -351,9 +377,7 @@ impl Characteristic {
     pub async fn write_io(&self) -> Result<CharacteristicWriter> {
         let options = PropMap::new();
         let (fd, mtu): (OwnedFd, u16) = self.call_method("AcquireWrite", (options,)).await?;
-        let socket = unsafe { std::os::unix::net::UnixDatagram::from_raw_fd(fd.into_fd()) };
-        socket.set_nonblocking(true)?;
-        let socket = UnixDatagram::from_std(socket)?;
+        let socket = SeqPacket::new(unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd.into_fd()) })?;
         let mtu = mtu_workaround(mtu.into());
         Ok(CharacteristicWriter {
             adapter_name: self.adapter_name().to_string(),
@@ -386,9 +410,22 @@ impl Characteristic {
         Ok(values)
     }
 
+    /// Starts a notification session from this characteristic
+    /// and decodes the notified values as the assigned characteristic `T`.
+    ///
+    /// Fails with [ErrorKind::InvalidArguments] if the UUID of the characteristic
+    /// does not match the UUID of `T`.
+    /// Values that cannot be decoded are delivered as errors.
+    pub async fn notify_typed<T: AssignedCharacteristic>(&self) -> Result<impl Stream<Item = Result<T>>> {
+        self.check_uuid::<T>().await?;
+        Ok(self.notify().await?.map(|value| Ok(T::decode(&value)?)))
+    }
+
     async fn notify_session(&self) -> Result<SingleSessionToken> {
         let dbus_path = self.dbus_path.clone();
         let connection = self.inner.connection.clone();
+        let service_name = self.inner.service_name.clone();
+        let timeout = self.inner.timeout;
         self.inner
             .single_session(
                 &self.dbus_path,
@@ -397,11 +434,11 @@ impl Characteristic {
                     Ok(())
                 },
                 async move {
-                    log::trace!("{}: {}.StopNotify ()", &dbus_path, SERVICE_NAME);
-                    let proxy = Proxy::new(SERVICE_NAME, &dbus_path, TIMEOUT, &*connection);
+                    log::trace!("{}: {}.StopNotify ()", &dbus_path, &service_name);
+                    let proxy = Proxy::new(service_name.clone(), &dbus_path, timeout, &*connection);
                     let result: std::result::Result<(), dbus::Error> =
                         proxy.method_call(CHARACTERISTIC_INTERFACE, "StopNotify", ()).await;
-                    log::trace!("{}: {}.StopNotify () -> {:?}", &dbus_path, SERVICE_NAME, &result);
+                    log::trace!("{}: {}.StopNotify () -> {:?}", &dbus_path, &service_name, &result);
                 },
             )
             .await
@@ -431,19 +468,16 @@ impl Characteristic {
     pub async fn notify_io(&self) -> Result<CharacteristicReader> {
         let options = PropMap::new();
         let (fd, mtu): (OwnedFd, u16) = self.call_method("AcquireNotify", (options,)).await?;
-        let socket = unsafe { std::os::unix::net::UnixDatagram::from_raw_fd(fd.into_fd()) };
-        socket.set_nonblocking(true)?;
-        let socket = UnixDatagram::from_std(socket)?;
+        let socket = SeqPacket::new(unsafe { std::os::unix::io::OwnedFd::from_raw_fd(fd.into_fd()) })?;
         Ok(CharacteristicReader {
             adapter_name: self.adapter_name().to_string(),
             device_address: self.device_address,
             mtu: mtu.into(),
             socket,
-            buf: Vec::new(),
         })
     }
 
-    dbus_interface!();
+    dbus_interface!(cached);
     dbus_default_interface!(CHARACTERISTIC_INTERFACE);
 }
 
@@ -585,7 +619,7 @@ impl Descriptor {
     }
 
     fn proxy(&self) -> Proxy<'_, &SyncConnection> {
-        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
     }
 
     pub(crate) fn dbus_path(
@@ -648,7 +682,7 @@ impl Descriptor {
         self.id
     }
 
-    dbus_interface!();
+    dbus_interface!(cached);
     dbus_default_interface!(DESCRIPTOR_INTERFACE);
 
     /// Issues a request to read the value of the
@@ -0,0 +1,704 @@
+//! Typed values of GATT characteristics.
+//!
+//! Values of characteristics are transferred as bytes.
+//! This provides the [Decode] and [Encode] traits for converting between
+//! bytes and typed values, including the IEEE-11073 [SFloat] and [Float]
+//! medical number formats.
+//!
+//! Commonly used characteristics assigned by the Bluetooth SIG implement
+//! [AssignedCharacteristic], which associates them with their UUID.
+//! They can be read from a remote device using
+//! [Characteristic::read_typed](super::remote::Characteristic::read_typed).
+
+use std::fmt;
+use uuid::Uuid;
+
+use crate::{Error, ErrorKind, InternalErrorKind};
+
+/// Returns the UUID of an assigned 16-bit characteristic UUID.
+const fn assigned(uuid: u16) -> Uuid {
+    Uuid::from_u128(0x00000000_0000_1000_8000_00805f9b34fb | (uuid as u128) << 96)
+}
+
+/// Invalid characteristic value.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvalidValue(pub String);
+
+impl fmt::Display for InvalidValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid characteristic value: {}", &self.0)
+    }
+}
+
+impl std::error::Error for InvalidValue {}
+
+impl From<InvalidValue> for Error {
+    fn from(err: InvalidValue) -> Self {
+        Self { kind: ErrorKind::Internal(InternalErrorKind::InvalidValue), message: err.0 }
+    }
+}
+
+/// A type that can be decoded from a characteristic value.
+pub trait Decode: Sized {
+    /// Decodes the value.
+    ///
+    /// Additional bytes following the value are ignored.
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue>;
+}
+
+/// A type that can be encoded into a characteristic value.
+pub trait Encode {
+    /// Appends the encoded value to the buffer.
+    fn encode_into(&self, buf: &mut Vec<u8>);
+
+    /// Encodes the value.
+    fn encode(&self) -> Vec<u8> {
+        let mut buf = Vec::new();
+        self.encode_into(&mut buf);
+        buf
+    }
+}
+
+/// A characteristic assigned by the Bluetooth SIG.
+pub trait AssignedCharacteristic: Decode + Encode {
+    /// Characteristic UUID.
+    const UUID: Uuid;
+
+    /// The characteristic in the database of assigned numbers.
+    #[cfg(feature = "id")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "id")))]
+    fn characteristic() -> Option<crate::id::Characteristic> {
+        crate::id::Characteristic::try_from(Self::UUID).ok()
+    }
+}
+
+/// Reader of fields of a characteristic value.
+struct Reader<'a>(&'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn bytes(&mut self, n: usize) -> Result<&'a [u8], InvalidValue> {
+        if self.0.len() < n {
+            return Err(InvalidValue(format!("expected {} more bytes but got {}", n, self.0.len())));
+        }
+        let (bytes, rest) = self.0.split_at(n);
+        self.0 = rest;
+        Ok(bytes)
+    }
+
+    fn read<T: Decode + Sized>(&mut self, n: usize) -> Result<T, InvalidValue> {
+        T::decode(self.bytes(n)?)
+    }
+
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
+macro_rules! impl_int {
+    ($($t:ty),*) => {
+        $(
+            impl Decode for $t {
+                fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+                    let bytes = Reader(data).bytes(std::mem::size_of::<$t>())?;
+                    Ok(<$t>::from_le_bytes(bytes.try_into().unwrap()))
+                }
+            }
+
+            impl Encode for $t {
+                fn encode_into(&self, buf: &mut Vec<u8>) {
+                    buf.extend_from_slice(&self.to_le_bytes());
+                }
+            }
+        )*
+    };
+}
+
+impl_int!(u8, i8, u16, i16, u32, i32, u64, i64);
+
+impl Decode for String {
+    /// Decodes an UTF-8 string, removing trailing null characters.
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let s = std::str::from_utf8(data).map_err(|err| InvalidValue(err.to_string()))?;
+        Ok(s.trim_end_matches('\0').to_string())
+    }
+}
+
+impl Encode for String {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.extend_from_slice(self.as_bytes());
+    }
+}
+
+/// IEEE-11073 16-bit floating point number (SFLOAT).
+///
+/// The value is `mantissa * 10^exponent` with a 12-bit signed mantissa and
+/// a 4-bit signed exponent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SFloat {
+    /// Mantissa between -2048 and 2047.
+    pub mantissa: i16,
+    /// Exponent between -8 and 7.
+    pub exponent: i8,
+}
+
+impl SFloat {
+    /// Not a number.
+    pub const NAN: Self = Self { mantissa: 0x07ff, exponent: 0 };
+    /// Not at this resolution.
+    pub const NRES: Self = Self { mantissa: -0x0800, exponent: 0 };
+    /// Positive infinity.
+    pub const INFINITY: Self = Self { mantissa: 0x07fe, exponent: 0 };
+    /// Negative infinity.
+    pub const NEG_INFINITY: Self = Self { mantissa: -0x07fe, exponent: 0 };
+
+    const MAX_MANTISSA: i64 = 0x07fd;
+
+    /// Creates a number from mantissa and exponent.
+    pub const fn new(mantissa: i16, exponent: i8) -> Self {
+        Self { mantissa, exponent }
+    }
+
+    /// Converts a floating point number using the highest possible precision.
+    ///
+    /// Numbers too large to be represented are converted to infinity.
+    pub fn from_f64(value: f64) -> Self {
+        match from_f64(value, Self::MAX_MANTISSA, -8..=7) {
+            Ok((mantissa, exponent)) => Self { mantissa: mantissa as i16, exponent },
+            Err(Special::Nan) => Self::NAN,
+            Err(Special::Infinity) => Self::INFINITY,
+            Err(Special::NegInfinity) => Self::NEG_INFINITY,
+        }
+    }
+
+    /// Converts to a floating point number.
+    ///
+    /// Not a number, not at this resolution and reserved values are converted to NaN.
+    pub fn to_f64(self) -> f64 {
+        match (self.exponent, self.mantissa) {
+            (0, 0x07fe) => f64::INFINITY,
+            (0, -0x07fe) => f64::NEG_INFINITY,
+            (0, 0x07ff | -0x0800 | -0x07ff) => f64::NAN,
+            (exponent, mantissa) => mantissa as f64 * 10f64.powi(exponent as i32),
+        }
+    }
+}
+
+impl From<u16> for SFloat {
+    fn from(raw: u16) -> Self {
+        Self { mantissa: ((raw << 4) as i16) >> 4, exponent: ((raw as i16) >> 12) as i8 }
+    }
+}
+
+impl From<SFloat> for u16 {
+    fn from(value: SFloat) -> Self {
+        (value.exponent as u16 & 0x000f) << 12 | (value.mantissa as u16 & 0x0fff)
+    }
+}
+
+impl fmt::Display for SFloat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Decode for SFloat {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        Ok(u16::decode(data)?.into())
+    }
+}
+
+impl Encode for SFloat {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        u16::from(*self).encode_into(buf)
+    }
+}
+
+/// IEEE-11073 32-bit floating point number (FLOAT).
+///
+/// The value is `mantissa * 10^exponent` with a 24-bit signed mantissa and
+/// an 8-bit signed exponent.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Float {
+    /// Mantissa between -8388608 and 8388607.
+    pub mantissa: i32,
+    /// Exponent.
+    pub exponent: i8,
+}
+
+impl Float {
+    /// Not a number.
+    pub const NAN: Self = Self { mantissa: 0x007f_ffff, exponent: 0 };
+    /// Not at this resolution.
+    pub const NRES: Self = Self { mantissa: -0x0080_0000, exponent: 0 };
+    /// Positive infinity.
+    pub const INFINITY: Self = Self { mantissa: 0x007f_fffe, exponent: 0 };
+    /// Negative infinity.
+    pub const NEG_INFINITY: Self = Self { mantissa: -0x007f_fffe, exponent: 0 };
+
+    const MAX_MANTISSA: i64 = 0x007f_fffd;
+
+    /// Creates a number from mantissa and exponent.
+    pub const fn new(mantissa: i32, exponent: i8) -> Self {
+        Self { mantissa, exponent }
+    }
+
+    /// Converts a floating point number using the highest possible precision.
+    ///
+    /// Numbers too large to be represented are converted to infinity.
+    pub fn from_f64(value: f64) -> Self {
+        match from_f64(value, Self::MAX_MANTISSA, -128..=127) {
+            Ok((mantissa, exponent)) => Self { mantissa: mantissa as i32, exponent },
+            Err(Special::Nan) => Self::NAN,
+            Err(Special::Infinity) => Self::INFINITY,
+            Err(Special::NegInfinity) => Self::NEG_INFINITY,
+        }
+    }
+
+    /// Converts to a floating point number.
+    ///
+    /// Not a number, not at this resolution and reserved values are converted to NaN.
+    pub fn to_f64(self) -> f64 {
+        match (self.exponent, self.mantissa) {
+            (0, 0x007f_fffe) => f64::INFINITY,
+            (0, -0x007f_fffe) => f64::NEG_INFINITY,
+            (0, 0x007f_ffff | -0x0080_0000 | -0x007f_ffff) => f64::NAN,
+            (exponent, mantissa) => mantissa as f64 * 10f64.powi(exponent as i32),
+        }
+    }
+}
+
+impl From<u32> for Float {
+    fn from(raw: u32) -> Self {
+        Self { mantissa: ((raw << 8) as i32) >> 8, exponent: (raw >> 24) as i8 }
+    }
+}
+
+impl From<Float> for u32 {
+    fn from(value: Float) -> Self {
+        (value.exponent as u8 as u32) << 24 | (value.mantissa as u32 & 0x00ff_ffff)
+    }
+}
+
+impl fmt::Display for Float {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_f64())
+    }
+}
+
+impl Decode for Float {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        Ok(u32::decode(data)?.into())
+    }
+}
+
+impl Encode for Float {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        u32::from(*self).encode_into(buf)
+    }
+}
+
+enum Special {
+    Nan,
+    Infinity,
+    NegInfinity,
+}
+
+/// Finds the smallest exponent that allows representing the value within the mantissa range.
+fn from_f64(
+    value: f64, max_mantissa: i64, exponents: std::ops::RangeInclusive<i8>,
+) -> Result<(i64, i8), Special> {
+    if value.is_nan() {
+        return Err(Special::Nan);
+    }
+    if value.is_finite() {
+        for exponent in exponents {
+            let mantissa = (value / 10f64.powi(exponent as i32)).round();
+            if mantissa == 0. {
+                return Ok((0, 0));
+            }
+            if mantissa.abs() <= max_mantissa as f64 {
+                return Ok((mantissa as i64, exponent));
+            }
+        }
+    }
+    if value > 0. {
+        Err(Special::Infinity)
+    } else {
+        Err(Special::NegInfinity)
+    }
+}
+
+/// Date and time.
+///
+/// Fields that are zero are not known.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DateTime {
+    /// Year between 1582 and 9999.
+    pub year: u16,
+    /// Month between 1 and 12.
+    pub month: u8,
+    /// Day of month between 1 and 31.
+    pub day: u8,
+    /// Hours between 0 and 23.
+    pub hours: u8,
+    /// Minutes between 0 and 59.
+    pub minutes: u8,
+    /// Seconds between 0 and 59.
+    pub seconds: u8,
+}
+
+impl Decode for DateTime {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let mut r = Reader(data);
+        Ok(Self {
+            year: r.read(2)?,
+            month: r.read(1)?,
+            day: r.read(1)?,
+            hours: r.read(1)?,
+            minutes: r.read(1)?,
+            seconds: r.read(1)?,
+        })
+    }
+}
+
+impl Encode for DateTime {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        self.year.encode_into(buf);
+        buf.extend_from_slice(&[self.month, self.day, self.hours, self.minutes, self.seconds]);
+    }
+}
+
+impl AssignedCharacteristic for DateTime {
+    const UUID: Uuid = assigned(0x2a08);
+}
+
+macro_rules! newtype_characteristic {
+    ($(#[$attr:meta])* $name:ident, $t:ty, $uuid:expr) => {
+        $(#[$attr])*
+        #[derive(Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        pub struct $name(pub $t);
+
+        impl Decode for $name {
+            fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+                Ok(Self(<$t>::decode(data)?))
+            }
+        }
+
+        impl Encode for $name {
+            fn encode_into(&self, buf: &mut Vec<u8>) {
+                self.0.encode_into(buf)
+            }
+        }
+
+        impl AssignedCharacteristic for $name {
+            const UUID: Uuid = assigned($uuid);
+        }
+    };
+}
+
+newtype_characteristic!(
+    /// Device name.
+    DeviceName, String, 0x2a00
+);
+newtype_characteristic!(
+    /// Battery level in percent.
+    BatteryLevel, u8, 0x2a19
+);
+newtype_characteristic!(
+    /// Model number of the device.
+    ModelNumberString, String, 0x2a24
+);
+newtype_characteristic!(
+    /// Serial number of the device.
+    SerialNumberString, String, 0x2a25
+);
+newtype_characteristic!(
+    /// Firmware revision of the device.
+    FirmwareRevisionString, String, 0x2a26
+);
+newtype_characteristic!(
+    /// Hardware revision of the device.
+    HardwareRevisionString, String, 0x2a27
+);
+newtype_characteristic!(
+    /// Software revision of the device.
+    SoftwareRevisionString, String, 0x2a28
+);
+newtype_characteristic!(
+    /// Name of the manufacturer of the device.
+    ManufacturerNameString, String, 0x2a29
+);
+newtype_characteristic!(
+    /// Location of a body sensor.
+    ///
+    /// 0 = other, 1 = chest, 2 = wrist, 3 = finger, 4 = hand, 5 = ear lobe, 6 = foot.
+    BodySensorLocation, u8, 0x2a38
+);
+newtype_characteristic!(
+    /// Temperature in units of 0.01 degrees Celsius.
+    Temperature, i16, 0x2a6e
+);
+newtype_characteristic!(
+    /// Relative humidity in units of 0.01 percent.
+    Humidity, u16, 0x2a6f
+);
+
+/// Heart rate measurement.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HeartRateMeasurement {
+    /// Heart rate in beats per minute.
+    pub heart_rate: u16,
+    /// Whether skin contact is detected, if supported by the sensor.
+    pub sensor_contact: Option<bool>,
+    /// Energy expended since the last reset in kJ.
+    pub energy_expended: Option<u16>,
+    /// Intervals between R waves in units of 1/1024 seconds.
+    pub rr_intervals: Vec<u16>,
+}
+
+impl Decode for HeartRateMeasurement {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let mut r = Reader(data);
+        let flags: u8 = r.read(1)?;
+        let heart_rate = if flags & 0x01 != 0 { r.read(2)? } else { r.read::<u8>(1)?.into() };
+        let sensor_contact = if flags & 0x04 != 0 { Some(flags & 0x02 != 0) } else { None };
+        let energy_expended = if flags & 0x08 != 0 { Some(r.read(2)?) } else { None };
+        let mut rr_intervals = Vec::new();
+        if flags & 0x10 != 0 {
+            while !r.is_empty() {
+                rr_intervals.push(r.read(2)?);
+            }
+        }
+        Ok(Self { heart_rate, sensor_contact, energy_expended, rr_intervals })
+    }
+}
+
+impl Encode for HeartRateMeasurement {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        let mut flags = 0;
+        if self.heart_rate > u8::MAX as u16 {
+            flags |= 0x01;
+        }
+        match self.sensor_contact {
+            Some(true) => flags |= 0x06,
+            Some(false) => flags |= 0x04,
+            None => (),
+        }
+        if self.energy_expended.is_some() {
+            flags |= 0x08;
+        }
+        if !self.rr_intervals.is_empty() {
+            flags |= 0x10;
+        }
+
+        buf.push(flags);
+        if flags & 0x01 != 0 {
+            self.heart_rate.encode_into(buf);
+        } else {
+            buf.push(self.heart_rate as u8);
+        }
+        if let Some(energy_expended) = self.energy_expended {
+            energy_expended.encode_into(buf);
+        }
+        for rr_interval in &self.rr_intervals {
+            rr_interval.encode_into(buf);
+        }
+    }
+}
+
+impl AssignedCharacteristic for HeartRateMeasurement {
+    const UUID: Uuid = assigned(0x2a37);
+}
+
+/// Temperature measurement of a health thermometer.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TemperatureMeasurement {
+    /// Temperature.
+    pub temperature: Float,
+    /// Whether the temperature is in degrees Fahrenheit instead of Celsius.
+    pub fahrenheit: bool,
+    /// Time of the measurement.
+    pub timestamp: Option<DateTime>,
+    /// Location of the measurement.
+    ///
+    /// 1 = armpit, 2 = body, 3 = ear, 4 = finger, 5 = gastrointestinal tract,
+    /// 6 = mouth, 7 = rectum, 8 = toe, 9 = tympanum.
+    pub temperature_type: Option<u8>,
+}
+
+impl Decode for TemperatureMeasurement {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let mut r = Reader(data);
+        let flags: u8 = r.read(1)?;
+        Ok(Self {
+            temperature: r.read(4)?,
+            fahrenheit: flags & 0x01 != 0,
+            timestamp: if flags & 0x02 != 0 { Some(r.read(7)?) } else { None },
+            temperature_type: if flags & 0x04 != 0 { Some(r.read(1)?) } else { None },
+        })
+    }
+}
+
+impl Encode for TemperatureMeasurement {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.push(
+            self.fahrenheit as u8
+                | (self.timestamp.is_some() as u8) << 1
+                | (self.temperature_type.is_some() as u8) << 2,
+        );
+        self.temperature.encode_into(buf);
+        if let Some(timestamp) = &self.timestamp {
+            timestamp.encode_into(buf);
+        }
+        if let Some(temperature_type) = self.temperature_type {
+            buf.push(temperature_type);
+        }
+    }
+}
+
+impl AssignedCharacteristic for TemperatureMeasurement {
+    const UUID: Uuid = assigned(0x2a1c);
+}
+
+/// Blood pressure measurement.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BloodPressureMeasurement {
+    /// Systolic pressure.
+    pub systolic: SFloat,
+    /// Diastolic pressure.
+    pub diastolic: SFloat,
+    /// Mean arterial pressure.
+    pub mean_arterial_pressure: SFloat,
+    /// Whether the pressures are in kPa instead of mmHg.
+    pub kilopascal: bool,
+    /// Time of the measurement.
+    pub timestamp: Option<DateTime>,
+    /// Pulse rate in beats per minute.
+    pub pulse_rate: Option<SFloat>,
+    /// User identifier.
+    pub user_id: Option<u8>,
+    /// Measurement status flags.
+    pub measurement_status: Option<u16>,
+}
+
+impl Decode for BloodPressureMeasurement {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let mut r = Reader(data);
+        let flags: u8 = r.read(1)?;
+        Ok(Self {
+            systolic: r.read(2)?,
+            diastolic: r.read(2)?,
+            mean_arterial_pressure: r.read(2)?,
+            kilopascal: flags & 0x01 != 0,
+            timestamp: if flags & 0x02 != 0 { Some(r.read(7)?) } else { None },
+            pulse_rate: if flags & 0x04 != 0 { Some(r.read(2)?) } else { None },
+            user_id: if flags & 0x08 != 0 { Some(r.read(1)?) } else { None },
+            measurement_status: if flags & 0x10 != 0 { Some(r.read(2)?) } else { None },
+        })
+    }
+}
+
+impl Encode for BloodPressureMeasurement {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.push(
+            self.kilopascal as u8
+                | (self.timestamp.is_some() as u8) << 1
+                | (self.pulse_rate.is_some() as u8) << 2
+                | (self.user_id.is_some() as u8) << 3
+                | (self.measurement_status.is_some() as u8) << 4,
+        );
+        self.systolic.encode_into(buf);
+        self.diastolic.encode_into(buf);
+        self.mean_arterial_pressure.encode_into(buf);
+        if let Some(timestamp) = &self.timestamp {
+            timestamp.encode_into(buf);
+        }
+        if let Some(pulse_rate) = &self.pulse_rate {
+            pulse_rate.encode_into(buf);
+        }
+        if let Some(user_id) = self.user_id {
+            buf.push(user_id);
+        }
+        if let Some(measurement_status) = self.measurement_status {
+            measurement_status.encode_into(buf);
+        }
+    }
+}
+
+impl AssignedCharacteristic for BloodPressureMeasurement {
+    const UUID: Uuid = assigned(0x2a35);
+}
+
+/// Wheel revolution data of a cycling speed and cadence measurement.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WheelRevolutions {
+    /// Cumulative number of wheel revolutions.
+    pub cumulative_revolutions: u32,
+    /// Time of the last wheel event in units of 1/1024 seconds.
+    pub last_event_time: u16,
+}
+
+/// Crank revolution data of a cycling speed and cadence measurement.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CrankRevolutions {
+    /// Cumulative number of crank revolutions.
+    pub cumulative_revolutions: u16,
+    /// Time of the last crank event in units of 1/1024 seconds.
+    pub last_event_time: u16,
+}
+
+/// Cycling speed and cadence (CSC) measurement.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CscMeasurement {
+    /// Wheel revolution data.
+    pub wheel: Option<WheelRevolutions>,
+    /// Crank revolution data.
+    pub crank: Option<CrankRevolutions>,
+}
+
+impl Decode for CscMeasurement {
+    fn decode(data: &[u8]) -> Result<Self, InvalidValue> {
+        let mut r = Reader(data);
+        let flags: u8 = r.read(1)?;
+        let wheel = if flags & 0x01 != 0 {
+            Some(WheelRevolutions { cumulative_revolutions: r.read(4)?, last_event_time: r.read(2)? })
+        } else {
+            None
+        };
+        let crank = if flags & 0x02 != 0 {
+            Some(CrankRevolutions { cumulative_revolutions: r.read(2)?, last_event_time: r.read(2)? })
+        } else {
+            None
+        };
+        Ok(Self { wheel, crank })
+    }
+}
+
+impl Encode for CscMeasurement {
+    fn encode_into(&self, buf: &mut Vec<u8>) {
+        buf.push(self.wheel.is_some() as u8 | (self.crank.is_some() as u8) << 1);
+        if let Some(wheel) = &self.wheel {
+            wheel.cumulative_revolutions.encode_into(buf);
+            wheel.last_event_time.encode_into(buf);
+        }
+        if let Some(crank) = &self.crank {
+            crank.cumulative_revolutions.encode_into(buf);
+            crank.last_event_time.encode_into(buf);
+        }
+    }
+}
+
+impl AssignedCharacteristic for CscMeasurement {
+    const UUID: Uuid = assigned(0x2a5b);
+}
@@ -0,0 +1,968 @@
+//! Host controller interface (HCI) sockets.
+//!
+//! HCI sockets provide direct access to Bluetooth controllers, bypassing
+//! the Bluetooth daemon.
+//! The following [channels](Channel) are available:
+//!
+//! * The [raw channel](Channel::Raw) sends and receives HCI packets while the
+//!   kernel continues to manage the controller.
+//! * The [user channel](Channel::User) provides exclusive access to a controller
+//!   that must be powered off and not in use by the kernel.
+//! * The [monitor channel](Channel::Monitor) receives a copy of all HCI traffic of
+//!   all controllers, as used by `btmon`.
+//!   Use [Monitor] to obtain decoded [MonitorRecord]s.
+//!
+//! Opening HCI sockets requires the `CAP_NET_RAW` capability.
+//!
+//! Packets are encoded and decoded using [Packet], [Command], [Event] and [AclData].
+//!
+
+use crate::{
+    sock::{self, OwnedFd},
+    sys::{
+        hci_filter, hci_mon_hdr, sockaddr_hci, BTPROTO_HCI, HCI_ACLDATA_PKT, HCI_CHANNEL_CONTROL,
+        HCI_CHANNEL_LOGGING, HCI_CHANNEL_MONITOR, HCI_CHANNEL_RAW, HCI_CHANNEL_USER, HCI_COMMAND_PKT,
+        HCI_DEV_NONE, HCI_EVENT_PKT, HCI_FILTER, HCI_ISODATA_PKT, HCI_MON_ACL_RX_PKT, HCI_MON_ACL_TX_PKT,
+        HCI_MON_CLOSE_INDEX, HCI_MON_COMMAND_PKT, HCI_MON_CTRL_CLOSE, HCI_MON_CTRL_COMMAND, HCI_MON_CTRL_EVENT,
+        HCI_MON_CTRL_OPEN, HCI_MON_DEL_INDEX, HCI_MON_EVENT_PKT, HCI_MON_INDEX_INFO, HCI_MON_ISO_RX_PKT,
+        HCI_MON_ISO_TX_PKT, HCI_MON_NEW_INDEX, HCI_MON_OPEN_INDEX, HCI_MON_SCO_RX_PKT, HCI_MON_SCO_TX_PKT,
+        HCI_MON_SYSTEM_NOTE, HCI_MON_USER_LOGGING, HCI_MON_VENDOR_DIAG, HCI_SCODATA_PKT, HCI_VENDOR_PKT, SOL_HCI,
+    },
+};
+use futures::{ready, Stream};
+use libc::{AF_BLUETOOTH, SOCK_RAW, SOL_SOCKET, SO_TIMESTAMPNS};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    mem::size_of,
+    os::{
+        raw::c_int,
+        unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    },
+    pin::Pin,
+    task::{Context, Poll},
+    time::SystemTime,
+};
+use tokio::io::{unix::AsyncFd, ReadBuf};
+
+/// Maximum size of a packet received from an HCI socket.
+const MAX_PACKET_SIZE: usize = 65536;
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// HCI socket channel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Channel {
+    /// Raw access to a controller managed by the kernel.
+    #[default]
+    Raw = HCI_CHANNEL_RAW as _,
+    /// Exclusive access to a controller not managed by the kernel.
+    User = HCI_CHANNEL_USER as _,
+    /// Monitor of traffic of all controllers.
+    Monitor = HCI_CHANNEL_MONITOR as _,
+    /// Kernel Bluetooth management interface.
+    Control = HCI_CHANNEL_CONTROL as _,
+    /// Logging into the monitor channel.
+    Logging = HCI_CHANNEL_LOGGING as _,
+}
+
+/// An HCI socket address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SocketAddr {
+    /// Controller index.
+    ///
+    /// This is the number in the controller name, i.e. `0` for `hci0`.
+    /// Use [SocketAddr::INDEX_NONE] for channels that are not bound to a controller.
+    pub index: u16,
+    /// Channel.
+    pub channel: Channel,
+}
+
+impl SocketAddr {
+    /// Index for channels that are not bound to a specific controller.
+    pub const INDEX_NONE: u16 = HCI_DEV_NONE;
+
+    /// Creates a new HCI socket address.
+    pub const fn new(index: u16, channel: Channel) -> Self {
+        Self { index, channel }
+    }
+
+    /// Socket address of the monitor channel.
+    pub const fn monitor() -> Self {
+        Self { index: Self::INDEX_NONE, channel: Channel::Monitor }
+    }
+
+    /// Socket address of the control channel.
+    pub const fn control() -> Self {
+        Self { index: Self::INDEX_NONE, channel: Channel::Control }
+    }
+}
+
+impl Default for SocketAddr {
+    fn default() -> Self {
+        Self { index: Self::INDEX_NONE, channel: Channel::default() }
+    }
+}
+
+impl sock::SysSockAddr for SocketAddr {
+    type SysSockAddr = sockaddr_hci;
+
+    fn into_sys_sock_addr(self) -> Self::SysSockAddr {
+        sockaddr_hci { hci_family: AF_BLUETOOTH as _, hci_dev: self.index, hci_channel: self.channel as _ }
+    }
+
+    fn try_from_sys_sock_addr(saddr: Self::SysSockAddr) -> Result<Self> {
+        if saddr.hci_family != AF_BLUETOOTH as _ {
+            return Err(Error::new(ErrorKind::InvalidInput, "sockaddr_hci::hci_family is not AF_BLUETOOTH"));
+        }
+        Ok(Self {
+            index: saddr.hci_dev,
+            channel: Channel::from_u16(saddr.hci_channel)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid sockaddr_hci::hci_channel"))?,
+        })
+    }
+}
+
+/// HCI packet type as used in the UART (H4) transport.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PacketType {
+    /// Command.
+    Command = HCI_COMMAND_PKT as _,
+    /// Asynchronous connection-oriented (ACL) data.
+    AclData = HCI_ACLDATA_PKT as _,
+    /// Synchronous connection-oriented (SCO) data.
+    ScoData = HCI_SCODATA_PKT as _,
+    /// Event.
+    Event = HCI_EVENT_PKT as _,
+    /// Isochronous (ISO) data.
+    IsoData = HCI_ISODATA_PKT as _,
+    /// Vendor-specific.
+    Vendor = HCI_VENDOR_PKT as _,
+}
+
+/// Filter for packets received on the [raw channel](Channel::Raw).
+///
+/// By default no packets are received.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    /// Bit mask of [packet types](PacketType) to receive.
+    ///
+    /// Bit `n` corresponds to packet type `n`.
+    pub type_mask: u32,
+    /// Bit mask of event codes to receive.
+    ///
+    /// Bit `n` corresponds to event code `n`.
+    pub event_mask: u64,
+    /// Only receive command complete and command status events for this opcode.
+    ///
+    /// Set to zero to receive these events for all opcodes.
+    pub opcode: u16,
+}
+
+impl Filter {
+    /// A filter that receives all packets.
+    pub fn all() -> Self {
+        Self { type_mask: u32::MAX, event_mask: u64::MAX, opcode: 0 }
+    }
+
+    /// Receive packets of the specified type.
+    pub fn set_type(&mut self, ty: PacketType) {
+        self.type_mask |= 1 << (ty as u32 & 0x1f);
+    }
+
+    /// Receive events with the specified code.
+    pub fn set_event(&mut self, code: u8) {
+        self.event_mask |= 1 << (code & 0x3f);
+    }
+
+    fn to_sys(&self) -> hci_filter {
+        hci_filter {
+            type_mask: self.type_mask,
+            event_mask: [self.event_mask as u32, (self.event_mask >> 32) as u32],
+            opcode: self.opcode.to_le(),
+        }
+    }
+
+    fn from_sys(filter: &hci_filter) -> Self {
+        Self {
+            type_mask: filter.type_mask,
+            event_mask: u64::from(filter.event_mask[0]) | (u64::from(filter.event_mask[1]) << 32),
+            opcode: u16::from_le(filter.opcode),
+        }
+    }
+}
+
+/// HCI command opcode.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Opcode {
+    /// Opcode group field (OGF).
+    ///
+    /// Only the lower 6 bits are used.
+    pub ogf: u8,
+    /// Opcode command field (OCF).
+    ///
+    /// Only the lower 10 bits are used.
+    pub ocf: u16,
+}
+
+impl Opcode {
+    /// Creates a new opcode from group and command field.
+    pub const fn new(ogf: u8, ocf: u16) -> Self {
+        Self { ogf, ocf }
+    }
+}
+
+impl From<u16> for Opcode {
+    fn from(opcode: u16) -> Self {
+        Self { ogf: (opcode >> 10) as u8, ocf: opcode & 0x03ff }
+    }
+}
+
+impl From<Opcode> for u16 {
+    fn from(opcode: Opcode) -> Self {
+        (u16::from(opcode.ogf & 0x3f) << 10) | (opcode.ocf & 0x03ff)
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "0x{:02x}|0x{:04x}", self.ogf, self.ocf)
+    }
+}
+
+/// HCI command packet header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommandHeader {
+    /// Opcode.
+    pub opcode: Opcode,
+    /// Length of parameters in bytes.
+    pub param_len: u8,
+}
+
+impl CommandHeader {
+    /// Encoded size in bytes.
+    pub const SIZE: usize = 3;
+
+    /// Encodes the header.
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let [o1, o2] = u16::from(self.opcode).to_le_bytes();
+        [o1, o2, self.param_len]
+    }
+
+    /// Decodes the header from the start of the buffer.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        match buf {
+            [o1, o2, param_len, ..] => {
+                Ok(Self { opcode: u16::from_le_bytes([*o1, *o2]).into(), param_len: *param_len })
+            }
+            _ => Err(invalid_data("HCI command header too short")),
+        }
+    }
+}
+
+/// HCI event packet header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EventHeader {
+    /// Event code.
+    pub code: u8,
+    /// Length of parameters in bytes.
+    pub param_len: u8,
+}
+
+impl EventHeader {
+    /// Encoded size in bytes.
+    pub const SIZE: usize = 2;
+
+    /// Encodes the header.
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        [self.code, self.param_len]
+    }
+
+    /// Decodes the header from the start of the buffer.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        match buf {
+            [code, param_len, ..] => Ok(Self { code: *code, param_len: *param_len }),
+            _ => Err(invalid_data("HCI event header too short")),
+        }
+    }
+}
+
+/// HCI asynchronous connection-oriented (ACL) data packet header.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AclHeader {
+    /// Connection handle.
+    ///
+    /// Only the lower 12 bits are used.
+    pub handle: u16,
+    /// Packet boundary flag.
+    ///
+    /// Only the lower 2 bits are used.
+    pub packet_boundary: u8,
+    /// Broadcast flag.
+    ///
+    /// Only the lower 2 bits are used.
+    pub broadcast: u8,
+    /// Length of data in bytes.
+    pub data_len: u16,
+}
+
+impl AclHeader {
+    /// Encoded size in bytes.
+    pub const SIZE: usize = 4;
+
+    /// Encodes the header.
+    pub fn encode(&self) -> [u8; Self::SIZE] {
+        let handle = (self.handle & 0x0fff)
+            | (u16::from(self.packet_boundary & 0x03) << 12)
+            | (u16::from(self.broadcast & 0x03) << 14);
+        let [h1, h2] = handle.to_le_bytes();
+        let [l1, l2] = self.data_len.to_le_bytes();
+        [h1, h2, l1, l2]
+    }
+
+    /// Decodes the header from the start of the buffer.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        match buf {
+            [h1, h2, l1, l2, ..] => {
+                let handle = u16::from_le_bytes([*h1, *h2]);
+                Ok(Self {
+                    handle: handle & 0x0fff,
+                    packet_boundary: ((handle >> 12) & 0x03) as u8,
+                    broadcast: ((handle >> 14) & 0x03) as u8,
+                    data_len: u16::from_le_bytes([*l1, *l2]),
+                })
+            }
+            _ => Err(invalid_data("HCI ACL header too short")),
+        }
+    }
+}
+
+/// HCI command packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// Opcode.
+    pub opcode: Opcode,
+    /// Parameters.
+    pub params: Vec<u8>,
+}
+
+impl Command {
+    /// Encodes the command including its header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let param_len =
+            self.params.len().try_into().map_err(|_| invalid_data("HCI command parameters too long"))?;
+        let mut buf = CommandHeader { opcode: self.opcode, param_len }.encode().to_vec();
+        buf.extend_from_slice(&self.params);
+        Ok(buf)
+    }
+
+    /// Decodes the command including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let hdr = CommandHeader::decode(buf)?;
+        let params = buf
+            .get(CommandHeader::SIZE..CommandHeader::SIZE + usize::from(hdr.param_len))
+            .ok_or_else(|| invalid_data("HCI command parameters truncated"))?;
+        Ok(Self { opcode: hdr.opcode, params: params.to_vec() })
+    }
+}
+
+/// HCI event packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Event {
+    /// Event code.
+    pub code: u8,
+    /// Parameters.
+    pub params: Vec<u8>,
+}
+
+impl Event {
+    /// Event code of the Command Complete event.
+    pub const COMMAND_COMPLETE: u8 = 0x0e;
+    /// Event code of the Command Status event.
+    pub const COMMAND_STATUS: u8 = 0x0f;
+    /// Event code of the LE Meta event.
+    pub const LE_META: u8 = 0x3e;
+
+    /// Encodes the event including its header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let param_len =
+            self.params.len().try_into().map_err(|_| invalid_data("HCI event parameters too long"))?;
+        let mut buf = EventHeader { code: self.code, param_len }.encode().to_vec();
+        buf.extend_from_slice(&self.params);
+        Ok(buf)
+    }
+
+    /// Decodes the event including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let hdr = EventHeader::decode(buf)?;
+        let params = buf
+            .get(EventHeader::SIZE..EventHeader::SIZE + usize::from(hdr.param_len))
+            .ok_or_else(|| invalid_data("HCI event parameters truncated"))?;
+        Ok(Self { code: hdr.code, params: params.to_vec() })
+    }
+
+    /// Opcode of the command this event completes, if it is
+    /// a Command Complete or Command Status event.
+    pub fn command_opcode(&self) -> Option<Opcode> {
+        let opcode = match self.code {
+            Self::COMMAND_COMPLETE => self.params.get(1..3)?,
+            Self::COMMAND_STATUS => self.params.get(2..4)?,
+            _ => return None,
+        };
+        Some(u16::from_le_bytes([opcode[0], opcode[1]]).into())
+    }
+}
+
+/// HCI asynchronous connection-oriented (ACL) data packet.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AclData {
+    /// Connection handle.
+    pub handle: u16,
+    /// Packet boundary flag.
+    pub packet_boundary: u8,
+    /// Broadcast flag.
+    pub broadcast: u8,
+    /// Data.
+    pub data: Vec<u8>,
+}
+
+impl AclData {
+    /// Header of this packet.
+    pub fn header(&self) -> Result<AclHeader> {
+        Ok(AclHeader {
+            handle: self.handle,
+            packet_boundary: self.packet_boundary,
+            broadcast: self.broadcast,
+            data_len: self.data.len().try_into().map_err(|_| invalid_data("HCI ACL data too long"))?,
+        })
+    }
+
+    /// Encodes the ACL data including its header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let mut buf = self.header()?.encode().to_vec();
+        buf.extend_from_slice(&self.data);
+        Ok(buf)
+    }
+
+    /// Decodes the ACL data including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let hdr = AclHeader::decode(buf)?;
+        let data = buf
+            .get(AclHeader::SIZE..AclHeader::SIZE + usize::from(hdr.data_len))
+            .ok_or_else(|| invalid_data("HCI ACL data truncated"))?;
+        Ok(Self {
+            handle: hdr.handle,
+            packet_boundary: hdr.packet_boundary,
+            broadcast: hdr.broadcast,
+            data: data.to_vec(),
+        })
+    }
+}
+
+/// HCI packet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Packet {
+    /// Command.
+    Command(Command),
+    /// Event.
+    Event(Event),
+    /// ACL data.
+    AclData(AclData),
+    /// Packet of other type that is not decoded.
+    Other {
+        /// Packet type.
+        ty: PacketType,
+        /// Packet data including header.
+        data: Vec<u8>,
+    },
+}
+
+impl Packet {
+    /// Packet type.
+    pub fn packet_type(&self) -> PacketType {
+        match self {
+            Self::Command(_) => PacketType::Command,
+            Self::Event(_) => PacketType::Event,
+            Self::AclData(_) => PacketType::AclData,
+            Self::Other { ty, .. } => *ty,
+        }
+    }
+
+    /// Encodes the packet without packet type indicator.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        match self {
+            Self::Command(cmd) => cmd.encode(),
+            Self::Event(evt) => evt.encode(),
+            Self::AclData(acl) => acl.encode(),
+            Self::Other { data, .. } => Ok(data.clone()),
+        }
+    }
+
+    /// Decodes a packet of the specified type without packet type indicator.
+    pub fn decode(ty: PacketType, buf: &[u8]) -> Result<Self> {
+        Ok(match ty {
+            PacketType::Command => Self::Command(Command::decode(buf)?),
+            PacketType::Event => Self::Event(Event::decode(buf)?),
+            PacketType::AclData => Self::AclData(AclData::decode(buf)?),
+            ty => Self::Other { ty, data: buf.to_vec() },
+        })
+    }
+
+    /// Encodes the packet prefixed with the UART (H4) packet type indicator.
+    ///
+    /// This is the format used on the [raw](Channel::Raw) and [user](Channel::User) channels.
+    pub fn encode_h4(&self) -> Result<Vec<u8>> {
+        let mut buf = vec![self.packet_type() as u8];
+        buf.extend(self.encode()?);
+        Ok(buf)
+    }
+
+    /// Decodes a packet prefixed with the UART (H4) packet type indicator.
+    ///
+    /// This is the format used on the [raw](Channel::Raw) and [user](Channel::User) channels.
+    pub fn decode_h4(buf: &[u8]) -> Result<Self> {
+        let (ty, buf) = buf.split_first().ok_or_else(|| invalid_data("HCI packet empty"))?;
+        let ty = PacketType::from_u8(*ty).ok_or_else(|| invalid_data("invalid HCI packet type"))?;
+        Self::decode(ty, buf)
+    }
+}
+
+/// An HCI socket.
+///
+/// Bind it to a [SocketAddr] before sending or receiving.
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Creates a new HCI socket.
+    pub fn new() -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(sock::socket(AF_BLUETOOTH, SOCK_RAW, BTPROTO_HCI)?)? })
+    }
+
+    /// Creates a new HCI socket bound to the specified address.
+    pub fn bound(sa: SocketAddr) -> Result<Self> {
+        let socket = Self::new()?;
+        socket.bind(sa)?;
+        Ok(socket)
+    }
+
+    /// Bind the socket to the given address.
+    pub fn bind(&self, sa: SocketAddr) -> Result<()> {
+        sock::bind(self.fd.get_ref(), sa)
+    }
+
+    /// Get the local address of this socket.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        sock::getsockname(self.fd.get_ref())
+    }
+
+    /// Gets the packet filter of the raw channel.
+    ///
+    /// This corresponds to the `HCI_FILTER` socket option.
+    pub fn filter(&self) -> Result<Filter> {
+        let filter: hci_filter = sock::getsockopt(self.fd.get_ref(), SOL_HCI, HCI_FILTER)?;
+        Ok(Filter::from_sys(&filter))
+    }
+
+    /// Sets the packet filter of the raw channel.
+    ///
+    /// This corresponds to the `HCI_FILTER` socket option.
+    pub fn set_filter(&self, filter: &Filter) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_HCI, HCI_FILTER, &filter.to_sys())
+    }
+
+    /// Gets whether received packets are timestamped.
+    ///
+    /// This corresponds to the `SO_TIMESTAMPNS` socket option.
+    pub fn is_timestamp(&self) -> Result<bool> {
+        let value: c_int = sock::getsockopt(self.fd.get_ref(), SOL_SOCKET, SO_TIMESTAMPNS)?;
+        Ok(value != 0)
+    }
+
+    /// Sets whether received packets are timestamped.
+    ///
+    /// Timestamps are provided on all channels except the raw channel.
+    ///
+    /// This corresponds to the `SO_TIMESTAMPNS` socket option.
+    pub fn set_timestamp(&self, timestamp: bool) -> Result<()> {
+        let value: c_int = timestamp.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_SOCKET, SO_TIMESTAMPNS, &value)
+    }
+
+    /// Sends a packet.
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        loop {
+            let mut guard = self.fd.writable().await?;
+            match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Attempts to send a packet.
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        loop {
+            let mut guard = ready!(self.fd.poll_write_ready(cx))?;
+            match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
+                Ok(result) => return Poll::Ready(result),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Receives a packet.
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut buf = ReadBuf::new(buf);
+        loop {
+            let mut guard = self.fd.readable().await?;
+            match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut buf, 0)) {
+                Ok(result) => return result,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Attempts to receive a packet.
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        loop {
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            match guard.try_io(|inner| sock::recv(inner.get_ref(), buf, 0)) {
+                Ok(result) => return Poll::Ready(result.map(|_| ())),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Receives a packet together with its receive timestamp.
+    ///
+    /// The timestamp is only available if [timestamping](Self::set_timestamp) is enabled.
+    pub async fn recv_with_timestamp(&self, buf: &mut [u8]) -> Result<(usize, Option<SystemTime>)> {
+        let mut buf = ReadBuf::new(buf);
+        futures::future::poll_fn(|cx| self.poll_recv_with_timestamp(cx, &mut buf)).await
+    }
+
+    /// Attempts to receive a packet together with its receive timestamp.
+    ///
+    /// The timestamp is only available if [timestamping](Self::set_timestamp) is enabled.
+    pub fn poll_recv_with_timestamp(
+        &self, cx: &mut Context, buf: &mut ReadBuf,
+    ) -> Poll<Result<(usize, Option<SystemTime>)>> {
+        loop {
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            match guard.try_io(|inner| sock::recvmsg(inner.get_ref(), buf, 0)) {
+                Ok(result) => {
+                    let (n, cmsgs) = result?;
+                    let timestamp = cmsgs.iter().find_map(|cmsg| cmsg.timestamp());
+                    return Poll::Ready(Ok((n, timestamp)));
+                }
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Sends an HCI packet on the raw or user channel.
+    pub async fn send_packet(&self, packet: &Packet) -> Result<()> {
+        self.send(&packet.encode_h4()?).await?;
+        Ok(())
+    }
+
+    /// Receives an HCI packet on the raw or user channel.
+    pub async fn recv_packet(&self) -> Result<Packet> {
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+        let n = self.recv(&mut buf).await?;
+        Packet::decode_h4(&buf[..n])
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// Opcode of a record received on the HCI monitor channel.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MonitorOpcode {
+    /// A controller was added.
+    NewIndex,
+    /// A controller was removed.
+    DelIndex,
+    /// Command sent to controller.
+    Command,
+    /// Event received from controller.
+    Event,
+    /// ACL data sent to controller.
+    AclTx,
+    /// ACL data received from controller.
+    AclRx,
+    /// SCO data sent to controller.
+    ScoTx,
+    /// SCO data received from controller.
+    ScoRx,
+    /// A controller was opened.
+    OpenIndex,
+    /// A controller was closed.
+    CloseIndex,
+    /// Controller information.
+    IndexInfo,
+    /// Vendor diagnostic information.
+    VendorDiag,
+    /// System note.
+    SystemNote,
+    /// User logging message.
+    UserLogging,
+    /// A management control socket was opened.
+    CtrlOpen,
+    /// A management control socket was closed.
+    CtrlClose,
+    /// Management command.
+    CtrlCommand,
+    /// Management event.
+    CtrlEvent,
+    /// ISO data sent to controller.
+    IsoTx,
+    /// ISO data received from controller.
+    IsoRx,
+    /// Unknown opcode.
+    Unknown(u16),
+}
+
+impl From<u16> for MonitorOpcode {
+    fn from(opcode: u16) -> Self {
+        match opcode {
+            HCI_MON_NEW_INDEX => Self::NewIndex,
+            HCI_MON_DEL_INDEX => Self::DelIndex,
+            HCI_MON_COMMAND_PKT => Self::Command,
+            HCI_MON_EVENT_PKT => Self::Event,
+            HCI_MON_ACL_TX_PKT => Self::AclTx,
+            HCI_MON_ACL_RX_PKT => Self::AclRx,
+            HCI_MON_SCO_TX_PKT => Self::ScoTx,
+            HCI_MON_SCO_RX_PKT => Self::ScoRx,
+            HCI_MON_OPEN_INDEX => Self::OpenIndex,
+            HCI_MON_CLOSE_INDEX => Self::CloseIndex,
+            HCI_MON_INDEX_INFO => Self::IndexInfo,
+            HCI_MON_VENDOR_DIAG => Self::VendorDiag,
+            HCI_MON_SYSTEM_NOTE => Self::SystemNote,
+            HCI_MON_USER_LOGGING => Self::UserLogging,
+            HCI_MON_CTRL_OPEN => Self::CtrlOpen,
+            HCI_MON_CTRL_CLOSE => Self::CtrlClose,
+            HCI_MON_CTRL_COMMAND => Self::CtrlCommand,
+            HCI_MON_CTRL_EVENT => Self::CtrlEvent,
+            HCI_MON_ISO_TX_PKT => Self::IsoTx,
+            HCI_MON_ISO_RX_PKT => Self::IsoRx,
+            other => Self::Unknown(other),
+        }
+    }
+}
+
+impl From<MonitorOpcode> for u16 {
+    fn from(opcode: MonitorOpcode) -> Self {
+        match opcode {
+            MonitorOpcode::NewIndex => HCI_MON_NEW_INDEX,
+            MonitorOpcode::DelIndex => HCI_MON_DEL_INDEX,
+            MonitorOpcode::Command => HCI_MON_COMMAND_PKT,
+            MonitorOpcode::Event => HCI_MON_EVENT_PKT,
+            MonitorOpcode::AclTx => HCI_MON_ACL_TX_PKT,
+            MonitorOpcode::AclRx => HCI_MON_ACL_RX_PKT,
+            MonitorOpcode::ScoTx => HCI_MON_SCO_TX_PKT,
+            MonitorOpcode::ScoRx => HCI_MON_SCO_RX_PKT,
+            MonitorOpcode::OpenIndex => HCI_MON_OPEN_INDEX,
+            MonitorOpcode::CloseIndex => HCI_MON_CLOSE_INDEX,
+            MonitorOpcode::IndexInfo => HCI_MON_INDEX_INFO,
+            MonitorOpcode::VendorDiag => HCI_MON_VENDOR_DIAG,
+            MonitorOpcode::SystemNote => HCI_MON_SYSTEM_NOTE,
+            MonitorOpcode::UserLogging => HCI_MON_USER_LOGGING,
+            MonitorOpcode::CtrlOpen => HCI_MON_CTRL_OPEN,
+            MonitorOpcode::CtrlClose => HCI_MON_CTRL_CLOSE,
+            MonitorOpcode::CtrlCommand => HCI_MON_CTRL_COMMAND,
+            MonitorOpcode::CtrlEvent => HCI_MON_CTRL_EVENT,
+            MonitorOpcode::IsoTx => HCI_MON_ISO_TX_PKT,
+            MonitorOpcode::IsoRx => HCI_MON_ISO_RX_PKT,
+            MonitorOpcode::Unknown(other) => other,
+        }
+    }
+}
+
+impl MonitorOpcode {
+    /// HCI packet type carried by records with this opcode.
+    pub fn packet_type(&self) -> Option<PacketType> {
+        match self {
+            Self::Command => Some(PacketType::Command),
+            Self::Event => Some(PacketType::Event),
+            Self::AclTx | Self::AclRx => Some(PacketType::AclData),
+            Self::ScoTx | Self::ScoRx => Some(PacketType::ScoData),
+            Self::IsoTx | Self::IsoRx => Some(PacketType::IsoData),
+            _ => None,
+        }
+    }
+
+    /// Whether records with this opcode were sent from the host to the controller.
+    pub fn is_sent(&self) -> bool {
+        matches!(self, Self::Command | Self::AclTx | Self::ScoTx | Self::IsoTx)
+    }
+}
+
+/// A record received on the HCI monitor channel.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MonitorRecord {
+    /// Controller index or [SocketAddr::INDEX_NONE].
+    pub index: u16,
+    /// Opcode.
+    pub opcode: MonitorOpcode,
+    /// Time when the record was received by the kernel.
+    pub timestamp: Option<SystemTime>,
+    /// Payload.
+    pub payload: Vec<u8>,
+}
+
+impl MonitorRecord {
+    /// Size of the monitor header in bytes.
+    pub const HEADER_SIZE: usize = size_of::<hci_mon_hdr>();
+
+    /// Decodes the payload as an HCI packet, if it carries one.
+    pub fn packet(&self) -> Option<Result<Packet>> {
+        self.opcode.packet_type().map(|ty| Packet::decode(ty, &self.payload))
+    }
+
+    /// Encodes the record including the monitor header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let len: u16 = self.payload.len().try_into().map_err(|_| invalid_data("monitor payload too long"))?;
+        let mut buf = Vec::with_capacity(Self::HEADER_SIZE + self.payload.len());
+        buf.extend_from_slice(&u16::from(self.opcode).to_le_bytes());
+        buf.extend_from_slice(&self.index.to_le_bytes());
+        buf.extend_from_slice(&len.to_le_bytes());
+        buf.extend_from_slice(&self.payload);
+        Ok(buf)
+    }
+
+    /// Decodes a record including the monitor header.
+    ///
+    /// The timestamp is set to `None`.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let hdr = buf.get(..Self::HEADER_SIZE).ok_or_else(|| invalid_data("monitor header too short"))?;
+        let opcode = u16::from_le_bytes([hdr[0], hdr[1]]);
+        let index = u16::from_le_bytes([hdr[2], hdr[3]]);
+        let len = u16::from_le_bytes([hdr[4], hdr[5]]);
+        let payload = buf
+            .get(Self::HEADER_SIZE..Self::HEADER_SIZE + usize::from(len))
+            .ok_or_else(|| invalid_data("monitor payload truncated"))?;
+        Ok(Self { index, opcode: opcode.into(), timestamp: None, payload: payload.to_vec() })
+    }
+}
+
+/// A stream of records received on the HCI monitor channel.
+///
+/// This receives a copy of the HCI traffic of all controllers, like `btmon`.
+#[derive(Debug)]
+pub struct Monitor {
+    socket: Socket,
+    buf: Vec<u8>,
+}
+
+impl Monitor {
+    /// Opens the HCI monitor channel.
+    ///
+    /// This requires the `CAP_NET_RAW` capability.
+    pub fn open() -> Result<Self> {
+        let socket = Socket::bound(SocketAddr::monitor())?;
+        socket.set_timestamp(true)?;
+        Ok(Self { socket, buf: vec![0; MAX_PACKET_SIZE] })
+    }
+
+    /// Receives the next record.
+    pub async fn recv(&mut self) -> Result<MonitorRecord> {
+        futures::future::poll_fn(|cx| self.poll_recv(cx)).await
+    }
+
+    /// Attempts to receive the next record.
+    pub fn poll_recv(&mut self, cx: &mut Context) -> Poll<Result<MonitorRecord>> {
+        let mut buf = ReadBuf::new(&mut self.buf);
+        let (n, timestamp) = ready!(self.socket.poll_recv_with_timestamp(cx, &mut buf))?;
+        let mut record = MonitorRecord::decode(&self.buf[..n])?;
+        record.timestamp = timestamp;
+        Poll::Ready(Ok(record))
+    }
+}
+
+impl AsRef<Socket> for Monitor {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Monitor {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl Stream for Monitor {
+    type Item = Result<MonitorRecord>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        self.get_mut().poll_recv(cx).map(Some)
+    }
+}
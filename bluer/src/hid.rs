@@ -0,0 +1,285 @@
+//! Human interface device (HID) role.
+//!
+//! This allows the local system to act as a Bluetooth keyboard, mouse or
+//! game controller towards a remote host using the classic Bluetooth (BR/EDR)
+//! HID profile.
+//!
+//! The HID service record is published by registering a [HidDevice] using
+//! [Session::register_hid_device](crate::Session::register_hid_device).
+//! Connections from the host are then accepted on the L2CAP control
+//! ([PSM_CONTROL]) and interrupt ([PSM_INTERRUPT]) channels through the returned
+//! [HidDeviceHandle].
+//!
+//! Listening on these PSMs requires the `CAP_NET_BIND_SERVICE` capability.
+//! Furthermore, the `input` plugin of the Bluetooth daemon must be disabled,
+//! since it occupies the same PSMs to act as a HID host.
+
+use std::{fmt, fmt::Write};
+use uuid::Uuid;
+
+use crate::{
+    class::PeripheralClass,
+    l2cap::{Security, SecurityLevel, SeqPacket, SeqPacketListener, Socket, SocketAddr},
+    rfcomm::{Profile, ProfileHandle, Role},
+    Address, AddressType, Result, Session,
+};
+
+/// L2CAP protocol service multiplexor (PSM) of the HID control channel.
+pub const PSM_CONTROL: u16 = 0x11;
+
+/// L2CAP protocol service multiplexor (PSM) of the HID interrupt channel.
+pub const PSM_INTERRUPT: u16 = 0x13;
+
+/// HID service class UUID.
+pub const HID_UUID: Uuid = Uuid::from_u128(0x00001124_0000_1000_8000_00805f9b34fb);
+
+/// HID protocol transaction header of an input report sent on the interrupt channel.
+const DATA_INPUT: u8 = 0xa1;
+
+/// HID protocol transaction header of an output report received on the interrupt channel.
+const DATA_OUTPUT: u8 = 0xa2;
+
+/// Human interface device definition.
+///
+/// Use [Session::register_hid_device](crate::Session::register_hid_device) to register it.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HidDevice {
+    /// Human readable name of the device.
+    pub name: String,
+    /// Human readable description of the device.
+    pub description: Option<String>,
+    /// Human readable name of the device provider.
+    pub provider: Option<String>,
+    /// Device subclass, i.e. the kind of peripheral device.
+    pub subclass: PeripheralClass,
+    /// Country code of localized hardware, for example keyboards.
+    ///
+    /// 0 if the hardware is not localized.
+    pub country_code: u8,
+    /// HID report descriptor describing the reports sent and received by the device.
+    pub report_descriptor: Vec<u8>,
+    /// Whether the device initiates reconnection after the connection was lost.
+    pub reconnect_initiate: bool,
+    /// Whether the device accepts connections from the host while it is
+    /// not connected.
+    pub normally_connectable: bool,
+    /// Whether the device supports the boot protocol.
+    pub boot_device: bool,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl HidDevice {
+    /// SDP service record of the device in the XML format used by BlueZ.
+    pub fn service_record(&self) -> String {
+        fn escape(s: &str) -> String {
+            s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+        }
+        fn boolean(v: bool) -> &'static str {
+            if v {
+                "true"
+            } else {
+                "false"
+            }
+        }
+
+        let mut descriptor = String::new();
+        for b in &self.report_descriptor {
+            let _ = write!(descriptor, "{b:02x}");
+        }
+
+        let mut r = String::new();
+        let _ = write!(
+            r,
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+<record>
+  <attribute id="0x0001"><sequence><uuid value="0x1124" /></sequence></attribute>
+  <attribute id="0x0004">
+    <sequence>
+      <sequence><uuid value="0x0100" /><uint16 value="0x{PSM_CONTROL:04x}" /></sequence>
+      <sequence><uuid value="0x0011" /></sequence>
+    </sequence>
+  </attribute>
+  <attribute id="0x0005"><sequence><uuid value="0x1002" /></sequence></attribute>
+  <attribute id="0x0006">
+    <sequence><uint16 value="0x656e" /><uint16 value="0x006a" /><uint16 value="0x0100" /></sequence>
+  </attribute>
+  <attribute id="0x0009">
+    <sequence><sequence><uuid value="0x1124" /><uint16 value="0x0101" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x000d">
+    <sequence>
+      <sequence>
+        <sequence><uuid value="0x0100" /><uint16 value="0x{PSM_INTERRUPT:04x}" /></sequence>
+        <sequence><uuid value="0x0011" /></sequence>
+      </sequence>
+    </sequence>
+  </attribute>
+  <attribute id="0x0100"><text value="{}" /></attribute>
+"#,
+            escape(&self.name)
+        );
+        if let Some(description) = &self.description {
+            let _ =
+                writeln!(r, r#"  <attribute id="0x0101"><text value="{}" /></attribute>"#, escape(description));
+        }
+        if let Some(provider) = &self.provider {
+            let _ = writeln!(r, r#"  <attribute id="0x0102"><text value="{}" /></attribute>"#, escape(provider));
+        }
+        let _ = write!(
+            r,
+            r#"  <attribute id="0x0201"><uint16 value="0x0111" /></attribute>
+  <attribute id="0x0202"><uint8 value="0x{:02x}" /></attribute>
+  <attribute id="0x0203"><uint8 value="0x{:02x}" /></attribute>
+  <attribute id="0x0204"><boolean value="true" /></attribute>
+  <attribute id="0x0205"><boolean value="{}" /></attribute>
+  <attribute id="0x0206">
+    <sequence><sequence><uint8 value="0x22" /><text encoding="hex" value="{descriptor}" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x0207">
+    <sequence><sequence><uint16 value="0x0409" /><uint16 value="0x0100" /></sequence></sequence>
+  </attribute>
+  <attribute id="0x020b"><uint16 value="0x0100" /></attribute>
+  <attribute id="0x020c"><uint16 value="0x0c80" /></attribute>
+  <attribute id="0x020d"><boolean value="{}" /></attribute>
+  <attribute id="0x020e"><boolean value="{}" /></attribute>
+</record>
+"#,
+            u8::from(self.subclass) << 2,
+            self.country_code,
+            boolean(self.reconnect_initiate),
+            boolean(self.normally_connectable),
+            boolean(self.boot_device),
+        );
+        r
+    }
+
+    pub(crate) async fn register(self, session: &Session) -> Result<HidDeviceHandle> {
+        let profile = Profile {
+            uuid: HID_UUID,
+            name: Some(self.name.clone()),
+            role: Some(Role::Server),
+            require_authentication: Some(true),
+            require_authorization: Some(false),
+            auto_connect: Some(false),
+            service_record: Some(self.service_record()),
+            ..Default::default()
+        };
+        let profile = session.register_profile(profile).await?;
+
+        let control = listen(PSM_CONTROL)?;
+        let interrupt = listen(PSM_INTERRUPT)?;
+
+        Ok(HidDeviceHandle { control, interrupt, _profile: profile })
+    }
+}
+
+fn listen(psm: u16) -> std::io::Result<SeqPacketListener> {
+    let socket = Socket::new_seq_packet()?;
+    socket.set_security(Security { level: SecurityLevel::Low, key_size: 0 })?;
+    socket.bind(SocketAddr::new(Address::any(), AddressType::BrEdr, psm))?;
+    socket.listen(1)
+}
+
+/// Handle to registered human interface device accepting connections from hosts.
+///
+/// Drop to unregister the device.
+#[must_use = "HidDeviceHandle must be held for the human interface device to be registered"]
+pub struct HidDeviceHandle {
+    control: SeqPacketListener,
+    interrupt: SeqPacketListener,
+    _profile: ProfileHandle,
+}
+
+impl fmt::Debug for HidDeviceHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HidDeviceHandle")
+    }
+}
+
+impl HidDeviceHandle {
+    /// Accepts a connection from a host.
+    ///
+    /// The host first connects the control channel and then the interrupt channel.
+    pub async fn accept(&self) -> std::io::Result<HidConnection> {
+        let (control, control_sa) = self.control.accept().await?;
+        loop {
+            let (interrupt, interrupt_sa) = self.interrupt.accept().await?;
+            if interrupt_sa.addr == control_sa.addr {
+                return Ok(HidConnection { address: control_sa.addr, control, interrupt });
+            }
+            log::debug!("Ignoring HID interrupt channel from {} without control channel", interrupt_sa.addr);
+        }
+    }
+}
+
+/// Connection of a human interface device to a host.
+pub struct HidConnection {
+    address: Address,
+    control: SeqPacket,
+    interrupt: SeqPacket,
+}
+
+impl fmt::Debug for HidConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HidConnection {{ {} }}", self.address)
+    }
+}
+
+impl HidConnection {
+    /// Connects to a host that the device has previously been connected to.
+    ///
+    /// This is used to restore the connection if the device initiates reconnection.
+    pub async fn connect(address: Address) -> std::io::Result<Self> {
+        let control = SeqPacket::connect(SocketAddr::new(address, AddressType::BrEdr, PSM_CONTROL)).await?;
+        let interrupt = SeqPacket::connect(SocketAddr::new(address, AddressType::BrEdr, PSM_INTERRUPT)).await?;
+        Ok(Self { address, control, interrupt })
+    }
+
+    /// Address of the host.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// L2CAP control channel.
+    ///
+    /// It carries HID protocol messages such as `SET_PROTOCOL` and `GET_REPORT`,
+    /// which must be answered by the device.
+    pub fn control(&self) -> &SeqPacket {
+        &self.control
+    }
+
+    /// L2CAP interrupt channel.
+    pub fn interrupt(&self) -> &SeqPacket {
+        &self.interrupt
+    }
+
+    /// Sends an input report to the host.
+    ///
+    /// The report must include the report id if the report descriptor uses report ids.
+    pub async fn send_input_report(&self, report: &[u8]) -> std::io::Result<()> {
+        let mut buf = Vec::with_capacity(report.len() + 1);
+        buf.push(DATA_INPUT);
+        buf.extend_from_slice(report);
+        self.interrupt.send(&buf).await?;
+        Ok(())
+    }
+
+    /// Receives an output report, for example the state of keyboard LEDs, from the host.
+    ///
+    /// Messages that are not output reports are skipped.
+    pub async fn recv_output_report(&self) -> std::io::Result<Vec<u8>> {
+        let mut buf = vec![0; self.interrupt.recv_mtu()?];
+        loop {
+            let n = self.interrupt.recv(&mut buf).await?;
+            if n == 0 {
+                return Err(std::io::ErrorKind::ConnectionReset.into());
+            }
+            if buf[0] == DATA_OUTPUT {
+                return Ok(buf[1..n].to_vec());
+            }
+        }
+    }
+}
@@ -1,10 +1,12 @@
-//! Manufacturer ids and assigned UUIDs for service classes and profiles,
+//! Manufacturer ids, appearance values and assigned UUIDs for service classes and profiles,
 //! GATT services, GATT characteristics and GATT descriptors.
 //!
 //! The data herein is provided in part by the [Bluetooth numbers database]
 //! created by Nordic Semiconductor ASA.
+//! Appearance values are taken from the [Bluetooth SIG Assigned Numbers].
 //!
 //! [Bluetooth numbers database]: https://github.com/NordicSemiconductor/bluetooth-numbers-database
+//! [Bluetooth SIG Assigned Numbers]: https://bitbucket.org/bluetooth-SIG/public
 
 use std::convert::TryFrom;
 use strum::{Display, EnumString};
@@ -12,6 +14,25 @@ use uuid::Uuid;
 
 include!(concat!(env!("OUT_DIR"), "/service_class.inc"));
 
+include!(concat!(env!("OUT_DIR"), "/appearance_category.inc"));
+include!(concat!(env!("OUT_DIR"), "/appearance.inc"));
+
+impl Appearance {
+    /// Category of the appearance value.
+    ///
+    /// `None` is returned if the category is not assigned.
+    pub fn category(self) -> Option<AppearanceCategory> {
+        AppearanceCategory::try_from(u16::from(self) >> 6).ok()
+    }
+
+    /// Subcategory of the appearance value within its category.
+    ///
+    /// Zero designates a generic device of the category.
+    pub fn subcategory(self) -> u8 {
+        (u16::from(self) & 0x3f) as u8
+    }
+}
+
 // =========================================================================================
 //
 // Copyright (c) 2019 - 2020, Nordic Semiconductor ASA
@@ -0,0 +1,1069 @@
+//! Isochronous channel (ISO) sockets.
+//!
+//! ISO sockets carry time-bounded data, such as LE Audio, over Bluetooth Low Energy.
+//! Two kinds of isochronous channels are supported:
+//!
+//! * Connected isochronous streams (CIS) are unicast channels between two devices.
+//!   Use [Stream::connect] to establish a CIS with a peer and [Listener] to accept
+//!   incoming CIS connections.
+//!   The channel is configured using [UnicastQos].
+//! * Broadcast isochronous streams (BIS) are one-to-many channels that are
+//!   grouped into a broadcast isochronous group (BIG).
+//!   Use [Stream::connect_broadcast] to act as a broadcast source and
+//!   [Listener::bind_broadcast] to synchronize to a broadcast source as a sink.
+//!   The channel is configured using [BroadcastQos].
+//!
+//! Data is exchanged as service data units (SDUs).
+//! Use [Stream::recv_packet] to obtain receive timestamps and packet status.
+//!
+
+use crate::{
+    sock::{self, OwnedFd},
+    sys::{
+        bt_iso_bcast_qos, bt_iso_io_qos, bt_iso_qos, bt_iso_ucast_qos, sockaddr_iso, sockaddr_iso_bc,
+        BASE_MAX_LENGTH, BTPROTO_ISO, BT_DEFER_SETUP, BT_ISO_BASE, BT_ISO_PHY_1M, BT_ISO_PHY_2M, BT_ISO_PHY_ANY,
+        BT_ISO_PHY_CODED, BT_ISO_QOS, BT_ISO_QOS_BIG_UNSET, BT_ISO_QOS_BIS_UNSET, BT_ISO_QOS_CIG_UNSET,
+        BT_ISO_QOS_CIS_UNSET, BT_ISO_SYNC_TIMEOUT, BT_PKT_STATUS, BT_SCM_PKT_STATUS, ISO_MAX_NUM_BIS,
+        SOCKADDR_ISO_BC_LEN, SOCKADDR_ISO_LEN,
+    },
+    Address, AddressType,
+};
+use futures::ready;
+use libc::{
+    AF_BLUETOOTH, EAGAIN, EINPROGRESS, MSG_PEEK, SHUT_RD, SHUT_RDWR, SHUT_WR, SOCK_SEQPACKET, SOL_BLUETOOTH,
+    SOL_SOCKET, SO_ERROR, SO_RCVBUF, SO_TIMESTAMPNS, TIOCINQ, TIOCOUTQ,
+};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    convert::TryInto,
+    fmt,
+    io::{Error, ErrorKind, Result},
+    net::Shutdown,
+    os::{
+        raw::c_int,
+        unix::io::{AsRawFd, FromRawFd, IntoRawFd, RawFd},
+    },
+    pin::Pin,
+    sync::atomic::{AtomicBool, Ordering},
+    task::{Context, Poll},
+    time::{Duration, SystemTime},
+};
+use tokio::io::{unix::AsyncFd, AsyncRead, AsyncWrite, ReadBuf};
+
+/// Maximum size of a service data unit (SDU) in bytes.
+pub const MAX_SDU: usize = 0x0fff;
+
+/// Highest broadcast isochronous stream (BIS) index.
+pub const MAX_BIS: u8 = ISO_MAX_NUM_BIS as _;
+
+/// An ISO socket address.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SocketAddr {
+    /// Device address.
+    ///
+    /// When listening or binding, specify [Address::any] for any local adapter address.
+    ///
+    /// When connecting, specify [Address::any] to create a broadcast source.
+    pub addr: Address,
+    /// Device address type.
+    ///
+    /// This must be an LE address type.
+    pub addr_type: AddressType,
+    /// Broadcast address.
+    ///
+    /// When binding a listening socket, this specifies the broadcast source
+    /// to synchronize to.
+    pub broadcast: Option<BroadcastAddr>,
+}
+
+impl SocketAddr {
+    /// Creates a new ISO socket address.
+    pub const fn new(addr: Address, addr_type: AddressType) -> Self {
+        Self { addr, addr_type, broadcast: None }
+    }
+
+    /// When specified to [Socket::bind] binds to any public, local adapter address.
+    pub const fn any_le() -> Self {
+        Self { addr: Address::any(), addr_type: AddressType::LePublic, broadcast: None }
+    }
+}
+
+impl fmt::Display for SocketAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}", self.addr, self.addr_type)?;
+        if let Some(broadcast) = &self.broadcast {
+            write!(f, " {broadcast}")?;
+        }
+        Ok(())
+    }
+}
+
+impl sock::SysSockAddr for SocketAddr {
+    type SysSockAddr = sockaddr_iso;
+
+    fn into_sys_sock_addr(self) -> Self::SysSockAddr {
+        let broadcast = self.broadcast.unwrap_or_default();
+        let mut bc_bis = [0; ISO_MAX_NUM_BIS];
+        let mut bc_num_bis = 0;
+        for (bis, index) in bc_bis.iter_mut().zip(broadcast.bis_indices()) {
+            *bis = index;
+            bc_num_bis += 1;
+        }
+
+        sockaddr_iso {
+            iso_family: AF_BLUETOOTH as _,
+            iso_bdaddr: self.addr.into(),
+            iso_bdaddr_type: self.addr_type as _,
+            iso_bc: sockaddr_iso_bc {
+                bc_bdaddr: broadcast.addr.into(),
+                bc_bdaddr_type: broadcast.addr_type as _,
+                bc_sid: broadcast.sid,
+                bc_num_bis,
+                bc_bis,
+            },
+        }
+    }
+
+    fn try_from_sys_sock_addr(saddr: Self::SysSockAddr) -> Result<Self> {
+        Self::try_from_sys_sock_addr_len(saddr, SOCKADDR_ISO_BC_LEN)
+    }
+
+    fn sys_sock_addr_len(&self) -> usize {
+        match self.broadcast {
+            Some(_) => SOCKADDR_ISO_BC_LEN,
+            None => SOCKADDR_ISO_LEN,
+        }
+    }
+
+    fn try_from_sys_sock_addr_len(saddr: Self::SysSockAddr, len: usize) -> Result<Self> {
+        if saddr.iso_family != AF_BLUETOOTH as _ {
+            return Err(Error::new(ErrorKind::InvalidInput, "sockaddr_iso::iso_family is not AF_BLUETOOTH"));
+        }
+        let addr_type = AddressType::from_u8(saddr.iso_bdaddr_type)
+            .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid sockaddr_iso::iso_bdaddr_type"))?;
+
+        let broadcast = match len {
+            SOCKADDR_ISO_LEN => None,
+            SOCKADDR_ISO_BC_LEN => {
+                let bc = saddr.iso_bc;
+                let num_bis = usize::from(bc.bc_num_bis).min(ISO_MAX_NUM_BIS);
+                let mut broadcast = BroadcastAddr {
+                    addr: Address::from(bc.bc_bdaddr),
+                    addr_type: AddressType::from_u8(bc.bc_bdaddr_type).unwrap_or_default(),
+                    sid: bc.bc_sid,
+                    bis: 0,
+                };
+                for &index in &bc.bc_bis[..num_bis] {
+                    broadcast.insert_bis(index);
+                }
+                Some(broadcast)
+            }
+            _ => return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length")),
+        };
+
+        Ok(Self { addr: Address::from(saddr.iso_bdaddr), addr_type, broadcast })
+    }
+}
+
+/// Broadcast source address and selection of broadcast isochronous streams (BISes).
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastAddr {
+    /// Address of broadcast source.
+    pub addr: Address,
+    /// Address type of broadcast source.
+    pub addr_type: AddressType,
+    /// Advertising set identifier (SID) of the periodic advertising train.
+    pub sid: u8,
+    /// Selected BIS indices as bit mask.
+    ///
+    /// Bit `n` is set when the BIS with index `n` is selected.
+    /// Valid BIS indices range from 1 to [MAX_BIS].
+    pub bis: u32,
+}
+
+impl BroadcastAddr {
+    /// Creates a new broadcast address with the specified BIS indices selected.
+    pub fn new(addr: Address, addr_type: AddressType, sid: u8, bis: impl IntoIterator<Item = u8>) -> Self {
+        let mut this = Self { addr, addr_type, sid, bis: 0 };
+        for index in bis {
+            this.insert_bis(index);
+        }
+        this
+    }
+
+    /// Selects the BIS with the specified index.
+    ///
+    /// Indices outside the valid range are ignored.
+    pub fn insert_bis(&mut self, index: u8) {
+        if (1..=MAX_BIS).contains(&index) {
+            self.bis |= 1 << index;
+        }
+    }
+
+    /// Selected BIS indices in ascending order.
+    pub fn bis_indices(&self) -> impl Iterator<Item = u8> {
+        let bis = self.bis;
+        (1..=MAX_BIS).filter(move |index| bis & (1 << index) != 0)
+    }
+}
+
+impl fmt::Display for BroadcastAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{} sid {} bis", self.addr, self.addr_type, self.sid)?;
+        for index in self.bis_indices() {
+            write!(f, " {index}")?;
+        }
+        Ok(())
+    }
+}
+
+/// LE physical layer (PHY) used for isochronous channels.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Phy {
+    /// LE 1M PHY.
+    Le1M = BT_ISO_PHY_1M as _,
+    /// LE 2M PHY.
+    #[default]
+    Le2M = BT_ISO_PHY_2M as _,
+    /// LE Coded PHY.
+    LeCoded = BT_ISO_PHY_CODED as _,
+    /// Any PHY chosen by the controller.
+    Any = BT_ISO_PHY_ANY as _,
+}
+
+impl Phy {
+    /// HCI value of the LE Coded PHY.
+    ///
+    /// Once a channel is established the kernel reports the PHY in use as HCI value,
+    /// which differs from the socket option value only for the LE Coded PHY.
+    const HCI_LE_CODED: u8 = 0x03;
+
+    fn from_sys(phy: u8) -> Option<Self> {
+        match phy {
+            Self::HCI_LE_CODED => Some(Self::LeCoded),
+            phy => Self::from_u8(phy),
+        }
+    }
+}
+
+/// Framing of service data units (SDUs) into isochronous PDUs.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Framing {
+    /// Unframed PDUs.
+    #[default]
+    Unframed = 0x00,
+    /// Framed PDUs.
+    Framed = 0x01,
+}
+
+/// Arrangement of subevents of multiple streams within a group.
+#[derive(Debug, Default, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Packing {
+    /// Sequential arrangement.
+    #[default]
+    Sequential = 0x00,
+    /// Interleaved arrangement.
+    Interleaved = 0x01,
+}
+
+/// Quality of service of one direction of an isochronous channel.
+///
+/// Set [sdu](Self::sdu) to zero if the direction is unused.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IoQos {
+    /// SDU interval.
+    ///
+    /// Has microsecond resolution.
+    pub sdu_interval: Duration,
+    /// Maximum transport latency.
+    ///
+    /// Has millisecond resolution.
+    pub latency: Duration,
+    /// Maximum SDU size in bytes.
+    pub sdu: u16,
+    /// PHY.
+    pub phy: Phy,
+    /// Retransmission number.
+    pub rtn: u8,
+}
+
+impl Default for IoQos {
+    fn default() -> Self {
+        Self {
+            sdu_interval: Duration::from_micros(10_000),
+            latency: Duration::from_millis(10),
+            sdu: 40,
+            phy: Phy::default(),
+            rtn: 2,
+        }
+    }
+}
+
+impl IoQos {
+    fn to_sys(&self) -> bt_iso_io_qos {
+        bt_iso_io_qos {
+            interval: self.sdu_interval.as_micros().try_into().unwrap_or(u32::MAX),
+            latency: self.latency.as_millis().try_into().unwrap_or(u16::MAX),
+            sdu: self.sdu,
+            phy: self.phy as _,
+            rtn: self.rtn,
+        }
+    }
+
+    fn from_sys(qos: &bt_iso_io_qos) -> Result<Self> {
+        Ok(Self {
+            sdu_interval: Duration::from_micros(qos.interval.into()),
+            latency: Duration::from_millis(qos.latency.into()),
+            sdu: qos.sdu,
+            phy: Phy::from_sys(qos.phy).ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid PHY"))?,
+            rtn: qos.rtn,
+        })
+    }
+}
+
+/// Quality of service of a connected isochronous stream (CIS).
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnicastQos {
+    /// Connected isochronous group (CIG) identifier.
+    ///
+    /// Set to `None` to let the kernel allocate one.
+    pub cig: Option<u8>,
+    /// Connected isochronous stream (CIS) identifier.
+    ///
+    /// Set to `None` to let the kernel allocate one.
+    pub cis: Option<u8>,
+    /// Worst case sleep clock accuracy.
+    pub sca: u8,
+    /// Packing.
+    pub packing: Packing,
+    /// Framing.
+    pub framing: Framing,
+    /// Input (receive) direction.
+    pub input: IoQos,
+    /// Output (transmit) direction.
+    pub output: IoQos,
+}
+
+impl UnicastQos {
+    fn to_sys(&self) -> bt_iso_qos {
+        bt_iso_qos {
+            ucast: bt_iso_ucast_qos {
+                cig: self.cig.unwrap_or(BT_ISO_QOS_CIG_UNSET),
+                cis: self.cis.unwrap_or(BT_ISO_QOS_CIS_UNSET),
+                sca: self.sca,
+                packing: self.packing as _,
+                framing: self.framing as _,
+                in_: self.input.to_sys(),
+                out: self.output.to_sys(),
+            },
+        }
+    }
+
+    fn from_sys(qos: &bt_iso_ucast_qos) -> Result<Self> {
+        Ok(Self {
+            cig: Some(qos.cig).filter(|&v| v != BT_ISO_QOS_CIG_UNSET),
+            cis: Some(qos.cis).filter(|&v| v != BT_ISO_QOS_CIS_UNSET),
+            sca: qos.sca,
+            packing: Packing::from_u8(qos.packing)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid packing"))?,
+            framing: Framing::from_u8(qos.framing)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid framing"))?,
+            input: IoQos::from_sys(&qos.in_)?,
+            output: IoQos::from_sys(&qos.out)?,
+        })
+    }
+}
+
+/// Quality of service and parameters of a broadcast isochronous group (BIG)
+/// and its broadcast isochronous streams (BISes).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct BroadcastQos {
+    /// Broadcast isochronous group (BIG) handle.
+    ///
+    /// Set to `None` to let the kernel allocate one.
+    pub big: Option<u8>,
+    /// Broadcast isochronous stream (BIS) index.
+    ///
+    /// Set to `None` to let the kernel allocate one.
+    pub bis: Option<u8>,
+    /// Periodic advertising interval factor used by a broadcast source.
+    pub sync_factor: u8,
+    /// Packing.
+    pub packing: Packing,
+    /// Framing.
+    pub framing: Framing,
+    /// Input (receive) direction used by a broadcast sink.
+    pub input: IoQos,
+    /// Output (transmit) direction used by a broadcast source.
+    pub output: IoQos,
+    /// Broadcast code for encryption.
+    ///
+    /// Set to `None` for an unencrypted broadcast.
+    pub broadcast_code: Option<[u8; 16]>,
+    /// Periodic advertising create sync options.
+    pub options: u8,
+    /// Number of periodic advertising events that can be skipped after a successful receive.
+    pub skip: u16,
+    /// Synchronization timeout for the periodic advertising train.
+    ///
+    /// Has a resolution of 10 milliseconds.
+    pub sync_timeout: Duration,
+    /// Constant tone extension (CTE) types that are not to be synchronized to.
+    pub sync_cte_type: u8,
+    /// Maximum number of subevents to receive per BIS event.
+    ///
+    /// Zero lets the controller decide.
+    pub mse: u8,
+    /// Synchronization timeout for the BIG.
+    ///
+    /// Has a resolution of 10 milliseconds.
+    pub timeout: Duration,
+}
+
+impl Default for BroadcastQos {
+    fn default() -> Self {
+        Self {
+            big: None,
+            bis: None,
+            sync_factor: 0x01,
+            packing: Packing::default(),
+            framing: Framing::default(),
+            input: IoQos::default(),
+            output: IoQos::default(),
+            broadcast_code: None,
+            options: 0x00,
+            skip: 0x0000,
+            sync_timeout: Duration::from_millis(u64::from(BT_ISO_SYNC_TIMEOUT) * 10),
+            sync_cte_type: 0x00,
+            mse: 0x00,
+            timeout: Duration::from_millis(u64::from(BT_ISO_SYNC_TIMEOUT) * 10),
+        }
+    }
+}
+
+impl BroadcastQos {
+    fn to_sys(&self) -> bt_iso_qos {
+        let timeout = |d: Duration| (d.as_millis() / 10).try_into().unwrap_or(u16::MAX);
+        bt_iso_qos {
+            bcast: bt_iso_bcast_qos {
+                big: self.big.unwrap_or(BT_ISO_QOS_BIG_UNSET),
+                bis: self.bis.unwrap_or(BT_ISO_QOS_BIS_UNSET),
+                sync_factor: self.sync_factor,
+                packing: self.packing as _,
+                framing: self.framing as _,
+                in_: self.input.to_sys(),
+                out: self.output.to_sys(),
+                encryption: self.broadcast_code.is_some().into(),
+                bcode: self.broadcast_code.unwrap_or_default(),
+                options: self.options,
+                skip: self.skip,
+                sync_timeout: timeout(self.sync_timeout),
+                sync_cte_type: self.sync_cte_type,
+                mse: self.mse,
+                timeout: timeout(self.timeout),
+            },
+        }
+    }
+
+    fn from_sys(qos: &bt_iso_bcast_qos) -> Result<Self> {
+        Ok(Self {
+            big: Some(qos.big).filter(|&v| v != BT_ISO_QOS_BIG_UNSET),
+            bis: Some(qos.bis).filter(|&v| v != BT_ISO_QOS_BIS_UNSET),
+            sync_factor: qos.sync_factor,
+            packing: Packing::from_u8(qos.packing)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid packing"))?,
+            framing: Framing::from_u8(qos.framing)
+                .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "invalid framing"))?,
+            input: IoQos::from_sys(&qos.in_)?,
+            output: IoQos::from_sys(&qos.out)?,
+            broadcast_code: if qos.encryption != 0 { Some(qos.bcode) } else { None },
+            options: qos.options,
+            skip: qos.skip,
+            sync_timeout: Duration::from_millis(u64::from(qos.sync_timeout) * 10),
+            sync_cte_type: qos.sync_cte_type,
+            mse: qos.mse,
+            timeout: Duration::from_millis(u64::from(qos.timeout) * 10),
+        })
+    }
+}
+
+/// Status of a received packet as reported by the controller.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PacketStatus {
+    /// Data is valid.
+    Valid = 0x00,
+    /// Data may contain errors.
+    PossiblyInvalid = 0x01,
+    /// Data was lost.
+    Lost = 0x02,
+}
+
+/// An isochronous packet.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Packet {
+    /// Service data unit (SDU).
+    pub data: Vec<u8>,
+    /// Time when the packet was received.
+    ///
+    /// This is only available if [Socket::set_timestamp] has been enabled.
+    pub timestamp: Option<SystemTime>,
+    /// Packet status reported by the controller.
+    ///
+    /// This is only available if [Socket::set_packet_status] has been enabled.
+    pub status: Option<PacketStatus>,
+}
+
+/// An ISO socket that has not yet been converted to a [Listener] or [Stream].
+///
+/// The primary use of this is to configure the socket before connecting or listening.
+pub struct Socket {
+    fd: AsyncFd<OwnedFd>,
+}
+
+impl fmt::Debug for Socket {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Socket").field("fd", &self.fd.as_raw_fd()).finish()
+    }
+}
+
+impl Socket {
+    /// Creates a new ISO socket.
+    pub fn new() -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(sock::socket(AF_BLUETOOTH, SOCK_SEQPACKET, BTPROTO_ISO)?)? })
+    }
+
+    /// Convert the socket into a [Listener].
+    ///
+    /// `backlog` defines the maximum number of pending connections are queued by the operating system
+    /// at any given time.
+    pub fn listen(self, backlog: u32) -> Result<Listener> {
+        sock::listen(
+            self.fd.get_ref(),
+            backlog.try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "invalid backlog"))?,
+        )?;
+        Ok(Listener { socket: self })
+    }
+
+    /// Establish an isochronous channel with a peer at the specified socket address.
+    ///
+    /// If the address is [Address::any] a broadcast source is created.
+    pub async fn connect(self, sa: SocketAddr) -> Result<Stream> {
+        self.connect_priv(sa).await?;
+        Ok(Stream::from_socket(self, false))
+    }
+
+    /// Bind the socket to the given address.
+    pub fn bind(&self, sa: SocketAddr) -> Result<()> {
+        sock::bind(self.fd.get_ref(), sa)
+    }
+
+    /// Get the local address of this socket.
+    pub fn local_addr(&self) -> Result<SocketAddr> {
+        sock::getsockname(self.fd.get_ref())
+    }
+
+    /// Get the peer address of this socket.
+    fn peer_addr_priv(&self) -> Result<SocketAddr> {
+        sock::getpeername(self.fd.get_ref())
+    }
+
+    fn qos(&self) -> Result<bt_iso_qos> {
+        sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS)
+    }
+
+    /// Gets the unicast quality of service.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn unicast_qos(&self) -> Result<UnicastQos> {
+        let qos = self.qos()?;
+        UnicastQos::from_sys(unsafe { &qos.ucast })
+    }
+
+    /// Sets the unicast quality of service.
+    ///
+    /// This must be set before connecting or listening.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn set_unicast_qos(&self, qos: &UnicastQos) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS, &qos.to_sys())
+    }
+
+    /// Gets the broadcast quality of service and group parameters.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn broadcast_qos(&self) -> Result<BroadcastQos> {
+        let qos = self.qos()?;
+        BroadcastQos::from_sys(unsafe { &qos.bcast })
+    }
+
+    /// Sets the broadcast quality of service and group parameters.
+    ///
+    /// This must be set before connecting or listening.
+    ///
+    /// This corresponds to the `BT_ISO_QOS` socket option.
+    pub fn set_broadcast_qos(&self, qos: &BroadcastQos) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_QOS, &qos.to_sys())
+    }
+
+    /// Gets the broadcast audio source endpoint (BASE) data.
+    ///
+    /// This corresponds to the `BT_ISO_BASE` socket option.
+    pub fn base(&self) -> Result<Vec<u8>> {
+        sock::getsockopt_vec(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_BASE, BASE_MAX_LENGTH)
+    }
+
+    /// Sets the broadcast audio source endpoint (BASE) data that is
+    /// included in the periodic advertising of a broadcast source.
+    ///
+    /// This must be set before connecting.
+    ///
+    /// This corresponds to the `BT_ISO_BASE` socket option.
+    pub fn set_base(&self, base: &[u8]) -> Result<()> {
+        if base.len() > BASE_MAX_LENGTH {
+            return Err(Error::new(ErrorKind::InvalidInput, "BASE too long"));
+        }
+        sock::setsockopt_slice(self.fd.get_ref(), SOL_BLUETOOTH, BT_ISO_BASE, base)
+    }
+
+    /// Gets whether deferred setup is enabled.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn is_defer_setup(&self) -> Result<bool> {
+        let value: u32 = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP)?;
+        Ok(value != 0)
+    }
+
+    /// Sets whether deferred setup is enabled.
+    ///
+    /// When enabled on a listening socket, incoming connections are accepted
+    /// by the operating system but the isochronous channel is not established until
+    /// [Stream::authorize] is called on the accepted stream.
+    ///
+    /// This corresponds to the `BT_DEFER_SETUP` socket option.
+    pub fn set_defer_setup(&self, defer_setup: bool) -> Result<()> {
+        let value: u32 = defer_setup.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_DEFER_SETUP, &value)
+    }
+
+    /// Gets whether the packet status is reported for received packets.
+    ///
+    /// This corresponds to the `BT_PKT_STATUS` socket option.
+    pub fn is_packet_status(&self) -> Result<bool> {
+        let value: c_int = sock::getsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_PKT_STATUS)?;
+        Ok(value != 0)
+    }
+
+    /// Sets whether the packet status is reported for received packets.
+    ///
+    /// This corresponds to the `BT_PKT_STATUS` socket option.
+    pub fn set_packet_status(&self, packet_status: bool) -> Result<()> {
+        let value: c_int = packet_status.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_BLUETOOTH, BT_PKT_STATUS, &value)
+    }
+
+    /// Gets whether received packets are timestamped.
+    ///
+    /// This corresponds to the `SO_TIMESTAMPNS` socket option.
+    pub fn is_timestamp(&self) -> Result<bool> {
+        let value: c_int = sock::getsockopt(self.fd.get_ref(), SOL_SOCKET, SO_TIMESTAMPNS)?;
+        Ok(value != 0)
+    }
+
+    /// Sets whether received packets are timestamped.
+    ///
+    /// This corresponds to the `SO_TIMESTAMPNS` socket option.
+    pub fn set_timestamp(&self, timestamp: bool) -> Result<()> {
+        let value: c_int = timestamp.into();
+        sock::setsockopt(self.fd.get_ref(), SOL_SOCKET, SO_TIMESTAMPNS, &value)
+    }
+
+    /// Gets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn recv_buffer(&self) -> Result<i32> {
+        sock::getsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF)
+    }
+
+    /// Sets the maximum socket receive buffer in bytes.
+    ///
+    /// This corresponds to the `SO_RCVBUF` socket option.
+    pub fn set_recv_buffer(&self, recv_buffer: i32) -> Result<()> {
+        sock::setsockopt(self.fd.get_ref(), SOL_SOCKET, SO_RCVBUF, &recv_buffer)
+    }
+
+    /// Get the number of bytes in the input buffer.
+    ///
+    /// This corresponds to the `TIOCINQ` IOCTL.
+    pub fn input_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCINQ)?;
+        Ok(value as _)
+    }
+
+    /// Get the number of bytes in the output buffer.
+    ///
+    /// This corresponds to the `TIOCOUTQ` IOCTL.
+    pub fn output_buffer(&self) -> Result<u32> {
+        let value: c_int = sock::ioctl_read(self.fd.get_ref(), TIOCOUTQ)?;
+        Ok(value as _)
+    }
+
+    /// Constructs a new [Socket] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(OwnedFd::new(fd))? })
+    }
+
+    fn from_owned_fd(fd: OwnedFd) -> Result<Self> {
+        Ok(Self { fd: AsyncFd::new(fd)? })
+    }
+
+    sock_priv!(packet);
+
+    fn poll_recv_packet_priv(&self, cx: &mut Context) -> Poll<Result<Packet>> {
+        // The buffer is only allocated once the socket is readable.
+        let mut data = Vec::new();
+        let (n, cmsgs) = loop {
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            if data.is_empty() {
+                data = vec![0; MAX_SDU];
+            }
+            let mut buf = ReadBuf::new(&mut data);
+            match guard.try_io(|inner| sock::recvmsg(inner.get_ref(), &mut buf, 0)) {
+                Ok(result) => break result?,
+                Err(_would_block) => continue,
+            }
+        };
+        data.truncate(n);
+
+        let mut timestamp = None;
+        let mut status = None;
+        for cmsg in cmsgs {
+            if let Some(ts) = cmsg.timestamp() {
+                timestamp = Some(ts);
+            } else if cmsg.level == SOL_BLUETOOTH && cmsg.ty == BT_SCM_PKT_STATUS && !cmsg.data.is_empty() {
+                status = PacketStatus::from_u8(cmsg.data[0]);
+            }
+        }
+
+        Poll::Ready(Ok(Packet { data, timestamp, status }))
+    }
+}
+
+impl AsRawFd for Socket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for Socket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
+
+impl FromRawFd for Socket {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Socket::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// An ISO socket server, listening for [Stream] connections.
+#[derive(Debug)]
+pub struct Listener {
+    socket: Socket,
+}
+
+impl Listener {
+    /// Creates a new Listener for connected isochronous streams (CISes),
+    /// which will be bound to the specified socket address.
+    ///
+    /// Specify [SocketAddr::any_le] for any local adapter address.
+    pub async fn bind(sa: SocketAddr) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(sa)?;
+        socket.listen(1)
+    }
+
+    /// Creates a new Listener acting as a broadcast sink, which synchronizes to
+    /// the specified broadcast isochronous streams (BISes) of a broadcast source.
+    ///
+    /// Depending on the kernel version, the first accepted [Stream] may represent the
+    /// synchronization to the periodic advertising train of the broadcast source
+    /// and the BISes are delivered by subsequent accepts.
+    pub async fn bind_broadcast(source: BroadcastAddr, qos: &BroadcastQos) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr { broadcast: Some(source), ..SocketAddr::any_le() })?;
+        socket.set_broadcast_qos(qos)?;
+        socket.listen(1)
+    }
+
+    /// Accepts a new incoming connection from this listener.
+    pub async fn accept(&self) -> Result<(Stream, SocketAddr)> {
+        let (socket, sa) = self.socket.accept_priv().await?;
+        Ok((Stream::from_socket(socket, self.socket.is_defer_setup()?), sa))
+    }
+
+    /// Polls to accept a new incoming connection to this listener.
+    pub fn poll_accept(&self, cx: &mut Context) -> Poll<Result<(Stream, SocketAddr)>> {
+        let (socket, sa) = ready!(self.socket.poll_accept_priv(cx))?;
+        Poll::Ready(Ok((Stream::from_socket(socket, self.socket.is_defer_setup()?), sa)))
+    }
+
+    /// Constructs a new [Listener] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self { socket: Socket::from_raw_fd(fd)? })
+    }
+}
+
+impl AsRef<Socket> for Listener {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Listener {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Listener {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Listener::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+/// An isochronous channel between a local and remote socket carrying
+/// service data units (SDUs) as packets.
+///
+/// Each write using [AsyncWrite] sends one packet and each read using [AsyncRead]
+/// receives one packet.
+/// Use [recv_packet](Self::recv_packet) to obtain receive timestamps and packet status.
+#[derive(Debug)]
+pub struct Stream {
+    socket: Socket,
+    /// Whether the connection was accepted with deferred setup and is not yet authorized.
+    pending_setup: AtomicBool,
+}
+
+impl Stream {
+    fn from_socket(socket: Socket, pending_setup: bool) -> Self {
+        Self { socket, pending_setup: AtomicBool::new(pending_setup) }
+    }
+
+    /// Establish a connected isochronous stream (CIS) with a peer at the specified socket address
+    /// using the specified quality of service.
+    ///
+    /// Uses any local Bluetooth adapter.
+    pub async fn connect(addr: SocketAddr, qos: &UnicastQos) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr::any_le())?;
+        socket.set_unicast_qos(qos)?;
+        socket.connect(addr).await
+    }
+
+    /// Creates a broadcast source transmitting a broadcast isochronous stream (BIS)
+    /// using the specified quality of service and group parameters.
+    ///
+    /// `base` specifies the broadcast audio source endpoint (BASE) data that is
+    /// included in the periodic advertising; it may be empty.
+    ///
+    /// Uses any local Bluetooth adapter.
+    pub async fn connect_broadcast(qos: &BroadcastQos, base: &[u8]) -> Result<Self> {
+        let socket = Socket::new()?;
+        socket.bind(SocketAddr::any_le())?;
+        socket.set_broadcast_qos(qos)?;
+        if !base.is_empty() {
+            socket.set_base(base)?;
+        }
+        socket.connect(SocketAddr::any_le()).await
+    }
+
+    /// Gets the peer address of this stream.
+    pub fn peer_addr(&self) -> Result<SocketAddr> {
+        self.socket.peer_addr_priv()
+    }
+
+    /// Establishes the isochronous channel of a connection that was accepted with
+    /// [deferred setup](Socket::set_defer_setup) enabled.
+    ///
+    /// The quality of service of the accepted connection can be changed before calling this.
+    ///
+    /// Fails with [ErrorKind::InvalidInput] if the connection is not awaiting deferred setup,
+    /// i.e. it was not accepted with deferred setup enabled or has already been authorized.
+    pub fn authorize(&self) -> Result<()> {
+        if !self.pending_setup.swap(false, Ordering::SeqCst) {
+            return Err(Error::new(ErrorKind::InvalidInput, "connection is not awaiting deferred setup"));
+        }
+
+        // A read on a socket with deferred setup authorizes the pending connection.
+        let mut buf = [0; 1];
+        let mut buf = ReadBuf::new(&mut buf);
+        match sock::recv(self.socket.fd.get_ref(), &mut buf, 0) {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == ErrorKind::WouldBlock => Ok(()),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Receives a packet containing one service data unit (SDU).
+    pub async fn recv_packet(&self) -> Result<Packet> {
+        futures::future::poll_fn(|cx| self.poll_recv_packet(cx)).await
+    }
+
+    /// Attempts to receive a packet containing one service data unit (SDU).
+    pub fn poll_recv_packet(&self, cx: &mut Context) -> Poll<Result<Packet>> {
+        self.socket.poll_recv_packet_priv(cx)
+    }
+
+    /// Sends a packet.
+    ///
+    /// The packet length must not exceed the maximum SDU size.
+    pub async fn send(&self, buf: &[u8]) -> Result<usize> {
+        self.socket.send_priv(buf).await
+    }
+
+    /// Attempts to send a packet.
+    ///
+    /// The packet length must not exceed the maximum SDU size.
+    pub fn poll_send(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.socket.poll_send_priv(cx, buf)
+    }
+
+    /// Receives a packet.
+    ///
+    /// The provided buffer must be as large as the maximum SDU size, otherwise
+    /// the packet may be truncated.
+    pub async fn recv(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.recv_priv(buf).await
+    }
+
+    /// Attempts to receive a packet.
+    ///
+    /// The provided buffer must be as large as the maximum SDU size, otherwise
+    /// the packet may be truncated.
+    pub fn poll_recv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.socket.poll_recv_priv(cx, buf)
+    }
+
+    /// Receives a packet without removing it from the queue.
+    ///
+    /// On success, returns the number of bytes peeked.
+    pub async fn peek(&self, buf: &mut [u8]) -> Result<usize> {
+        self.socket.peek_priv(buf).await
+    }
+
+    /// Attempts to receive a packet without removing it from the queue,
+    /// registering the current task for wakeup if data is not yet available.
+    pub fn poll_peek(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<usize>> {
+        self.socket.poll_peek_priv(cx, buf)
+    }
+
+    /// Shuts down the read, write, or both halves of this connection.
+    pub fn shutdown(&self, how: Shutdown) -> Result<()> {
+        self.socket.shutdown_priv(how)
+    }
+
+    /// Constructs a new [Stream] from the given raw file descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// This function *consumes ownership* of the specified file descriptor.
+    /// The returned object will take responsibility for closing it when the object goes out of scope.
+    ///
+    /// # Safety
+    /// If the passed file descriptor is invalid, undefined behavior may occur.
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Result<Self> {
+        Ok(Self::from_socket(Socket::from_raw_fd(fd)?, false))
+    }
+}
+
+impl AsRef<Socket> for Stream {
+    fn as_ref(&self) -> &Socket {
+        &self.socket
+    }
+}
+
+impl AsRawFd for Stream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl FromRawFd for Stream {
+    /// Constructs a new instance of `Self` from the given raw file
+    /// descriptor.
+    ///
+    /// The file descriptor must have been set to non-blocking mode.
+    ///
+    /// # Panics
+    /// Panics when the conversion fails.
+    /// Use [Stream::from_raw_fd] for a non-panicking variant.
+    unsafe fn from_raw_fd(fd: RawFd) -> Self {
+        Self::from_raw_fd(fd).expect("from_raw_fd failed")
+    }
+}
+
+impl AsyncRead for Stream {
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        self.poll_recv(cx, buf)
+    }
+}
+
+impl AsyncWrite for Stream {
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        self.poll_send(cx, buf)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_flush_priv(cx)
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context) -> Poll<Result<()>> {
+        self.socket.poll_shutdown_priv(cx, Shutdown::Write)
+    }
+}
@@ -436,7 +436,7 @@ impl<Type> Socket<Type> {
         Ok(Self { fd: AsyncFd::new(fd)?, _type: PhantomData })
     }
 
-    sock_priv!();
+    sock_priv!(datagram);
 }
 
 impl<Type> AsRawFd for Socket<Type> {
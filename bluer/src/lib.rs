@@ -8,7 +8,7 @@
 //!
 //! This library provides the official Rust interface to the [Linux Bluetooth protocol stack (BlueZ)].
 //! Both publishing local and consuming remote [GATT services] using *idiomatic* Rust code is supported.
-//! L2CAP and RFCOMM sockets are presented using an API similar to Tokio networking.
+//! L2CAP, RFCOMM, SCO and ISO sockets are presented using an API similar to Tokio networking.
 //!
 //! This library depends on the [tokio] asynchronous runtime.
 //!
@@ -21,15 +21,19 @@
 //! * [Bluetooth devices](Device)
 //!     * [discovery](Adapter::discover_devices) with custom filters
 //!     * querying of address, name, class, signal strength (RSSI), etc.
+//!     * [property snapshots](DeviceInfo) updatable from change events
+//!     * [Class of Device](class::ClassOfDevice) decoding
 //!     * Bluetooth Low Energy advertisements
 //!     * [change events stream](Adapter::events)
 //!     * connecting and pairing
 //!     * [passive LE advertisement monitoring](Adapter::monitor)
+//!     * [coordinated sets](DeviceSet) of devices forming one product
 //! * [consumption of remote GATT services](Device::services)
 //!     * GATT service discovery
 //!     * read, write and notify operations on characteristics
 //!     * read and write operations on characteristic descriptors
 //!     * optional use of low-overhead [AsyncRead] and [AsyncWrite] streams for notify and write operations
+//!     * [typed values](gatt::value) of common characteristics, including IEEE-11073 `SFLOAT` and `FLOAT` numbers
 //! * [publishing local GATT services](Adapter::serve_gatt_application)
 //!     * read, write and notify operations on characteristics
 //!     * read and write operations on characteristic descriptors
@@ -37,10 +41,21 @@
 //!         * callback-based interface
 //!         * low-overhead [AsyncRead] and [AsyncWrite] streams
 //! * [sending Bluetooth Low Energy advertisements](Adapter::advertise)
+//! * [parsing and building of advertising data](ad)
+//! * [beacon formats](beacon)
+//!     * iBeacon, Eddystone and AltBeacon decoding and encoding
 //! * [Bluetooth authorization agent](agent::Agent)
+//! * [publishing battery levels of remote devices](Adapter::register_battery_provider)
+//! * [Personal Area Networking](network)
+//!     * connecting to network access points and group ad-hoc networks
+//!     * network server registration with bridging of incoming connections
 //! * efficient event dispatching
 //!     * not affected by D-Bus match rule count
 //!     * O(1) in number of subscriptions
+//! * optional cache of objects and properties avoiding D-Bus round-trips
+//! * recovery from Bluetooth daemon restarts
+//!     * daemon stopped and started events
+//!     * optional re-registration of advertisements, GATT applications, agents, profiles and monitors
 //! * [L2CAP sockets](l2cap)
 //!     * support for both classic Bluetooth (BR/EDR) and Bluetooth LE
 //!     * stream oriented
@@ -51,12 +66,45 @@
 //!     * support for classic Bluetooth (BR/EDR)
 //!     * stream oriented
 //!     * async IO interface with [AsyncRead] and [AsyncWrite] support
+//! * [SCO sockets](sco)
+//!     * synchronous voice links for classic Bluetooth (BR/EDR)
+//!     * CVSD and transparent voice settings
+//!     * async IO interface with [AsyncRead] and [AsyncWrite] support
+//! * [ISO sockets](iso)
+//!     * connected isochronous streams (CIS) for Bluetooth LE Audio unicast
+//!     * broadcast isochronous streams (BIS) as source or sink
+//!     * packets with receive timestamps and status
+//! * [HCI sockets](hci)
+//!     * raw, user and monitor channels
+//!     * encoding and decoding of HCI commands, events and ACL data
+//!     * capture of traffic of all controllers like `btmon`
+//! * [human interface device role](hid)
+//!     * acting as a Bluetooth keyboard, mouse or game controller
+//!     * HID service record generation from a report descriptor
+//! * [kernel management interface](mgmt)
+//!     * controller configuration without a running Bluetooth daemon
+//!     * loading of link keys and long term keys
+//!     * management event stream
+//! * [HCI capture files](capture)
+//!     * btsnoop and pcap reading and writing
+//!     * recording from the HCI monitor and replay with original timing
+//! * [OBEX client](obex)
+//!     * Object Push, File Transfer, Phone Book Access and Message Access profiles
+//!     * vCard parsing
+//!     * transfer progress events stream
+//! * [media endpoints](media)
+//!     * codec negotiation for A2DP and LE Audio streaming
+//!     * media transport acquisition with [AsyncRead] and [AsyncWrite] audio packet stream
+//!     * AVRCP media player control and local player publishing
 //! * [Bluetooth Mesh](mesh)
 //!     * provision and join networks
 //!     * send and receive messages
 //! * [database of assigned numbers](id)
 //!     * manufacturer ids
-//!     * services classes, GATT services, characteristics and descriptors
+//!     * appearance values
+//!     * service classes, GATT services, characteristics and descriptors
+//! * [mock Bluetooth daemon](testing) for testing applications without Bluetooth hardware
+//!     * scriptable adapters, devices, GATT databases, advertisements and pairing outcomes
 //!
 //! Currently, some classic Bluetooth (BR/EDR) functionality is missing.
 //! However, pull requests and contributions are welcome!
@@ -68,18 +116,28 @@
 //! * `id`: Enables database of assigned numbers.
 //! * `l2cap`: Enables L2CAP sockets.
 //! * `rfcomm`: Enables RFCOMM sockets.
+//! * `sco`: Enables SCO sockets.
+//! * `iso`: Enables ISO sockets.
+//! * `hci`: Enables HCI sockets.
+//! * `hid`: Enables the human interface device role.
+//! * `mgmt`: Enables the kernel management interface.
+//! * `capture`: Enables reading and writing of HCI capture files.
+//! * `obex`: Enables the OBEX client.
+//! * `media`: Enables media endpoints for audio streaming and media player control.
 //! * `mesh`: Enables Bluetooth mesh functionality.
 //! * `serde`: Enables serialization and deserialization of some data types.
+//! * `testing`: Enables the mock Bluetooth daemon for testing applications without Bluetooth hardware.
+//!   Intended to be enabled for `[dev-dependencies]` only.
 //!
-//! To enable all crate features specify the `full` crate feature.
+//! To enable all crate features except `testing` specify the `full` crate feature.
 //!
 //! ## Basic usage
 //! Create a [Session] using [Session::new]; this establishes a connection to the Bluetooth daemon.
 //! Then obtain a Bluetooth adapter using [Session::adapter].
 //! From there on you can access most of the functionality using the methods provided by [Adapter].
 //!
-//! ## L2CAP and RFCOMM sockets
-//! Refer to the [l2cap] and [rfcomm] modules.
+//! ## L2CAP, RFCOMM, SCO and ISO sockets
+//! Refer to the [l2cap], [rfcomm], [sco] and [iso] modules.
 //! No [Session] and therefore no running Bluetooth daemon is required.
 //!
 //! [Linux Bluetooth protocol stack (BlueZ)]: http://www.bluez.org/
@@ -95,7 +153,7 @@ compile_error!("BlueR only supports the Linux operating system.");
 #[cfg(feature = "bluetoothd")]
 use dbus::{
     arg::{prop_cast, AppendAll, PropMap, RefArg, Variant},
-    nonblock::{stdintf::org_freedesktop_dbus::ObjectManager, Proxy, SyncConnection},
+    nonblock::{stdintf::org_freedesktop_dbus::ObjectManager, Proxy},
     Path,
 };
 #[cfg(feature = "bluetoothd")]
@@ -135,6 +193,57 @@ macro_rules! publish_path {
 #[cfg(feature = "bluetoothd")]
 macro_rules! dbus_interface {
     () => {
+        dbus_interface!(@methods);
+
+        #[allow(dead_code)]
+        fn cached_property<T>(
+            &self, _name: &str, _interface: &str, _f: impl FnOnce(&(dyn dbus::arg::RefArg + 'static)) -> Option<T>,
+        ) -> Option<Option<T>> {
+            None
+        }
+
+        #[allow(dead_code)]
+        fn cached_properties(&self, _interface: &str) -> Option<Option<dbus::arg::PropMap>> {
+            None
+        }
+
+        #[allow(dead_code)]
+        fn invalidate_cached_property(&self, _name: &str, _interface: &str) {}
+    };
+
+    (cached) => {
+        dbus_interface!(@methods);
+
+        #[allow(dead_code)]
+        fn cached_property<T>(
+            &self, name: &str, interface: &str, f: impl FnOnce(&(dyn dbus::arg::RefArg + 'static)) -> Option<T>,
+        ) -> Option<Option<T>> {
+            let value = self.inner.cache.as_ref()?.property(&self.dbus_path, interface, name, |value| {
+                log::trace!("{}: {}.{} = {:?} (cached)", &self.dbus_path, &interface, &name, value);
+                f(value)
+            })?;
+            if value.is_none() {
+                log::trace!("{}: {}.{} = None (cached)", &self.dbus_path, &interface, &name);
+            }
+            Some(value)
+        }
+
+        #[allow(dead_code)]
+        fn cached_properties(&self, interface: &str) -> Option<Option<dbus::arg::PropMap>> {
+            let props = self.inner.cache.as_ref()?.properties(&self.dbus_path, interface)?;
+            log::trace!("{}: {}.* = {:?} (cached)", &self.dbus_path, &interface, &props);
+            Some(props)
+        }
+
+        #[allow(dead_code)]
+        fn invalidate_cached_property(&self, name: &str, interface: &str) {
+            if let Some(cache) = &self.inner.cache {
+                cache.invalidate(&self.dbus_path, interface, name);
+            }
+        }
+    };
+
+    (@methods) => {
         #[allow(dead_code)]
         async fn get_property_with_interface<R>(&self, name: &str, interface: &str) -> crate::Result<R>
         where
@@ -167,6 +276,28 @@ macro_rules! dbus_interface {
             }
         }
 
+        #[allow(dead_code)]
+        async fn get_all_properties_with_interface(&self, interface: &str) -> crate::Result<Option<dbus::arg::PropMap>> {
+            use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
+            if let Some(props) = self.cached_properties(interface) {
+                return Ok(props);
+            }
+            match self.proxy().get_all(interface).await {
+                Ok(props) => {
+                    log::trace!("{}: {}.* = {:?}", &self.proxy().path, &interface, &props);
+                    Ok(Some(props))
+                }
+                Err(err)
+                    if err.name() == Some("org.freedesktop.DBus.Error.InvalidArgs")
+                        || err.name() == Some("org.freedesktop.DBus.Error.UnknownInterface") =>
+                {
+                    log::trace!("{}: {}.* = None", &self.proxy().path, &interface);
+                    Ok(None)
+                }
+                Err(err) => Err(err.into()),
+            }
+        }
+
         #[allow(dead_code)]
         async fn set_property_with_interface<T>(&self, name: &str, value: T, interface: &str) -> crate::Result<()>
         where
@@ -175,6 +306,7 @@ macro_rules! dbus_interface {
             use dbus::nonblock::stdintf::org_freedesktop_dbus::Properties;
             log::trace!("{}: {}.{} := {:?}", &self.proxy().path, &interface, &name, &value);
             self.proxy().set(interface, name, value).await?;
+            self.invalidate_cached_property(name, interface);
             Ok(())
         }
 
@@ -239,6 +371,9 @@ macro_rules! define_properties {
     ) => {
         $(#[$outer])*
         pub async fn $getter_name(&self) -> crate::Result<Option<$type>> {
+            if let Some(value) = define_properties!(@cached self, $dbus_name, $dbus_interface, $dbus_value : $dbus_type => $getter_transform => $type) {
+                return value.transpose();
+            }
             let dbus_opt_value: Option<$dbus_type> = self.get_opt_property_with_interface($dbus_name, $dbus_interface).await?;
             #[allow(clippy::manual_map)]
             let value: Option<$type> = match dbus_opt_value.as_ref() {
@@ -256,6 +391,9 @@ macro_rules! define_properties {
     ) => {
         $(#[$outer])*
         pub async fn $getter_name(&self) -> crate::Result<$type> {
+            if let Some(Some(value)) = define_properties!(@cached self, $dbus_name, $dbus_interface, $dbus_value : $dbus_type => $getter_transform => $type) {
+                return value;
+            }
             let dbus_value: $dbus_type = self.get_property_with_interface($dbus_name, $dbus_interface).await?;
             let $dbus_value = &dbus_value;
             let value: $type = $getter_transform;
@@ -263,6 +401,16 @@ macro_rules! define_properties {
         }
     };
 
+    (@cached
+        $self:ident, $dbus_name:expr, $dbus_interface:expr, $dbus_value:ident : $dbus_type:ty => $getter_transform:block => $type:ty
+    ) => {
+        $self.cached_property($dbus_name, $dbus_interface, |value| {
+            crate::with_variant_property_cast(value, |dbus_opt_value: Option<&$dbus_type>| {
+                dbus_opt_value.map(|$dbus_value| -> crate::Result<$type> { Ok($getter_transform) })
+            })
+        })
+    };
+
     (@set
         $(#[$outer:meta])*
         set: ($setter_name:ident, $value:ident => $setter_transform:block),,
@@ -296,6 +444,150 @@ macro_rules! define_properties {
         $props_name.push($enum_name::$name($self.$getter_name().await?));
     };
 
+    (
+        $struct_name:ident, $(#[$enum_outer:meta])* $enum_vis:vis $enum_name:ident,
+        $(#[$kind_outer:meta])* $kind_vis:vis $kind_name:ident,
+        $(#[$info_outer:meta])* $info_vis:vis $info_name:ident =>
+        {$(
+            $(#[$outer:meta])*
+            property(
+                $name:ident, $type:ty,
+                dbus: ($dbus_interface:expr, $dbus_name:expr, $dbus_type:ty, $opt:tt),
+                get: ($getter_name:ident, $dbus_value:ident => $getter_transform:block),
+                $( $set_tt:tt )*
+            );
+        )*}
+    ) => {
+        define_properties!(
+            $struct_name, $(#[$enum_outer])* $enum_vis $enum_name =>
+            {$(
+                $(#[$outer])*
+                property(
+                    $name, $type,
+                    dbus: ($dbus_interface, $dbus_name, $dbus_type, $opt),
+                    get: ($getter_name, $dbus_value => $getter_transform),
+                    $( $set_tt )*
+                );
+            )*}
+        );
+
+        impl $struct_name {
+            /// Queries all properties and returns them as a snapshot.
+            ///
+            /// Each D-Bus interface of the object is queried using a single `GetAll` call.
+            /// Properties of interfaces the object does not provide are `None`.
+            $info_vis async fn info(&self) -> Result<$info_name> {
+                let mut info = $info_name::default();
+                let interfaces = [$($dbus_interface),*];
+                for (i, interface) in interfaces.iter().enumerate() {
+                    if interfaces[..i].contains(interface) {
+                        continue;
+                    }
+                    if let Some(props) = self.get_all_properties_with_interface(interface).await? {
+                        info.extend($enum_name::from_prop_map(props));
+                    }
+                }
+                Ok(info)
+            }
+        }
+
+        $(#[$kind_outer])*
+        #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+        #[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[non_exhaustive]
+        $kind_vis enum $kind_name {
+            $(
+                $(#[$outer])*
+                $name,
+            )*
+        }
+
+        impl $kind_name {
+            /// The property with the specified name of the specified D-Bus interface.
+            #[allow(dead_code)]
+            fn from_dbus_name(interface: &str, name: &str) -> Option<Self> {
+                $(
+                    if interface == $dbus_interface && name == $dbus_name {
+                        return Some(Self::$name);
+                    }
+                )*
+                None
+            }
+
+            /// All properties of the specified D-Bus interface.
+            #[allow(dead_code)]
+            fn of_interface(interface: &str) -> Vec<Self> {
+                let mut kinds = Vec::new();
+                $(
+                    if interface == $dbus_interface {
+                        kinds.push(Self::$name);
+                    }
+                )*
+                kinds
+            }
+        }
+
+        impl $enum_name {
+            /// The kind of the property.
+            pub fn kind(&self) -> $kind_name {
+                match self {
+                    $(
+                        Self::$name(_) => $kind_name::$name,
+                    )*
+                }
+            }
+        }
+
+        $(#[$info_outer])*
+        #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+        #[derive(Debug, Clone, Default)]
+        #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+        #[non_exhaustive]
+        $info_vis struct $info_name {
+            $(
+                $(#[$outer])*
+                pub $getter_name: Option<$type>,
+            )*
+        }
+
+        impl $info_name {
+            /// Applies a changed property to the snapshot.
+            pub fn apply(&mut self, property: $enum_name) {
+                match property {
+                    $(
+                        $enum_name::$name(value) => self.$getter_name = Some(value),
+                    )*
+                }
+            }
+
+            /// Clears an invalidated property of the snapshot.
+            pub fn invalidate(&mut self, kind: $kind_name) {
+                match kind {
+                    $(
+                        $kind_name::$name => self.$getter_name = None,
+                    )*
+                }
+            }
+        }
+
+        impl Extend<$enum_name> for $info_name {
+            fn extend<T: IntoIterator<Item = $enum_name>>(&mut self, iter: T) {
+                for property in iter {
+                    self.apply(property);
+                }
+            }
+        }
+
+        impl FromIterator<$enum_name> for $info_name {
+            fn from_iter<T: IntoIterator<Item = $enum_name>>(iter: T) -> Self {
+                let mut info = Self::default();
+                info.extend(iter);
+                info
+            }
+        }
+    };
+
     (
         $struct_name:ident, $(#[$enum_outer:meta])* $enum_vis:vis $enum_name:ident =>
         {$(
@@ -514,10 +806,11 @@ macro_rules! read_opt_prop {
     };
 }
 
-#[cfg(any(feature = "l2cap", feature = "rfcomm"))]
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso", feature = "hci"))]
 #[macro_use]
 mod sock;
 
+pub mod ad;
 #[cfg(feature = "bluetoothd")]
 mod adapter;
 #[cfg(feature = "bluetoothd")]
@@ -527,28 +820,69 @@ pub mod adv;
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod agent;
 #[cfg(feature = "bluetoothd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+pub mod battery;
+pub mod beacon;
+#[cfg(feature = "bluetoothd")]
+mod cache;
+#[cfg(feature = "capture")]
+#[cfg_attr(docsrs, doc(cfg(feature = "capture")))]
+pub mod capture;
+pub mod class;
+#[cfg(feature = "bluetoothd")]
 mod device;
 #[cfg(feature = "bluetoothd")]
+mod device_set;
+#[cfg(feature = "bluetoothd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod gatt;
+#[cfg(feature = "hci")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hci")))]
+pub mod hci;
+#[cfg(feature = "hid")]
+#[cfg_attr(docsrs, doc(cfg(feature = "hid")))]
+pub mod hid;
+#[cfg(feature = "iso")]
+#[cfg_attr(docsrs, doc(cfg(feature = "iso")))]
+pub mod iso;
 #[cfg(feature = "l2cap")]
 #[cfg_attr(docsrs, doc(cfg(feature = "l2cap")))]
 pub mod l2cap;
+#[cfg(feature = "media")]
+#[cfg_attr(docsrs, doc(cfg(feature = "media")))]
+pub mod media;
 #[cfg(feature = "mesh")]
 #[cfg_attr(docsrs, doc(cfg(feature = "mesh")))]
 pub mod mesh;
+#[cfg(feature = "mgmt")]
+#[cfg_attr(docsrs, doc(cfg(feature = "mgmt")))]
+pub mod mgmt;
 #[cfg(feature = "bluetoothd")]
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 pub mod monitor;
+#[cfg(feature = "bluetoothd")]
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+pub mod network;
+#[cfg(feature = "obex")]
+#[cfg_attr(docsrs, doc(cfg(feature = "obex")))]
+pub mod obex;
 #[cfg(feature = "rfcomm")]
 #[cfg_attr(docsrs, doc(cfg(feature = "rfcomm")))]
 pub mod rfcomm;
+#[cfg(feature = "sco")]
+#[cfg_attr(docsrs, doc(cfg(feature = "sco")))]
+pub mod sco;
+#[cfg(feature = "bluetoothd")]
+mod seq_packet;
 #[cfg(feature = "bluetoothd")]
 mod session;
 mod sys;
+#[cfg(feature = "testing")]
+#[cfg_attr(docsrs, doc(cfg(feature = "testing")))]
+pub mod testing;
 
 #[cfg(feature = "bluetoothd")]
-pub use crate::{adapter::*, device::*, session::*};
+pub use crate::{adapter::*, device::*, device_set::*, session::*};
 
 #[doc(no_inline)]
 pub use uuid::Uuid;
@@ -722,7 +1056,13 @@ impl From<dbus::Error> for Error {
         }
         let kind = match err
             .name()
-            .and_then(|name| name.strip_prefix(ERR_PREFIX))
+            .and_then(|name| {
+                #[cfg(feature = "obex")]
+                if let Some(kind) = name.strip_prefix(obex::ERR_PREFIX) {
+                    return Some(kind);
+                }
+                name.strip_prefix(ERR_PREFIX)
+            })
             .and_then(|s| ErrorKind::from_str(s).ok())
         {
             Some(kind) => kind,
@@ -1029,11 +1369,14 @@ impl FromStr for Modalias {
 }
 
 /// Gets all D-Bus objects from the BlueZ service.
+///
+/// They are taken from the object cache, if it is enabled and valid.
 #[cfg(feature = "bluetoothd")]
-async fn all_dbus_objects(
-    connection: &SyncConnection,
-) -> Result<HashMap<Path<'static>, HashMap<String, PropMap>>> {
-    let p = Proxy::new(SERVICE_NAME, "/", TIMEOUT, connection);
+async fn all_dbus_objects(inner: &SessionInner) -> Result<HashMap<Path<'static>, HashMap<String, PropMap>>> {
+    if let Some(objects) = inner.cache.as_ref().and_then(|cache| cache.managed_objects()) {
+        return Ok(objects);
+    }
+    let p = Proxy::new(inner.service_name.clone(), "/", inner.timeout, &*inner.connection);
     Ok(p.get_managed_objects().await?)
 }
 
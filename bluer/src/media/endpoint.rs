@@ -0,0 +1,327 @@
+//! Local media endpoint.
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::Proxy,
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use futures::{channel::oneshot, Future};
+use std::{
+    collections::HashMap,
+    fmt,
+    pin::Pin,
+    sync::{Arc, Weak},
+};
+use uuid::Uuid;
+
+use super::{MediaTransport, ReqError, ReqResult, MANAGER_INTERFACE};
+use crate::{method_call, Adapter, Address, DbusResult, Device, Result, SessionInner};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaEndpoint1";
+pub(crate) const ENDPOINT_PREFIX: &str = publish_path!("media/endpoint/");
+
+/// A2DP SBC codec identifier.
+pub const CODEC_SBC: u8 = 0x00;
+/// A2DP MPEG-1,2 Audio codec identifier.
+pub const CODEC_MPEG12: u8 = 0x01;
+/// A2DP MPEG-2,4 AAC codec identifier.
+pub const CODEC_MPEG24: u8 = 0x02;
+/// A2DP ATRAC family codec identifier.
+pub const CODEC_ATRAC: u8 = 0x04;
+/// LC3 codec identifier used by LE Audio.
+pub const CODEC_LC3: u8 = 0x06;
+/// Vendor specific codec identifier.
+///
+/// The codec is further identified by [MediaEndpoint::vendor].
+pub const CODEC_VENDOR: u8 = 0xff;
+
+/// Vendor specific codec.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct VendorCodec {
+    /// Company identifier assigned by the Bluetooth SIG.
+    pub company_id: u16,
+    /// Vendor specific codec identifier.
+    pub codec_id: u16,
+}
+
+impl VendorCodec {
+    fn to_u32(self) -> u32 {
+        (u32::from(self.company_id) << 16) | u32::from(self.codec_id)
+    }
+}
+
+/// Request to select a codec configuration.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SelectConfigurationRequest {
+    /// Codec capabilities of the remote endpoint.
+    pub capabilities: Vec<u8>,
+}
+
+/// Function handling a select configuration request.
+///
+/// It returns the codec configuration to use, which must be compatible with
+/// the capabilities of both the local and the remote endpoint.
+pub type SelectConfigurationFn = Box<
+    dyn (Fn(SelectConfigurationRequest) -> Pin<Box<dyn Future<Output = ReqResult<Vec<u8>>> + Send>>)
+        + Send
+        + Sync,
+>;
+
+/// Request to set the configuration of a media transport.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct SetConfigurationRequest {
+    /// Name of adapter making this request.
+    pub adapter_name: String,
+    /// Address of remote device.
+    pub device_address: Address,
+    /// Configured media transport.
+    pub transport: MediaTransport,
+    /// UUID of the profile the transport belongs to.
+    pub uuid: Option<Uuid>,
+    /// Codec identifier.
+    pub codec: Option<u8>,
+    /// Codec configuration.
+    pub configuration: Vec<u8>,
+}
+
+impl SetConfigurationRequest {
+    fn from_dict(transport: MediaTransport, dict: &PropMap) -> DbusResult<Self> {
+        let device = read_prop!(dict, "Device", Path);
+        let (adapter_name, device_address) = Device::parse_dbus_path(&device).ok_or_else(|| {
+            log::warn!("cannot parse device path: {}", device);
+            MethodErr::invalid_arg("Device")
+        })?;
+        Ok(Self {
+            adapter_name: adapter_name.to_string(),
+            device_address,
+            transport,
+            uuid: read_opt_prop!(dict, "UUID", String).and_then(|v| v.parse().ok()),
+            codec: read_opt_prop!(dict, "Codec", u8),
+            configuration: read_opt_prop!(dict, "Configuration", Vec<u8>).unwrap_or_default(),
+        })
+    }
+}
+
+/// Function handling a set configuration request.
+pub type SetConfigurationFn =
+    Box<dyn (Fn(SetConfigurationRequest) -> Pin<Box<dyn Future<Output = ReqResult<()>> + Send>>) + Send + Sync>;
+
+/// Request to clear the configuration of a media transport.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ClearConfigurationRequest {
+    /// Media transport.
+    pub transport: MediaTransport,
+}
+
+/// Function handling a clear configuration request.
+pub type ClearConfigurationFn =
+    Box<dyn (Fn(ClearConfigurationRequest) -> Pin<Box<dyn Future<Output = ReqResult<()>> + Send>>) + Send + Sync>;
+
+/// Local media endpoint definition.
+///
+/// Use [Adapter::register_media_endpoint] to register the endpoint.
+#[derive(custom_debug::Debug, Default)]
+pub struct MediaEndpoint {
+    /// UUID of the profile the endpoint is for,
+    /// for example `0000110a-0000-1000-8000-00805f9b34fb` for an A2DP source
+    /// or `0000110b-0000-1000-8000-00805f9b34fb` for an A2DP sink.
+    pub uuid: Uuid,
+    /// Codec identifier, for example [CODEC_SBC].
+    pub codec: u8,
+    /// Vendor specific codec.
+    ///
+    /// Must be specified if [codec](Self::codec) is [CODEC_VENDOR].
+    pub vendor: Option<VendorCodec>,
+    /// Codec capabilities.
+    pub capabilities: Vec<u8>,
+    /// Metadata.
+    pub metadata: Vec<u8>,
+    /// Whether delay reporting is supported.
+    pub delay_reporting: bool,
+    /// Function called when the remote endpoint requests the local
+    /// endpoint to select a codec configuration.
+    ///
+    /// If unset, the request is rejected.
+    #[debug(skip)]
+    pub select_configuration: Option<SelectConfigurationFn>,
+    /// Function called when a media transport has been configured.
+    ///
+    /// If unset, the configuration is accepted.
+    #[debug(skip)]
+    pub set_configuration: Option<SetConfigurationFn>,
+    /// Function called when the configuration of a media transport
+    /// has been cleared.
+    #[debug(skip)]
+    pub clear_configuration: Option<ClearConfigurationFn>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl MediaEndpoint {
+    fn properties(&self) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        hm.insert("UUID", Variant(Box::new(self.uuid.to_string())));
+        hm.insert("Codec", Variant(Box::new(self.codec)));
+        if let Some(vendor) = self.vendor {
+            hm.insert("Vendor", Variant(Box::new(vendor.to_u32())));
+        }
+        hm.insert("Capabilities", Variant(Box::new(self.capabilities.clone())));
+        if !self.metadata.is_empty() {
+            hm.insert("Metadata", Variant(Box::new(self.metadata.clone())));
+        }
+        hm.insert("DelayReporting", Variant(Box::new(self.delay_reporting)));
+        hm
+    }
+
+    pub(crate) async fn register(
+        self, inner: Arc<SessionInner>, adapter_name: Arc<String>,
+    ) -> Result<MediaEndpointHandle> {
+        let name = Path::new(format!("{}{}", ENDPOINT_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        log::trace!("Publishing media endpoint at {}", &name);
+
+        let reg = Arc::new(RegisteredMediaEndpoint { e: self, inner: Arc::downgrade(&inner) });
+        {
+            let mut cr = inner.crossroads.lock().await;
+            cr.insert(name.clone(), &[inner.media_endpoint_token], reg.clone());
+        }
+
+        log::trace!("Registering media endpoint at {}", &name);
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let reg_name = name.clone();
+        let res = inner
+            .register(&name, adapter_path.clone(), MANAGER_INTERFACE, "RegisterEndpoint", move || {
+                (reg_name.clone(), reg.e.properties())
+            })
+            .await;
+        if let Err(err) = res {
+            let mut cr = inner.crossroads.lock().await;
+            let _: Option<Arc<RegisteredMediaEndpoint>> = cr.remove(&name);
+            return Err(err);
+        }
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let unreg_name = name.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+            inner.forget_registrations(&unreg_name).await;
+
+            log::trace!("Unregistering media endpoint at {}", &unreg_name);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(MANAGER_INTERFACE, "UnregisterEndpoint", (unreg_name.clone(),)).await;
+
+            log::trace!("Unpublishing media endpoint at {}", &unreg_name);
+            let mut cr = inner.crossroads.lock().await;
+            let _: Option<Arc<RegisteredMediaEndpoint>> = cr.remove(&unreg_name);
+        });
+
+        Ok(MediaEndpointHandle { name, _drop_tx: drop_tx })
+    }
+}
+
+pub(crate) struct RegisteredMediaEndpoint {
+    e: MediaEndpoint,
+    inner: Weak<SessionInner>,
+}
+
+impl RegisteredMediaEndpoint {
+    fn transport(&self, dbus_path: Path<'static>) -> ReqResult<MediaTransport> {
+        let inner = self.inner.upgrade().ok_or(ReqError::Rejected)?;
+        Ok(MediaTransport::new(inner, dbus_path))
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            cr_property!(ib, "UUID", reg => {
+                Some(reg.e.uuid.to_string())
+            });
+            cr_property!(ib, "Codec", reg => {
+                Some(reg.e.codec)
+            });
+            cr_property!(ib, "Vendor", reg => {
+                reg.e.vendor.map(|v| v.to_u32())
+            });
+            cr_property!(ib, "Capabilities", reg => {
+                Some(reg.e.capabilities.clone())
+            });
+            cr_property!(ib, "Metadata", reg => {
+                if reg.e.metadata.is_empty() { None } else { Some(reg.e.metadata.clone()) }
+            });
+            cr_property!(ib, "DelayReporting", reg => {
+                Some(reg.e.delay_reporting)
+            });
+            ib.method_with_cr_async(
+                "SetConfiguration",
+                ("transport", "properties"),
+                (),
+                |ctx, cr, (transport, properties): (Path<'static>, PropMap)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        let req = SetConfigurationRequest::from_dict(reg.transport(transport)?, &properties)?;
+                        if let Some(fun) = &reg.e.set_configuration {
+                            fun(req).await?;
+                        }
+                        Ok(())
+                    })
+                },
+            );
+            ib.method_with_cr_async(
+                "SelectConfiguration",
+                ("capabilities",),
+                ("configuration",),
+                |ctx, cr, (capabilities,): (Vec<u8>,)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        match &reg.e.select_configuration {
+                            Some(fun) => {
+                                let configuration = fun(SelectConfigurationRequest { capabilities }).await?;
+                                Ok((configuration,))
+                            }
+                            None => Err(ReqError::Rejected.into()),
+                        }
+                    })
+                },
+            );
+            ib.method_with_cr_async(
+                "ClearConfiguration",
+                ("transport",),
+                (),
+                |ctx, cr, (transport,): (Path<'static>,)| {
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        if let Some(fun) = &reg.e.clear_configuration {
+                            fun(ClearConfigurationRequest { transport: reg.transport(transport)? }).await?;
+                        }
+                        Ok(())
+                    })
+                },
+            );
+            ib.method_with_cr_async("Release", (), (), |ctx, cr, ()| {
+                method_call(ctx, cr, |_reg: Arc<Self>| async move { Ok(()) })
+            });
+        })
+    }
+}
+
+/// Handle to registered media endpoint.
+///
+/// Drop to unregister media endpoint.
+#[must_use = "MediaEndpointHandle must be held for media endpoint to be registered"]
+pub struct MediaEndpointHandle {
+    name: Path<'static>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl Drop for MediaEndpointHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for MediaEndpointHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MediaEndpointHandle {{ {} }}", &self.name)
+    }
+}
@@ -0,0 +1,439 @@
+//! Local media player published to remote devices.
+
+use dbus::{
+    arg::{PropMap, Variant},
+    channel::Sender,
+    message::SignalArgs,
+    nonblock::{stdintf::org_freedesktop_dbus::PropertiesPropertiesChanged, Proxy, SyncConnection},
+    Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use futures::{channel::oneshot, Future};
+use std::{
+    fmt,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+use super::{ReqError, ReqResult, Track, MANAGER_INTERFACE};
+use crate::{method_call, Adapter, Error, ErrorKind, Result, SessionInner};
+
+pub(crate) const INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+pub(crate) const PLAYER_PREFIX: &str = publish_path!("media/player/");
+
+/// Playback status of a local media player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PlaybackStatus {
+    /// Playing.
+    Playing,
+    /// Paused.
+    Paused,
+    /// Stopped.
+    #[default]
+    Stopped,
+}
+
+/// Loop status of a local media player.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum LoopStatus {
+    /// Playback stops when there are no more tracks to play.
+    #[default]
+    None,
+    /// The current track is played repeatedly.
+    Track,
+    /// The playlist is played repeatedly.
+    Playlist,
+}
+
+/// State of a local media player.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LocalPlayerState {
+    /// Playback status.
+    pub status: PlaybackStatus,
+    /// Loop status.
+    pub loop_status: LoopStatus,
+    /// Whether tracks are played in random order.
+    pub shuffle: bool,
+    /// Volume between 0.0 and 1.0.
+    pub volume: f64,
+    /// Playback position within the current track.
+    pub position: Duration,
+    /// Current track metadata.
+    pub track: Track,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl Default for LocalPlayerState {
+    fn default() -> Self {
+        Self {
+            status: PlaybackStatus::default(),
+            loop_status: LoopStatus::default(),
+            shuffle: false,
+            volume: 1.0,
+            position: Duration::ZERO,
+            track: Track::default(),
+            _non_exhaustive: (),
+        }
+    }
+}
+
+impl LocalPlayerState {
+    fn metadata(&self) -> PropMap {
+        let track = &self.track;
+        let mut metadata = PropMap::new();
+        if let Some(title) = &track.title {
+            metadata.insert("xesam:title".to_string(), Variant(Box::new(title.clone())));
+        }
+        if let Some(artist) = &track.artist {
+            metadata.insert("xesam:artist".to_string(), Variant(Box::new(vec![artist.clone()])));
+        }
+        if let Some(album) = &track.album {
+            metadata.insert("xesam:album".to_string(), Variant(Box::new(album.clone())));
+        }
+        if let Some(genre) = &track.genre {
+            metadata.insert("xesam:genre".to_string(), Variant(Box::new(vec![genre.clone()])));
+        }
+        if let Some(track_number) = track.track_number {
+            metadata.insert("xesam:trackNumber".to_string(), Variant(Box::new(track_number as i32)));
+        }
+        if let Some(duration) = track.duration {
+            metadata.insert("mpris:length".to_string(), Variant(Box::new(duration.as_micros() as i64)));
+        }
+        metadata
+    }
+
+    fn position_us(&self) -> i64 {
+        self.position.as_micros() as i64
+    }
+
+    /// All properties.
+    fn properties(&self) -> PropMap {
+        self.changed_properties(None)
+    }
+
+    /// Properties that differ from `old` or all properties if `old` is unspecified.
+    fn changed_properties(&self, old: Option<&Self>) -> PropMap {
+        let mut props = PropMap::new();
+        if old.map(|old| old.status != self.status).unwrap_or(true) {
+            props.insert("PlaybackStatus".to_string(), Variant(Box::new(self.status.to_string())));
+        }
+        if old.map(|old| old.loop_status != self.loop_status).unwrap_or(true) {
+            props.insert("LoopStatus".to_string(), Variant(Box::new(self.loop_status.to_string())));
+        }
+        if old.map(|old| old.shuffle != self.shuffle).unwrap_or(true) {
+            props.insert("Shuffle".to_string(), Variant(Box::new(self.shuffle)));
+        }
+        if old.map(|old| old.volume != self.volume).unwrap_or(true) {
+            props.insert("Volume".to_string(), Variant(Box::new(self.volume)));
+        }
+        if old.map(|old| old.position != self.position).unwrap_or(true) {
+            props.insert("Position".to_string(), Variant(Box::new(self.position_us())));
+        }
+        if old.map(|old| old.track != self.track).unwrap_or(true) {
+            props.insert("Metadata".to_string(), Variant(Box::new(self.metadata())));
+        }
+        props
+    }
+}
+
+/// Command sent by a remote device to a local media player.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum PlayerCommand {
+    /// Start or resume playback.
+    Play,
+    /// Pause playback.
+    Pause,
+    /// Toggle between playing and paused.
+    PlayPause,
+    /// Stop playback.
+    Stop,
+    /// Skip to the next track.
+    Next,
+    /// Skip to the previous track.
+    Previous,
+    /// Seek forward (positive) or backward (negative) by the specified
+    /// number of microseconds.
+    Seek(i64),
+    /// Set the playback position within the current track.
+    SetPosition(Duration),
+    /// Set the loop status.
+    SetLoopStatus(LoopStatus),
+    /// Set whether tracks are played in random order.
+    SetShuffle(bool),
+    /// Set the volume between 0.0 and 1.0.
+    SetVolume(f64),
+}
+
+/// Function handling a command sent to a local media player.
+pub type PlayerControlFn =
+    Box<dyn (Fn(PlayerCommand) -> Pin<Box<dyn Future<Output = ReqResult<()>> + Send>>) + Send + Sync>;
+
+/// Local media player definition.
+///
+/// The player is exposed to remote devices, for example car head units,
+/// using the Audio/Video Remote Control Profile (AVRCP).
+///
+/// Use [Adapter::register_media_player] to register the player and
+/// [LocalPlayerHandle::set_state] to update its state.
+#[derive(custom_debug::Debug, Default)]
+pub struct LocalPlayer {
+    /// Initial player state.
+    pub state: LocalPlayerState,
+    /// Whether skipping to the next track is supported.
+    pub can_go_next: bool,
+    /// Whether skipping to the previous track is supported.
+    pub can_go_previous: bool,
+    /// Whether starting or resuming playback is supported.
+    pub can_play: bool,
+    /// Whether pausing playback is supported.
+    pub can_pause: bool,
+    /// Whether seeking is supported.
+    pub can_seek: bool,
+    /// Function called for each command sent by a remote device.
+    ///
+    /// Changes of the loop status, shuffle mode and volume are
+    /// applied to the player state before the function is called.
+    ///
+    /// If unset, all commands are rejected.
+    #[debug(skip)]
+    pub control: Option<PlayerControlFn>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl LocalPlayer {
+    pub(crate) async fn register(
+        self, inner: Arc<SessionInner>, adapter_name: Arc<String>,
+    ) -> Result<LocalPlayerHandle> {
+        let name = Path::new(format!("{}{}", PLAYER_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        log::trace!("Publishing media player at {}", &name);
+
+        let reg = Arc::new(RegisteredLocalPlayer { state: Mutex::new(self.state.clone()), p: self });
+        {
+            let mut cr = inner.crossroads.lock().await;
+            cr.insert(name.clone(), &[inner.media_player_token], reg.clone());
+        }
+
+        log::trace!("Registering media player at {}", &name);
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let reg_name = name.clone();
+        let reg_player = reg.clone();
+        let res = inner
+            .register(&name, adapter_path.clone(), MANAGER_INTERFACE, "RegisterPlayer", move || {
+                (reg_name.clone(), reg_player.state.lock().unwrap().properties())
+            })
+            .await;
+        if let Err(err) = res {
+            let mut cr = inner.crossroads.lock().await;
+            let _: Option<Arc<RegisteredLocalPlayer>> = cr.remove(&name);
+            return Err(err);
+        }
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let unreg_name = name.clone();
+        let connection = inner.connection.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+            inner.forget_registrations(&unreg_name).await;
+
+            log::trace!("Unregistering media player at {}", &unreg_name);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(MANAGER_INTERFACE, "UnregisterPlayer", (unreg_name.clone(),)).await;
+
+            log::trace!("Unpublishing media player at {}", &unreg_name);
+            let mut cr = inner.crossroads.lock().await;
+            let _: Option<Arc<RegisteredLocalPlayer>> = cr.remove(&unreg_name);
+        });
+
+        Ok(LocalPlayerHandle { name, reg, connection, _drop_tx: drop_tx })
+    }
+}
+
+pub(crate) struct RegisteredLocalPlayer {
+    p: LocalPlayer,
+    state: Mutex<LocalPlayerState>,
+}
+
+impl RegisteredLocalPlayer {
+    async fn control(&self, command: PlayerCommand) -> ReqResult<()> {
+        match &self.p.control {
+            Some(control) => control(command).await,
+            None => Err(ReqError::NotSupported),
+        }
+    }
+
+    /// Updates the player state and notifies the control function
+    /// about a property change made by a remote device.
+    fn set_by_remote(self: &Arc<Self>, f: impl FnOnce(&mut LocalPlayerState), command: PlayerCommand) {
+        f(&mut self.state.lock().unwrap());
+        let this = self.clone();
+        tokio::spawn(async move {
+            if let Err(err) = this.control(command).await {
+                log::debug!("Media player control function failed: {}", err);
+            }
+        });
+    }
+
+    pub(crate) fn register_interface(cr: &mut Crossroads) -> IfaceToken<Arc<Self>> {
+        cr.register(INTERFACE, |ib: &mut IfaceBuilder<Arc<Self>>| {
+            cr_property!(ib, "PlaybackStatus", reg => {
+                Some(reg.state.lock().unwrap().status.to_string())
+            });
+            ib.property("LoopStatus").get(|_ctx, reg| Ok(reg.state.lock().unwrap().loop_status.to_string())).set(
+                |_ctx, reg, value: String| {
+                    let loop_status: LoopStatus =
+                        value.parse().map_err(|_| dbus::MethodErr::invalid_arg("LoopStatus"))?;
+                    reg.set_by_remote(|s| s.loop_status = loop_status, PlayerCommand::SetLoopStatus(loop_status));
+                    Ok(Some(value))
+                },
+            );
+            ib.property("Shuffle").get(|_ctx, reg| Ok(reg.state.lock().unwrap().shuffle)).set(
+                |_ctx, reg, shuffle: bool| {
+                    reg.set_by_remote(|s| s.shuffle = shuffle, PlayerCommand::SetShuffle(shuffle));
+                    Ok(Some(shuffle))
+                },
+            );
+            ib.property("Volume").get(|_ctx, reg| Ok(reg.state.lock().unwrap().volume)).set(
+                |_ctx, reg, volume: f64| {
+                    reg.set_by_remote(|s| s.volume = volume, PlayerCommand::SetVolume(volume));
+                    Ok(Some(volume))
+                },
+            );
+            cr_property!(ib, "Metadata", reg => {
+                Some(reg.state.lock().unwrap().metadata())
+            });
+            cr_property!(ib, "Position", reg => {
+                Some(reg.state.lock().unwrap().position_us())
+            });
+            cr_property!(ib, "Rate", _reg => {
+                Some(1.0f64)
+            });
+            cr_property!(ib, "MinimumRate", _reg => {
+                Some(1.0f64)
+            });
+            cr_property!(ib, "MaximumRate", _reg => {
+                Some(1.0f64)
+            });
+            cr_property!(ib, "CanGoNext", reg => {
+                Some(reg.p.can_go_next)
+            });
+            cr_property!(ib, "CanGoPrevious", reg => {
+                Some(reg.p.can_go_previous)
+            });
+            cr_property!(ib, "CanPlay", reg => {
+                Some(reg.p.can_play)
+            });
+            cr_property!(ib, "CanPause", reg => {
+                Some(reg.p.can_pause)
+            });
+            cr_property!(ib, "CanSeek", reg => {
+                Some(reg.p.can_seek)
+            });
+            cr_property!(ib, "CanControl", reg => {
+                Some(reg.p.control.is_some())
+            });
+
+            for (method, command) in [
+                ("Play", PlayerCommand::Play),
+                ("Pause", PlayerCommand::Pause),
+                ("PlayPause", PlayerCommand::PlayPause),
+                ("Stop", PlayerCommand::Stop),
+                ("Next", PlayerCommand::Next),
+                ("Previous", PlayerCommand::Previous),
+            ] {
+                ib.method_with_cr_async(method, (), (), move |ctx, cr, ()| {
+                    let command = command.clone();
+                    method_call(ctx, cr, |reg: Arc<Self>| async move {
+                        reg.control(command).await?;
+                        Ok(())
+                    })
+                });
+            }
+            ib.method_with_cr_async("Seek", ("offset",), (), |ctx, cr, (offset,): (i64,)| {
+                method_call(ctx, cr, move |reg: Arc<Self>| async move {
+                    reg.control(PlayerCommand::Seek(offset)).await?;
+                    Ok(())
+                })
+            });
+            ib.method_with_cr_async(
+                "SetPosition",
+                ("track_id", "position"),
+                (),
+                |ctx, cr, (_track_id, position): (Path<'static>, i64)| {
+                    method_call(ctx, cr, move |reg: Arc<Self>| async move {
+                        let position = Duration::from_micros(position.max(0) as u64);
+                        reg.control(PlayerCommand::SetPosition(position)).await?;
+                        Ok(())
+                    })
+                },
+            );
+        })
+    }
+}
+
+/// Handle to registered local media player.
+///
+/// Drop to unregister media player.
+#[must_use = "LocalPlayerHandle must be held for media player to be registered"]
+pub struct LocalPlayerHandle {
+    name: Path<'static>,
+    reg: Arc<RegisteredLocalPlayer>,
+    connection: Arc<SyncConnection>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl LocalPlayerHandle {
+    /// Current player state.
+    pub fn state(&self) -> LocalPlayerState {
+        self.reg.state.lock().unwrap().clone()
+    }
+
+    /// Update the player state.
+    ///
+    /// Remote devices are notified about the changes.
+    pub fn set_state(&self, state: LocalPlayerState) -> Result<()> {
+        let changed_properties = {
+            let mut current = self.reg.state.lock().unwrap();
+            let changed = state.changed_properties(Some(&current));
+            *current = state;
+            changed
+        };
+        if changed_properties.is_empty() {
+            return Ok(());
+        }
+
+        let ppc = PropertiesPropertiesChanged {
+            interface_name: INTERFACE.to_string(),
+            changed_properties,
+            invalidated_properties: Vec::new(),
+        };
+        self.connection
+            .send(ppc.to_emit_message(&self.name))
+            .map_err(|_| Error::new(ErrorKind::Internal(crate::InternalErrorKind::DBusConnectionLost)))?;
+        Ok(())
+    }
+}
+
+impl Drop for LocalPlayerHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for LocalPlayerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "LocalPlayerHandle {{ {} }}", &self.name)
+    }
+}
@@ -0,0 +1,66 @@
+//! Media endpoints for audio streaming.
+//!
+//! A [MediaEndpoint] announces support for a codec of an audio profile, such as
+//! A2DP source or sink, to BlueZ.
+//! When a remote device connects using that profile, BlueZ negotiates the
+//! codec configuration with the endpoint and creates a [MediaTransport] for
+//! streaming the audio data.
+//! Acquiring the transport provides a [MediaStream] for transferring
+//! encoded audio packets without passing them through D-Bus.
+//!
+//! Use [Adapter::register_media_endpoint](crate::Adapter::register_media_endpoint)
+//! to register a media endpoint.
+//!
+//! Media players of remote devices are controlled using the Audio/Video
+//! Remote Control Profile (AVRCP) through [MediaPlayer], obtained from
+//! [Device::media_players](crate::Device::media_players).
+//! A [LocalPlayer] can be registered using
+//! [Adapter::register_media_player](crate::Adapter::register_media_player)
+//! to expose track information and playback control to remote devices.
+
+use dbus::MethodErr;
+use strum::IntoStaticStr;
+
+use crate::ERR_PREFIX;
+
+mod endpoint;
+mod local_player;
+mod player;
+mod transport;
+
+pub use endpoint::*;
+pub use local_player::*;
+pub use player::*;
+pub use transport::*;
+
+pub(crate) use player::INTERFACE as PLAYER_INTERFACE;
+
+pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.Media1";
+
+/// Error response from us to a Bluetooth media request.
+#[derive(
+    Clone, Copy, Debug, Default, displaydoc::Display, Eq, PartialEq, Ord, PartialOrd, Hash, IntoStaticStr,
+)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ReqError {
+    /// Request was rejected.
+    #[default]
+    Rejected,
+    /// Invalid arguments.
+    InvalidArguments,
+    /// Request not supported.
+    NotSupported,
+}
+
+impl std::error::Error for ReqError {}
+
+impl From<ReqError> for MethodErr {
+    fn from(err: ReqError) -> Self {
+        let name: &'static str = err.into();
+        Self::from((ERR_PREFIX.to_string() + name, &err.to_string()))
+    }
+}
+
+/// Result of a Bluetooth media request to us.
+pub type ReqResult<T> = std::result::Result<T, ReqError>;
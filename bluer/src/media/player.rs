@@ -0,0 +1,318 @@
+//! Remote media player.
+
+use dbus::{
+    arg::{cast, RefArg, Variant},
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{collections::HashMap, fmt, sync::Arc, time::Duration};
+use strum::{Display, EnumString};
+
+use crate::{Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaPlayer1";
+
+/// Playback status of a media player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum PlayerStatus {
+    /// Playing.
+    #[strum(serialize = "playing")]
+    Playing,
+    /// Stopped.
+    #[strum(serialize = "stopped")]
+    Stopped,
+    /// Paused.
+    #[strum(serialize = "paused")]
+    Paused,
+    /// Seeking forward.
+    #[strum(serialize = "forward-seek")]
+    ForwardSeek,
+    /// Seeking backward.
+    #[strum(serialize = "reverse-seek")]
+    ReverseSeek,
+    /// Error.
+    #[strum(serialize = "error")]
+    Error,
+}
+
+/// Repeat mode of a media player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum RepeatMode {
+    /// Do not repeat.
+    #[strum(serialize = "off")]
+    Off,
+    /// Repeat the current track.
+    #[strum(serialize = "singletrack")]
+    SingleTrack,
+    /// Repeat all tracks.
+    #[strum(serialize = "alltracks")]
+    AllTracks,
+    /// Repeat the current group.
+    #[strum(serialize = "group")]
+    Group,
+}
+
+/// Shuffle or scan mode of a media player.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum ShuffleMode {
+    /// Off.
+    #[strum(serialize = "off")]
+    Off,
+    /// All tracks.
+    #[strum(serialize = "alltracks")]
+    AllTracks,
+    /// Current group.
+    #[strum(serialize = "group")]
+    Group,
+}
+
+/// Track metadata.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Track {
+    /// Track title.
+    pub title: Option<String>,
+    /// Track artist.
+    pub artist: Option<String>,
+    /// Track album.
+    pub album: Option<String>,
+    /// Track genre.
+    pub genre: Option<String>,
+    /// Number of tracks in total.
+    pub number_of_tracks: Option<u32>,
+    /// Track number.
+    pub track_number: Option<u32>,
+    /// Track duration.
+    pub duration: Option<Duration>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl Track {
+    fn from_dict(dict: &HashMap<String, Variant<Box<dyn RefArg + 'static>>>) -> Self {
+        let string = |key: &str| dict.get(key).and_then(|v| cast::<String>(&v.0)).cloned();
+        let number = |key: &str| dict.get(key).and_then(|v| cast::<u32>(&v.0)).cloned();
+        Self {
+            title: string("Title"),
+            artist: string("Artist"),
+            album: string("Album"),
+            genre: string("Genre"),
+            number_of_tracks: number("NumberOfTracks"),
+            track_number: number("TrackNumber"),
+            duration: number("Duration").map(|ms| Duration::from_millis(ms.into())),
+            _non_exhaustive: (),
+        }
+    }
+}
+
+/// Interface to a media player of a remote device.
+///
+/// Use [Device::media_players] to obtain the media players of a device.
+#[derive(Clone)]
+pub struct MediaPlayer {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+}
+
+impl fmt::Debug for MediaPlayer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MediaPlayer {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl MediaPlayer {
+    pub(crate) fn new(inner: Arc<SessionInner>, dbus_path: Path<'static>) -> Self {
+        Self { inner, dbus_path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
+    }
+
+    dbus_interface!(cached);
+    dbus_default_interface!(INTERFACE);
+
+    /// Streams media player property changes, such as track and status changes.
+    ///
+    /// The stream ends when the media player is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = MediaPlayerEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { changed, .. } => stream::iter(
+                MediaPlayerProperty::from_prop_map(changed).into_iter().map(MediaPlayerEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Resume playback.
+    pub async fn play(&self) -> Result<()> {
+        self.call_method("Play", ()).await
+    }
+
+    /// Pause playback.
+    pub async fn pause(&self) -> Result<()> {
+        self.call_method("Pause", ()).await
+    }
+
+    /// Stop playback.
+    pub async fn stop(&self) -> Result<()> {
+        self.call_method("Stop", ()).await
+    }
+
+    /// Skip to the next track.
+    pub async fn next(&self) -> Result<()> {
+        self.call_method("Next", ()).await
+    }
+
+    /// Skip to the previous track.
+    pub async fn previous(&self) -> Result<()> {
+        self.call_method("Previous", ()).await
+    }
+
+    /// Fast forward playback.
+    pub async fn fast_forward(&self) -> Result<()> {
+        self.call_method("FastForward", ()).await
+    }
+
+    /// Rewind playback.
+    pub async fn rewind(&self) -> Result<()> {
+        self.call_method("Rewind", ()).await
+    }
+
+    /// Press a specific key to send as passthrough command.
+    ///
+    /// The key is specified as AV/C operation id.
+    /// The key will be released automatically.
+    pub async fn press(&self, avc_key: u8) -> Result<()> {
+        self.call_method("Press", (avc_key,)).await
+    }
+}
+
+define_properties!(
+    MediaPlayer,
+    /// Media player property.
+    pub MediaPlayerProperty => {
+        /// Whether the equalizer is on.
+        property(
+            Equalizer, bool,
+            dbus: (INTERFACE, "Equalizer", String, OPTIONAL),
+            get: (is_equalizer, v => {v == "on"}),
+            set: (set_equalizer, v => {if v { "on" } else { "off" }.to_string()}),
+        );
+
+        /// Repeat mode.
+        property(
+            Repeat, RepeatMode,
+            dbus: (INTERFACE, "Repeat", String, OPTIONAL),
+            get: (repeat, v => {v.parse()?}),
+            set: (set_repeat, v => {v.to_string()}),
+        );
+
+        /// Shuffle mode.
+        property(
+            Shuffle, ShuffleMode,
+            dbus: (INTERFACE, "Shuffle", String, OPTIONAL),
+            get: (shuffle, v => {v.parse()?}),
+            set: (set_shuffle, v => {v.to_string()}),
+        );
+
+        /// Scan mode.
+        property(
+            Scan, ShuffleMode,
+            dbus: (INTERFACE, "Scan", String, OPTIONAL),
+            get: (scan, v => {v.parse()?}),
+            set: (set_scan, v => {v.to_string()}),
+        );
+
+        /// Playback status.
+        property(
+            Status, PlayerStatus,
+            dbus: (INTERFACE, "Status", String, OPTIONAL),
+            get: (status, v => {v.parse()?}),
+        );
+
+        /// Playback position.
+        property(
+            Position, Duration,
+            dbus: (INTERFACE, "Position", u32, OPTIONAL),
+            get: (position, v => {Duration::from_millis((*v).into())}),
+        );
+
+        /// Current track metadata.
+        property(
+            Track, Track,
+            dbus: (INTERFACE, "Track", HashMap<String, Variant<Box<dyn RefArg  + 'static>>>, OPTIONAL),
+            get: (track, v => {Track::from_dict(v)}),
+        );
+
+        /// Address of the device the player belongs to.
+        property(
+            Device, Address,
+            dbus: (INTERFACE, "Device", Path<'static>, MANDATORY),
+            get: (device, v => {
+                Device::parse_dbus_path(v)
+                    .map(|(_, address)| address)
+                    .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+            }),
+        );
+
+        /// Player name.
+        property(
+            Name, String,
+            dbus: (INTERFACE, "Name", String, OPTIONAL),
+            get: (name, v => {v.to_owned()}),
+        );
+
+        /// Player type, for example `Audio` or `Video`.
+        property(
+            Type, String,
+            dbus: (INTERFACE, "Type", String, OPTIONAL),
+            get: (player_type, v => {v.to_owned()}),
+        );
+
+        /// Player subtype, for example `Audio Book` or `Podcast`.
+        property(
+            Subtype, String,
+            dbus: (INTERFACE, "Subtype", String, OPTIONAL),
+            get: (subtype, v => {v.to_owned()}),
+        );
+
+        /// Whether the player supports browsing.
+        property(
+            Browsable, bool,
+            dbus: (INTERFACE, "Browsable", bool, OPTIONAL),
+            get: (is_browsable, v => {v.to_owned()}),
+        );
+
+        /// Whether the player supports searching.
+        property(
+            Searchable, bool,
+            dbus: (INTERFACE, "Searchable", bool, OPTIONAL),
+            get: (is_searchable, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// Media player event.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MediaPlayerEvent {
+    /// Property changed.
+    PropertyChanged(MediaPlayerProperty),
+}
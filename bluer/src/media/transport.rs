@@ -0,0 +1,334 @@
+//! Media transport.
+
+use dbus::{
+    arg::OwnedFd as DbusOwnedFd,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{
+    fmt,
+    os::unix::io::{AsRawFd, FromRawFd, IntoRawFd, OwnedFd, RawFd},
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+use strum::{Display, EnumString};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use uuid::Uuid;
+
+use crate::{
+    seq_packet::SeqPacket, Address, Device, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner,
+};
+
+pub(crate) const INTERFACE: &str = "org.bluez.MediaTransport1";
+
+/// State of a media transport.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MediaTransportState {
+    /// Not streaming.
+    #[strum(serialize = "idle")]
+    Idle,
+    /// Streaming but not acquired.
+    #[strum(serialize = "pending")]
+    Pending,
+    /// Streaming a broadcast but not acquired.
+    #[strum(serialize = "broadcasting")]
+    Broadcasting,
+    /// Streaming and acquired.
+    #[strum(serialize = "active")]
+    Active,
+}
+
+/// Interface to a media transport.
+///
+/// A media transport is created by BlueZ once a [media endpoint](super::MediaEndpoint)
+/// has been configured and is passed to its
+/// [set configuration function](super::MediaEndpoint::set_configuration).
+///
+/// Use [acquire](Self::acquire) to obtain a [MediaStream] for transferring
+/// encoded audio data.
+#[derive(Clone)]
+pub struct MediaTransport {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+}
+
+impl fmt::Debug for MediaTransport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MediaTransport {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl PartialEq for MediaTransport {
+    fn eq(&self, other: &Self) -> bool {
+        self.dbus_path == other.dbus_path
+    }
+}
+
+impl Eq for MediaTransport {}
+
+impl std::hash::Hash for MediaTransport {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.dbus_path.hash(state);
+    }
+}
+
+impl MediaTransport {
+    pub(crate) fn new(inner: Arc<SessionInner>, dbus_path: Path<'static>) -> Self {
+        Self { inner, dbus_path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
+    }
+
+    dbus_interface!(cached);
+    dbus_default_interface!(INTERFACE);
+
+    /// Streams media transport property changes.
+    ///
+    /// The stream ends when the media transport is removed.
+    pub async fn events(&self) -> Result<impl Stream<Item = MediaTransportEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { changed, .. } => stream::iter(
+                MediaTransportProperty::from_prop_map(changed)
+                    .into_iter()
+                    .map(MediaTransportEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Acquire the media transport for streaming.
+    ///
+    /// Use [release](Self::release) to release the transport when done.
+    pub async fn acquire(&self) -> Result<MediaStream> {
+        let (fd, read_mtu, write_mtu): (DbusOwnedFd, u16, u16) = self.call_method("Acquire", ()).await?;
+        MediaStream::new(fd, read_mtu, write_mtu)
+    }
+
+    /// Acquire the media transport for streaming, but only if it is
+    /// in [pending](MediaTransportState::Pending) or
+    /// [broadcasting](MediaTransportState::Broadcasting) state.
+    ///
+    /// Fails with [ErrorKind::NotAvailable] if the transport is not streaming.
+    ///
+    /// Use [release](Self::release) to release the transport when done.
+    pub async fn try_acquire(&self) -> Result<MediaStream> {
+        let (fd, read_mtu, write_mtu): (DbusOwnedFd, u16, u16) = self.call_method("TryAcquire", ()).await?;
+        MediaStream::new(fd, read_mtu, write_mtu)
+    }
+
+    /// Release the media transport.
+    ///
+    /// This should be called after dropping the [MediaStream] obtained by acquiring it.
+    pub async fn release(&self) -> Result<()> {
+        self.call_method("Release", ()).await
+    }
+}
+
+define_properties!(
+    MediaTransport,
+    /// Media transport property.
+    pub MediaTransportProperty => {
+        /// Address of the remote device.
+        property(
+            Device, Address,
+            dbus: (INTERFACE, "Device", Path<'static>, MANDATORY),
+            get: (device, v => {
+                Device::parse_dbus_path(v)
+                    .map(|(_, address)| address)
+                    .ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+            }),
+        );
+
+        /// UUID of the profile the transport is for.
+        property(
+            Uuid, Uuid,
+            dbus: (INTERFACE, "UUID", String, MANDATORY),
+            get: (uuid, v => {
+                v.parse().map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(v.to_string()))))?
+            }),
+        );
+
+        /// Assigned number of the codec that the transport supports.
+        ///
+        /// The values should match the codec identifiers used
+        /// when registering the media endpoint.
+        property(
+            Codec, u8,
+            dbus: (INTERFACE, "Codec", u8, MANDATORY),
+            get: (codec, v => {v.to_owned()}),
+        );
+
+        /// Configuration blob, used when configuring the transport.
+        property(
+            Configuration, Vec<u8>,
+            dbus: (INTERFACE, "Configuration", Vec<u8>, MANDATORY),
+            get: (configuration, v => {v.to_owned()}),
+        );
+
+        /// Indicates the state of the transport.
+        property(
+            State, MediaTransportState,
+            dbus: (INTERFACE, "State", String, MANDATORY),
+            get: (state, v => {v.parse()?}),
+        );
+
+        /// Transport delay in 1/10 of milliseconds.
+        ///
+        /// This property is only present if the delay reporting
+        /// feature is in use.
+        property(
+            Delay, u16,
+            dbus: (INTERFACE, "Delay", u16, OPTIONAL),
+            get: (delay, v => {v.to_owned()}),
+            set: (set_delay, v => {v}),
+        );
+
+        /// Indicates volume level of the transport.
+        ///
+        /// Possible values are 0 to 127.
+        /// This property is only present if the volume is supported.
+        property(
+            Volume, u16,
+            dbus: (INTERFACE, "Volume", u16, OPTIONAL),
+            get: (volume, v => {v.to_owned()}),
+            set: (set_volume, v => {v}),
+        );
+    }
+);
+
+/// Media transport event.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MediaTransportEvent {
+    /// Property changed.
+    PropertyChanged(MediaTransportProperty),
+}
+
+/// Streams encoded audio data of an acquired media transport with low overhead.
+///
+/// Each read or write operation transfers a single packet.
+/// Use [read_mtu](Self::read_mtu) and [write_mtu](Self::write_mtu) to determine the
+/// maximum packet sizes.
+#[derive(Debug)]
+pub struct MediaStream {
+    read_mtu: usize,
+    write_mtu: usize,
+    socket: SeqPacket,
+}
+
+impl MediaStream {
+    fn new(fd: DbusOwnedFd, read_mtu: u16, write_mtu: u16) -> Result<Self> {
+        let fd = unsafe { OwnedFd::from_raw_fd(fd.into_fd()) };
+        Ok(Self { read_mtu: read_mtu.into(), write_mtu: write_mtu.into(), socket: SeqPacket::new(fd)? })
+    }
+
+    /// Maximum size of a received packet.
+    pub fn read_mtu(&self) -> usize {
+        self.read_mtu
+    }
+
+    /// Maximum size of a sent packet.
+    pub fn write_mtu(&self) -> usize {
+        self.write_mtu
+    }
+
+    /// Wait for a new packet to become available.
+    pub async fn recvable(&self) -> std::io::Result<()> {
+        self.socket.readable().await
+    }
+
+    /// Try to receive a single packet.
+    ///
+    /// Does not wait for new data to arrive.
+    pub fn try_recv(&self) -> std::io::Result<Vec<u8>> {
+        self.socket.try_recv(self.read_mtu)
+    }
+
+    /// Receive a single packet.
+    ///
+    /// Waits for data to arrive.
+    pub async fn recv(&self) -> std::io::Result<Vec<u8>> {
+        self.socket.recv(self.read_mtu).await
+    }
+
+    /// Waits for send space to become available.
+    pub async fn sendable(&self) -> std::io::Result<()> {
+        self.socket.writable().await
+    }
+
+    /// Tries to send a single packet.
+    ///
+    /// The length of `buf` must not exceed [Self::write_mtu].
+    ///
+    /// Does not wait for send space to become available.
+    pub fn try_send(&self, buf: &[u8]) -> std::io::Result<()> {
+        self.socket.try_send(buf, self.write_mtu)
+    }
+
+    /// Sends a single packet.
+    ///
+    /// The length of `buf` must not exceed [Self::write_mtu].
+    ///
+    /// Waits for send space to become available.
+    pub async fn send(&self, buf: &[u8]) -> std::io::Result<()> {
+        self.socket.send(buf, self.write_mtu).await
+    }
+}
+
+impl AsyncRead for MediaStream {
+    /// Attempts to read from the media stream into `buf`.
+    ///
+    /// When a buffer of size less than [read_mtu] bytes is provided, the received
+    /// packet will be buffered internally and split over multiple read operations.
+    /// Thus, for best efficiency, provide a buffer of at least [read_mtu] bytes.
+    ///
+    /// [read_mtu]: MediaStream::read_mtu
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context, buf: &mut ReadBuf) -> Poll<std::io::Result<()>> {
+        let this = self.get_mut();
+        this.socket.poll_read(cx, buf, this.read_mtu)
+    }
+}
+
+impl AsyncWrite for MediaStream {
+    /// Attempt to write bytes from `buf` into the media stream.
+    ///
+    /// A single write operation will send no more than [write_mtu](MediaStream::write_mtu) bytes.
+    /// However, attempting to send a larger buffer will not result in an error but a partial send.
+    fn poll_write(self: Pin<&mut Self>, cx: &mut Context, buf: &[u8]) -> Poll<std::io::Result<usize>> {
+        self.socket.poll_write(cx, buf, self.write_mtu)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_shutdown(self: Pin<&mut Self>, _cx: &mut Context) -> Poll<std::io::Result<()>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsRawFd for MediaStream {
+    fn as_raw_fd(&self) -> RawFd {
+        self.socket.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for MediaStream {
+    fn into_raw_fd(self) -> RawFd {
+        self.socket.into_raw_fd()
+    }
+}
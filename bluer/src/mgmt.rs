@@ -0,0 +1,893 @@
+//! Kernel Bluetooth management (mgmt) interface.
+//!
+//! The management interface configures Bluetooth controllers directly through
+//! the kernel, independent of a running Bluetooth daemon.
+//! It uses the [control channel](crate::hci::Channel::Control) of an HCI socket.
+//!
+//! Use [Management::new] to open the interface.
+//! Controllers are identified by their index, i.e. `0` for `hci0`.
+//!
+//! Changing settings requires the `CAP_NET_ADMIN` capability.
+//!
+
+use crate::{
+    class::{ClassOfDevice, DeviceClass},
+    hci::{self, Channel},
+    sys::bdaddr_t,
+    Address, AddressType,
+};
+use futures::{
+    channel::{mpsc, oneshot},
+    Stream,
+};
+use num_derive::{FromPrimitive, ToPrimitive};
+use num_traits::FromPrimitive;
+use std::{
+    fmt,
+    io::{Error, ErrorKind, Result},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+};
+
+/// Controller index used for commands and events not related to a controller.
+pub const INDEX_NONE: u16 = hci::SocketAddr::INDEX_NONE;
+
+/// Size of the management packet header in bytes.
+const HEADER_SIZE: usize = 6;
+
+/// Maximum size of a management packet.
+const MAX_PACKET_SIZE: usize = HEADER_SIZE + u16::MAX as usize;
+
+mod opcode {
+    pub const READ_VERSION: u16 = 0x0001;
+    pub const READ_INDEX_LIST: u16 = 0x0003;
+    pub const READ_INFO: u16 = 0x0004;
+    pub const SET_POWERED: u16 = 0x0005;
+    pub const SET_CONNECTABLE: u16 = 0x0007;
+    pub const SET_SSP: u16 = 0x000b;
+    pub const SET_LE: u16 = 0x000d;
+    pub const SET_DEV_CLASS: u16 = 0x000e;
+    pub const LOAD_LINK_KEYS: u16 = 0x0012;
+    pub const LOAD_LONG_TERM_KEYS: u16 = 0x0013;
+    pub const SET_PRIVACY: u16 = 0x002f;
+}
+
+mod event_code {
+    pub const CMD_COMPLETE: u16 = 0x0001;
+    pub const CMD_STATUS: u16 = 0x0002;
+    pub const CONTROLLER_ERROR: u16 = 0x0003;
+    pub const INDEX_ADDED: u16 = 0x0004;
+    pub const INDEX_REMOVED: u16 = 0x0005;
+    pub const NEW_SETTINGS: u16 = 0x0006;
+    pub const CLASS_OF_DEV_CHANGED: u16 = 0x0007;
+    pub const LOCAL_NAME_CHANGED: u16 = 0x0008;
+    pub const NEW_LINK_KEY: u16 = 0x0009;
+    pub const NEW_LONG_TERM_KEY: u16 = 0x000a;
+    pub const DEVICE_CONNECTED: u16 = 0x000b;
+    pub const DEVICE_DISCONNECTED: u16 = 0x000c;
+}
+
+/// Possible bit values of controller [settings](ControllerInfo::current_settings).
+pub mod settings {
+    /// Powered.
+    pub const POWERED: u32 = 1 << 0;
+    /// Connectable.
+    pub const CONNECTABLE: u32 = 1 << 1;
+    /// Fast connectable.
+    pub const FAST_CONNECTABLE: u32 = 1 << 2;
+    /// Discoverable.
+    pub const DISCOVERABLE: u32 = 1 << 3;
+    /// Bondable.
+    pub const BONDABLE: u32 = 1 << 4;
+    /// Link level security.
+    pub const LINK_SECURITY: u32 = 1 << 5;
+    /// Secure Simple Pairing.
+    pub const SSP: u32 = 1 << 6;
+    /// Classic Bluetooth (BR/EDR).
+    pub const BREDR: u32 = 1 << 7;
+    /// High speed.
+    pub const HS: u32 = 1 << 8;
+    /// Bluetooth Low Energy.
+    pub const LE: u32 = 1 << 9;
+    /// Advertising.
+    pub const ADVERTISING: u32 = 1 << 10;
+    /// Secure connections.
+    pub const SECURE_CONN: u32 = 1 << 11;
+    /// Debug keys.
+    pub const DEBUG_KEYS: u32 = 1 << 12;
+    /// Privacy.
+    pub const PRIVACY: u32 = 1 << 13;
+    /// Controller configuration.
+    pub const CONFIGURATION: u32 = 1 << 14;
+    /// Static address.
+    pub const STATIC_ADDRESS: u32 = 1 << 15;
+    /// PHY configuration.
+    pub const PHY_CONFIGURATION: u32 = 1 << 16;
+    /// Wideband speech.
+    pub const WIDEBAND_SPEECH: u32 = 1 << 17;
+    /// Connected isochronous stream central.
+    pub const CIS_CENTRAL: u32 = 1 << 18;
+    /// Connected isochronous stream peripheral.
+    pub const CIS_PERIPHERAL: u32 = 1 << 19;
+    /// Isochronous broadcaster.
+    pub const ISO_BROADCASTER: u32 = 1 << 20;
+    /// Synchronized receiver.
+    pub const ISO_SYNC_RECEIVER: u32 = 1 << 21;
+    /// Link layer privacy.
+    pub const LL_PRIVACY: u32 = 1 << 22;
+}
+
+/// Management command status.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Status {
+    /// Success.
+    Success = 0x00,
+    /// Unknown command.
+    UnknownCommand = 0x01,
+    /// Not connected.
+    NotConnected = 0x02,
+    /// Failed.
+    Failed = 0x03,
+    /// Connect failed.
+    ConnectFailed = 0x04,
+    /// Authentication failed.
+    AuthenticationFailed = 0x05,
+    /// Not paired.
+    NotPaired = 0x06,
+    /// No resources.
+    NoResources = 0x07,
+    /// Timeout.
+    Timeout = 0x08,
+    /// Already connected.
+    AlreadyConnected = 0x09,
+    /// Busy.
+    Busy = 0x0a,
+    /// Rejected.
+    Rejected = 0x0b,
+    /// Not supported.
+    NotSupported = 0x0c,
+    /// Invalid parameters.
+    InvalidParameters = 0x0d,
+    /// Disconnected.
+    Disconnected = 0x0e,
+    /// Not powered.
+    NotPowered = 0x0f,
+    /// Cancelled.
+    Cancelled = 0x10,
+    /// Invalid index.
+    InvalidIndex = 0x11,
+    /// Blocked through rfkill.
+    Rfkilled = 0x12,
+    /// Already paired.
+    AlreadyPaired = 0x13,
+    /// Permission denied.
+    PermissionDenied = 0x14,
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "management command failed: {self:?}")
+    }
+}
+
+impl std::error::Error for Status {}
+
+impl From<Status> for Error {
+    fn from(status: Status) -> Self {
+        let kind = match status {
+            Status::UnknownCommand | Status::NotSupported => ErrorKind::Unsupported,
+            Status::InvalidParameters | Status::InvalidIndex => ErrorKind::InvalidInput,
+            Status::Timeout => ErrorKind::TimedOut,
+            Status::PermissionDenied => ErrorKind::PermissionDenied,
+            Status::NotConnected | Status::Disconnected => ErrorKind::NotConnected,
+            _ => ErrorKind::Other,
+        };
+        Error::new(kind, status)
+    }
+}
+
+/// Management interface version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Version {
+    /// Version.
+    pub version: u8,
+    /// Revision.
+    pub revision: u16,
+}
+
+impl Version {
+    /// Decodes the reply parameters of the Read Version command.
+    pub fn decode(params: &[u8]) -> Result<Self> {
+        Ok(Self { version: get_u8(params, 0)?, revision: get_u16(params, 1)? })
+    }
+}
+
+/// Controller information.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct ControllerInfo {
+    /// Public address.
+    pub address: Address,
+    /// Bluetooth core specification version.
+    pub bluetooth_version: u8,
+    /// Manufacturer identifier.
+    pub manufacturer: u16,
+    /// Supported [settings].
+    pub supported_settings: u32,
+    /// Current [settings].
+    pub current_settings: u32,
+    /// Class of device.
+    pub class: u32,
+    /// Name.
+    pub name: String,
+    /// Short name.
+    pub short_name: String,
+}
+
+impl ControllerInfo {
+    /// Decodes the reply parameters of the Read Controller Information command.
+    pub fn decode(params: &[u8]) -> Result<Self> {
+        Ok(Self {
+            address: get_bdaddr(params)?,
+            bluetooth_version: get_u8(params, 6)?,
+            manufacturer: get_u16(params, 7)?,
+            supported_settings: get_u32(params, 9)?,
+            current_settings: get_u32(params, 13)?,
+            class: get_u24(params, 17)?,
+            name: get_str(params.get(20..269).ok_or_else(|| invalid_data("name too short"))?),
+            short_name: get_str(params.get(269..280).ok_or_else(|| invalid_data("short name too short"))?),
+        })
+    }
+}
+
+/// Privacy mode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, FromPrimitive, ToPrimitive)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Privacy {
+    /// Privacy disabled.
+    Disabled = 0x00,
+    /// Privacy enabled.
+    Enabled = 0x01,
+    /// Limited privacy, where the identity address is used while discoverable.
+    Limited = 0x02,
+}
+
+/// Classic Bluetooth (BR/EDR) link key.
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LinkKey {
+    /// Device address.
+    pub address: Address,
+    /// Device address type.
+    pub address_type: AddressType,
+    /// Key type.
+    pub key_type: u8,
+    /// Key value.
+    pub value: [u8; 16],
+    /// PIN length.
+    pub pin_length: u8,
+}
+
+impl fmt::Debug for LinkKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LinkKey")
+            .field("address", &self.address)
+            .field("address_type", &self.address_type)
+            .field("key_type", &self.key_type)
+            .field("pin_length", &self.pin_length)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LinkKey {
+    const SIZE: usize = 25;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_address(buf, self.address, self.address_type);
+        buf.push(self.key_type);
+        buf.extend_from_slice(&self.value);
+        buf.push(self.pin_length);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let buf = buf.get(..Self::SIZE).ok_or_else(|| invalid_data("link key too short"))?;
+        let (address, address_type) = get_address(buf)?;
+        Ok(Self {
+            address,
+            address_type,
+            key_type: buf[7],
+            value: buf[8..24].try_into().unwrap(),
+            pin_length: buf[24],
+        })
+    }
+}
+
+/// Bluetooth Low Energy long term key (LTK).
+#[derive(Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct LongTermKey {
+    /// Device address.
+    pub address: Address,
+    /// Device address type.
+    pub address_type: AddressType,
+    /// Key type.
+    pub key_type: u8,
+    /// Whether the key is used when the local device is central.
+    pub central: bool,
+    /// Encryption key size.
+    pub encryption_size: u8,
+    /// Encrypted diversifier.
+    pub ediv: u16,
+    /// Random number.
+    pub rand: u64,
+    /// Key value.
+    pub value: [u8; 16],
+}
+
+impl fmt::Debug for LongTermKey {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("LongTermKey")
+            .field("address", &self.address)
+            .field("address_type", &self.address_type)
+            .field("key_type", &self.key_type)
+            .field("central", &self.central)
+            .field("encryption_size", &self.encryption_size)
+            .finish_non_exhaustive()
+    }
+}
+
+impl LongTermKey {
+    const SIZE: usize = 36;
+
+    fn encode(&self, buf: &mut Vec<u8>) {
+        put_address(buf, self.address, self.address_type);
+        buf.push(self.key_type);
+        buf.push(self.central.into());
+        buf.push(self.encryption_size);
+        buf.extend_from_slice(&self.ediv.to_le_bytes());
+        buf.extend_from_slice(&self.rand.to_le_bytes());
+        buf.extend_from_slice(&self.value);
+    }
+
+    fn decode(buf: &[u8]) -> Result<Self> {
+        let buf = buf.get(..Self::SIZE).ok_or_else(|| invalid_data("long term key too short"))?;
+        let (address, address_type) = get_address(buf)?;
+        Ok(Self {
+            address,
+            address_type,
+            key_type: buf[7],
+            central: buf[8] != 0,
+            encryption_size: buf[9],
+            ediv: u16::from_le_bytes([buf[10], buf[11]]),
+            rand: u64::from_le_bytes(buf[12..20].try_into().unwrap()),
+            value: buf[20..36].try_into().unwrap(),
+        })
+    }
+}
+
+/// Management command.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Command {
+    /// Controller index or [INDEX_NONE].
+    pub index: u16,
+    /// Command opcode.
+    pub opcode: u16,
+    /// Parameters.
+    pub params: Vec<u8>,
+}
+
+impl Command {
+    /// Encodes the command including its header.
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        encode_packet(self.opcode, self.index, &self.params)
+    }
+
+    /// Decodes the command including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let (opcode, index, params) = decode_packet(buf)?;
+        Ok(Self { index, opcode, params: params.to_vec() })
+    }
+}
+
+/// Reply to a management command.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Reply {
+    /// Controller index or [INDEX_NONE].
+    pub index: u16,
+    /// Opcode of the command this is the reply to.
+    pub opcode: u16,
+    /// Command status.
+    pub status: Status,
+    /// Reply parameters.
+    pub params: Vec<u8>,
+}
+
+impl Reply {
+    /// Decodes a Command Complete or Command Status event including its header.
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let (code, index, params) = decode_packet(buf)?;
+        if !matches!(code, event_code::CMD_COMPLETE | event_code::CMD_STATUS) {
+            return Err(invalid_data("management event is not a command reply"));
+        }
+        Ok(Self {
+            index,
+            opcode: get_u16(params, 0)?,
+            status: Status::from_u8(get_u8(params, 2)?).unwrap_or(Status::Failed),
+            params: params[3..].to_vec(),
+        })
+    }
+}
+
+/// Management event.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Event {
+    /// A controller reported a hardware error.
+    ControllerError {
+        /// Controller index.
+        index: u16,
+        /// Error code.
+        code: u8,
+    },
+    /// A controller was added.
+    IndexAdded {
+        /// Controller index.
+        index: u16,
+    },
+    /// A controller was removed.
+    IndexRemoved {
+        /// Controller index.
+        index: u16,
+    },
+    /// Controller settings changed.
+    NewSettings {
+        /// Controller index.
+        index: u16,
+        /// Current [settings].
+        settings: u32,
+    },
+    /// Class of device changed.
+    ClassOfDeviceChanged {
+        /// Controller index.
+        index: u16,
+        /// Class of device.
+        class: u32,
+    },
+    /// Local name changed.
+    LocalNameChanged {
+        /// Controller index.
+        index: u16,
+        /// Name.
+        name: String,
+        /// Short name.
+        short_name: String,
+    },
+    /// A new link key was created.
+    NewLinkKey {
+        /// Controller index.
+        index: u16,
+        /// Whether the key should be stored persistently.
+        store_hint: bool,
+        /// Key.
+        key: LinkKey,
+    },
+    /// A new long term key was created.
+    NewLongTermKey {
+        /// Controller index.
+        index: u16,
+        /// Whether the key should be stored persistently.
+        store_hint: bool,
+        /// Key.
+        key: LongTermKey,
+    },
+    /// A device connected.
+    DeviceConnected {
+        /// Controller index.
+        index: u16,
+        /// Device address.
+        address: Address,
+        /// Device address type.
+        address_type: AddressType,
+        /// Flags.
+        flags: u32,
+        /// Extended inquiry response (EIR) or advertising data.
+        eir: Vec<u8>,
+    },
+    /// A device disconnected.
+    DeviceDisconnected {
+        /// Controller index.
+        index: u16,
+        /// Device address.
+        address: Address,
+        /// Device address type.
+        address_type: AddressType,
+        /// Disconnection reason.
+        reason: u8,
+    },
+    /// Other event that is not decoded.
+    Other {
+        /// Controller index.
+        index: u16,
+        /// Event code.
+        code: u16,
+        /// Parameters.
+        params: Vec<u8>,
+    },
+}
+
+impl Event {
+    /// Decodes the event including its header.
+    ///
+    /// Command Complete and Command Status events are decoded using [Reply::decode].
+    pub fn decode(buf: &[u8]) -> Result<Self> {
+        let (code, index, params) = decode_packet(buf)?;
+        let event = match code {
+            event_code::CMD_COMPLETE | event_code::CMD_STATUS => {
+                return Err(invalid_data("command reply is not a management event"))
+            }
+            event_code::CONTROLLER_ERROR => Self::ControllerError { index, code: get_u8(params, 0)? },
+            event_code::INDEX_ADDED => Self::IndexAdded { index },
+            event_code::INDEX_REMOVED => Self::IndexRemoved { index },
+            event_code::NEW_SETTINGS => Self::NewSettings { index, settings: get_u32(params, 0)? },
+            event_code::CLASS_OF_DEV_CHANGED => Self::ClassOfDeviceChanged { index, class: get_u24(params, 0)? },
+            event_code::LOCAL_NAME_CHANGED => Self::LocalNameChanged {
+                index,
+                name: get_str(params.get(..249).ok_or_else(|| invalid_data("name too short"))?),
+                short_name: get_str(params.get(249..260).ok_or_else(|| invalid_data("short name too short"))?),
+            },
+            event_code::NEW_LINK_KEY => Self::NewLinkKey {
+                index,
+                store_hint: get_u8(params, 0)? != 0,
+                key: LinkKey::decode(params.get(1..).unwrap_or_default())?,
+            },
+            event_code::NEW_LONG_TERM_KEY => Self::NewLongTermKey {
+                index,
+                store_hint: get_u8(params, 0)? != 0,
+                key: LongTermKey::decode(params.get(1..).unwrap_or_default())?,
+            },
+            event_code::DEVICE_CONNECTED => {
+                let (address, address_type) = get_address(params)?;
+                let eir_len = usize::from(get_u16(params, 11)?);
+                Self::DeviceConnected {
+                    index,
+                    address,
+                    address_type,
+                    flags: get_u32(params, 7)?,
+                    eir: params.get(13..13 + eir_len).ok_or_else(|| invalid_data("EIR data truncated"))?.to_vec(),
+                }
+            }
+            event_code::DEVICE_DISCONNECTED => {
+                let (address, address_type) = get_address(params)?;
+                Self::DeviceDisconnected { index, address, address_type, reason: get_u8(params, 7)? }
+            }
+            code => Self::Other { index, code, params: params.to_vec() },
+        };
+        Ok(event)
+    }
+}
+
+fn invalid_data(msg: &str) -> Error {
+    Error::new(ErrorKind::InvalidData, msg)
+}
+
+/// Encodes a management packet consisting of code, index and parameters.
+fn encode_packet(code: u16, index: u16, params: &[u8]) -> Result<Vec<u8>> {
+    let len: u16 =
+        params.len().try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "parameters too long"))?;
+    let mut buf = Vec::with_capacity(HEADER_SIZE + params.len());
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&index.to_le_bytes());
+    buf.extend_from_slice(&len.to_le_bytes());
+    buf.extend_from_slice(params);
+    Ok(buf)
+}
+
+/// Decodes a management packet into code, index and parameters.
+fn decode_packet(buf: &[u8]) -> Result<(u16, u16, &[u8])> {
+    let code = get_u16(buf, 0)?;
+    let index = get_u16(buf, 2)?;
+    let len = usize::from(get_u16(buf, 4)?);
+    let params =
+        buf.get(HEADER_SIZE..HEADER_SIZE + len).ok_or_else(|| invalid_data("management packet truncated"))?;
+    Ok((code, index, params))
+}
+
+fn get_u8(buf: &[u8], pos: usize) -> Result<u8> {
+    buf.get(pos).copied().ok_or_else(|| invalid_data("management packet too short"))
+}
+
+fn get_u16(buf: &[u8], pos: usize) -> Result<u16> {
+    Ok(u16::from_le_bytes([get_u8(buf, pos)?, get_u8(buf, pos + 1)?]))
+}
+
+fn get_u24(buf: &[u8], pos: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes([get_u8(buf, pos)?, get_u8(buf, pos + 1)?, get_u8(buf, pos + 2)?, 0]))
+}
+
+fn get_u32(buf: &[u8], pos: usize) -> Result<u32> {
+    Ok(u32::from_le_bytes([
+        get_u8(buf, pos)?,
+        get_u8(buf, pos + 1)?,
+        get_u8(buf, pos + 2)?,
+        get_u8(buf, pos + 3)?,
+    ]))
+}
+
+fn get_str(buf: &[u8]) -> String {
+    let len = buf.iter().position(|&b| b == 0).unwrap_or(buf.len());
+    String::from_utf8_lossy(&buf[..len]).into_owned()
+}
+
+fn get_bdaddr(buf: &[u8]) -> Result<Address> {
+    let b: [u8; 6] = buf.get(..6).ok_or_else(|| invalid_data("address too short"))?.try_into().unwrap();
+    Ok(Address::from(bdaddr_t { b }))
+}
+
+fn get_address(buf: &[u8]) -> Result<(Address, AddressType)> {
+    let address_type =
+        AddressType::from_u8(get_u8(buf, 6)?).ok_or_else(|| invalid_data("invalid address type"))?;
+    Ok((get_bdaddr(buf)?, address_type))
+}
+
+fn put_address(buf: &mut Vec<u8>, address: Address, address_type: AddressType) {
+    let addr: bdaddr_t = address.into();
+    buf.extend_from_slice(&addr.b);
+    buf.push(address_type as _);
+}
+
+struct PendingCommand {
+    id: u64,
+    opcode: u16,
+    index: u16,
+    tx: oneshot::Sender<std::result::Result<Vec<u8>, Status>>,
+}
+
+struct Inner {
+    socket: hci::Socket,
+    next_id: AtomicU64,
+    pending: Mutex<Vec<PendingCommand>>,
+    event_txs: Mutex<Vec<mpsc::UnboundedSender<Event>>>,
+}
+
+impl Inner {
+    fn dispatch(&self, buf: &[u8]) -> Result<()> {
+        match get_u16(buf, 0)? {
+            event_code::CMD_COMPLETE | event_code::CMD_STATUS => {
+                let Reply { index, opcode, status, params } = Reply::decode(buf)?;
+                let result = match status {
+                    Status::Success => Ok(params),
+                    status => Err(status),
+                };
+
+                let mut pending = self.pending.lock().unwrap();
+                if let Some(pos) = pending.iter().position(|p| p.opcode == opcode && p.index == index) {
+                    let _ = pending.remove(pos).tx.send(result);
+                }
+            }
+            _ => {
+                let event = Event::decode(buf)?;
+                let mut event_txs = self.event_txs.lock().unwrap();
+                event_txs.retain(|tx| tx.unbounded_send(event.clone()).is_ok());
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Kernel Bluetooth management interface.
+///
+/// Commands may be sent concurrently.
+/// Dropping this closes the interface and ends all [event streams](Self::events).
+pub struct Management {
+    inner: Arc<Inner>,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl fmt::Debug for Management {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Management").field("socket", &self.inner.socket).finish()
+    }
+}
+
+impl Management {
+    /// Opens the kernel Bluetooth management interface.
+    ///
+    /// A task receiving replies and events is spawned on the current Tokio runtime.
+    pub async fn new() -> Result<Self> {
+        let socket = hci::Socket::bound(hci::SocketAddr::new(INDEX_NONE, Channel::Control))?;
+        let inner = Arc::new(Inner {
+            socket,
+            next_id: AtomicU64::new(0),
+            pending: Mutex::new(Vec::new()),
+            event_txs: Mutex::new(Vec::new()),
+        });
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        tokio::spawn(Self::recv_task(inner.clone(), drop_rx));
+
+        Ok(Self { inner, _drop_tx: drop_tx })
+    }
+
+    async fn recv_task(inner: Arc<Inner>, mut drop_rx: oneshot::Receiver<()>) {
+        let mut buf = vec![0; MAX_PACKET_SIZE];
+        loop {
+            tokio::select! {
+                res = inner.socket.recv(&mut buf) => match res {
+                    Ok(n) => {
+                        if let Err(err) = inner.dispatch(&buf[..n]) {
+                            log::warn!("Invalid management packet: {}", &err);
+                        }
+                    }
+                    Err(err) => {
+                        log::warn!("Receiving from management socket failed: {}", &err);
+                        break;
+                    }
+                },
+                _ = &mut drop_rx => break,
+            }
+        }
+
+        inner.pending.lock().unwrap().clear();
+        inner.event_txs.lock().unwrap().clear();
+    }
+
+    /// Sends a management command to the specified controller and returns the
+    /// response parameters.
+    ///
+    /// Use [INDEX_NONE] for commands not related to a controller.
+    pub async fn send_command(&self, index: u16, opcode: u16, params: &[u8]) -> Result<Vec<u8>> {
+        let buf = encode_packet(opcode, index, params)?;
+
+        let id = self.inner.next_id.fetch_add(1, Ordering::SeqCst);
+        let (tx, rx) = oneshot::channel();
+        self.inner.pending.lock().unwrap().push(PendingCommand { id, opcode, index, tx });
+
+        if let Err(err) = self.inner.socket.send(&buf).await {
+            self.inner.pending.lock().unwrap().retain(|p| p.id != id);
+            return Err(err);
+        }
+
+        match rx.await {
+            Ok(Ok(rp)) => Ok(rp),
+            Ok(Err(status)) => Err(status.into()),
+            Err(_) => Err(Error::new(ErrorKind::BrokenPipe, "management socket closed")),
+        }
+    }
+
+    /// Reads the version of the management interface.
+    pub async fn version(&self) -> Result<Version> {
+        let rp = self.send_command(INDEX_NONE, opcode::READ_VERSION, &[]).await?;
+        Version::decode(&rp)
+    }
+
+    /// Reads the indices of all controllers.
+    pub async fn index_list(&self) -> Result<Vec<u16>> {
+        let rp = self.send_command(INDEX_NONE, opcode::READ_INDEX_LIST, &[]).await?;
+        let count = usize::from(get_u16(&rp, 0)?);
+        (0..count).map(|n| get_u16(&rp, 2 + 2 * n)).collect()
+    }
+
+    /// Reads information about the specified controller.
+    pub async fn controller_info(&self, index: u16) -> Result<ControllerInfo> {
+        let rp = self.send_command(index, opcode::READ_INFO, &[]).await?;
+        ControllerInfo::decode(&rp)
+    }
+
+    async fn set_mode(&self, index: u16, opcode: u16, mode: u8) -> Result<u32> {
+        let rp = self.send_command(index, opcode, &[mode]).await?;
+        get_u32(&rp, 0)
+    }
+
+    /// Switches the controller on or off.
+    ///
+    /// Returns the current [settings].
+    pub async fn set_powered(&self, index: u16, powered: bool) -> Result<u32> {
+        self.set_mode(index, opcode::SET_POWERED, powered.into()).await
+    }
+
+    /// Sets whether the controller accepts incoming connections.
+    ///
+    /// Returns the current [settings].
+    pub async fn set_connectable(&self, index: u16, connectable: bool) -> Result<u32> {
+        self.set_mode(index, opcode::SET_CONNECTABLE, connectable.into()).await
+    }
+
+    /// Enables or disables Bluetooth Low Energy.
+    ///
+    /// Returns the current [settings].
+    pub async fn set_le(&self, index: u16, le: bool) -> Result<u32> {
+        self.set_mode(index, opcode::SET_LE, le.into()).await
+    }
+
+    /// Enables or disables Secure Simple Pairing (SSP).
+    ///
+    /// Returns the current [settings].
+    pub async fn set_ssp(&self, index: u16, ssp: bool) -> Result<u32> {
+        self.set_mode(index, opcode::SET_SSP, ssp.into()).await
+    }
+
+    /// Sets the major and minor device class of the controller.
+    ///
+    /// The major service classes are derived by the kernel from the
+    /// registered services and cannot be set.
+    /// Returns the resulting Class of Device.
+    pub async fn set_device_class(&self, index: u16, device_class: DeviceClass) -> Result<ClassOfDevice> {
+        let params = [u8::from(device_class.major()), device_class.minor() << 2];
+        let rp = self.send_command(index, opcode::SET_DEV_CLASS, &params).await?;
+        Ok(get_u24(&rp, 0)?.into())
+    }
+
+    /// Sets the privacy mode and the local identity resolving key (IRK).
+    ///
+    /// The controller must be powered off.
+    /// Returns the current [settings].
+    pub async fn set_privacy(&self, index: u16, privacy: Privacy, irk: [u8; 16]) -> Result<u32> {
+        let mut params = vec![privacy as u8];
+        params.extend_from_slice(&irk);
+        let rp = self.send_command(index, opcode::SET_PRIVACY, &params).await?;
+        get_u32(&rp, 0)
+    }
+
+    /// Replaces the link keys known to the kernel.
+    ///
+    /// If `debug_keys` is true, debug keys are accepted and kept.
+    pub async fn load_link_keys(&self, index: u16, debug_keys: bool, keys: &[LinkKey]) -> Result<()> {
+        let count: u16 =
+            keys.len().try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "too many link keys"))?;
+        let mut params = vec![debug_keys.into()];
+        params.extend_from_slice(&count.to_le_bytes());
+        for key in keys {
+            key.encode(&mut params);
+        }
+        self.send_command(index, opcode::LOAD_LINK_KEYS, &params).await?;
+        Ok(())
+    }
+
+    /// Replaces the long term keys known to the kernel.
+    pub async fn load_long_term_keys(&self, index: u16, keys: &[LongTermKey]) -> Result<()> {
+        let count: u16 =
+            keys.len().try_into().map_err(|_| Error::new(ErrorKind::InvalidInput, "too many long term keys"))?;
+        let mut params = count.to_le_bytes().to_vec();
+        for key in keys {
+            key.encode(&mut params);
+        }
+        self.send_command(index, opcode::LOAD_LONG_TERM_KEYS, &params).await?;
+        Ok(())
+    }
+
+    /// Streams management events of all controllers.
+    ///
+    /// The stream ends when the [Management] interface is dropped.
+    pub fn events(&self) -> EventStream {
+        let (tx, rx) = mpsc::unbounded();
+        self.inner.event_txs.lock().unwrap().push(tx);
+        EventStream { rx }
+    }
+}
+
+/// Stream of management [events](Event).
+#[derive(Debug)]
+#[must_use = "EventStream must be polled to receive events"]
+pub struct EventStream {
+    rx: mpsc::UnboundedReceiver<Event>,
+}
+
+impl Stream for EventStream {
+    type Item = Event;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.rx).poll_next(cx)
+    }
+}
@@ -19,9 +19,7 @@ use tokio::sync::{mpsc, oneshot, Mutex};
 use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
-use crate::{
-    method_call, Address, DbusResult, Device, Error, ErrorKind, Result, SessionInner, SERVICE_NAME, TIMEOUT,
-};
+use crate::{method_call, Address, DbusResult, Device, Error, ErrorKind, Result, SessionInner};
 
 pub(crate) const INTERFACE: &str = "org.bluez.AdvertisementMonitor1";
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.AdvertisementMonitorManager1";
@@ -401,14 +399,20 @@ impl MonitorManager {
         }
 
         log::trace!("Registering advertisement monitor root at {}", &root);
-        let proxy = Proxy::new(SERVICE_NAME, manager_path, TIMEOUT, inner.connection.clone());
-        let () = proxy.method_call(MANAGER_INTERFACE, "RegisterMonitor", (root.clone(),)).await?;
+        let reg_root = root.clone();
+        inner
+            .register(&root, manager_path.clone(), MANAGER_INTERFACE, "RegisterMonitor", move || {
+                (reg_root.clone(),)
+            })
+            .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), manager_path, inner.timeout, inner.connection.clone());
 
         let (_drop_tx, drop_rx) = oneshot::channel();
         let unreg_root = root.clone();
         let unreg_inner = inner.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            unreg_inner.forget_registrations(&unreg_root).await;
 
             log::trace!("Unregistering advertisement monitor root at {}", &unreg_root);
             let _: std::result::Result<(), dbus::Error> =
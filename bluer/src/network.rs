@@ -0,0 +1,283 @@
+//! Bluetooth Personal Area Networking (PAN).
+//!
+//! A remote device offering a PAN service is connected using [Network::connect],
+//! which makes BlueZ create a BNEP network interface, for example `bnep0`,
+//! that is then configured like any other network interface.
+//! Use [Device::network](crate::Device::network) to obtain the [Network] of a device.
+//!
+//! A local PAN service is provided by [registering a network server](crate::Adapter::register_network_server)
+//! that attaches incoming connections to a network bridge.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{stream, Stream, StreamExt};
+use std::{fmt, sync::Arc};
+use strum::{Display, EnumString};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{Adapter, Error, ErrorKind, Event, InternalErrorKind, Result, SessionInner, UuidExt};
+
+pub(crate) const INTERFACE: &str = "org.bluez.Network1";
+pub(crate) const SERVER_INTERFACE: &str = "org.bluez.NetworkServer1";
+
+/// Prefix of names identifying network server registrations.
+///
+/// No object is published at these paths.
+pub(crate) const SERVER_PREFIX: &str = publish_path!("network/server/");
+
+/// Personal Area Networking role.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Role {
+    /// PAN user.
+    #[strum(serialize = "panu")]
+    Panu,
+    /// Network access point.
+    #[strum(serialize = "nap")]
+    Nap,
+    /// Group ad-hoc network.
+    #[strum(serialize = "gn")]
+    Gn,
+}
+
+impl Role {
+    /// Service class UUID of the role.
+    pub fn uuid(&self) -> Uuid {
+        Uuid::from_u16(match self {
+            Self::Panu => 0x1115,
+            Self::Nap => 0x1116,
+            Self::Gn => 0x1117,
+        })
+    }
+
+    /// Role from service class UUID.
+    pub fn from_uuid(uuid: &Uuid) -> Option<Self> {
+        match uuid.as_u16()? {
+            0x1115 => Some(Self::Panu),
+            0x1116 => Some(Self::Nap),
+            0x1117 => Some(Self::Gn),
+            _ => None,
+        }
+    }
+}
+
+/// Interface to the network service of a remote Bluetooth device.
+#[derive(Clone)]
+pub struct Network {
+    inner: Arc<SessionInner>,
+    dbus_path: Path<'static>,
+}
+
+impl fmt::Debug for Network {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Network {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Network {
+    pub(crate) fn new(inner: Arc<SessionInner>, dbus_path: Path<'static>) -> Self {
+        Self { inner, dbus_path }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(self.inner.service_name.clone(), &self.dbus_path, self.inner.timeout, &*self.inner.connection)
+    }
+
+    dbus_interface!(cached);
+    dbus_default_interface!(INTERFACE);
+
+    /// Streams network property changes.
+    pub async fn events(&self) -> Result<impl Stream<Item = NetworkEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { changed, .. } => stream::iter(
+                NetworkProperty::from_prop_map(changed).into_iter().map(NetworkEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Connect to the network service of the remote device
+    /// providing the specified role.
+    ///
+    /// The returned [NetworkConnection] provides the name of the
+    /// created network interface.
+    /// The connection is closed when it is dropped.
+    pub async fn connect(&self, role: Role) -> Result<NetworkConnection> {
+        let (interface,): (String,) = self.call_method("Connect", (role.to_string(),)).await?;
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let network = self.clone();
+        tokio::spawn(async move {
+            if drop_rx.await.is_err() {
+                log::trace!("Disconnecting network {}", &network.dbus_path);
+                let _ = network.disconnect().await;
+            }
+        });
+
+        Ok(NetworkConnection { network: self.clone(), interface, drop_tx: Some(drop_tx) })
+    }
+
+    /// Disconnect from the network service of the remote device.
+    pub async fn disconnect(&self) -> Result<()> {
+        self.call_method("Disconnect", ()).await
+    }
+}
+
+define_properties!(
+    Network,
+    /// Network property.
+    pub NetworkProperty => {
+        /// Indicates if the device is connected.
+        property(
+            Connected, bool,
+            dbus: (INTERFACE, "Connected", bool, MANDATORY),
+            get: (is_connected, v => {v.to_owned()}),
+        );
+
+        /// Name of the network interface when connected.
+        property(
+            Interface, String,
+            dbus: (INTERFACE, "Interface", String, OPTIONAL),
+            get: (interface, v => {v.to_owned()}),
+        );
+
+        /// Role of the remote device when connected.
+        property(
+            Role, Role,
+            dbus: (INTERFACE, "UUID", String, OPTIONAL),
+            get: (role, v => {
+                let uuid: Uuid = v
+                    .parse()
+                    .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(v.to_string()))))?;
+                Role::from_uuid(&uuid).ok_or_else(|| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidValue)))?
+            }),
+        );
+    }
+);
+
+/// Network event.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum NetworkEvent {
+    /// Property changed.
+    PropertyChanged(NetworkProperty),
+}
+
+/// Connection to the network service of a remote device.
+///
+/// Drop to disconnect.
+#[must_use = "NetworkConnection must be held for the network to stay connected"]
+pub struct NetworkConnection {
+    network: Network,
+    interface: String,
+    drop_tx: Option<oneshot::Sender<()>>,
+}
+
+impl fmt::Debug for NetworkConnection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NetworkConnection {{ {} }}", &self.interface)
+    }
+}
+
+impl NetworkConnection {
+    /// Name of the network interface, for example `bnep0`.
+    pub fn interface(&self) -> &str {
+        &self.interface
+    }
+
+    /// Network service of the remote device.
+    pub fn network(&self) -> &Network {
+        &self.network
+    }
+
+    /// Disconnect and wait for the disconnection to complete.
+    pub async fn disconnect(mut self) -> Result<()> {
+        let res = self.network.disconnect().await;
+        if let Some(drop_tx) = self.drop_tx.take() {
+            let _ = drop_tx.send(());
+        }
+        res
+    }
+}
+
+impl Drop for NetworkConnection {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl Network {
+    pub(crate) async fn register_server(
+        inner: Arc<SessionInner>, adapter_name: Arc<String>, role: Role, bridge: String,
+    ) -> Result<NetworkServerHandle> {
+        log::trace!("Registering network server for {} on bridge {}", role, &bridge);
+        let name = dbus::Path::new(format!("{}{}", SERVER_PREFIX, Uuid::new_v4().as_simple())).unwrap();
+        let adapter_path = Adapter::dbus_path(&adapter_name)?;
+        let reg_bridge = bridge.clone();
+        inner
+            .register(&name, adapter_path.clone(), SERVER_INTERFACE, "Register", move || {
+                (role.to_string(), reg_bridge.clone())
+            })
+            .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), adapter_path, inner.timeout, inner.connection.clone());
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+            inner.forget_registrations(&name).await;
+
+            log::trace!("Unregistering network server for {}", role);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(SERVER_INTERFACE, "Unregister", (role.to_string(),)).await;
+        });
+
+        Ok(NetworkServerHandle { role, bridge, _drop_tx: drop_tx })
+    }
+}
+
+/// Handle to registered network server.
+///
+/// Drop to unregister network server.
+#[must_use = "NetworkServerHandle must be held for network server to be registered"]
+pub struct NetworkServerHandle {
+    role: Role,
+    bridge: String,
+    _drop_tx: oneshot::Sender<()>,
+}
+
+impl NetworkServerHandle {
+    /// Role provided by the network server.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Name of the network bridge incoming connections are attached to.
+    pub fn bridge(&self) -> &str {
+        &self.bridge
+    }
+}
+
+impl Drop for NetworkServerHandle {
+    fn drop(&mut self) {
+        // required for drop order
+    }
+}
+
+impl fmt::Debug for NetworkServerHandle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NetworkServerHandle {{ {} on {} }}", self.role, &self.bridge)
+    }
+}
@@ -0,0 +1,494 @@
+//! Message Access Profile (MAP) client.
+//!
+//! Use [Session::message_access](super::Session::message_access) on a session
+//! created with the [Map](super::Target::Map) target to obtain a [MessageAccess] instance.
+
+use dbus::{
+    arg::{PropMap, RefArg, Variant},
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{channel::oneshot, stream, Stream, StreamExt};
+use std::{collections::HashMap, fmt, path::Path as FsPath, sync::Arc};
+use strum::{Display, EnumString};
+
+use super::{path_to_str, ObexInner, Session, Transfer, SERVICE_NAME, TIMEOUT};
+use crate::{read_dict, Event, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.MessageAccess1";
+pub(crate) const MESSAGE_INTERFACE: &str = "org.bluez.obex.Message1";
+
+/// Message type.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MessageType {
+    /// Email.
+    #[strum(serialize = "email")]
+    Email,
+    /// GSM short message.
+    #[strum(serialize = "sms-gsm")]
+    SmsGsm,
+    /// CDMA short message.
+    #[strum(serialize = "sms-cdma")]
+    SmsCdma,
+    /// Multimedia message.
+    #[strum(serialize = "mms")]
+    Mms,
+    /// Instant message.
+    #[strum(serialize = "im")]
+    Im,
+}
+
+/// Message reception status.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum MessageStatus {
+    /// Message has been completely received.
+    #[strum(serialize = "complete")]
+    Complete,
+    /// Message has been partially received.
+    #[strum(serialize = "fractioned")]
+    Fractioned,
+    /// Only a notification of the message has been received.
+    #[strum(serialize = "notification")]
+    Notification,
+}
+
+/// Character set of a pushed message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Charset {
+    /// UTF-8.
+    #[strum(serialize = "utf8")]
+    Utf8,
+    /// Native encoding of the message type.
+    #[strum(serialize = "native")]
+    Native,
+}
+
+/// Filter for listing folders.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FolderFilter {
+    /// Offset of the first entry.
+    pub offset: Option<u16>,
+    /// Maximum number of entries.
+    pub max_count: Option<u16>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl FolderFilter {
+    fn into_dict(self) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        let Self { offset, max_count, _non_exhaustive } = self;
+        if let Some(offset) = offset {
+            hm.insert("Offset", Variant(Box::new(offset)));
+        }
+        if let Some(max_count) = max_count {
+            hm.insert("MaxCount", Variant(Box::new(max_count)));
+        }
+        hm
+    }
+}
+
+/// Filter for listing messages.
+///
+/// Timestamps are in the format `YYYYMMDDTHHMMSS`.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct MessageFilter {
+    /// Offset of the first entry.
+    pub offset: Option<u16>,
+    /// Maximum number of entries.
+    pub max_count: Option<u16>,
+    /// Maximum length of the subject.
+    pub subject_length: Option<u8>,
+    /// Message fields to include.
+    ///
+    /// See [MessageAccess::filter_fields] for supported values.
+    /// If empty, all fields are included.
+    pub fields: Vec<String>,
+    /// Message types to include.
+    ///
+    /// If empty, all types are included.
+    pub types: Vec<MessageType>,
+    /// Include only messages received at or after this time.
+    pub period_begin: Option<String>,
+    /// Include only messages received before this time.
+    pub period_end: Option<String>,
+    /// Include only read (`true`) or unread (`false`) messages.
+    pub read: Option<bool>,
+    /// Include only messages whose recipient contains this string.
+    pub recipient: Option<String>,
+    /// Include only messages whose sender contains this string.
+    pub sender: Option<String>,
+    /// Include only messages with (`true`) or without (`false`) high priority.
+    pub priority: Option<bool>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl MessageFilter {
+    fn into_dict(self) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        let Self {
+            offset,
+            max_count,
+            subject_length,
+            fields,
+            types,
+            period_begin,
+            period_end,
+            read,
+            recipient,
+            sender,
+            priority,
+            _non_exhaustive,
+        } = self;
+        if let Some(offset) = offset {
+            hm.insert("Offset", Variant(Box::new(offset)));
+        }
+        if let Some(max_count) = max_count {
+            hm.insert("MaxCount", Variant(Box::new(max_count)));
+        }
+        if let Some(subject_length) = subject_length {
+            hm.insert("SubjectLength", Variant(Box::new(subject_length)));
+        }
+        if !fields.is_empty() {
+            hm.insert("Fields", Variant(Box::new(fields)));
+        }
+        if !types.is_empty() {
+            hm.insert("Types", Variant(Box::new(types.into_iter().map(|t| t.to_string()).collect::<Vec<_>>())));
+        }
+        if let Some(period_begin) = period_begin {
+            hm.insert("PeriodBegin", Variant(Box::new(period_begin)));
+        }
+        if let Some(period_end) = period_end {
+            hm.insert("PeriodEnd", Variant(Box::new(period_end)));
+        }
+        if let Some(read) = read {
+            hm.insert("Status", Variant(Box::new(if read { "read" } else { "unread" }.to_string())));
+        }
+        if let Some(recipient) = recipient {
+            hm.insert("Recipient", Variant(Box::new(recipient)));
+        }
+        if let Some(sender) = sender {
+            hm.insert("Sender", Variant(Box::new(sender)));
+        }
+        if let Some(priority) = priority {
+            hm.insert("Priority", Variant(Box::new(priority)));
+        }
+        hm
+    }
+}
+
+/// Options for pushing a message.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PushOptions {
+    /// Do not keep a copy of the message in the sent folder.
+    pub transparent: Option<bool>,
+    /// Retry sending the message if it fails.
+    pub retry: Option<bool>,
+    /// Character set of the message.
+    pub charset: Option<Charset>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl PushOptions {
+    fn into_dict(self) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        let Self { transparent, retry, charset, _non_exhaustive } = self;
+        if let Some(transparent) = transparent {
+            hm.insert("Transparent", Variant(Box::new(transparent)));
+        }
+        if let Some(retry) = retry {
+            hm.insert("Retry", Variant(Box::new(retry)));
+        }
+        if let Some(charset) = charset {
+            hm.insert("Charset", Variant(Box::new(charset.to_string())));
+        }
+        hm
+    }
+}
+
+/// Interface to message access functions of an OBEX session.
+///
+/// Use [Session::message_access] to obtain an instance.
+#[derive(Clone)]
+pub struct MessageAccess {
+    session: Session,
+}
+
+impl fmt::Debug for MessageAccess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MessageAccess {{ {} }}", &self.session.dbus_path)
+    }
+}
+
+impl MessageAccess {
+    pub(crate) fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        self.session.proxy()
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    /// The OBEX session.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    fn message(&self, dbus_path: Path<'static>) -> Message {
+        Message::new(self.session.inner.clone(), dbus_path, self.session.drop_tx.clone())
+    }
+
+    /// Set the current folder.
+    ///
+    /// Specify `..` to change to the parent folder or
+    /// an empty string to change to the root folder.
+    pub async fn set_folder(&self, name: &str) -> Result<()> {
+        self.call_method("SetFolder", (name,)).await
+    }
+
+    /// Names of the subfolders of the current folder.
+    pub async fn list_folders(&self, filter: FolderFilter) -> Result<Vec<String>> {
+        let (folders,): (Vec<PropMap>,) = self.call_method("ListFolders", (filter.into_dict(),)).await?;
+        folders.iter().map(|folder| Ok(read_dict::<String>(folder, "Name")?.clone())).collect()
+    }
+
+    /// Message fields supported by [MessageFilter::fields].
+    pub async fn filter_fields(&self) -> Result<Vec<String>> {
+        let (fields,): (Vec<String>,) = self.call_method("ListFilterFields", ()).await?;
+        Ok(fields)
+    }
+
+    /// List the messages in the subfolder `folder` of the current folder.
+    ///
+    /// Specify an empty string to list the messages in the current folder.
+    pub async fn list_messages(&self, folder: &str, filter: MessageFilter) -> Result<Vec<Message>> {
+        let (messages,): (HashMap<Path<'static>, PropMap>,) =
+            self.call_method("ListMessages", (folder, filter.into_dict())).await?;
+        Ok(messages.into_keys().map(|dbus_path| self.message(dbus_path)).collect())
+    }
+
+    /// Request the remote device to check for new messages.
+    pub async fn update_inbox(&self) -> Result<()> {
+        self.call_method("UpdateInbox", ()).await
+    }
+
+    /// Push the message in the local file `source_file` into the subfolder `folder`
+    /// of the current folder.
+    ///
+    /// The local file must be specified by an absolute path and contain the message
+    /// in bMessage format.
+    /// Specify an empty string as `folder` to push into the current folder.
+    pub async fn push_message(
+        &self, source_file: impl AsRef<FsPath>, folder: &str, options: PushOptions,
+    ) -> Result<Transfer> {
+        let source_file = path_to_str(source_file.as_ref())?;
+        let transfer = self.call_method("PushMessage", (source_file, folder, options.into_dict())).await?;
+        Ok(self.session.transfer(transfer))
+    }
+
+    /// Streams messages as they become known to the OBEX daemon.
+    ///
+    /// This includes new messages notified by the remote device
+    /// as well as messages that are returned by [list_messages](Self::list_messages)
+    /// for the first time.
+    pub async fn notifications(&self) -> Result<impl Stream<Item = Message>> {
+        let events = self.session.inner.events(self.session.dbus_path.clone(), true).await?;
+        let this = self.clone();
+        let stream = events.flat_map(move |event| match event {
+            Event::ObjectAdded { object, interfaces } if interfaces.contains(MESSAGE_INTERFACE) => {
+                stream::once(std::future::ready(this.message(object))).boxed()
+            }
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+}
+
+/// Interface to a message on a remote device.
+///
+/// Obtain messages using [MessageAccess::list_messages] or
+/// [MessageAccess::notifications].
+/// A message keeps its session alive.
+#[derive(Clone)]
+pub struct Message {
+    inner: Arc<ObexInner>,
+    dbus_path: Path<'static>,
+    session_drop_tx: Arc<oneshot::Sender<()>>,
+}
+
+impl fmt::Debug for Message {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Message {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Message {
+    pub(crate) fn new(
+        inner: Arc<ObexInner>, dbus_path: Path<'static>, session_drop_tx: Arc<oneshot::Sender<()>>,
+    ) -> Self {
+        Self { inner, dbus_path, session_drop_tx }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(MESSAGE_INTERFACE);
+
+    /// Message handle assigned by the remote device.
+    pub fn handle(&self) -> &str {
+        self.dbus_path.rsplit_once("/message").map(|(_, handle)| handle).unwrap_or_default()
+    }
+
+    /// Download the message into the local file `target_file`.
+    ///
+    /// The local file must be specified by an absolute path.
+    /// The file will contain the message in bMessage format.
+    /// If `attachment` is true, attachments are included.
+    pub async fn get(&self, target_file: impl AsRef<FsPath>, attachment: bool) -> Result<Transfer> {
+        let target_file = path_to_str(target_file.as_ref())?;
+        let (dbus_path, properties): (Path<'static>, PropMap) =
+            self.call_method("Get", (target_file, attachment)).await?;
+        log::trace!("Started OBEX transfer at {} with {:?}", &dbus_path, &properties);
+        Ok(Transfer::new(self.inner.clone(), dbus_path, self.session_drop_tx.clone()))
+    }
+}
+
+define_properties!(
+    Message,
+    /// Message property.
+    pub MessageProperty => {
+        /// Folder which the message belongs to.
+        property(
+            Folder, String,
+            dbus: (MESSAGE_INTERFACE, "Folder", String, MANDATORY),
+            get: (folder, v => {v.to_owned()}),
+        );
+
+        /// Message subject.
+        property(
+            Subject, String,
+            dbus: (MESSAGE_INTERFACE, "Subject", String, OPTIONAL),
+            get: (subject, v => {v.to_owned()}),
+        );
+
+        /// Message timestamp in the format `YYYYMMDDTHHMMSS`.
+        property(
+            Timestamp, String,
+            dbus: (MESSAGE_INTERFACE, "Timestamp", String, OPTIONAL),
+            get: (timestamp, v => {v.to_owned()}),
+        );
+
+        /// Message sender name.
+        property(
+            Sender, String,
+            dbus: (MESSAGE_INTERFACE, "Sender", String, OPTIONAL),
+            get: (sender, v => {v.to_owned()}),
+        );
+
+        /// Message sender address.
+        property(
+            SenderAddress, String,
+            dbus: (MESSAGE_INTERFACE, "SenderAddress", String, OPTIONAL),
+            get: (sender_address, v => {v.to_owned()}),
+        );
+
+        /// Message reply-to address.
+        property(
+            ReplyTo, String,
+            dbus: (MESSAGE_INTERFACE, "ReplyTo", String, OPTIONAL),
+            get: (reply_to, v => {v.to_owned()}),
+        );
+
+        /// Message recipient name.
+        property(
+            Recipient, String,
+            dbus: (MESSAGE_INTERFACE, "Recipient", String, OPTIONAL),
+            get: (recipient, v => {v.to_owned()}),
+        );
+
+        /// Message recipient address.
+        property(
+            RecipientAddress, String,
+            dbus: (MESSAGE_INTERFACE, "RecipientAddress", String, OPTIONAL),
+            get: (recipient_address, v => {v.to_owned()}),
+        );
+
+        /// Message type.
+        property(
+            Type, MessageType,
+            dbus: (MESSAGE_INTERFACE, "Type", String, MANDATORY),
+            get: (message_type, v => {v.parse()?}),
+        );
+
+        /// Message size in bytes.
+        property(
+            Size, u64,
+            dbus: (MESSAGE_INTERFACE, "Size", u64, OPTIONAL),
+            get: (size, v => {v.to_owned()}),
+        );
+
+        /// Message reception status.
+        property(
+            Status, MessageStatus,
+            dbus: (MESSAGE_INTERFACE, "Status", String, OPTIONAL),
+            get: (status, v => {v.parse()?}),
+        );
+
+        /// Message has high priority.
+        property(
+            Priority, bool,
+            dbus: (MESSAGE_INTERFACE, "Priority", bool, OPTIONAL),
+            get: (is_priority, v => {v.to_owned()}),
+        );
+
+        /// Message has been read.
+        property(
+            Read, bool,
+            dbus: (MESSAGE_INTERFACE, "Read", bool, OPTIONAL),
+            get: (is_read, v => {v.to_owned()}),
+            set: (set_read, v => {v}),
+        );
+
+        /// Message has been sent.
+        property(
+            Sent, bool,
+            dbus: (MESSAGE_INTERFACE, "Sent", bool, OPTIONAL),
+            get: (is_sent, v => {v.to_owned()}),
+        );
+
+        /// Message is protected by digital rights management.
+        property(
+            Protected, bool,
+            dbus: (MESSAGE_INTERFACE, "Protected", bool, OPTIONAL),
+            get: (is_protected, v => {v.to_owned()}),
+        );
+
+        /// Message is marked as deleted.
+        property(
+            Deleted, bool,
+            dbus: (MESSAGE_INTERFACE, "Deleted", bool, OPTIONAL),
+            get: (is_deleted, v => {v.to_owned()}),
+            set: (set_deleted, v => {v}),
+        );
+    }
+);
@@ -0,0 +1,203 @@
+//! OBEX client.
+//!
+//! This talks to the OBEX daemon (`obexd`) of BlueZ, which provides its services
+//! on the D-Bus session bus.
+//!
+//! Use [Client::new] to connect to the OBEX daemon and [Client::create_session]
+//! to establish an OBEX session with a remote device.
+//! Depending on the [Target] of the session the following functionality is available:
+//!
+//! * [Target::Opp] (Object Push): [sending files](Session::send_file),
+//! * [Target::Ftp] (File Transfer): [listing](Session::list_folder),
+//!   [changing](Session::change_folder) and [creating](Session::create_folder) folders,
+//!   [downloading](Session::get_file), [uploading](Session::put_file) and
+//!   [deleting](Session::delete) files,
+//! * [Target::Pbap] (Phone Book Access): [phonebook access](Session::phonebook_access)
+//!   with [vCard](vcard) parsing,
+//! * [Target::Map] (Message Access): [message access](Session::message_access)
+//!   including new-message notifications.
+//!
+//! File transfers are represented by [Transfer] objects, which provide
+//! a [stream of progress events](Transfer::events).
+//!
+
+use dbus::{
+    arg::{RefArg, Variant},
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use dbus_tokio::connection;
+use futures::channel::{mpsc, oneshot};
+use std::{collections::HashMap, fmt, path::Path as FsPath, sync::Arc, time::Duration};
+use strum::{Display, EnumString};
+use tokio::task::{spawn_blocking, JoinHandle};
+
+use crate::{Address, Error, ErrorKind, Event, Result, SubscriptionReq};
+
+pub mod map;
+pub mod pbap;
+mod session;
+mod transfer;
+pub mod vcard;
+
+pub use session::*;
+pub use transfer::*;
+
+pub(crate) const SERVICE_NAME: &str = "org.bluez.obex";
+pub(crate) const ERR_PREFIX: &str = "org.bluez.obex.Error.";
+pub(crate) const PATH: &str = "/org/bluez/obex";
+pub(crate) const TIMEOUT: Duration = Duration::from_secs(120);
+
+pub(crate) const CLIENT_INTERFACE: &str = "org.bluez.obex.Client1";
+
+/// Shared state of all objects of an OBEX client.
+pub(crate) struct ObexInner {
+    pub connection: Arc<SyncConnection>,
+    pub event_sub_tx: mpsc::Sender<SubscriptionReq>,
+    dbus_task: JoinHandle<connection::IOResourceError>,
+}
+
+impl ObexInner {
+    pub async fn events(
+        &self, path: Path<'static>, child_objects: bool,
+    ) -> Result<mpsc::UnboundedReceiver<Event>> {
+        Event::subscribe(&mut self.event_sub_tx.clone(), path, child_objects, false).await
+    }
+}
+
+impl Drop for ObexInner {
+    fn drop(&mut self) {
+        // documentation for dbus_tokio::connection::IOResource indicates it is abortable
+        self.dbus_task.abort();
+    }
+}
+
+/// OBEX profile of a session.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Target {
+    /// File Transfer Profile (FTP).
+    #[strum(serialize = "ftp")]
+    Ftp,
+    /// Message Access Profile (MAP).
+    #[strum(serialize = "map")]
+    Map,
+    /// Object Push Profile (OPP).
+    #[strum(serialize = "opp")]
+    Opp,
+    /// Phone Book Access Profile (PBAP).
+    #[strum(serialize = "pbap")]
+    Pbap,
+    /// Synchronization Profile (SYNC).
+    #[strum(serialize = "sync")]
+    Sync,
+}
+
+/// Options for creating an OBEX session.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SessionOptions {
+    /// Address of the local adapter to use.
+    ///
+    /// If unspecified, the default adapter is used.
+    pub source: Option<Address>,
+    /// RFCOMM channel to connect to.
+    ///
+    /// If unspecified, the channel is determined using SDP.
+    pub channel: Option<u8>,
+    /// L2CAP PSM to connect to.
+    ///
+    /// If unspecified, the PSM is determined using SDP.
+    pub psm: Option<u16>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl SessionOptions {
+    fn into_dict(self, target: Target) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        let Self { source, channel, psm, _non_exhaustive } = self;
+        hm.insert("Target", Variant(Box::new(target.to_string())));
+        if let Some(source) = source {
+            hm.insert("Source", Variant(Box::new(source.to_string())));
+        }
+        if let Some(channel) = channel {
+            hm.insert("Channel", Variant(Box::new(channel)));
+        }
+        if let Some(psm) = psm {
+            hm.insert("PSM", Variant(Box::new(psm)));
+        }
+        hm
+    }
+}
+
+/// OBEX client.
+///
+/// Encapsulates a connection to the OBEX daemon over the D-Bus session bus.
+#[derive(Clone)]
+pub struct Client {
+    inner: Arc<ObexInner>,
+}
+
+impl fmt::Debug for Client {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Client {{ {} }}", self.inner.connection.unique_name())
+    }
+}
+
+impl Client {
+    /// Create a new OBEX client.
+    ///
+    /// This establishes a connection to the OBEX daemon over the D-Bus session bus.
+    pub async fn new() -> Result<Self> {
+        let (resource, connection) = spawn_blocking(connection::new_session_sync).await??;
+        let dbus_task = tokio::spawn(resource);
+        log::trace!("Connected to D-Bus session bus with unique name {}", &connection.unique_name());
+
+        let (event_sub_tx, event_sub_rx) = mpsc::channel(1);
+        Event::handle_connection(connection.clone(), event_sub_rx, SERVICE_NAME.into(), None).await?;
+
+        Ok(Self { inner: Arc::new(ObexInner { connection, event_sub_tx, dbus_task }) })
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, PATH, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(CLIENT_INTERFACE);
+
+    /// Create a new OBEX session with the remote device with the specified address
+    /// using the specified profile.
+    ///
+    /// The session is removed when the returned [Session] and all
+    /// [transfers](Transfer) started from it are dropped.
+    pub async fn create_session(
+        &self, destination: Address, target: Target, options: SessionOptions,
+    ) -> Result<Session> {
+        let (dbus_path,): (Path<'static>,) =
+            self.call_method("CreateSession", (destination.to_string(), options.into_dict(target))).await?;
+        log::trace!("Created OBEX session at {}", &dbus_path);
+
+        let (drop_tx, drop_rx) = oneshot::channel();
+        let connection = self.inner.connection.clone();
+        let unreg_path = dbus_path.clone();
+        tokio::spawn(async move {
+            let _ = drop_rx.await;
+
+            log::trace!("Removing OBEX session at {}", &unreg_path);
+            let proxy = Proxy::new(SERVICE_NAME, PATH, TIMEOUT, &*connection);
+            let _: std::result::Result<(), dbus::Error> =
+                proxy.method_call(CLIENT_INTERFACE, "RemoveSession", (unreg_path,)).await;
+        });
+
+        Ok(Session::new(self.inner.clone(), dbus_path, Arc::new(drop_tx)))
+    }
+}
+
+/// Converts a local path into a string for passing over D-Bus.
+fn path_to_str(path: &FsPath) -> Result<&str> {
+    path.to_str().ok_or_else(|| Error::new(ErrorKind::InvalidArguments))
+}
@@ -0,0 +1,307 @@
+//! Phone Book Access Profile (PBAP) client.
+//!
+//! Use [Session::phonebook_access](super::Session::phonebook_access) on a session
+//! created with the [Pbap](super::Target::Pbap) target to obtain a [PhonebookAccess] instance.
+//!
+//! Phonebook entries are transferred as vCards, which can be parsed
+//! using [VCard::parse_all](super::vcard::VCard::parse_all).
+
+use dbus::{
+    arg::{RefArg, Variant},
+    nonblock::{Proxy, SyncConnection},
+};
+use std::{collections::HashMap, fmt, path::Path as FsPath};
+use strum::{Display, EnumString};
+
+use super::{path_to_str, Session, Transfer};
+use crate::Result;
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.PhonebookAccess1";
+
+/// Phonebook repository location.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Location {
+    /// Phone internal memory.
+    #[strum(serialize = "int")]
+    Internal,
+    /// First SIM card.
+    #[strum(serialize = "sim1")]
+    Sim1,
+    /// Second SIM card.
+    #[strum(serialize = "sim2")]
+    Sim2,
+    /// Third SIM card.
+    #[strum(serialize = "sim3")]
+    Sim3,
+}
+
+/// Phonebook object.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Phonebook {
+    /// Contacts.
+    #[strum(serialize = "pb")]
+    Contacts,
+    /// Incoming call history.
+    #[strum(serialize = "ich")]
+    IncomingCalls,
+    /// Outgoing call history.
+    #[strum(serialize = "och")]
+    OutgoingCalls,
+    /// Missed call history.
+    #[strum(serialize = "mch")]
+    MissedCalls,
+    /// Combined call history.
+    #[strum(serialize = "cch")]
+    CombinedCalls,
+    /// Speed dials.
+    #[strum(serialize = "spd")]
+    SpeedDials,
+    /// Favorite contacts.
+    #[strum(serialize = "fav")]
+    Favorites,
+}
+
+/// vCard format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Format {
+    /// vCard 2.1.
+    #[strum(serialize = "vcard21")]
+    VCard21,
+    /// vCard 3.0.
+    #[strum(serialize = "vcard30")]
+    VCard30,
+}
+
+/// Sort order of phonebook listings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum Order {
+    /// By index.
+    #[strum(serialize = "indexed")]
+    Indexed,
+    /// Alphanumerically by name.
+    #[strum(serialize = "alphanumeric")]
+    Alphanumeric,
+    /// Phonetically by sound.
+    #[strum(serialize = "phonetic")]
+    Phonetic,
+}
+
+/// Field to search in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum SearchField {
+    /// Name.
+    #[strum(serialize = "name")]
+    Name,
+    /// Phone number.
+    #[strum(serialize = "number")]
+    Number,
+    /// Sound.
+    #[strum(serialize = "sound")]
+    Sound,
+}
+
+/// Filter for pulling and listing phonebook entries.
+#[derive(Default, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    /// vCard format.
+    ///
+    /// Only applies when pulling entries.
+    /// If unspecified, vCard 2.1 is used.
+    pub format: Option<Format>,
+    /// Sort order.
+    ///
+    /// Only applies when listing or searching entries.
+    /// If unspecified, the entries are sorted by index.
+    pub order: Option<Order>,
+    /// Offset of the first entry.
+    pub offset: Option<u16>,
+    /// Maximum number of entries.
+    pub max_count: Option<u16>,
+    /// vCard fields to include.
+    ///
+    /// Only applies when pulling entries.
+    /// See [PhonebookAccess::filter_fields] for supported values.
+    /// If empty, all fields are included.
+    pub fields: Vec<String>,
+    #[doc(hidden)]
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub _non_exhaustive: (),
+}
+
+impl Filter {
+    fn into_dict(self) -> HashMap<&'static str, Variant<Box<dyn RefArg>>> {
+        let mut hm: HashMap<&'static str, Variant<Box<dyn RefArg>>> = HashMap::new();
+        let Self { format, order, offset, max_count, fields, _non_exhaustive } = self;
+        if let Some(format) = format {
+            hm.insert("Format", Variant(Box::new(format.to_string())));
+        }
+        if let Some(order) = order {
+            hm.insert("Order", Variant(Box::new(order.to_string())));
+        }
+        if let Some(offset) = offset {
+            hm.insert("Offset", Variant(Box::new(offset)));
+        }
+        if let Some(max_count) = max_count {
+            hm.insert("MaxCount", Variant(Box::new(max_count)));
+        }
+        if !fields.is_empty() {
+            hm.insert("Fields", Variant(Box::new(fields)));
+        }
+        hm
+    }
+}
+
+/// Entry of a phonebook listing.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ListEntry {
+    /// vCard handle for use with [PhonebookAccess::pull].
+    pub handle: String,
+    /// Name.
+    pub name: String,
+}
+
+/// Interface to phonebook access functions of an OBEX session.
+///
+/// Use [Session::phonebook_access] to obtain an instance.
+#[derive(Clone)]
+pub struct PhonebookAccess {
+    session: Session,
+}
+
+impl fmt::Debug for PhonebookAccess {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "PhonebookAccess {{ {} }}", &self.session.dbus_path)
+    }
+}
+
+impl PhonebookAccess {
+    pub(crate) fn new(session: Session) -> Self {
+        Self { session }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        self.session.proxy()
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    /// The OBEX session.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Select the phonebook object for other operations.
+    pub async fn select(&self, location: Location, phonebook: Phonebook) -> Result<()> {
+        self.call_method("Select", (location.to_string(), phonebook.to_string())).await
+    }
+
+    /// Pull the entire selected phonebook into the local file `target_file`.
+    ///
+    /// The local file must be specified by an absolute path.
+    /// The file will contain the entries in vCard format.
+    pub async fn pull_all(&self, target_file: impl AsRef<FsPath>, filter: Filter) -> Result<Transfer> {
+        let target_file = path_to_str(target_file.as_ref())?;
+        let transfer = self.call_method("PullAll", (target_file, filter.into_dict())).await?;
+        Ok(self.session.transfer(transfer))
+    }
+
+    /// List the entries of the selected phonebook.
+    pub async fn list(&self, filter: Filter) -> Result<Vec<ListEntry>> {
+        let (entries,): (Vec<(String, String)>,) = self.call_method("List", (filter.into_dict(),)).await?;
+        Ok(entries.into_iter().map(|(handle, name)| ListEntry { handle, name }).collect())
+    }
+
+    /// Pull the vCard with the specified handle from the selected phonebook
+    /// into the local file `target_file`.
+    ///
+    /// The local file must be specified by an absolute path.
+    pub async fn pull(&self, handle: &str, target_file: impl AsRef<FsPath>, filter: Filter) -> Result<Transfer> {
+        let target_file = path_to_str(target_file.as_ref())?;
+        let transfer = self.call_method("Pull", (handle, target_file, filter.into_dict())).await?;
+        Ok(self.session.transfer(transfer))
+    }
+
+    /// Search for entries of the selected phonebook whose `field` matches `value`.
+    pub async fn search(&self, field: SearchField, value: &str, filter: Filter) -> Result<Vec<ListEntry>> {
+        let (entries,): (Vec<(String, String)>,) =
+            self.call_method("Search", (field.to_string(), value, filter.into_dict())).await?;
+        Ok(entries.into_iter().map(|(handle, name)| ListEntry { handle, name }).collect())
+    }
+
+    /// Number of entries in the selected phonebook.
+    pub async fn size(&self) -> Result<u16> {
+        let (size,): (u16,) = self.call_method("GetSize", ()).await?;
+        Ok(size)
+    }
+
+    /// vCard fields supported by [Filter::fields].
+    pub async fn filter_fields(&self) -> Result<Vec<String>> {
+        let (fields,): (Vec<String>,) = self.call_method("ListFilterFields", ()).await?;
+        Ok(fields)
+    }
+
+    /// Request the remote device to update the primary and secondary version counters.
+    pub async fn update_version(&self) -> Result<()> {
+        self.call_method("UpdateVersion", ()).await
+    }
+}
+
+define_properties!(
+    PhonebookAccess,
+    /// Phonebook access property.
+    pub PhonebookProperty => {
+        /// Current selected phonebook.
+        property(
+            Folder, String,
+            dbus: (INTERFACE, "Folder", String, MANDATORY),
+            get: (folder, v => {v.to_owned()}),
+        );
+
+        /// 128-bit value identifying the database on the remote device.
+        ///
+        /// Changes when the remote device resets its phonebook.
+        property(
+            DatabaseIdentifier, String,
+            dbus: (INTERFACE, "DatabaseIdentifier", String, OPTIONAL),
+            get: (database_identifier, v => {v.to_owned()}),
+        );
+
+        /// 128-bit value that changes whenever a contact of the
+        /// selected phonebook has been added, removed or its
+        /// name, phone number, email or address changed.
+        property(
+            PrimaryCounter, String,
+            dbus: (INTERFACE, "PrimaryCounter", String, OPTIONAL),
+            get: (primary_counter, v => {v.to_owned()}),
+        );
+
+        /// 128-bit value that changes whenever a contact of the
+        /// selected phonebook has been changed in any way.
+        property(
+            SecondaryCounter, String,
+            dbus: (INTERFACE, "SecondaryCounter", String, OPTIONAL),
+            get: (secondary_counter, v => {v.to_owned()}),
+        );
+
+        /// Indicates whether the remote device only supports
+        /// images of a fixed size.
+        property(
+            FixedImageSize, bool,
+            dbus: (INTERFACE, "FixedImageSize", bool, OPTIONAL),
+            get: (is_fixed_image_size, v => {v.to_owned()}),
+        );
+    }
+);
@@ -0,0 +1,267 @@
+//! OBEX session.
+
+use dbus::{
+    arg::PropMap,
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::channel::oneshot;
+use std::{fmt, path::Path as FsPath, sync::Arc};
+use strum::{Display, EnumString};
+use uuid::Uuid;
+
+use super::{map::MessageAccess, path_to_str, pbap::PhonebookAccess, ObexInner, Transfer, SERVICE_NAME, TIMEOUT};
+use crate::{read_dict, Address, Error, ErrorKind, InternalErrorKind, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.Session1";
+pub(crate) const OBJECT_PUSH_INTERFACE: &str = "org.bluez.obex.ObjectPush1";
+pub(crate) const FILE_TRANSFER_INTERFACE: &str = "org.bluez.obex.FileTransfer1";
+
+/// Interface to an OBEX session with a remote device.
+///
+/// Use [Client::create_session](super::Client::create_session) to obtain an instance.
+///
+/// The session is removed when this and all clones of it and all
+/// [transfers](Transfer) started from it are dropped.
+#[derive(Clone)]
+pub struct Session {
+    pub(super) inner: Arc<ObexInner>,
+    pub(super) dbus_path: Path<'static>,
+    pub(super) drop_tx: Arc<oneshot::Sender<()>>,
+}
+
+impl fmt::Debug for Session {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Session {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Session {
+    pub(crate) fn new(
+        inner: Arc<ObexInner>, dbus_path: Path<'static>, drop_tx: Arc<oneshot::Sender<()>>,
+    ) -> Self {
+        Self { inner, dbus_path, drop_tx }
+    }
+
+    pub(super) fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    pub(super) fn transfer(&self, (dbus_path, properties): (Path<'static>, PropMap)) -> Transfer {
+        log::trace!("Started OBEX transfer at {} with {:?}", &dbus_path, &properties);
+        Transfer::new(self.inner.clone(), dbus_path, self.drop_tx.clone())
+    }
+
+    /// Phonebook access functions.
+    ///
+    /// Requires a session with the [Pbap](super::Target::Pbap) target.
+    pub fn phonebook_access(&self) -> PhonebookAccess {
+        PhonebookAccess::new(self.clone())
+    }
+
+    /// Message access functions.
+    ///
+    /// Requires a session with the [Map](super::Target::Map) target.
+    pub fn message_access(&self) -> MessageAccess {
+        MessageAccess::new(self.clone())
+    }
+
+    /// Get remote device capabilities.
+    ///
+    /// Returns the capabilities object in XML format.
+    pub async fn capabilities(&self) -> Result<String> {
+        let (caps,): (String,) = self.call_method("GetCapabilities", ()).await?;
+        Ok(caps)
+    }
+
+    // ===========================================================================================
+    // Object Push
+    // ===========================================================================================
+
+    /// Send a local file to the remote device.
+    ///
+    /// The local file must be specified by an absolute path.
+    ///
+    /// Requires a session with the [Opp](super::Target::Opp) target.
+    pub async fn send_file(&self, source_file: impl AsRef<FsPath>) -> Result<Transfer> {
+        let source_file = path_to_str(source_file.as_ref())?;
+        let transfer = self.call_method_with_interface("SendFile", (source_file,), OBJECT_PUSH_INTERFACE).await?;
+        Ok(self.transfer(transfer))
+    }
+
+    // ===========================================================================================
+    // File Transfer
+    // ===========================================================================================
+
+    /// Change the current folder of the remote device.
+    ///
+    /// Specify `..` to change to the parent folder.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn change_folder(&self, folder: &str) -> Result<()> {
+        self.call_method_with_interface("ChangeFolder", (folder,), FILE_TRANSFER_INTERFACE).await
+    }
+
+    /// Create a new folder in the current folder of the remote device
+    /// and change into it.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn create_folder(&self, folder: &str) -> Result<()> {
+        self.call_method_with_interface("CreateFolder", (folder,), FILE_TRANSFER_INTERFACE).await
+    }
+
+    /// List the contents of the current folder of the remote device.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn list_folder(&self) -> Result<Vec<FolderEntry>> {
+        let (entries,): (Vec<PropMap>,) =
+            self.call_method_with_interface("ListFolder", (), FILE_TRANSFER_INTERFACE).await?;
+        entries.iter().map(FolderEntry::from_dict).collect()
+    }
+
+    /// Copy the file `source_file` from the current folder of the remote device
+    /// to the local file `target_file`.
+    ///
+    /// The local file must be specified by an absolute path.
+    /// If it already exists it is overwritten.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn get_file(&self, target_file: impl AsRef<FsPath>, source_file: &str) -> Result<Transfer> {
+        let target_file = path_to_str(target_file.as_ref())?;
+        let transfer = self
+            .call_method_with_interface("GetFile", (target_file, source_file), FILE_TRANSFER_INTERFACE)
+            .await?;
+        Ok(self.transfer(transfer))
+    }
+
+    /// Copy the local file `source_file` to the file `target_file`
+    /// in the current folder of the remote device.
+    ///
+    /// The local file must be specified by an absolute path.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn put_file(&self, source_file: impl AsRef<FsPath>, target_file: &str) -> Result<Transfer> {
+        let source_file = path_to_str(source_file.as_ref())?;
+        let transfer = self
+            .call_method_with_interface("PutFile", (source_file, target_file), FILE_TRANSFER_INTERFACE)
+            .await?;
+        Ok(self.transfer(transfer))
+    }
+
+    /// Delete the file or empty folder `file` from the current folder of the remote device.
+    ///
+    /// Requires a session with the [Ftp](super::Target::Ftp) target.
+    pub async fn delete(&self, file: &str) -> Result<()> {
+        self.call_method_with_interface("Delete", (file,), FILE_TRANSFER_INTERFACE).await
+    }
+}
+
+define_properties!(
+    Session,
+    /// OBEX session property.
+    pub SessionProperty => {
+        /// Bluetooth adapter address.
+        property(
+            Source, Address,
+            dbus: (INTERFACE, "Source", String, MANDATORY),
+            get: (source, v => {v.parse()?}),
+        );
+
+        /// Bluetooth device address of the remote device.
+        property(
+            Destination, Address,
+            dbus: (INTERFACE, "Destination", String, MANDATORY),
+            get: (destination, v => {v.parse()?}),
+        );
+
+        /// Bluetooth channel.
+        property(
+            Channel, u8,
+            dbus: (INTERFACE, "Channel", u8, OPTIONAL),
+            get: (channel, v => {v.to_owned()}),
+        );
+
+        /// L2CAP PSM.
+        property(
+            Psm, u16,
+            dbus: (INTERFACE, "PSM", u16, OPTIONAL),
+            get: (psm, v => {v.to_owned()}),
+        );
+
+        /// Target UUID.
+        property(
+            Target, Uuid,
+            dbus: (INTERFACE, "Target", String, MANDATORY),
+            get: (target, v => {
+                v.parse().map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::InvalidUuid(v.to_string()))))?
+            }),
+        );
+
+        /// Root path.
+        property(
+            Root, String,
+            dbus: (INTERFACE, "Root", String, OPTIONAL),
+            get: (root, v => {v.to_owned()}),
+        );
+    }
+);
+
+/// Type of a folder entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum FolderEntryKind {
+    /// Folder.
+    #[strum(serialize = "folder")]
+    Folder,
+    /// File.
+    #[strum(serialize = "file")]
+    File,
+}
+
+/// Entry of a folder listing of the remote device.
+///
+/// Timestamps and permissions are provided as reported by the remote device.
+/// Timestamps are usually in the format `YYYYMMDDTHHMMSS` with an optional `Z` suffix for UTC.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct FolderEntry {
+    /// Name.
+    pub name: String,
+    /// Type.
+    pub kind: FolderEntryKind,
+    /// Size in bytes.
+    pub size: Option<u64>,
+    /// Last modification time.
+    pub modified: Option<String>,
+    /// Creation time.
+    pub created: Option<String>,
+    /// Last access time.
+    pub accessed: Option<String>,
+    /// Permissions of the owner.
+    pub user_perm: Option<String>,
+    /// Permissions of the group.
+    pub group_perm: Option<String>,
+    /// Permissions of others.
+    pub other_perm: Option<String>,
+}
+
+impl FolderEntry {
+    fn from_dict(dict: &PropMap) -> Result<Self> {
+        Ok(Self {
+            name: read_dict::<String>(dict, "Name")?.clone(),
+            kind: read_dict::<String>(dict, "Type")?.parse()?,
+            size: read_opt_prop!(dict, "Size", u64),
+            modified: read_opt_prop!(dict, "Modified", String),
+            created: read_opt_prop!(dict, "Created", String),
+            accessed: read_opt_prop!(dict, "Accessed", String),
+            user_perm: read_opt_prop!(dict, "User-perm", String),
+            group_perm: read_opt_prop!(dict, "Group-perm", String),
+            other_perm: read_opt_prop!(dict, "Other-perm", String),
+        })
+    }
+}
@@ -0,0 +1,182 @@
+//! OBEX file transfer.
+
+use dbus::{
+    nonblock::{Proxy, SyncConnection},
+    Path,
+};
+use futures::{channel::oneshot, stream, Stream, StreamExt};
+use std::{fmt, path::PathBuf, sync::Arc};
+use strum::{Display, EnumString};
+
+use super::{ObexInner, SERVICE_NAME, TIMEOUT};
+use crate::{Event, Result};
+
+pub(crate) const INTERFACE: &str = "org.bluez.obex.Transfer1";
+
+/// Interface to an OBEX file transfer.
+///
+/// Transfers are started using the methods of [Session](super::Session).
+/// A transfer keeps its session alive.
+///
+/// The transfer is removed by the OBEX daemon once it has completed or failed.
+#[derive(Clone)]
+pub struct Transfer {
+    inner: Arc<ObexInner>,
+    dbus_path: Path<'static>,
+    _session_drop_tx: Arc<oneshot::Sender<()>>,
+}
+
+impl fmt::Debug for Transfer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Transfer {{ {} }}", &self.dbus_path)
+    }
+}
+
+impl Transfer {
+    pub(crate) fn new(
+        inner: Arc<ObexInner>, dbus_path: Path<'static>, session_drop_tx: Arc<oneshot::Sender<()>>,
+    ) -> Self {
+        Self { inner, dbus_path, _session_drop_tx: session_drop_tx }
+    }
+
+    fn proxy(&self) -> Proxy<'_, &SyncConnection> {
+        Proxy::new(SERVICE_NAME, &self.dbus_path, TIMEOUT, &*self.inner.connection)
+    }
+
+    dbus_interface!();
+    dbus_default_interface!(INTERFACE);
+
+    /// Streams transfer property changes, most notably progress
+    /// ([TransferProperty::Transferred]) and [status](TransferProperty::Status) changes.
+    ///
+    /// The stream ends when the transfer is removed by the OBEX daemon
+    /// after it has completed or failed.
+    pub async fn events(&self) -> Result<impl Stream<Item = TransferEvent>> {
+        let events = self.inner.events(self.dbus_path.clone(), false).await?;
+        let stream = events.flat_map(move |event| match event {
+            Event::PropertiesChanged { changed, .. } => stream::iter(
+                TransferProperty::from_prop_map(changed).into_iter().map(TransferEvent::PropertyChanged),
+            )
+            .boxed(),
+            _ => stream::empty().boxed(),
+        });
+
+        Ok(stream)
+    }
+
+    // ===========================================================================================
+    // Methods
+    // ===========================================================================================
+
+    /// Stop the current transfer.
+    pub async fn cancel(&self) -> Result<()> {
+        self.call_method("Cancel", ()).await
+    }
+
+    /// Suspend the transfer.
+    ///
+    /// Only transfers that are [active](TransferStatus::Active) can be suspended.
+    pub async fn suspend(&self) -> Result<()> {
+        self.call_method("Suspend", ()).await
+    }
+
+    /// Resume a suspended transfer.
+    pub async fn resume(&self) -> Result<()> {
+        self.call_method("Resume", ()).await
+    }
+}
+
+/// Status of an OBEX transfer.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Display, EnumString)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TransferStatus {
+    /// Transfer is queued.
+    #[strum(serialize = "queued")]
+    Queued,
+    /// Transfer is in progress.
+    #[strum(serialize = "active")]
+    Active,
+    /// Transfer is suspended.
+    #[strum(serialize = "suspended")]
+    Suspended,
+    /// Transfer has completed successfully.
+    #[strum(serialize = "complete")]
+    Complete,
+    /// Transfer has failed.
+    #[strum(serialize = "error")]
+    Error,
+}
+
+define_properties!(
+    Transfer,
+    /// OBEX transfer property.
+    pub TransferProperty => {
+        /// Status of the transfer.
+        property(
+            Status, TransferStatus,
+            dbus: (INTERFACE, "Status", String, MANDATORY),
+            get: (status, v => {v.parse()?}),
+        );
+
+        /// Name of the object being transferred.
+        ///
+        /// Either the name of the remote file or the
+        /// name of the local file, depending on the direction.
+        property(
+            Name, String,
+            dbus: (INTERFACE, "Name", String, OPTIONAL),
+            get: (name, v => {v.to_owned()}),
+        );
+
+        /// Type of the object being transferred, if available.
+        property(
+            Type, String,
+            dbus: (INTERFACE, "Type", String, OPTIONAL),
+            get: (mime_type, v => {v.to_owned()}),
+        );
+
+        /// Time of the object being transferred, if available.
+        property(
+            Time, u64,
+            dbus: (INTERFACE, "Time", u64, OPTIONAL),
+            get: (time, v => {v.to_owned()}),
+        );
+
+        /// Size of the object being transferred in bytes.
+        ///
+        /// If the size is unknown, then this
+        /// property will not be present.
+        property(
+            Size, u64,
+            dbus: (INTERFACE, "Size", u64, OPTIONAL),
+            get: (size, v => {v.to_owned()}),
+        );
+
+        /// Number of bytes transferred.
+        ///
+        /// For queued transfers, this
+        /// value will not be present.
+        property(
+            Transferred, u64,
+            dbus: (INTERFACE, "Transferred", u64, OPTIONAL),
+            get: (transferred, v => {v.to_owned()}),
+        );
+
+        /// Complete name of the local file being transferred.
+        property(
+            Filename, PathBuf,
+            dbus: (INTERFACE, "Filename", String, OPTIONAL),
+            get: (filename, v => {PathBuf::from(v)}),
+        );
+    }
+);
+
+/// OBEX transfer event.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum TransferEvent {
+    /// Property changed.
+    PropertyChanged(TransferProperty),
+}
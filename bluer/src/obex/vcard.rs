@@ -0,0 +1,427 @@
+//! vCard parsing.
+//!
+//! Phonebook entries transferred using the [Phone Book Access Profile](super::pbap)
+//! are in vCard 2.1 or 3.0 format.
+//! This provides a lenient parser that handles line folding, quoted-printable encoding
+//! and character sets commonly found in vCards produced by mobile phones.
+//!
+//! Commonly used properties are available as typed fields of [VCard].
+//! All properties, including the commonly used ones, are available in raw form
+//! through [VCard::properties].
+
+use std::{fmt, str::FromStr};
+
+/// Invalid vCard error.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct InvalidVCard(pub String);
+
+impl fmt::Display for InvalidVCard {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "invalid vCard: {}", &self.0)
+    }
+}
+
+impl std::error::Error for InvalidVCard {}
+
+/// Raw vCard property.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Property {
+    /// Group, if the property name was prefixed by one.
+    pub group: Option<String>,
+    /// Property name in upper case, for example `TEL`.
+    pub name: String,
+    /// Parameters as pairs of upper-case name and value.
+    ///
+    /// vCard 2.1 parameters specified without name are
+    /// reported as `ENCODING` or `TYPE` parameters.
+    pub params: Vec<(String, String)>,
+    /// Value after decoding of quoted-printable encoding and character set.
+    ///
+    /// Escape sequences are preserved; use [text](Self::text) or
+    /// [components](Self::components) to obtain the unescaped value.
+    pub value: String,
+}
+
+impl Property {
+    /// Values of the parameter with the specified upper-case name.
+    pub fn param(&self, name: &str) -> impl Iterator<Item = &str> {
+        let name = name.to_string();
+        self.params.iter().filter(move |(n, _)| *n == name).map(|(_, v)| v.as_str())
+    }
+
+    /// Types specified by `TYPE` parameters in upper case, for example `CELL` or `HOME`.
+    pub fn types(&self) -> Vec<String> {
+        self.param("TYPE")
+            .flat_map(|v| v.split(','))
+            .map(|t| t.trim_matches('"').trim().to_uppercase())
+            .filter(|t| !t.is_empty())
+            .collect()
+    }
+
+    /// Unescaped value.
+    pub fn text(&self) -> String {
+        unescape(&self.value)
+    }
+
+    /// Unescaped components of a structured value separated by semicolons.
+    pub fn components(&self) -> Vec<String> {
+        let mut components = Vec::new();
+        let mut current = String::new();
+        let mut chars = self.value.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\\' => {
+                    current.push(c);
+                    if let Some(c) = chars.next() {
+                        current.push(c);
+                    }
+                }
+                ';' => components.push(unescape(&std::mem::take(&mut current))),
+                c => current.push(c),
+            }
+        }
+        components.push(unescape(&current));
+        components
+    }
+
+    fn parse(line: &str) -> Option<Self> {
+        let sep = value_separator(line)?;
+        let (head, value) = (&line[..sep], &line[sep + 1..]);
+
+        let mut head = head.split(';');
+        let name = head.next()?.trim();
+        let (group, name) = match name.rsplit_once('.') {
+            Some((group, name)) => (Some(group.to_string()), name),
+            None => (None, name),
+        };
+        if name.is_empty() {
+            return None;
+        }
+
+        let params = head
+            .filter(|p| !p.is_empty())
+            .map(|p| match p.split_once('=') {
+                Some((n, v)) => (n.trim().to_uppercase(), v.trim().to_string()),
+                None => {
+                    let v = p.trim().to_string();
+                    match v.to_uppercase().as_str() {
+                        "QUOTED-PRINTABLE" | "BASE64" | "B" | "8BIT" | "7BIT" => ("ENCODING".to_string(), v),
+                        _ => ("TYPE".to_string(), v),
+                    }
+                }
+            })
+            .collect();
+
+        let mut prop = Self { group, name: name.to_uppercase(), params, value: String::new() };
+        prop.value = prop.decode(value);
+        Some(prop)
+    }
+
+    fn is_quoted_printable(head: &str) -> bool {
+        head.to_uppercase().contains("QUOTED-PRINTABLE")
+    }
+
+    fn decode(&self, value: &str) -> String {
+        if !self.param("ENCODING").any(|e| e.eq_ignore_ascii_case("QUOTED-PRINTABLE")) {
+            return value.to_string();
+        }
+
+        let bytes = value.as_bytes();
+        let mut decoded = Vec::with_capacity(bytes.len());
+        let mut i = 0;
+        while i < bytes.len() {
+            if bytes[i] == b'=' {
+                if let Some(b) = value.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                    decoded.push(b);
+                    i += 3;
+                    continue;
+                }
+            }
+            decoded.push(bytes[i]);
+            i += 1;
+        }
+
+        match self.param("CHARSET").next() {
+            Some(cs) if cs.eq_ignore_ascii_case("ISO-8859-1") || cs.eq_ignore_ascii_case("LATIN1") => {
+                decoded.into_iter().map(char::from).collect()
+            }
+            _ => String::from_utf8_lossy(&decoded).into_owned(),
+        }
+    }
+}
+
+/// Returns the position of the colon separating the property name and parameters from the value.
+fn value_separator(line: &str) -> Option<usize> {
+    let mut quoted = false;
+    for (i, c) in line.char_indices() {
+        match c {
+            '"' => quoted = !quoted,
+            ':' if !quoted => return Some(i),
+            _ => (),
+        }
+    }
+    None
+}
+
+/// Removes escape sequences from a value.
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next() {
+                Some('n' | 'N') => unescaped.push('\n'),
+                Some(c) => unescaped.push(c),
+                None => unescaped.push('\\'),
+            }
+        } else {
+            unescaped.push(c);
+        }
+    }
+    unescaped
+}
+
+/// Structured name.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Name {
+    /// Family name.
+    pub family: String,
+    /// Given name.
+    pub given: String,
+    /// Additional names.
+    pub additional: String,
+    /// Honorific prefixes.
+    pub prefix: String,
+    /// Honorific suffixes.
+    pub suffix: String,
+}
+
+/// Value with types, such as a telephone number or email address.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TypedValue {
+    /// Value.
+    pub value: String,
+    /// Types in upper case, for example `CELL`, `HOME` or `WORK`.
+    pub types: Vec<String>,
+}
+
+/// Postal address.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Address {
+    /// Post office box.
+    pub po_box: String,
+    /// Extended address, for example apartment or suite number.
+    pub extended: String,
+    /// Street address.
+    pub street: String,
+    /// Locality, for example city.
+    pub locality: String,
+    /// Region, for example state or province.
+    pub region: String,
+    /// Postal code.
+    pub postal_code: String,
+    /// Country.
+    pub country: String,
+    /// Types in upper case, for example `HOME` or `WORK`.
+    pub types: Vec<String>,
+}
+
+/// Kind of call history entry.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum CallKind {
+    /// Missed call.
+    Missed,
+    /// Received call.
+    Received,
+    /// Dialed call.
+    Dialed,
+}
+
+/// Call history information.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Call {
+    /// Kind of call, if specified.
+    pub kind: Option<CallKind>,
+    /// Time of call in the format `YYYYMMDDTHHMMSS` with an optional `Z` suffix for UTC.
+    pub datetime: String,
+}
+
+/// Parsed vCard.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct VCard {
+    /// vCard version, for example `2.1` or `3.0`.
+    pub version: Option<String>,
+    /// Formatted name.
+    pub formatted_name: Option<String>,
+    /// Structured name.
+    pub name: Option<Name>,
+    /// Telephone numbers.
+    pub telephones: Vec<TypedValue>,
+    /// Email addresses.
+    pub emails: Vec<TypedValue>,
+    /// Postal addresses.
+    pub addresses: Vec<Address>,
+    /// Organization name.
+    pub organization: Option<String>,
+    /// Job title.
+    pub title: Option<String>,
+    /// Birthday.
+    pub birthday: Option<String>,
+    /// Note.
+    pub note: Option<String>,
+    /// Unique identifier.
+    pub uid: Option<String>,
+    /// URL.
+    pub url: Option<String>,
+    /// Call history information, if this vCard is an entry of a call history.
+    pub call: Option<Call>,
+    /// All properties in raw form.
+    pub properties: Vec<Property>,
+}
+
+impl VCard {
+    /// Parses a single vCard.
+    ///
+    /// Fails if the input does not contain exactly one vCard.
+    pub fn parse(text: &str) -> Result<Self, InvalidVCard> {
+        let mut cards = Self::parse_all(text)?;
+        match cards.len() {
+            1 => Ok(cards.remove(0)),
+            n => Err(InvalidVCard(format!("expected one vCard, found {n}"))),
+        }
+    }
+
+    /// Parses all vCards contained in the input, for example a phonebook
+    /// pulled using [PhonebookAccess::pull_all](super::pbap::PhonebookAccess::pull_all).
+    pub fn parse_all(text: &str) -> Result<Vec<Self>, InvalidVCard> {
+        let mut cards = Vec::new();
+        let mut current: Option<Vec<Property>> = None;
+        let mut nested = 0;
+
+        for line in unfold(text) {
+            let Some(prop) = Property::parse(&line) else { continue };
+            let value = prop.value.trim();
+            match (prop.name.as_str(), &mut current) {
+                ("BEGIN", None) if value.eq_ignore_ascii_case("VCARD") => current = Some(Vec::new()),
+                ("BEGIN", Some(_)) if value.eq_ignore_ascii_case("VCARD") => nested += 1,
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") && nested > 0 => nested -= 1,
+                ("END", Some(_)) if value.eq_ignore_ascii_case("VCARD") => {
+                    cards.push(Self::from_properties(current.take().unwrap()))
+                }
+                ("END", None) if value.eq_ignore_ascii_case("VCARD") => {
+                    return Err(InvalidVCard("END without BEGIN".to_string()))
+                }
+                (_, Some(props)) if nested == 0 => props.push(prop),
+                _ => (),
+            }
+        }
+
+        if current.is_some() {
+            return Err(InvalidVCard("missing END".to_string()));
+        }
+        Ok(cards)
+    }
+
+    /// Properties with the specified upper-case name.
+    pub fn get(&self, name: &str) -> impl Iterator<Item = &Property> {
+        let name = name.to_string();
+        self.properties.iter().filter(move |p| p.name == name)
+    }
+
+    fn from_properties(properties: Vec<Property>) -> Self {
+        let mut card = Self::default();
+
+        for prop in &properties {
+            let component = |c: &[String], i: usize| c.get(i).cloned().unwrap_or_default();
+            match prop.name.as_str() {
+                "VERSION" => card.version = Some(prop.text()),
+                "FN" => card.formatted_name = Some(prop.text()),
+                "N" => {
+                    let c = prop.components();
+                    card.name = Some(Name {
+                        family: component(&c, 0),
+                        given: component(&c, 1),
+                        additional: component(&c, 2),
+                        prefix: component(&c, 3),
+                        suffix: component(&c, 4),
+                    });
+                }
+                "TEL" => card.telephones.push(TypedValue { value: prop.text(), types: prop.types() }),
+                "EMAIL" => card.emails.push(TypedValue { value: prop.text(), types: prop.types() }),
+                "ADR" => {
+                    let c = prop.components();
+                    card.addresses.push(Address {
+                        po_box: component(&c, 0),
+                        extended: component(&c, 1),
+                        street: component(&c, 2),
+                        locality: component(&c, 3),
+                        region: component(&c, 4),
+                        postal_code: component(&c, 5),
+                        country: component(&c, 6),
+                        types: prop.types(),
+                    });
+                }
+                "ORG" => card.organization = prop.components().into_iter().next(),
+                "TITLE" => card.title = Some(prop.text()),
+                "BDAY" => card.birthday = Some(prop.text()),
+                "NOTE" => card.note = Some(prop.text()),
+                "UID" => card.uid = Some(prop.text()),
+                "URL" => card.url = Some(prop.text()),
+                "X-IRMC-CALL-DATETIME" => {
+                    let kind = prop.types().iter().find_map(|t| match t.as_str() {
+                        "MISSED" => Some(CallKind::Missed),
+                        "RECEIVED" => Some(CallKind::Received),
+                        "DIALED" => Some(CallKind::Dialed),
+                        _ => None,
+                    });
+                    card.call = Some(Call { kind, datetime: prop.text() });
+                }
+                _ => (),
+            }
+        }
+
+        card.properties = properties;
+        card
+    }
+}
+
+impl FromStr for VCard {
+    type Err = InvalidVCard;
+
+    fn from_str(s: &str) -> Result<Self, InvalidVCard> {
+        Self::parse(s)
+    }
+}
+
+/// Joins folded lines and quoted-printable soft line breaks into logical lines.
+fn unfold(text: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for raw in text.lines() {
+        if let Some(last) = lines.last_mut() {
+            if raw.starts_with([' ', '\t']) {
+                last.push_str(&raw[1..]);
+                continue;
+            }
+            let head = value_separator(last).map(|sep| &last[..sep]).unwrap_or_default();
+            if Property::is_quoted_printable(head) && last.ends_with('=') {
+                last.pop();
+                last.push_str(raw);
+                continue;
+            }
+        }
+        if !raw.trim().is_empty() {
+            lines.push(raw.to_string());
+        }
+    }
+    lines
+}
@@ -21,7 +21,7 @@ use tokio_stream::wrappers::ReceiverStream;
 use uuid::Uuid;
 
 use super::{Socket, Stream};
-use crate::{method_call, read_dict, Address, Device, Result, SessionInner, ERR_PREFIX, SERVICE_NAME, TIMEOUT};
+use crate::{method_call, read_dict, Address, Device, Result, SessionInner, ERR_PREFIX};
 
 pub(crate) const MANAGER_INTERFACE: &str = "org.bluez.ProfileManager1";
 pub(crate) const MANAGER_PATH: &str = "/org/bluez";
@@ -342,19 +342,19 @@ impl RegisteredProfile {
         }
 
         log::trace!("Registering profile at {}", &name);
-        let proxy = Proxy::new(SERVICE_NAME, MANAGER_PATH, TIMEOUT, inner.connection.clone());
-        let () = proxy
-            .method_call(
-                MANAGER_INTERFACE,
-                "RegisterProfile",
-                (name.clone(), profile.uuid.to_string(), profile.to_dict()),
-            )
+        let reg_name = name.clone();
+        inner
+            .register(&name, MANAGER_PATH.into(), MANAGER_INTERFACE, "RegisterProfile", move || {
+                (reg_name.clone(), profile.uuid.to_string(), profile.to_dict())
+            })
             .await?;
+        let proxy = Proxy::new(inner.service_name.clone(), MANAGER_PATH, inner.timeout, inner.connection.clone());
 
         let (drop_tx, drop_rx) = oneshot::channel();
         let unreg_name = name.clone();
         tokio::spawn(async move {
             let _ = drop_rx.await;
+            inner.forget_registrations(&unreg_name).await;
 
             log::trace!("Unregistering profile at {}", &unreg_name);
             let _: std::result::Result<(), dbus::Error> =
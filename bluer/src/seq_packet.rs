@@ -0,0 +1,187 @@
+//! Non-blocking packet socket used for GATT characteristic and media streams.
+
+use futures::{ready, task::noop_waker_ref};
+use std::{
+    io::{Error, ErrorKind, Result},
+    os::unix::io::{AsRawFd, IntoRawFd, OwnedFd, RawFd},
+    task::{Context, Poll},
+};
+use tokio::io::{unix::AsyncFd, ReadBuf};
+
+/// `SOCK_SEQPACKET` socket transferring a single packet per operation.
+///
+/// The part of a received packet that does not fit into the buffer provided
+/// to [SeqPacket::poll_read] is kept and returned by subsequent reads.
+#[derive(Debug)]
+pub(crate) struct SeqPacket {
+    fd: AsyncFd<OwnedFd>,
+    buf: Vec<u8>,
+}
+
+impl SeqPacket {
+    /// Wraps a socket and switches it to non-blocking mode.
+    pub fn new(fd: OwnedFd) -> Result<Self> {
+        let flags = unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_GETFL) };
+        if flags == -1 || unsafe { libc::fcntl(fd.as_raw_fd(), libc::F_SETFL, flags | libc::O_NONBLOCK) } == -1 {
+            return Err(Error::last_os_error());
+        }
+        Ok(Self { fd: AsyncFd::new(fd)?, buf: Vec::new() })
+    }
+
+    fn recv_raw(&self, buf: &mut [u8]) -> Result<usize> {
+        match unsafe { libc::recv(self.fd.as_raw_fd(), buf.as_mut_ptr() as *mut _, buf.len(), 0) } {
+            -1 => Err(Error::last_os_error()),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn recv_packet_raw(&self, mtu: usize) -> Result<Vec<u8>> {
+        let mut buf = vec![0; mtu];
+        let n = self.recv_raw(&mut buf)?;
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    fn send_raw(&self, buf: &[u8]) -> Result<usize> {
+        match unsafe { libc::send(self.fd.as_raw_fd(), buf.as_ptr() as *const _, buf.len(), libc::MSG_NOSIGNAL) }
+        {
+            -1 => Err(Error::last_os_error()),
+            n => Ok(n as usize),
+        }
+    }
+
+    fn send_packet_raw(&self, buf: &[u8], mtu: usize) -> Result<()> {
+        if buf.len() > mtu {
+            return Err(Error::new(ErrorKind::WriteZero, "data length exceeds MTU"));
+        }
+        match self.send_raw(buf) {
+            Ok(n) if n == buf.len() => Ok(()),
+            Ok(_) => Err(Error::other("partial write occurred")),
+            Err(err) => Err(err),
+        }
+    }
+
+    /// Waits for a packet to become available.
+    pub async fn readable(&self) -> Result<()> {
+        self.fd.readable().await?.retain_ready();
+        Ok(())
+    }
+
+    /// Waits for send space to become available.
+    pub async fn writable(&self) -> Result<()> {
+        self.fd.writable().await?.retain_ready();
+        Ok(())
+    }
+
+    /// Tries to receive a packet into `buf` without waiting.
+    ///
+    /// The read readiness is cleared if no packet is available.
+    pub fn try_recv_into(&self, buf: &mut [u8]) -> Result<usize> {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        match self.fd.poll_read_ready(&mut cx) {
+            Poll::Ready(guard) => match guard?.try_io(|_| self.recv_raw(buf)) {
+                Ok(res) => res,
+                Err(_would_block) => Err(ErrorKind::WouldBlock.into()),
+            },
+            Poll::Pending => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    /// Tries to receive a packet of at most `mtu` bytes without waiting.
+    pub fn try_recv(&self, mtu: usize) -> Result<Vec<u8>> {
+        let mut buf = vec![0; mtu];
+        let n = self.try_recv_into(&mut buf)?;
+        buf.truncate(n);
+        Ok(buf)
+    }
+
+    /// Receives a packet of at most `mtu` bytes, waiting for it to arrive.
+    pub async fn recv(&self, mtu: usize) -> Result<Vec<u8>> {
+        loop {
+            let mut guard = self.fd.readable().await?;
+            match guard.try_io(|_| self.recv_packet_raw(mtu)) {
+                Ok(res) => return res,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Tries to send `buf` as a single packet of at most `mtu` bytes without waiting.
+    ///
+    /// The write readiness is cleared if no send space is available.
+    pub fn try_send(&self, buf: &[u8], mtu: usize) -> Result<()> {
+        let mut cx = Context::from_waker(noop_waker_ref());
+        match self.fd.poll_write_ready(&mut cx) {
+            Poll::Ready(guard) => match guard?.try_io(|_| self.send_packet_raw(buf, mtu)) {
+                Ok(res) => res,
+                Err(_would_block) => Err(ErrorKind::WouldBlock.into()),
+            },
+            Poll::Pending => Err(ErrorKind::WouldBlock.into()),
+        }
+    }
+
+    /// Sends `buf` as a single packet of at most `mtu` bytes, waiting for send space.
+    pub async fn send(&self, buf: &[u8], mtu: usize) -> Result<()> {
+        loop {
+            let mut guard = self.fd.writable().await?;
+            match guard.try_io(|_| self.send_packet_raw(buf, mtu)) {
+                Ok(res) => return res,
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Reads from the stream of packets of at most `mtu` bytes into `buf`.
+    ///
+    /// Data left over from a previously received packet is returned first.
+    pub fn poll_read(&mut self, cx: &mut Context, buf: &mut ReadBuf, mtu: usize) -> Poll<Result<()>> {
+        let buf_space = buf.remaining();
+        if !self.buf.is_empty() {
+            // Return buffered data first, if any.
+            let to_read = buf_space.min(self.buf.len());
+            let remaining = self.buf.split_off(to_read);
+            buf.put_slice(&self.buf);
+            self.buf = remaining;
+            return Poll::Ready(Ok(()));
+        }
+
+        loop {
+            let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+            match guard.try_io(|_| self.recv_packet_raw(mtu)) {
+                Ok(Ok(mut packet)) => {
+                    // Fill provided buffer and keep the rest in our internal buffer.
+                    let rest = packet.split_off(buf_space.min(packet.len()));
+                    buf.put_slice(&packet);
+                    self.buf = rest;
+                    return Poll::Ready(Ok(()));
+                }
+                Ok(Err(err)) => return Poll::Ready(Err(err)),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+
+    /// Writes at most `mtu` bytes of `buf` as a single packet.
+    pub fn poll_write(&self, cx: &mut Context, buf: &[u8], mtu: usize) -> Poll<Result<usize>> {
+        let buf = &buf[..buf.len().min(mtu)];
+        loop {
+            let mut guard = ready!(self.fd.poll_write_ready(cx))?;
+            match guard.try_io(|_| self.send_raw(buf)) {
+                Ok(res) => return Poll::Ready(res),
+                Err(_would_block) => continue,
+            }
+        }
+    }
+}
+
+impl AsRawFd for SeqPacket {
+    fn as_raw_fd(&self) -> RawFd {
+        self.fd.as_raw_fd()
+    }
+}
+
+impl IntoRawFd for SeqPacket {
+    fn into_raw_fd(self) -> RawFd {
+        self.fd.into_inner().into_raw_fd()
+    }
+}
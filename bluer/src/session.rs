@@ -1,13 +1,14 @@
 //! Bluetooth session.
 
 use dbus::{
-    arg::Variant,
+    arg::{AppendAll, Variant},
+    channel::Channel,
     message::MatchRule,
     nonblock::{
         stdintf::org_freedesktop_dbus::{
             ObjectManagerInterfacesAdded, ObjectManagerInterfacesRemoved, PropertiesPropertiesChanged,
         },
-        SyncConnection,
+        MethodReply, Proxy, SyncConnection,
     },
     strings::BusName,
     Message,
@@ -17,13 +18,13 @@ use dbus_tokio::connection;
 use futures::{
     channel::{mpsc, oneshot},
     lock::Mutex,
-    Future, SinkExt, Stream, StreamExt,
+    stream, Future, SinkExt, Stream, StreamExt,
 };
-use lazy_static::lazy_static;
 use std::{
     collections::{HashMap, HashSet},
     fmt::{Debug, Formatter},
     sync::{Arc, Weak},
+    time::Duration,
 };
 use tokio::{
     select,
@@ -34,9 +35,13 @@ use crate::{
     adapter,
     adv::Advertisement,
     agent::{Agent, AgentHandle, RegisteredAgent},
-    all_dbus_objects, gatt,
+    all_dbus_objects,
+    battery::RegisteredBattery,
+    cache::ObjectCache,
+    device_set::{self, DeviceSet},
+    gatt,
     monitor::RegisteredMonitor,
-    parent_path, Adapter, DiscoveryFilter, Error, ErrorKind, InternalErrorKind, Result, SERVICE_NAME,
+    parent_path, Adapter, DiscoveryFilter, Error, ErrorKind, InternalErrorKind, Result, SERVICE_NAME, TIMEOUT,
 };
 
 #[cfg(feature = "mesh")]
@@ -45,15 +50,45 @@ use crate::mesh::{
     network::Network, provisioner::RegisteredProvisioner,
 };
 
+#[cfg(feature = "hid")]
+use crate::hid;
+#[cfg(feature = "media")]
+use crate::media::{RegisteredLocalPlayer, RegisteredMediaEndpoint};
 #[cfg(feature = "rfcomm")]
 use crate::rfcomm::{profile::RegisteredProfile, Profile, ProfileHandle};
 
 /// Terminate TX and terminated RX for single session.
 type SingleSessionTerm = (Weak<oneshot::Sender<()>>, oneshot::Receiver<()>);
 
+/// D-Bus path of the message bus, which emits `NameOwnerChanged` signals.
+const DBUS_PATH: &str = "/org/freedesktop/DBus";
+/// D-Bus interface of the message bus.
+const DBUS_INTERFACE: &str = "org.freedesktop.DBus";
+/// Standard D-Bus properties interface.
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// Method call registering a published object with the Bluetooth daemon.
+type RegistrationCall = Box<dyn Fn(&SessionInner) -> MethodReply<()> + Send + Sync>;
+
+/// Registration of a published object with the Bluetooth daemon that is
+/// repeated when the daemon restarts.
+struct Reregistration {
+    /// Path of the published object.
+    name: dbus::Path<'static>,
+    /// Object of the Bluetooth daemon the registration is made with.
+    target: dbus::Path<'static>,
+    /// Whether the registration was lost and must be repeated once the target is available.
+    pending: bool,
+    /// Performs the registration method call.
+    call: RegistrationCall,
+}
+
 /// Shared state of all objects in a Bluetooth session.
 pub(crate) struct SessionInner {
     pub connection: Arc<SyncConnection>,
+    pub service_name: BusName<'static>,
+    pub timeout: Duration,
+    pub cache: Option<Arc<ObjectCache>>,
     pub crossroads: Mutex<Crossroads>,
     pub le_advertisment_token: IfaceToken<Advertisement>,
     pub gatt_reg_service_token: IfaceToken<Arc<gatt::local::RegisteredService>>,
@@ -70,12 +105,18 @@ pub(crate) struct SessionInner {
     #[cfg(feature = "mesh")]
     pub provision_agent_token: IfaceToken<Arc<RegisteredProvisionAgent>>,
     pub monitor_token: IfaceToken<Arc<RegisteredMonitor>>,
+    pub battery_token: IfaceToken<Arc<RegisteredBattery>>,
     #[cfg(feature = "rfcomm")]
     pub profile_token: IfaceToken<Arc<RegisteredProfile>>,
+    #[cfg(feature = "media")]
+    pub media_endpoint_token: IfaceToken<Arc<RegisteredMediaEndpoint>>,
+    #[cfg(feature = "media")]
+    pub media_player_token: IfaceToken<Arc<RegisteredLocalPlayer>>,
     pub single_sessions: Mutex<HashMap<dbus::Path<'static>, SingleSessionTerm>>,
     pub event_sub_tx: mpsc::Sender<SubscriptionReq>,
-    dbus_task: JoinHandle<connection::IOResourceError>,
+    dbus_task: Option<JoinHandle<connection::IOResourceError>>,
     pub adapter_discovery_filter: Mutex<HashMap<String, DiscoveryFilter>>,
+    reregistrations: Option<Mutex<Vec<Reregistration>>>,
 }
 
 impl SessionInner {
@@ -140,14 +181,105 @@ impl SessionInner {
     pub async fn events(
         &self, path: dbus::Path<'static>, child_objects: bool,
     ) -> Result<mpsc::UnboundedReceiver<Event>> {
-        Event::subscribe(&mut self.event_sub_tx.clone(), path, child_objects).await
+        Event::subscribe(&mut self.event_sub_tx.clone(), path, child_objects, false).await
+    }
+
+    /// Registers the published object `name` by calling `method` on the object `target`
+    /// of the Bluetooth daemon.
+    ///
+    /// If re-registration is enabled, the call is repeated with the same arguments
+    /// after the Bluetooth daemon has restarted and `target` is available again.
+    pub async fn register<A>(
+        &self, name: &dbus::Path<'static>, target: dbus::Path<'static>, interface: &'static str,
+        method: &'static str, args: impl Fn() -> A + Send + Sync + 'static,
+    ) -> Result<()>
+    where
+        A: AppendAll,
+    {
+        let call_target = target.clone();
+        let call = move |inner: &SessionInner| {
+            let proxy =
+                Proxy::new(inner.service_name.clone(), &call_target, inner.timeout, inner.connection.clone());
+            proxy.method_call(interface, method, args())
+        };
+        call(self).await?;
+
+        if let Some(reregistrations) = &self.reregistrations {
+            reregistrations.lock().await.push(Reregistration {
+                name: name.clone(),
+                target,
+                pending: false,
+                call: Box::new(call),
+            });
+        }
+        Ok(())
+    }
+
+    /// Stops repeating the registrations of the published object `name`.
+    pub async fn forget_registrations(&self, name: &dbus::Path<'static>) {
+        if let Some(reregistrations) = &self.reregistrations {
+            reregistrations.lock().await.retain(|r| &r.name != name);
+        }
+    }
+
+    /// Repeats the pending registrations with the object `target` of the Bluetooth daemon.
+    async fn reregister(&self, target: &dbus::Path<'static>) {
+        let Some(reregistrations) = &self.reregistrations else { return };
+
+        let calls: Vec<_> = reregistrations
+            .lock()
+            .await
+            .iter_mut()
+            .filter(|r| r.pending && &r.target == target)
+            .map(|r| {
+                r.pending = false;
+                log::trace!("Re-registering {} with {}", &r.name, &r.target);
+                (r.name.clone(), (r.call)(self))
+            })
+            .collect();
+
+        for (name, call) in calls {
+            if let Err(err) = call.await {
+                log::warn!("Re-registering {} with {} failed: {}", &name, target, &err);
+            }
+        }
+    }
+
+    /// Repeats registrations after the Bluetooth daemon has restarted.
+    ///
+    /// Registrations with an adapter are repeated once the adapter has been added
+    /// by the restarted daemon.
+    async fn reregistration_task(inner: Weak<Self>, mut events: mpsc::UnboundedReceiver<Event>) {
+        while let Some(evt) = events.next().await {
+            let Some(inner) = inner.upgrade() else { break };
+            match evt {
+                Event::ServiceOwnerChanged { old_owner, new_owner } => {
+                    if old_owner.is_some() {
+                        if let Some(reregistrations) = &inner.reregistrations {
+                            for r in reregistrations.lock().await.iter_mut() {
+                                r.pending = true;
+                            }
+                        }
+                    }
+                    if new_owner.is_some() {
+                        inner.reregister(&adapter::PATH.into()).await;
+                    }
+                }
+                Event::ObjectAdded { object, interfaces } if interfaces.contains(adapter::INTERFACE) => {
+                    inner.reregister(&object).await;
+                }
+                _ => (),
+            }
+        }
     }
 }
 
 impl Drop for SessionInner {
     fn drop(&mut self) {
         // documentation for dbus_tokio::connection::IOResource indicates it is abortable
-        self.dbus_task.abort();
+        if let Some(dbus_task) = &self.dbus_task {
+            dbus_task.abort();
+        }
     }
 }
 
@@ -161,6 +293,65 @@ impl Drop for SingleSessionToken {
     }
 }
 
+/// Bluetooth session options.
+///
+/// Use [Session::new_with_options], [Session::new_with_address] or
+/// [Session::new_with_connection] to create a session using these options.
+#[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SessionOptions {
+    /// D-Bus service name of the Bluetooth daemon.
+    ///
+    /// By default this is `org.bluez`.
+    pub service_name: String,
+    /// Timeout for D-Bus method calls to the Bluetooth daemon.
+    ///
+    /// This is also the maximum time [Device::services](crate::Device::services)
+    /// waits for service discovery to complete.
+    ///
+    /// By default this is 120 seconds.
+    pub timeout: Duration,
+    /// Register advertisements, GATT applications and profiles, agents, profiles,
+    /// advertisement monitors, media endpoints and players, battery providers
+    /// and network servers again after the Bluetooth daemon has restarted.
+    ///
+    /// When the Bluetooth daemon terminates, all registrations made through the session
+    /// are lost.
+    /// If this is enabled, the session repeats them once the daemon is available again,
+    /// so that the corresponding handles remain functional.
+    /// Registrations with an adapter are repeated once the adapter reappears.
+    ///
+    /// By default this is disabled.
+    /// Use [Session::events] to be notified when the daemon stops and starts.
+    pub reregister: bool,
+    /// Keep a local copy of all objects and properties of the Bluetooth daemon.
+    ///
+    /// If this is enabled, the session fetches all objects once and keeps them up to date
+    /// from the signals emitted by the Bluetooth daemon.
+    /// Property getters, such as [Device::all_properties](crate::Device::all_properties),
+    /// and enumeration of adapters and devices are then answered locally
+    /// without D-Bus round-trips.
+    /// The copy is updated before the corresponding event is delivered, so that property
+    /// values are consistent with the event streams.
+    ///
+    /// By default this is disabled.
+    pub cache_properties: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for SessionOptions {
+    fn default() -> Self {
+        Self {
+            service_name: SERVICE_NAME.to_string(),
+            timeout: TIMEOUT,
+            reregister: false,
+            cache_properties: false,
+            _non_exhaustive: (),
+        }
+    }
+}
+
 /// Bluetooth session.
 ///
 /// Encapsulates a connection to the system Bluetooth daemon.
@@ -180,11 +371,20 @@ impl Debug for Session {
 #[cfg_attr(docsrs, doc(cfg(feature = "bluetoothd")))]
 #[derive(Clone, Debug, Eq, PartialEq)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
 pub enum SessionEvent {
     /// Adapter added.
     AdapterAdded(String),
     /// Adapter removed.
     AdapterRemoved(String),
+    /// The Bluetooth daemon has stopped.
+    ///
+    /// All registrations, for example of advertisements and GATT applications, have been lost.
+    /// Interfaces to adapters and devices become usable again once the daemon has restarted
+    /// and the corresponding objects are added.
+    DaemonStopped,
+    /// The Bluetooth daemon has started.
+    DaemonStarted,
 }
 
 impl Session {
@@ -192,8 +392,52 @@ impl Session {
     ///
     /// This establishes a connection to the system Bluetooth daemon over D-Bus.
     pub async fn new() -> Result<Self> {
+        Self::new_with_options(SessionOptions::default()).await
+    }
+
+    /// Create a new Bluetooth session using the specified options.
+    ///
+    /// This establishes a connection to the Bluetooth daemon over the system D-Bus.
+    pub async fn new_with_options(options: SessionOptions) -> Result<Self> {
         let (resource, connection) = spawn_blocking(connection::new_system_sync).await??;
-        let dbus_task = tokio::spawn(resource);
+        Self::with_connection(connection, Some(resource), options).await
+    }
+
+    /// Create a new Bluetooth session on the D-Bus bus with the specified address.
+    ///
+    /// The address has the form used by the `DBUS_SYSTEM_BUS_ADDRESS` environment variable,
+    /// for example `unix:path=/run/dbus/system_bus_socket`.
+    pub async fn new_with_address(address: &str, options: SessionOptions) -> Result<Self> {
+        let address = address.to_string();
+        let (resource, connection) = spawn_blocking(move || {
+            let mut channel = Channel::open_private(&address)?;
+            channel.register()?;
+            connection::from_channel(channel)
+        })
+        .await??;
+        Self::with_connection(connection, Some(resource), options).await
+    }
+
+    /// Create a new Bluetooth session using an existing D-Bus connection.
+    ///
+    /// The caller is responsible for driving the connection, i.e. the
+    /// [IOResource](dbus_tokio::connection::IOResource) returned when the connection
+    /// was established must be spawned and kept running for the lifetime of the session.
+    ///
+    /// The session handles all incoming method calls on the connection to serve
+    /// objects it publishes, such as agents, advertisements and GATT applications.
+    /// Thus the connection should not be shared with other D-Bus services.
+    pub async fn new_with_connection(connection: Arc<SyncConnection>, options: SessionOptions) -> Result<Self> {
+        Self::with_connection(connection, None, options).await
+    }
+
+    async fn with_connection(
+        connection: Arc<SyncConnection>, resource: Option<connection::IOResource<SyncConnection>>,
+        options: SessionOptions,
+    ) -> Result<Self> {
+        let SessionOptions { service_name, timeout, reregister, cache_properties, _non_exhaustive } = options;
+        let service_name = BusName::new(service_name).map_err(|_| Error::new(ErrorKind::InvalidArguments))?;
+        let dbus_task = resource.map(tokio::spawn);
         log::trace!("Connected to D-Bus with unique name {}", &connection.unique_name());
 
         let mut crossroads = Crossroads::new();
@@ -215,8 +459,13 @@ impl Session {
         let gatt_profile_token = gatt::local::Profile::register_interface(&mut crossroads);
         let agent_token = RegisteredAgent::register_interface(&mut crossroads);
         let monitor_token = RegisteredMonitor::register_interface(&mut crossroads);
+        let battery_token = RegisteredBattery::register_interface(&mut crossroads);
         #[cfg(feature = "rfcomm")]
         let profile_token = RegisteredProfile::register_interface(&mut crossroads);
+        #[cfg(feature = "media")]
+        let media_endpoint_token = RegisteredMediaEndpoint::register_interface(&mut crossroads);
+        #[cfg(feature = "media")]
+        let media_player_token = RegisteredLocalPlayer::register_interface(&mut crossroads);
         #[cfg(feature = "mesh")]
         let application_token = RegisteredApplication::register_interface(&mut crossroads);
         #[cfg(feature = "mesh")]
@@ -227,10 +476,14 @@ impl Session {
         let provision_agent_token = RegisteredProvisionAgent::register_interface(&mut crossroads);
 
         let (event_sub_tx, event_sub_rx) = mpsc::channel(1);
-        Event::handle_connection(connection.clone(), event_sub_rx).await?;
+        let cache = cache_properties.then(|| Arc::new(ObjectCache::new(service_name.clone(), timeout)));
+        Event::handle_connection(connection.clone(), event_sub_rx, service_name.clone(), cache.clone()).await?;
 
         let inner = Arc::new(SessionInner {
             connection: connection.clone(),
+            service_name,
+            timeout,
+            cache,
             crossroads: Mutex::new(crossroads),
             le_advertisment_token,
             gatt_reg_service_token: gatt_service_token,
@@ -247,12 +500,18 @@ impl Session {
             #[cfg(feature = "mesh")]
             provision_agent_token,
             monitor_token,
+            battery_token,
             #[cfg(feature = "rfcomm")]
             profile_token,
+            #[cfg(feature = "media")]
+            media_endpoint_token,
+            #[cfg(feature = "media")]
+            media_player_token,
             single_sessions: Mutex::new(HashMap::new()),
             event_sub_tx,
             dbus_task,
             adapter_discovery_filter: Mutex::new(HashMap::new()),
+            reregistrations: reregister.then(|| Mutex::new(Vec::new())),
         });
 
         let mc_callback = connection.add_match(MatchRule::new_method_call()).await?;
@@ -269,6 +528,12 @@ impl Session {
             }
         });
 
+        if reregister {
+            let events =
+                Event::subscribe(&mut inner.event_sub_tx.clone(), adapter::PATH.into(), true, true).await?;
+            tokio::spawn(SessionInner::reregistration_task(Arc::downgrade(&inner), events));
+        }
+
         Ok(Self { inner })
     }
 
@@ -295,7 +560,7 @@ impl Session {
     /// Enumerate connected Bluetooth adapters and return their names.
     pub async fn adapter_names(&self) -> Result<Vec<String>> {
         let mut names = Vec::new();
-        for (path, interfaces) in all_dbus_objects(&self.inner.connection).await? {
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
             match Adapter::parse_dbus_path(&path) {
                 Some(name) if interfaces.contains_key(adapter::INTERFACE) => {
                     names.push(name.to_string());
@@ -306,6 +571,20 @@ impl Session {
         Ok(names)
     }
 
+    /// Interfaces to known coordinated sets of Bluetooth devices on all adapters.
+    pub async fn device_sets(&self) -> Result<Vec<DeviceSet>> {
+        let mut sets = Vec::new();
+        for (path, interfaces) in all_dbus_objects(&self.inner).await? {
+            match DeviceSet::parse_dbus_path(&path) {
+                Some((adapter_name, id)) if interfaces.contains_key(device_set::INTERFACE) => {
+                    sets.push(DeviceSet::new(self.inner.clone(), Arc::new(adapter_name.to_string()), id)?);
+                }
+                _ => (),
+            }
+        }
+        Ok(sets)
+    }
+
     /// Create an interface to the Bluetooth adapter with the specified name.
     pub fn adapter(&self, adapter_name: &str) -> Result<Adapter> {
         Adapter::new(self.inner.clone(), adapter_name)
@@ -353,23 +632,50 @@ impl Session {
         reg_profile.register(self.inner.clone(), profile, req_rx).await
     }
 
-    /// Stream adapter added and removed events.
+    /// This registers a [human interface device](hid::HidDevice), allowing the local system
+    /// to act as a Bluetooth keyboard, mouse or game controller.
+    ///
+    /// The returned [HidDeviceHandle](hid::HidDeviceHandle) accepts connections from hosts.
+    ///
+    /// Drop the handle to unregister the device.
+    #[cfg(feature = "hid")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "hid")))]
+    pub async fn register_hid_device(&self, device: hid::HidDevice) -> Result<hid::HidDeviceHandle> {
+        device.register(self).await
+    }
+
+    /// Stream adapter added and removed events as well as
+    /// Bluetooth daemon stopped and started events.
     pub async fn events(&self) -> Result<impl Stream<Item = SessionEvent>> {
-        let obj_events = self.inner.events(adapter::PATH.into(), true).await?;
-        let events = obj_events.filter_map(|evt| async move {
-            match evt {
-                Event::ObjectAdded { object, interfaces }
-                    if interfaces.iter().any(|i| i == adapter::INTERFACE) =>
-                {
-                    Adapter::parse_dbus_path(&object).map(|name| SessionEvent::AdapterAdded(name.to_string()))
+        let obj_events =
+            Event::subscribe(&mut self.inner.event_sub_tx.clone(), adapter::PATH.into(), true, true).await?;
+        let events = obj_events.flat_map(|evt| {
+            let evts = match evt {
+                Event::ObjectAdded { object, interfaces } if interfaces.contains(adapter::INTERFACE) => {
+                    Adapter::parse_dbus_path(&object)
+                        .map(|name| SessionEvent::AdapterAdded(name.to_string()))
+                        .into_iter()
+                        .collect()
                 }
-                Event::ObjectRemoved { object, interfaces }
-                    if interfaces.iter().any(|i| i == adapter::INTERFACE) =>
-                {
-                    Adapter::parse_dbus_path(&object).map(|name| SessionEvent::AdapterRemoved(name.to_string()))
+                Event::ObjectRemoved { object, interfaces } if interfaces.contains(adapter::INTERFACE) => {
+                    Adapter::parse_dbus_path(&object)
+                        .map(|name| SessionEvent::AdapterRemoved(name.to_string()))
+                        .into_iter()
+                        .collect()
                 }
-                _ => None,
-            }
+                Event::ServiceOwnerChanged { old_owner, new_owner } => {
+                    let mut evts = Vec::new();
+                    if old_owner.is_some() {
+                        evts.push(SessionEvent::DaemonStopped);
+                    }
+                    if new_owner.is_some() {
+                        evts.push(SessionEvent::DaemonStarted);
+                    }
+                    evts
+                }
+                _ => Vec::new(),
+            };
+            stream::iter(evts)
         });
         Ok(events)
     }
@@ -381,9 +687,20 @@ pub(crate) enum Event {
     /// Object or object interfaces added.
     ObjectAdded { object: dbus::Path<'static>, interfaces: HashSet<String> },
     /// Object or object interfaces removed.
+    ///
+    /// Delivered to subscriptions of the parent object with child objects
+    /// and to subscriptions of the object itself, which end afterwards
+    /// if the object has been removed entirely.
     ObjectRemoved { object: dbus::Path<'static>, interfaces: HashSet<String> },
-    /// Properties changed.
-    PropertiesChanged { object: dbus::Path<'static>, interface: String, changed: dbus::arg::PropMap },
+    /// Properties changed or invalidated.
+    PropertiesChanged {
+        object: dbus::Path<'static>,
+        interface: String,
+        changed: dbus::arg::PropMap,
+        invalidated: Vec<String>,
+    },
+    /// Owner of the D-Bus service changed, i.e. the service stopped or started.
+    ServiceOwnerChanged { old_owner: Option<String>, new_owner: Option<String> },
 }
 
 impl Clone for Event {
@@ -395,11 +712,15 @@ impl Clone for Event {
             Self::ObjectRemoved { object, interfaces } => {
                 Self::ObjectRemoved { object: object.clone(), interfaces: interfaces.clone() }
             }
-            Self::PropertiesChanged { object, interface, changed } => Self::PropertiesChanged {
+            Self::PropertiesChanged { object, interface, changed, invalidated } => Self::PropertiesChanged {
                 object: object.clone(),
                 interface: interface.clone(),
                 changed: changed.iter().map(|(k, v)| (k.clone(), Variant(v.0.box_clone()))).collect(),
+                invalidated: invalidated.clone(),
             },
+            Self::ServiceOwnerChanged { old_owner, new_owner } => {
+                Self::ServiceOwnerChanged { old_owner: old_owner.clone(), new_owner: new_owner.clone() }
+            }
         }
     }
 }
@@ -408,20 +729,24 @@ impl Clone for Event {
 pub(crate) struct SubscriptionReq {
     path: dbus::Path<'static>,
     child_objects: bool,
+    owner_changes: bool,
     tx: mpsc::UnboundedSender<Event>,
     ready_tx: oneshot::Sender<()>,
 }
 
 impl Event {
     /// Spawns a task that handles events for the specified connection.
+    ///
+    /// Only events originating from the D-Bus service `service_name` are handled.
+    ///
+    /// If a `cache` is specified, it is filled and kept up to date before events are delivered.
+    /// When the service is restarted, the cache is refilled in a separate task and answers
+    /// no queries until that has finished.
     pub(crate) async fn handle_connection(
         connection: Arc<SyncConnection>, mut sub_rx: mpsc::Receiver<SubscriptionReq>,
+        service_name: BusName<'static>, cache: Option<Arc<ObjectCache>>,
     ) -> Result<()> {
         use dbus::message::SignalArgs;
-        lazy_static! {
-            static ref SERVICE_NAME_BUS: BusName<'static> = BusName::new(SERVICE_NAME).unwrap();
-            static ref SERVICE_NAME_REF: Option<&'static BusName<'static>> = Some(&SERVICE_NAME_BUS);
-        }
 
         let (msg_tx, mut msg_rx) = mpsc::unbounded();
         let handle_msg = move |msg: Message| {
@@ -429,20 +754,30 @@ impl Event {
             true
         };
 
-        let rule_add = ObjectManagerInterfacesAdded::match_rule(*SERVICE_NAME_REF, None);
+        let rule_add = ObjectManagerInterfacesAdded::match_rule(Some(&service_name), None).static_clone();
         let msg_match_add = connection.add_match(rule_add).await?.msg_cb(handle_msg.clone());
 
-        let rule_removed = ObjectManagerInterfacesRemoved::match_rule(*SERVICE_NAME_REF, None);
+        let rule_removed = ObjectManagerInterfacesRemoved::match_rule(Some(&service_name), None).static_clone();
         let msg_match_removed = connection.add_match(rule_removed).await?.msg_cb(handle_msg.clone());
 
-        let rule_prop = PropertiesPropertiesChanged::match_rule(*SERVICE_NAME_REF, None);
+        let rule_prop = PropertiesPropertiesChanged::match_rule(Some(&service_name), None).static_clone();
         let msg_match_prop = connection.add_match(rule_prop).await?.msg_cb(handle_msg.clone());
 
+        let rule_owner = MatchRule::new_signal(DBUS_INTERFACE, "NameOwnerChanged")
+            .with_sender(DBUS_INTERFACE)
+            .with_path(DBUS_PATH);
+        let msg_match_owner = connection.add_match(rule_owner).await?.msg_cb(handle_msg.clone());
+
+        if let Some(cache) = &cache {
+            cache.refresh(&connection).await;
+        }
+
         tokio::spawn(async move {
             log::trace!("Starting event loop for {}", &connection.unique_name());
 
             struct Subscription {
                 child_objects: bool,
+                owner_changes: bool,
                 tx: mpsc::UnboundedSender<Event>,
             }
             let mut subs: HashMap<String, Vec<Subscription>> = HashMap::new();
@@ -453,15 +788,20 @@ impl Event {
                         match msg_opt {
                             Some(msg) => {
                                 // Properties changed.
-                                if let (Some(object), Some(PropertiesPropertiesChanged { interface_name, changed_properties, .. })) =
+                                if let (Some(object), Some(PropertiesPropertiesChanged { interface_name, changed_properties, invalidated_properties })) =
                                     (msg.path(), PropertiesPropertiesChanged::from_message(&msg))
                                 {
+                                    if let Some(cache) = &cache {
+                                        cache.change_properties(&object.clone().into_static(), &interface_name, &changed_properties, &invalidated_properties);
+                                    }
+
                                     // Check for direct path match for PropertiesChanged event.
                                     if let Some(path_subs) = subs.get_mut(&*object) {
                                         let evt = Self::PropertiesChanged {
                                             object: object.clone().into_static(),
                                             interface: interface_name,
                                             changed: changed_properties,
+                                            invalidated: invalidated_properties,
                                         };
                                         log::trace!("Event: {:?}", &evt);
                                         path_subs.retain(|sub| sub.tx.unbounded_send(evt.clone()).is_ok());
@@ -475,6 +815,10 @@ impl Event {
                                 if let Some(ObjectManagerInterfacesAdded { object, interfaces }) =
                                     ObjectManagerInterfacesAdded::from_message(&msg)
                                 {
+                                    if let Some(cache) = &cache {
+                                        cache.add_interfaces(&object, &interfaces);
+                                    }
+
                                     // Check for parent path match for ObjectAdded event.
                                     let parent = parent_path(&object);
                                     if let Some(parent_subs) = subs.get_mut(&*parent) {
@@ -500,10 +844,23 @@ impl Event {
                                 if let Some(ObjectManagerInterfacesRemoved { object, interfaces, .. }) =
                                     ObjectManagerInterfacesRemoved::from_message(&msg)
                                 {
-                                    // Remove subscriptions for removed object.
+                                    if let Some(cache) = &cache {
+                                        cache.remove_interfaces(&object, &interfaces);
+                                    }
+
+                                    // Notify subscriptions of the object and remove them if the object is gone.
                                     // This ends the event streams of the subscriptions.
-                                    if subs.remove(&*object).is_some() {
-                                        log::trace!("Event subscription for {} ended because object was removed", &object);
+                                    let object_removed = Self::is_object_removed(cache.as_deref(), &object, &interfaces);
+                                    if let Some(path_subs) = subs.get_mut(&*object) {
+                                        let evt = Self::ObjectRemoved { object: object.clone(), interfaces: interfaces.iter().cloned().collect() };
+                                        log::trace!("Event: {:?}", &evt);
+                                        path_subs.retain(|sub| sub.tx.unbounded_send(evt.clone()).is_ok());
+                                        if object_removed {
+                                            subs.remove(&*object);
+                                            log::trace!("Event subscription for {} ended because object was removed", &object);
+                                        } else if path_subs.is_empty() {
+                                            subs.remove(&*object);
+                                        }
                                     }
 
                                     // Check for parent path match for ObjectRemoved event.
@@ -523,18 +880,56 @@ impl Event {
                                         }
                                     }
                                 }
+
+                                // Service owner changed.
+                                if msg.interface().as_deref() == Some(DBUS_INTERFACE) && msg.member().as_deref() == Some("NameOwnerChanged") {
+                                    if let Ok((name, old_owner, new_owner)) = msg.read3::<String, String, String>() {
+                                        if name == *service_name {
+                                            if let Some(cache) = &cache {
+                                                if new_owner.is_empty() {
+                                                    cache.clear();
+                                                } else {
+                                                    // Refilling the cache must not delay event delivery.
+                                                    let generation = cache.start_refresh();
+                                                    let cache = cache.clone();
+                                                    let connection = connection.clone();
+                                                    tokio::spawn(async move {
+                                                        cache.finish_refresh(generation, &connection).await
+                                                    });
+                                                }
+                                            }
+
+                                            let evt = Self::ServiceOwnerChanged {
+                                                old_owner: Some(old_owner).filter(|o| !o.is_empty()),
+                                                new_owner: Some(new_owner).filter(|o| !o.is_empty()),
+                                            };
+                                            log::trace!("Event: {:?}", &evt);
+                                            for path_subs in subs.values_mut() {
+                                                path_subs.retain(|sub| {
+                                                    if sub.owner_changes {
+                                                        sub.tx.unbounded_send(evt.clone()).is_ok()
+                                                    } else {
+                                                        true
+                                                    }
+                                                });
+                                            }
+                                            subs.retain(|_, path_subs| !path_subs.is_empty());
+                                        }
+                                    }
+                                }
                             },
                             None => break,
                         }
                     },
                     sub_opt = sub_rx.next() => {
                         match sub_opt {
-                            Some(SubscriptionReq { path, child_objects, tx, ready_tx }) => {
-                                log::trace!("Adding event subscription for {} with child_objects={:?}", &path, &child_objects);
+                            Some(SubscriptionReq { path, child_objects, owner_changes, tx, ready_tx }) => {
+                                log::trace!("Adding event subscription for {} with child_objects={:?} and owner_changes={:?}",
+                                    &path, &child_objects, &owner_changes);
                                 let _ = ready_tx.send(());
                                 let path_subs = subs.entry(path.to_string()).or_default();
                                 path_subs.push(Subscription {
-                                    child_objects, tx
+                                    child_objects, owner_changes, tx
                                 });
                             }
                             None => break,
@@ -546,6 +941,7 @@ impl Event {
             let _ = connection.remove_match(msg_match_add.token()).await;
             let _ = connection.remove_match(msg_match_removed.token()).await;
             let _ = connection.remove_match(msg_match_prop.token()).await;
+            let _ = connection.remove_match(msg_match_owner.token()).await;
             log::trace!("Terminated event loop for {}", &connection.unique_name());
         });
 
@@ -556,16 +952,35 @@ impl Event {
     ///
     /// If `child_objects` is [true] events about *direct* child objects being added and removed
     /// will also be delivered.
+    ///
+    /// If `owner_changes` is [true] events about the owner of the D-Bus service changing
+    /// will also be delivered.
     pub(crate) async fn subscribe(
         sub_tx: &mut mpsc::Sender<SubscriptionReq>, path: dbus::Path<'static>, child_objects: bool,
+        owner_changes: bool,
     ) -> Result<mpsc::UnboundedReceiver<Event>> {
         let (tx, rx) = mpsc::unbounded();
         let (ready_tx, ready_rx) = oneshot::channel();
         sub_tx
-            .send(SubscriptionReq { path, child_objects, tx, ready_tx })
+            .send(SubscriptionReq { path, child_objects, owner_changes, tx, ready_tx })
             .await
             .map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::DBusConnectionLost)))?;
         ready_rx.await.map_err(|_| Error::new(ErrorKind::Internal(InternalErrorKind::DBusConnectionLost)))?;
         Ok(rx)
     }
+
+    /// Whether the object has been removed entirely after `interfaces` were removed from it,
+    /// rather than only some of its interfaces.
+    ///
+    /// A valid cache knows whether interfaces remain.
+    /// Otherwise the removal of the standard properties interface is relied upon,
+    /// which the Bluetooth daemon reports only when the object itself is removed.
+    fn is_object_removed(
+        cache: Option<&ObjectCache>, object: &dbus::Path<'static>, interfaces: &[String],
+    ) -> bool {
+        match cache.and_then(|cache| cache.contains(object)) {
+            Some(contains) => !contains,
+            None => interfaces.iter().any(|interface| interface == PROPERTIES_INTERFACE),
+        }
+    }
 }
@@ -1,6 +1,6 @@
 //! System socket base.
 
-use libc::{c_int, sockaddr, socklen_t, Ioctl, SOCK_CLOEXEC, SOCK_NONBLOCK};
+use libc::{c_int, sockaddr, socklen_t, SOCK_CLOEXEC, SOCK_NONBLOCK};
 use std::{
     io::{Error, ErrorKind, Result},
     mem::{size_of, MaybeUninit},
@@ -8,6 +8,14 @@ use std::{
 };
 use tokio::io::ReadBuf;
 
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
+use libc::Ioctl;
+#[cfg(any(feature = "iso", feature = "hci"))]
+use std::{
+    mem, ptr, slice,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
 /// File descriptor that is closed on drop.
 #[derive(Debug)]
 pub struct OwnedFd {
@@ -53,6 +61,23 @@ pub trait SysSockAddr: Sized {
 
     /// Convert from system socket address.
     fn try_from_sys_sock_addr(addr: Self::SysSockAddr) -> Result<Self>;
+
+    /// Length of the system socket address passed to the kernel.
+    ///
+    /// Defaults to the size of the system socket address type.
+    fn sys_sock_addr_len(&self) -> usize {
+        size_of::<Self::SysSockAddr>()
+    }
+
+    /// Convert from system socket address of the specified length returned by the kernel.
+    ///
+    /// By default the length must equal the size of the system socket address type.
+    fn try_from_sys_sock_addr_len(addr: Self::SysSockAddr, len: usize) -> Result<Self> {
+        if len != size_of::<Self::SysSockAddr>() {
+            return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length"));
+        }
+        Self::try_from_sys_sock_addr(addr)
+    }
 }
 
 /// Creates a socket of the specified type and returns its file descriptor.
@@ -71,15 +96,9 @@ pub fn bind<SA>(socket: &OwnedFd, sa: SA) -> Result<()>
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
-    if unsafe {
-        libc::bind(
-            socket.as_raw_fd(),
-            &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
-        )
-    } == 0
-    {
+    if unsafe { libc::bind(socket.as_raw_fd(), &addr as *const _ as *const sockaddr, len as socklen_t) } == 0 {
         Ok(())
     } else {
         Err(Error::last_os_error())
@@ -91,40 +110,36 @@ pub fn getsockname<SA>(socket: &OwnedFd) -> Result<SA>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     if unsafe { libc::getsockname(socket.as_raw_fd(), saddr.as_mut_ptr() as *mut _, &mut length) } == -1 {
         return Err(Error::last_os_error());
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length from getsockname"));
-    }
     let saddr = unsafe { saddr.assume_init() };
-    SA::try_from_sys_sock_addr(saddr)
+    SA::try_from_sys_sock_addr_len(saddr, length as _)
 }
 
 /// Gets the address the socket is connected to.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn getpeername<SA>(socket: &OwnedFd) -> Result<SA>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     if unsafe { libc::getpeername(socket.as_raw_fd(), saddr.as_mut_ptr() as *mut _, &mut length) } == -1 {
         return Err(Error::last_os_error());
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length from getpeername"));
-    }
     let saddr = unsafe { saddr.assume_init() };
-    SA::try_from_sys_sock_addr(saddr)
+    SA::try_from_sys_sock_addr_len(saddr, length as _)
 }
 
 /// Puts socket in listen mode.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn listen(socket: &OwnedFd, backlog: i32) -> Result<()> {
     if unsafe { libc::listen(socket.as_raw_fd(), backlog) } == 0 {
         Ok(())
@@ -136,11 +151,12 @@ pub fn listen(socket: &OwnedFd, backlog: i32) -> Result<()> {
 /// Accept a connection on the provided socket.
 ///
 /// The accepted socket is set into non-blocking mode.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn accept<SA>(socket: &OwnedFd) -> Result<(OwnedFd, SA)>
 where
     SA: SysSockAddr,
 {
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
 
     let fd = match unsafe {
@@ -150,29 +166,21 @@ where
         fd => unsafe { OwnedFd::new(fd) },
     };
 
-    if length != size_of::<SA::SysSockAddr>() as socklen_t {
-        return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length"));
-    }
     let saddr = unsafe { saddr.assume_init() };
-    let sa = SA::try_from_sys_sock_addr(saddr)?;
+    let sa = SA::try_from_sys_sock_addr_len(saddr, length as _)?;
 
     Ok((fd, sa))
 }
 
 /// Initiate a connection on a socket to the specified address.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn connect<SA>(socket: &OwnedFd, sa: SA) -> Result<()>
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
-    if unsafe {
-        libc::connect(
-            socket.as_raw_fd(),
-            &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
-        )
-    } == 0
-    {
+    if unsafe { libc::connect(socket.as_raw_fd(), &addr as *const _ as *const sockaddr, len as socklen_t) } == 0 {
         Ok(())
     } else {
         Err(Error::last_os_error())
@@ -188,10 +196,12 @@ pub fn send(socket: &OwnedFd, buf: &[u8], flags: c_int) -> Result<usize> {
 }
 
 /// Sends from buffer into socket using destination address.
+#[cfg(feature = "l2cap")]
 pub fn sendto<SA>(socket: &OwnedFd, buf: &[u8], flags: c_int, sa: SA) -> Result<usize>
 where
     SA: SysSockAddr,
 {
+    let len = sa.sys_sock_addr_len();
     let addr: SA::SysSockAddr = sa.into_sys_sock_addr();
     match unsafe {
         libc::sendto(
@@ -200,7 +210,7 @@ where
             buf.len(),
             flags,
             &addr as *const _ as *const sockaddr,
-            size_of::<SA::SysSockAddr>() as socklen_t,
+            len as socklen_t,
         )
     } {
         -1 => Err(Error::last_os_error()),
@@ -225,12 +235,13 @@ pub fn recv(socket: &OwnedFd, buf: &mut ReadBuf, flags: c_int) -> Result<usize>
 }
 
 /// Receive from socket into buffer with source address.
+#[cfg(feature = "l2cap")]
 pub fn recvfrom<SA>(socket: &OwnedFd, buf: &mut ReadBuf, flags: c_int) -> Result<(usize, SA)>
 where
     SA: SysSockAddr,
 {
     let unfilled = unsafe { buf.unfilled_mut() };
-    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::uninit();
+    let mut saddr: MaybeUninit<SA::SysSockAddr> = MaybeUninit::zeroed();
     let mut length = size_of::<SA::SysSockAddr>() as socklen_t;
     match unsafe {
         libc::recvfrom(
@@ -250,18 +261,77 @@ where
             }
             buf.advance(n);
 
-            if length != size_of::<SA::SysSockAddr>() as socklen_t {
-                return Err(Error::new(ErrorKind::InvalidInput, "invalid sockaddr length"));
-            }
             let saddr = unsafe { saddr.assume_init() };
-            let sa = SA::try_from_sys_sock_addr(saddr)?;
+            let sa = SA::try_from_sys_sock_addr_len(saddr, length as _)?;
 
             Ok((n, sa))
         }
     }
 }
 
+/// Control message received together with data.
+#[cfg(any(feature = "iso", feature = "hci"))]
+#[derive(Debug, Clone)]
+pub struct ControlMessage {
+    /// Originating protocol.
+    pub level: c_int,
+    /// Protocol-specific type.
+    pub ty: c_int,
+    /// Data.
+    pub data: Vec<u8>,
+}
+
+#[cfg(any(feature = "iso", feature = "hci"))]
+impl ControlMessage {
+    /// Receive timestamp, if this is a `SCM_TIMESTAMPNS` control message.
+    pub fn timestamp(&self) -> Option<SystemTime> {
+        if self.level != libc::SOL_SOCKET
+            || self.ty != libc::SCM_TIMESTAMPNS
+            || self.data.len() < size_of::<libc::timespec>()
+        {
+            return None;
+        }
+        let ts: libc::timespec = unsafe { ptr::read_unaligned(self.data.as_ptr() as *const _) };
+        Some(UNIX_EPOCH + Duration::new(ts.tv_sec as _, ts.tv_nsec as _))
+    }
+}
+
+/// Receive from socket into buffer together with control messages.
+#[cfg(any(feature = "iso", feature = "hci"))]
+pub fn recvmsg(socket: &OwnedFd, buf: &mut ReadBuf, flags: c_int) -> Result<(usize, Vec<ControlMessage>)> {
+    let unfilled = unsafe { buf.unfilled_mut() };
+    let mut iov = libc::iovec { iov_base: unfilled.as_mut_ptr() as *mut _, iov_len: unfilled.len() };
+    let mut control = [0u64; 64];
+    let mut msg: libc::msghdr = unsafe { mem::zeroed() };
+    msg.msg_iov = &mut iov;
+    msg.msg_iovlen = 1;
+    msg.msg_control = control.as_mut_ptr() as *mut _;
+    msg.msg_controllen = mem::size_of_val(&control) as _;
+
+    let n = match unsafe { libc::recvmsg(socket.as_raw_fd(), &mut msg, flags) } {
+        -1 => return Err(Error::last_os_error()),
+        n => n as usize,
+    };
+    unsafe {
+        buf.assume_init(n);
+    }
+    buf.advance(n);
+
+    let mut cmsgs = Vec::new();
+    let mut cmsg = unsafe { libc::CMSG_FIRSTHDR(&msg) };
+    while !cmsg.is_null() {
+        let hdr = unsafe { &*cmsg };
+        let len = (hdr.cmsg_len as usize).saturating_sub(unsafe { libc::CMSG_LEN(0) } as usize);
+        let data = unsafe { slice::from_raw_parts(libc::CMSG_DATA(cmsg), len) }.to_vec();
+        cmsgs.push(ControlMessage { level: hdr.cmsg_level, ty: hdr.cmsg_type, data });
+        cmsg = unsafe { libc::CMSG_NXTHDR(&msg, cmsg) };
+    }
+
+    Ok((n, cmsgs))
+}
+
 /// Shut down part of a socket.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn shutdown(socket: &OwnedFd, how: c_int) -> Result<()> {
     if unsafe { libc::shutdown(socket.as_raw_fd(), how) } == 0 {
         Ok(())
@@ -297,7 +367,34 @@ pub fn setsockopt<T>(socket: &OwnedFd, level: c_int, optname: i32, optval: &T) -
     Ok(())
 }
 
+/// Get socket option of variable length.
+#[cfg(feature = "iso")]
+pub fn getsockopt_vec(socket: &OwnedFd, level: c_int, optname: c_int, max_len: usize) -> Result<Vec<u8>> {
+    let mut optval = vec![0; max_len];
+    let mut optlen: socklen_t = max_len as _;
+    if unsafe { libc::getsockopt(socket.as_raw_fd(), level, optname, optval.as_mut_ptr() as *mut _, &mut optlen) }
+        == -1
+    {
+        return Err(Error::last_os_error());
+    }
+    optval.truncate(optlen as _);
+    Ok(optval)
+}
+
+/// Set socket option of variable length.
+#[cfg(feature = "iso")]
+pub fn setsockopt_slice(socket: &OwnedFd, level: c_int, optname: i32, optval: &[u8]) -> Result<()> {
+    if unsafe {
+        libc::setsockopt(socket.as_raw_fd(), level, optname, optval.as_ptr() as *const _, optval.len() as _)
+    } == -1
+    {
+        return Err(Error::last_os_error());
+    }
+    Ok(())
+}
+
 /// Perform an IOCTL that reads a single value.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 pub fn ioctl_read<T>(socket: &OwnedFd, request: Ioctl) -> Result<T> {
     let mut value: MaybeUninit<T> = MaybeUninit::uninit();
     let ret = unsafe { libc::ioctl(socket.as_raw_fd(), request, value.as_mut_ptr()) };
@@ -309,7 +406,7 @@ pub fn ioctl_read<T>(socket: &OwnedFd, request: Ioctl) -> Result<T> {
 }
 
 /// Perform an IOCTL that writes a single value.
-#[allow(dead_code)]
+#[cfg(feature = "rfcomm")]
 pub fn ioctl_write<T>(socket: &OwnedFd, request: Ioctl, value: &T) -> Result<c_int> {
     let ret = unsafe { libc::ioctl(socket.as_raw_fd(), request, value as *const _) };
     if ret == -1 {
@@ -319,6 +416,10 @@ pub fn ioctl_write<T>(socket: &OwnedFd, request: Ioctl, value: &T) -> Result<c_i
 }
 
 /// Private socket implementation functions.
+///
+/// Use `sock_priv!(packet)` to add `send_priv` and `recv_priv` and
+/// `sock_priv!(datagram)` to additionally add addressed sending and receiving.
+#[cfg(any(feature = "l2cap", feature = "rfcomm", feature = "sco", feature = "iso"))]
 macro_rules! sock_priv {
     () => {
         async fn accept_priv(&self) -> Result<(Self, SocketAddr)> {
@@ -371,133 +472,135 @@ macro_rules! sock_priv {
             }
         }
 
-        #[allow(dead_code)]
-        async fn send_priv(&self, buf: &[u8]) -> Result<usize> {
+        fn poll_send_priv(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
             loop {
-                let mut guard = self.fd.writable().await?;
+                let mut guard = ready!(self.fd.poll_write_ready(cx))?;
                 match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
-                    Ok(result) => return result,
+                    Ok(result) => return Poll::Ready(result),
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        fn poll_send_priv(&self, cx: &mut Context, buf: &[u8]) -> Poll<Result<usize>> {
+        fn poll_recv_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
             loop {
-                let mut guard = ready!(self.fd.poll_write_ready(cx))?;
-                match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
-                    Ok(result) => return Poll::Ready(result),
+                let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+                match guard.try_io(|inner| sock::recv(inner.get_ref(), buf, 0)) {
+                    Ok(result) => return Poll::Ready(result.map(|_| ())),
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        #[allow(dead_code)]
-        async fn send_to_priv(&self, buf: &[u8], target: SocketAddr) -> Result<usize> {
+        async fn peek_priv(&self, buf: &mut [u8]) -> Result<usize> {
+            let mut buf = ReadBuf::new(buf);
             loop {
-                let mut guard = self.fd.writable().await?;
-                match guard.try_io(|inner| sock::sendto(inner.get_ref(), buf, 0, target)) {
+                let mut guard = self.fd.readable().await?;
+                match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut buf, MSG_PEEK)) {
                     Ok(result) => return result,
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        #[allow(dead_code)]
-        fn poll_send_to_priv(&self, cx: &mut Context, buf: &[u8], target: SocketAddr) -> Poll<Result<usize>> {
+        fn poll_peek_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<usize>> {
             loop {
-                let mut guard = ready!(self.fd.poll_write_ready(cx))?;
-                match guard.try_io(|inner| sock::sendto(inner.get_ref(), buf, 0, target)) {
+                let mut guard = ready!(self.fd.poll_read_ready(cx))?;
+                match guard.try_io(|inner| sock::recv(inner.get_ref(), buf, MSG_PEEK)) {
                     Ok(result) => return Poll::Ready(result),
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        #[allow(dead_code)]
-        async fn recv_priv(&self, buf: &mut [u8]) -> Result<usize> {
-            let mut buf = ReadBuf::new(buf);
+        fn poll_flush_priv(&self, _cx: &mut Context) -> Poll<Result<()>> {
+            // Flush is a no-op.
+            Poll::Ready(Ok(()))
+        }
+
+        fn shutdown_priv(&self, how: Shutdown) -> Result<()> {
+            let how = match how {
+                Shutdown::Read => SHUT_RD,
+                Shutdown::Write => SHUT_WR,
+                Shutdown::Both => SHUT_RDWR,
+            };
+            sock::shutdown(self.fd.get_ref(), how)?;
+            Ok(())
+        }
+
+        fn poll_shutdown_priv(&self, _cx: &mut Context, how: Shutdown) -> Poll<Result<()>> {
+            self.shutdown_priv(how)?;
+            Poll::Ready(Ok(()))
+        }
+    };
+
+    (packet) => {
+        sock_priv!();
+
+        async fn send_priv(&self, buf: &[u8]) -> Result<usize> {
             loop {
-                let mut guard = self.fd.readable().await?;
-                match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut buf, 0)) {
+                let mut guard = self.fd.writable().await?;
+                match guard.try_io(|inner| sock::send(inner.get_ref(), buf, 0)) {
                     Ok(result) => return result,
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        fn poll_recv_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<()>> {
+        async fn recv_priv(&self, buf: &mut [u8]) -> Result<usize> {
+            let mut buf = ReadBuf::new(buf);
             loop {
-                let mut guard = ready!(self.fd.poll_read_ready(cx))?;
-                match guard.try_io(|inner| sock::recv(inner.get_ref(), buf, 0)) {
-                    Ok(result) => return Poll::Ready(result.map(|_| ())),
+                let mut guard = self.fd.readable().await?;
+                match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut buf, 0)) {
+                    Ok(result) => return result,
                     Err(_would_block) => continue,
                 }
             }
         }
+    };
 
-        #[allow(dead_code)]
-        async fn recv_from_priv(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
-            let mut buf = ReadBuf::new(buf);
+    (datagram) => {
+        sock_priv!(packet);
+
+        async fn send_to_priv(&self, buf: &[u8], target: SocketAddr) -> Result<usize> {
             loop {
-                let mut guard = self.fd.readable().await?;
-                match guard.try_io(|inner| sock::recvfrom(inner.get_ref(), &mut buf, 0)) {
+                let mut guard = self.fd.writable().await?;
+                match guard.try_io(|inner| sock::sendto(inner.get_ref(), buf, 0, target)) {
                     Ok(result) => return result,
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        #[allow(dead_code)]
-        fn poll_recv_from_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<SocketAddr>> {
+        fn poll_send_to_priv(&self, cx: &mut Context, buf: &[u8], target: SocketAddr) -> Poll<Result<usize>> {
             loop {
-                let mut guard = ready!(self.fd.poll_read_ready(cx))?;
-                match guard.try_io(|inner| sock::recvfrom(inner.get_ref(), buf, 0)) {
-                    Ok(result) => return Poll::Ready(result.map(|(_n, sa)| sa)),
+                let mut guard = ready!(self.fd.poll_write_ready(cx))?;
+                match guard.try_io(|inner| sock::sendto(inner.get_ref(), buf, 0, target)) {
+                    Ok(result) => return Poll::Ready(result),
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        async fn peek_priv(&self, buf: &mut [u8]) -> Result<usize> {
+        async fn recv_from_priv(&self, buf: &mut [u8]) -> Result<(usize, SocketAddr)> {
             let mut buf = ReadBuf::new(buf);
             loop {
                 let mut guard = self.fd.readable().await?;
-                match guard.try_io(|inner| sock::recv(inner.get_ref(), &mut buf, MSG_PEEK)) {
+                match guard.try_io(|inner| sock::recvfrom(inner.get_ref(), &mut buf, 0)) {
                     Ok(result) => return result,
                     Err(_would_block) => continue,
                 }
             }
         }
 
-        fn poll_peek_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<usize>> {
+        fn poll_recv_from_priv(&self, cx: &mut Context, buf: &mut ReadBuf) -> Poll<Result<SocketAddr>> {
             loop {
                 let mut guard = ready!(self.fd.poll_read_ready(cx))?;
-                match guard.try_io(|inner| sock::recv(inner.get_ref(), buf, MSG_PEEK)) {
-                    Ok(result) => return Poll::Ready(result),
+                match guard.try_io(|inner| sock::recvfrom(inner.get_ref(), buf, 0)) {
+                    Ok(result) => return Poll::Ready(result.map(|(_n, sa)| sa)),
                     Err(_would_block) => continue,
                 }
             }
         }
-
-        fn poll_flush_priv(&self, _cx: &mut Context) -> Poll<Result<()>> {
-            // Flush is a no-op.
-            Poll::Ready(Ok(()))
-        }
-
-        fn shutdown_priv(&self, how: Shutdown) -> Result<()> {
-            let how = match how {
-                Shutdown::Read => SHUT_RD,
-                Shutdown::Write => SHUT_WR,
-                Shutdown::Both => SHUT_RDWR,
-            };
-            sock::shutdown(self.fd.get_ref(), how)?;
-            Ok(())
-        }
-
-        fn poll_shutdown_priv(&self, _cx: &mut Context, how: Shutdown) -> Poll<Result<()>> {
-            self.shutdown_priv(how)?;
-            Poll::Ready(Ok(()))
-        }
     };
 }
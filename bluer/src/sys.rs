@@ -5,7 +5,9 @@ use libc::{c_int, c_ushort, sa_family_t};
 use nix::{request_code_write, sys::ioctl::ioctl_num_type};
 use std::mem::size_of;
 
+pub const SOL_HCI: i32 = 0;
 pub const SOL_L2CAP: i32 = 6;
+pub const SOL_SCO: i32 = 17;
 pub const SOL_RFCOMM: i32 = 18;
 
 /// Bluetooth security.
@@ -35,10 +37,94 @@ pub const BT_POWER: i32 = 9;
 pub const BT_POWER_FORCE_ACTIVE_OFF: i32 = 0;
 pub const BT_POWER_FORCE_ACTIVE_ON: i32 = 1;
 
+pub const BT_DEFER_SETUP: i32 = 7;
+
+/// Voice setting.
+#[repr(C)]
+#[derive(Clone)]
+pub struct bt_voice {
+    pub setting: u16,
+}
+
+pub const BT_VOICE: i32 = 11;
+pub const BT_VOICE_TRANSPARENT: u16 = 0x0003;
+pub const BT_VOICE_CVSD_16BIT: u16 = 0x0060;
+
 pub const BT_SNDMTU: i32 = 12;
 pub const BT_RCVMTU: i32 = 13;
 pub const BT_PHY: i32 = 14;
 pub const BT_MODE: i32 = 15;
+pub const BT_PKT_STATUS: i32 = 16;
+pub const BT_SCM_PKT_STATUS: i32 = 0x03;
+pub const BT_ISO_QOS: i32 = 17;
+pub const BT_ISO_BASE: i32 = 20;
+
+pub const BT_ISO_QOS_CIG_UNSET: u8 = 0xff;
+pub const BT_ISO_QOS_CIS_UNSET: u8 = 0xff;
+pub const BT_ISO_QOS_BIG_UNSET: u8 = 0xff;
+pub const BT_ISO_QOS_BIS_UNSET: u8 = 0xff;
+pub const BT_ISO_SYNC_TIMEOUT: u16 = 0x07d0;
+
+pub const BT_ISO_PHY_1M: u8 = 0x01;
+pub const BT_ISO_PHY_2M: u8 = 0x02;
+pub const BT_ISO_PHY_CODED: u8 = 0x04;
+pub const BT_ISO_PHY_ANY: u8 = BT_ISO_PHY_1M | BT_ISO_PHY_2M | BT_ISO_PHY_CODED;
+
+/// ISO input or output QoS.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct bt_iso_io_qos {
+    pub interval: u32,
+    pub latency: u16,
+    pub sdu: u16,
+    pub phy: u8,
+    pub rtn: u8,
+}
+
+/// ISO unicast QoS.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct bt_iso_ucast_qos {
+    pub cig: u8,
+    pub cis: u8,
+    pub sca: u8,
+    pub packing: u8,
+    pub framing: u8,
+    pub in_: bt_iso_io_qos,
+    pub out: bt_iso_io_qos,
+}
+
+/// ISO broadcast QoS.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub struct bt_iso_bcast_qos {
+    pub big: u8,
+    pub bis: u8,
+    pub sync_factor: u8,
+    pub packing: u8,
+    pub framing: u8,
+    pub in_: bt_iso_io_qos,
+    pub out: bt_iso_io_qos,
+    pub encryption: u8,
+    pub bcode: [u8; 16],
+    pub options: u8,
+    pub skip: u16,
+    pub sync_timeout: u16,
+    pub sync_cte_type: u8,
+    pub mse: u8,
+    pub timeout: u16,
+}
+
+/// ISO QoS.
+#[repr(C)]
+#[derive(Clone, Copy)]
+pub union bt_iso_qos {
+    pub ucast: bt_iso_ucast_qos,
+    pub bcast: bt_iso_bcast_qos,
+}
+
+/// Maximum length of broadcast audio source endpoint (BASE) data.
+pub const BASE_MAX_LENGTH: usize = 248;
 
 /// BR1M1SLOT PHY.
 pub const BR1M1SLOT: i32 = 1 << 0;
@@ -72,7 +158,10 @@ pub const LECODEDTX: i32 = 1 << 13;
 pub const LECODEDRX: i32 = 1 << 14;
 
 pub const BTPROTO_L2CAP: i32 = 0;
+pub const BTPROTO_HCI: i32 = 1;
+pub const BTPROTO_SCO: i32 = 2;
 pub const BTPROTO_RFCOMM: i32 = 3;
+pub const BTPROTO_ISO: i32 = 8;
 
 /// Bluetooth address.
 #[repr(packed)]
@@ -86,6 +175,71 @@ pub const BDADDR_BREDR: u8 = 0x00;
 pub const BDADDR_LE_PUBLIC: u8 = 0x01;
 pub const BDADDR_LE_RANDOM: u8 = 0x02;
 
+/// HCI socket address.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_hci {
+    pub hci_family: sa_family_t,
+    pub hci_dev: c_ushort,
+    pub hci_channel: c_ushort,
+}
+
+pub const HCI_DEV_NONE: u16 = 0xffff;
+
+pub const HCI_CHANNEL_RAW: u16 = 0;
+pub const HCI_CHANNEL_USER: u16 = 1;
+pub const HCI_CHANNEL_MONITOR: u16 = 2;
+pub const HCI_CHANNEL_CONTROL: u16 = 3;
+pub const HCI_CHANNEL_LOGGING: u16 = 4;
+
+pub const HCI_FILTER: i32 = 2;
+
+/// HCI socket filter.
+#[repr(C)]
+#[derive(Clone, Default)]
+pub struct hci_filter {
+    pub type_mask: u32,
+    pub event_mask: [u32; 2],
+    pub opcode: u16,
+}
+
+/// HCI monitor channel packet header.
+#[repr(C, packed)]
+#[derive(Clone, Default)]
+pub struct hci_mon_hdr {
+    pub opcode: u16,
+    pub index: u16,
+    pub len: u16,
+}
+
+pub const HCI_MON_NEW_INDEX: u16 = 0;
+pub const HCI_MON_DEL_INDEX: u16 = 1;
+pub const HCI_MON_COMMAND_PKT: u16 = 2;
+pub const HCI_MON_EVENT_PKT: u16 = 3;
+pub const HCI_MON_ACL_TX_PKT: u16 = 4;
+pub const HCI_MON_ACL_RX_PKT: u16 = 5;
+pub const HCI_MON_SCO_TX_PKT: u16 = 6;
+pub const HCI_MON_SCO_RX_PKT: u16 = 7;
+pub const HCI_MON_OPEN_INDEX: u16 = 8;
+pub const HCI_MON_CLOSE_INDEX: u16 = 9;
+pub const HCI_MON_INDEX_INFO: u16 = 10;
+pub const HCI_MON_VENDOR_DIAG: u16 = 11;
+pub const HCI_MON_SYSTEM_NOTE: u16 = 12;
+pub const HCI_MON_USER_LOGGING: u16 = 13;
+pub const HCI_MON_CTRL_OPEN: u16 = 14;
+pub const HCI_MON_CTRL_CLOSE: u16 = 15;
+pub const HCI_MON_CTRL_COMMAND: u16 = 16;
+pub const HCI_MON_CTRL_EVENT: u16 = 17;
+pub const HCI_MON_ISO_TX_PKT: u16 = 18;
+pub const HCI_MON_ISO_RX_PKT: u16 = 19;
+
+pub const HCI_COMMAND_PKT: u8 = 0x01;
+pub const HCI_ACLDATA_PKT: u8 = 0x02;
+pub const HCI_SCODATA_PKT: u8 = 0x03;
+pub const HCI_EVENT_PKT: u8 = 0x04;
+pub const HCI_ISODATA_PKT: u8 = 0x05;
+pub const HCI_VENDOR_PKT: u8 = 0xff;
+
 /// L2CAP socket address.
 #[repr(C)]
 #[derive(Clone)]
@@ -156,6 +310,69 @@ pub struct l2cap_conninfo {
     pub dev_class: [u8; 3],
 }
 
+/// SCO socket address.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_sco {
+    pub sco_family: sa_family_t,
+    pub sco_bdaddr: bdaddr_t,
+}
+
+pub const SCO_OPTIONS: i32 = 0x01;
+pub const SCO_CONNINFO: i32 = 0x02;
+
+/// SCO socket options.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sco_options {
+    pub mtu: u16,
+}
+
+/// SCO socket connection information.
+#[repr(C)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub struct sco_conninfo {
+    /// Host controller interface (HCI) handle for the connection.
+    pub hci_handle: u16,
+    /// Device class.
+    pub dev_class: [u8; 3],
+}
+
+pub const ISO_MAX_NUM_BIS: usize = 0x1f;
+
+/// ISO broadcast socket address.
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_iso_bc {
+    pub bc_bdaddr: bdaddr_t,
+    pub bc_bdaddr_type: u8,
+    pub bc_sid: u8,
+    pub bc_num_bis: u8,
+    pub bc_bis: [u8; ISO_MAX_NUM_BIS],
+}
+
+/// ISO socket address.
+///
+/// In the kernel `iso_bc` is a flexible array member that is only present
+/// for broadcast addresses.
+/// Thus the length of this address is either [SOCKADDR_ISO_LEN] or
+/// [SOCKADDR_ISO_BC_LEN].
+#[repr(C)]
+#[derive(Clone)]
+pub struct sockaddr_iso {
+    pub iso_family: sa_family_t,
+    pub iso_bdaddr: bdaddr_t,
+    pub iso_bdaddr_type: u8,
+    pub iso_bc: sockaddr_iso_bc,
+}
+
+/// Length of ISO socket address without broadcast address.
+pub const SOCKADDR_ISO_LEN: usize = 10;
+/// Length of ISO socket address with broadcast address.
+pub const SOCKADDR_ISO_BC_LEN: usize = SOCKADDR_ISO_LEN + size_of::<sockaddr_iso_bc>();
+
 /// RFCOMM socket address.
 #[repr(C)]
 #[derive(Clone)]
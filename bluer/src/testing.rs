@@ -0,0 +1,1223 @@
+//! Mock Bluetooth daemon for testing applications without Bluetooth hardware.
+//!
+//! [MockBluez] starts a private D-Bus daemon and serves a fake `org.bluez` service on it.
+//! Adapters, remote devices and their GATT databases are scripted by the test,
+//! while the code under test uses a regular [Session] obtained from [MockBluez::session].
+//!
+//! The `dbus-daemon` executable must be available in the search path.
+//!
+//! Only a subset of the BlueZ D-Bus interface is implemented, namely
+//! adapter properties and discovery, device connection and pairing, and
+//! reading, writing and notifications of GATT characteristics and descriptors.
+//! Calling other methods fails with an unknown method error.
+//!
+//! # Example
+//! ```no_run
+//! # async fn example() -> bluer::Result<()> {
+//! use bluer::{
+//!     testing::{MockAdapter, MockBluez, MockDevice},
+//!     Address,
+//! };
+//!
+//! let mock = MockBluez::new().await?;
+//! mock.add_adapter(MockAdapter::default())?;
+//! let address = Address::new([0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc]);
+//! mock.add_device("hci0", MockDevice { address, name: Some("Sensor".to_string()), ..Default::default() })?;
+//!
+//! let session = mock.session().await?;
+//! let device = session.default_adapter().await?.device(address)?;
+//! assert_eq!(device.name().await?.as_deref(), Some("Sensor"));
+//! # Ok(())
+//! # }
+//! ```
+
+use dbus::{
+    arg::{prop_cast, PropMap, RefArg, Variant},
+    channel::{Channel, MatchingReceiver, Sender},
+    message::{MatchRule, SignalArgs},
+    nonblock::{
+        stdintf::org_freedesktop_dbus::{
+            ObjectManagerInterfacesAdded, ObjectManagerInterfacesRemoved, PropertiesPropertiesChanged,
+        },
+        SyncConnection,
+    },
+    MethodErr, Path,
+};
+use dbus_crossroads::{Crossroads, IfaceBuilder, IfaceToken};
+use dbus_tokio::connection;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    fmt,
+    io::{BufRead, BufReader},
+    process::{Child, Command, Stdio},
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use tokio::task::{spawn_blocking, JoinHandle};
+use uuid::Uuid;
+
+use crate::{
+    ad::{AdvertisingData, Structure},
+    adapter, agent, device,
+    gatt::{self, CharacteristicFlags},
+    Address, AddressType, Error, ErrorKind, Result, Session, SessionOptions, UuidExt, ERR_PREFIX, SERVICE_NAME,
+};
+
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+const OBJECT_MANAGER_INTERFACE: &str = "org.freedesktop.DBus.ObjectManager";
+
+/// Scripted Bluetooth adapter.
+#[derive(Clone, Debug)]
+pub struct MockAdapter {
+    /// Adapter name, for example `hci0`.
+    pub name: String,
+    /// Adapter address.
+    pub address: Address,
+    /// Whether the adapter is powered.
+    pub powered: bool,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+impl Default for MockAdapter {
+    fn default() -> Self {
+        Self {
+            name: adapter::DEFAULT_NAME.to_string(),
+            address: Address::new([0x00, 0x00, 0x5e, 0x00, 0x53, 0x00]),
+            powered: true,
+            _non_exhaustive: (),
+        }
+    }
+}
+
+/// Scripted remote Bluetooth device.
+#[derive(Clone, Debug, Default)]
+pub struct MockDevice {
+    /// Device address.
+    pub address: Address,
+    /// Device address type.
+    pub address_type: AddressType,
+    /// Remote name.
+    pub name: Option<String>,
+    /// Bluetooth class of device.
+    pub class: Option<u32>,
+    /// External appearance.
+    pub appearance: Option<u16>,
+    /// Received signal strength indicator.
+    pub rssi: Option<i16>,
+    /// Advertised transmitted power level.
+    pub tx_power: Option<i16>,
+    /// Advertised service UUIDs.
+    pub service_uuids: BTreeSet<Uuid>,
+    /// Advertised manufacturer specific data by company identifier.
+    pub manufacturer_data: BTreeMap<u16, Vec<u8>>,
+    /// Advertised service data by service UUID.
+    pub service_data: BTreeMap<Uuid, Vec<u8>>,
+    /// Whether the device is paired.
+    pub paired: bool,
+    /// Whether the device is trusted.
+    pub trusted: bool,
+    /// Battery level in percent.
+    ///
+    /// If `None`, the device has no battery interface.
+    pub battery_percentage: Option<u8>,
+    /// Error returned when connecting the device.
+    ///
+    /// If `None`, connecting succeeds.
+    pub connect_error: Option<ErrorKind>,
+    /// Error returned when pairing with the device.
+    ///
+    /// If `None`, pairing succeeds.
+    pub pairing_error: Option<ErrorKind>,
+    /// GATT database of the device.
+    ///
+    /// The services are resolved when the device is connected.
+    pub services: Vec<MockService>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Scripted GATT service of a remote device.
+#[derive(Clone, Debug, Default)]
+pub struct MockService {
+    /// Service UUID.
+    pub uuid: Uuid,
+    /// Whether the service is a primary service.
+    pub primary: bool,
+    /// Characteristics.
+    pub characteristics: Vec<MockCharacteristic>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Scripted GATT characteristic of a remote device.
+#[derive(Clone, Debug, Default)]
+pub struct MockCharacteristic {
+    /// Characteristic UUID.
+    pub uuid: Uuid,
+    /// Characteristic flags.
+    ///
+    /// Reading, writing and notifications are only allowed when
+    /// the corresponding flags are set.
+    pub flags: CharacteristicFlags,
+    /// Initial value.
+    pub value: Vec<u8>,
+    /// Descriptors.
+    pub descriptors: Vec<MockDescriptor>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Scripted GATT characteristic descriptor of a remote device.
+#[derive(Clone, Debug, Default)]
+pub struct MockDescriptor {
+    /// Descriptor UUID.
+    pub uuid: Uuid,
+    /// Initial value.
+    pub value: Vec<u8>,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Advertisement received by a mock adapter.
+#[derive(Clone, Debug, Default)]
+pub struct MockAdvertisement {
+    /// Address of the advertising device.
+    pub address: Address,
+    /// Address type of the advertising device.
+    pub address_type: AddressType,
+    /// Received signal strength indicator.
+    pub rssi: Option<i16>,
+    /// Advertising data.
+    pub data: AdvertisingData,
+    #[doc(hidden)]
+    pub _non_exhaustive: (),
+}
+
+/// Interfaces and their properties of a D-Bus object.
+type Interfaces = BTreeMap<String, PropMap>;
+
+/// Scripted behavior of a device.
+struct DeviceScript {
+    connect_error: Option<ErrorKind>,
+    pairing_error: Option<ErrorKind>,
+    services: Vec<MockService>,
+    services_published: bool,
+}
+
+/// Objects served by the mock daemon.
+#[derive(Default)]
+struct State {
+    objects: BTreeMap<Path<'static>, Interfaces>,
+    devices: HashMap<Path<'static>, DeviceScript>,
+    agents: BTreeSet<Path<'static>>,
+    tokens: HashMap<&'static str, IfaceToken<()>>,
+}
+
+/// State shared with the D-Bus method handlers.
+struct Shared {
+    connection: Arc<SyncConnection>,
+    state: Mutex<State>,
+}
+
+/// Access to the objects for modification.
+///
+/// Changes are announced using the corresponding D-Bus signals.
+struct Objects<'a> {
+    cr: &'a mut Crossroads,
+    state: &'a mut State,
+    connection: &'a SyncConnection,
+}
+
+impl Objects<'_> {
+    fn contains(&self, path: &Path<'static>, interface: &str) -> bool {
+        self.state.objects.get(path).map(|o| o.contains_key(interface)).unwrap_or_default()
+    }
+
+    fn get<T: Clone + 'static>(&self, path: &Path<'static>, interface: &str, name: &str) -> Option<T> {
+        prop_cast::<T>(self.state.objects.get(path)?.get(interface)?, name).cloned()
+    }
+
+    fn add(&mut self, path: Path<'static>, interfaces: Interfaces) {
+        let mut tokens = vec![self.state.tokens[PROPERTIES_INTERFACE]];
+        tokens.extend(interfaces.keys().filter_map(|name| self.state.tokens.get(name.as_str()).copied()));
+        self.cr.insert(path.clone(), &tokens, ());
+        self.announce(&path, &interfaces);
+        self.state.objects.insert(path, interfaces);
+    }
+
+    /// Adds an interface to an existing object.
+    fn add_interface(&mut self, path: &Path<'static>, interface: &str, props: PropMap) {
+        let Some(interfaces) = self.state.objects.get_mut(path) else { return };
+        interfaces.insert(interface.to_string(), clone_props(&props));
+        self.announce(path, &[(interface.to_string(), props)].into());
+    }
+
+    /// Removes an interface from an object, which itself is kept.
+    fn remove_interface(&mut self, path: &Path<'static>, interface: &str) {
+        let Some(interfaces) = self.state.objects.get_mut(path) else { return };
+        if interfaces.remove(interface).is_none() {
+            return;
+        }
+
+        let msg =
+            ObjectManagerInterfacesRemoved { object: path.clone(), interfaces: vec![interface.to_string()] }
+                .to_emit_message(&Path::from("/"));
+        let _ = self.connection.send(msg);
+    }
+
+    /// Emits the signal for the object being added.
+    fn announce(&self, path: &Path<'static>, interfaces: &Interfaces) {
+        let msg = ObjectManagerInterfacesAdded {
+            object: path.clone(),
+            interfaces: interfaces.iter().map(|(name, props)| (name.clone(), clone_props(props))).collect(),
+        }
+        .to_emit_message(&Path::from("/"));
+        let _ = self.connection.send(msg);
+    }
+
+    /// Removes the object and all its child objects.
+    fn remove(&mut self, path: &Path<'static>) {
+        let prefix = format!("{path}/");
+        let paths: Vec<_> =
+            self.state.objects.keys().filter(|p| *p == path || p.starts_with(&prefix)).cloned().collect();
+        for path in paths.into_iter().rev() {
+            if let Some(interfaces) = self.state.objects.remove(&path) {
+                self.cr.remove::<()>(&path);
+                // Like BlueZ, report the properties interface as removed together with the object.
+                let msg = ObjectManagerInterfacesRemoved {
+                    object: path.clone(),
+                    interfaces: interfaces.into_keys().chain([PROPERTIES_INTERFACE.to_string()]).collect(),
+                }
+                .to_emit_message(&Path::from("/"));
+                let _ = self.connection.send(msg);
+            }
+            self.state.devices.remove(&path);
+        }
+    }
+
+    fn set(&mut self, path: &Path<'static>, interface: &str, changed: PropMap) {
+        let Some(props) = self.state.objects.get_mut(path).and_then(|o| o.get_mut(interface)) else { return };
+        props.extend(clone_props(&changed));
+
+        let msg = PropertiesPropertiesChanged {
+            interface_name: interface.to_string(),
+            changed_properties: changed,
+            invalidated_properties: Vec::new(),
+        }
+        .to_emit_message(path);
+        let _ = self.connection.send(msg);
+    }
+
+    /// Removes properties and announces them as invalidated.
+    fn invalidate(&mut self, path: &Path<'static>, interface: &str, names: &[&str]) {
+        let Some(props) = self.state.objects.get_mut(path).and_then(|o| o.get_mut(interface)) else { return };
+        let invalidated: Vec<_> =
+            names.iter().filter(|name| props.remove(**name).is_some()).map(|name| name.to_string()).collect();
+        if invalidated.is_empty() {
+            return;
+        }
+
+        let msg = PropertiesPropertiesChanged {
+            interface_name: interface.to_string(),
+            changed_properties: PropMap::new(),
+            invalidated_properties: invalidated,
+        }
+        .to_emit_message(path);
+        let _ = self.connection.send(msg);
+    }
+
+    /// Sets the value of a characteristic or descriptor without announcing the change.
+    fn store_value(&mut self, path: &Path<'static>, interface: &str, value: Vec<u8>) {
+        if let Some(props) = self.state.objects.get_mut(path).and_then(|o| o.get_mut(interface)) {
+            props.insert("Value".to_string(), var(value));
+        }
+    }
+
+    fn publish_services(&mut self, device_path: &Path<'static>) {
+        let Some(script) = self.state.devices.get_mut(device_path) else { return };
+        if script.services_published {
+            return;
+        }
+        script.services_published = true;
+        let services = script.services.clone();
+
+        let mut handle = 0u16;
+        for service in services {
+            handle += 1;
+            let service_path = Path::from(format!("{device_path}/service{handle:04x}"));
+            self.add(
+                service_path.clone(),
+                interfaces(
+                    gatt::SERVICE_INTERFACE,
+                    [
+                        ("UUID", var(service.uuid.to_string())),
+                        ("Primary", var(service.primary)),
+                        ("Device", var(device_path.clone())),
+                        ("Includes", var(Vec::<Path<'static>>::new())),
+                    ],
+                ),
+            );
+
+            for characteristic in service.characteristics {
+                handle += 1;
+                let char_path = Path::from(format!("{service_path}/char{handle:04x}"));
+                handle += 1;
+                self.add(
+                    char_path.clone(),
+                    interfaces(
+                        gatt::CHARACTERISTIC_INTERFACE,
+                        [
+                            ("UUID", var(characteristic.uuid.to_string())),
+                            ("Service", var(service_path.clone())),
+                            ("Value", var(characteristic.value)),
+                            ("Notifying", var(false)),
+                            ("Flags", var(characteristic.flags.as_vec())),
+                            ("MTU", var(23u16)),
+                        ],
+                    ),
+                );
+
+                for descriptor in characteristic.descriptors {
+                    handle += 1;
+                    self.add(
+                        Path::from(format!("{char_path}/desc{handle:04x}")),
+                        interfaces(
+                            gatt::DESCRIPTOR_INTERFACE,
+                            [
+                                ("UUID", var(descriptor.uuid.to_string())),
+                                ("Characteristic", var(char_path.clone())),
+                                ("Value", var(descriptor.value)),
+                                ("Flags", var(vec!["read".to_string(), "write".to_string()])),
+                            ],
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Finds the first characteristic with the specified UUID of a device.
+    fn find_characteristic(&self, device_path: &Path<'static>, uuid: Uuid) -> Result<Path<'static>> {
+        let prefix = format!("{device_path}/");
+        let uuid = uuid.to_string();
+        self.state
+            .objects
+            .iter()
+            .find(|(path, interfaces)| {
+                path.starts_with(&prefix)
+                    && interfaces
+                        .get(gatt::CHARACTERISTIC_INTERFACE)
+                        .and_then(|props| prop_cast::<String>(props, "UUID"))
+                        .is_some_and(|u| *u == uuid)
+            })
+            .map(|(path, _)| path.clone())
+            .ok_or_else(|| Error::new(ErrorKind::NotFound))
+    }
+}
+
+/// Mock Bluetooth daemon serving a fake `org.bluez` service on a private D-Bus daemon.
+///
+/// The private D-Bus daemon is terminated when this is dropped.
+pub struct MockBluez {
+    shared: Arc<Shared>,
+    crossroads: Arc<Mutex<Crossroads>>,
+    address: String,
+    dbus_task: JoinHandle<connection::IOResourceError>,
+    _daemon: BusDaemon,
+}
+
+impl fmt::Debug for MockBluez {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MockBluez {{ {} }}", &self.address)
+    }
+}
+
+impl Drop for MockBluez {
+    fn drop(&mut self) {
+        self.dbus_task.abort();
+    }
+}
+
+impl MockBluez {
+    /// Starts a private D-Bus daemon and serves the mock Bluetooth daemon on it.
+    ///
+    /// Initially no adapters are present.
+    pub async fn new() -> Result<Self> {
+        let (daemon, address) = spawn_blocking(BusDaemon::start).await??;
+        log::trace!("Started private D-Bus daemon at {}", &address);
+
+        let (resource, connection) = {
+            let address = address.clone();
+            spawn_blocking(move || {
+                let mut channel = Channel::open_private(&address)?;
+                channel.register()?;
+                connection::from_channel::<SyncConnection>(channel)
+            })
+            .await??
+        };
+        let dbus_task = tokio::spawn(resource);
+        connection.request_name(SERVICE_NAME, false, true, true).await?;
+
+        let shared = Arc::new(Shared { connection: connection.clone(), state: Mutex::new(State::default()) });
+        let mut cr = Crossroads::new();
+        let tokens = register_interfaces(&mut cr, &shared);
+        cr.insert("/", &[tokens[OBJECT_MANAGER_INTERFACE]], ());
+        cr.insert(agent::MANAGER_PATH, &[tokens[agent::MANAGER_INTERFACE]], ());
+        shared.state.lock().unwrap().tokens = tokens;
+
+        let crossroads = Arc::new(Mutex::new(cr));
+        let mc_crossroads = crossroads.clone();
+        connection.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |msg, conn| {
+                let _ = mc_crossroads.lock().unwrap().handle_message(msg, conn);
+                true
+            }),
+        );
+
+        Ok(Self { shared, crossroads, address, dbus_task, _daemon: daemon })
+    }
+
+    /// Address of the private D-Bus daemon.
+    ///
+    /// Pass it to [Session::new_with_address] to connect with custom [SessionOptions].
+    pub fn bus_address(&self) -> &str {
+        &self.address
+    }
+
+    /// Creates a new Bluetooth session connected to the mock Bluetooth daemon.
+    pub async fn session(&self) -> Result<Session> {
+        Session::new_with_address(&self.address, SessionOptions::default()).await
+    }
+
+    fn with_objects<R>(&self, f: impl FnOnce(&mut Objects) -> Result<R>) -> Result<R> {
+        let mut cr = self.crossroads.lock().unwrap();
+        let mut state = self.shared.state.lock().unwrap();
+        f(&mut Objects { cr: &mut cr, state: &mut state, connection: &self.shared.connection })
+    }
+
+    /// Adds an adapter.
+    pub fn add_adapter(&self, adapter: MockAdapter) -> Result<()> {
+        let path = crate::Adapter::dbus_path(&adapter.name)?;
+        self.with_objects(|objs| {
+            if objs.state.objects.contains_key(&path) {
+                return Err(Error::new(ErrorKind::AlreadyExists));
+            }
+            objs.add(
+                path,
+                interfaces(
+                    adapter::INTERFACE,
+                    [
+                        ("Address", var(adapter.address.to_string())),
+                        ("AddressType", var("public".to_string())),
+                        ("Name", var(adapter.name.clone())),
+                        ("Alias", var(adapter.name.clone())),
+                        ("Class", var(0u32)),
+                        ("Powered", var(adapter.powered)),
+                        ("Discoverable", var(false)),
+                        ("Pairable", var(true)),
+                        ("PairableTimeout", var(0u32)),
+                        ("DiscoverableTimeout", var(180u32)),
+                        ("Discovering", var(false)),
+                        ("UUIDs", var(Vec::<String>::new())),
+                        ("Roles", var(vec!["central".to_string(), "peripheral".to_string()])),
+                    ],
+                ),
+            );
+            Ok(())
+        })
+    }
+
+    /// Removes an adapter together with all its devices.
+    pub fn remove_adapter(&self, adapter_name: &str) -> Result<()> {
+        let path = crate::Adapter::dbus_path(adapter_name)?;
+        self.with_objects(|objs| {
+            if !objs.contains(&path, adapter::INTERFACE) {
+                return Err(Error::new(ErrorKind::NotFound));
+            }
+            objs.remove(&path);
+            Ok(())
+        })
+    }
+
+    /// Whether the adapter is discovering devices.
+    pub fn is_discovering(&self, adapter_name: &str) -> Result<bool> {
+        let path = crate::Adapter::dbus_path(adapter_name)?;
+        self.with_objects(|objs| {
+            objs.get(&path, adapter::INTERFACE, "Discovering").ok_or_else(|| Error::new(ErrorKind::NotFound))
+        })
+    }
+
+    /// Adds a remote device to an adapter.
+    pub fn add_device(&self, adapter_name: &str, device: MockDevice) -> Result<()> {
+        let adapter_path = crate::Adapter::dbus_path(adapter_name)?;
+        let path = crate::Device::dbus_path(adapter_name, device.address)?;
+        self.with_objects(|objs| {
+            if !objs.contains(&adapter_path, adapter::INTERFACE) {
+                return Err(Error::new(ErrorKind::NotFound));
+            }
+            if objs.state.objects.contains_key(&path) {
+                return Err(Error::new(ErrorKind::AlreadyExists));
+            }
+
+            let mut props: PropMap = [
+                ("Adapter", var(adapter_path)),
+                ("Address", var(device.address.to_string())),
+                ("AddressType", var(device.address_type.to_string())),
+                (
+                    "Alias",
+                    var(device.name.clone().unwrap_or_else(|| device.address.to_string().replace(':', "-"))),
+                ),
+                ("UUIDs", var(device.service_uuids.iter().map(|uuid| uuid.to_string()).collect::<Vec<_>>())),
+                ("Paired", var(device.paired)),
+                ("Bonded", var(device.paired)),
+                ("Trusted", var(device.trusted)),
+                ("Blocked", var(false)),
+                ("LegacyPairing", var(false)),
+                ("Connected", var(false)),
+                ("ServicesResolved", var(false)),
+            ]
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+            if let Some(name) = device.name {
+                props.insert("Name".to_string(), var(name));
+            }
+            if let Some(class) = device.class {
+                props.insert("Class".to_string(), var(class));
+            }
+            if let Some(appearance) = device.appearance {
+                props.insert("Appearance".to_string(), var(appearance));
+            }
+            if let Some(rssi) = device.rssi {
+                props.insert("RSSI".to_string(), var(rssi));
+            }
+            if let Some(tx_power) = device.tx_power {
+                props.insert("TxPower".to_string(), var(tx_power));
+            }
+            if !device.manufacturer_data.is_empty() {
+                props.insert("ManufacturerData".to_string(), manufacturer_data_var(&device.manufacturer_data));
+            }
+            if !device.service_data.is_empty() {
+                props.insert("ServiceData".to_string(), service_data_var(&device.service_data));
+            }
+
+            objs.state.devices.insert(
+                path.clone(),
+                DeviceScript {
+                    connect_error: device.connect_error,
+                    pairing_error: device.pairing_error,
+                    services: device.services,
+                    services_published: false,
+                },
+            );
+            let mut interfaces: Interfaces = [(device::INTERFACE.to_string(), props)].into();
+            if let Some(percentage) = device.battery_percentage {
+                interfaces.insert(device::BATTERY_INTERFACE.to_string(), battery_props(percentage));
+            }
+            objs.add(path, interfaces);
+            Ok(())
+        })
+    }
+
+    /// Removes a remote device from an adapter.
+    pub fn remove_device(&self, adapter_name: &str, address: Address) -> Result<()> {
+        let path = crate::Device::dbus_path(adapter_name, address)?;
+        self.with_objects(|objs| {
+            if !objs.contains(&path, device::INTERFACE) {
+                return Err(Error::new(ErrorKind::NotFound));
+            }
+            objs.remove(&path);
+            Ok(())
+        })
+    }
+
+    /// Simulates the reception of an advertisement by an adapter.
+    ///
+    /// If the advertising device is not yet known, it is added to the adapter.
+    /// Otherwise its properties are updated from the advertisement.
+    /// Unlike BlueZ, the advertisement is reported even when the adapter is not discovering.
+    pub fn receive_advertisement(&self, adapter_name: &str, advertisement: MockAdvertisement) -> Result<()> {
+        let MockAdvertisement { address, address_type, rssi, data, .. } = advertisement;
+        let path = crate::Device::dbus_path(adapter_name, address)?;
+
+        let exists = self.with_objects(|objs| Ok(objs.contains(&path, device::INTERFACE)))?;
+        if !exists {
+            self.add_device(adapter_name, MockDevice { address, address_type, ..Default::default() })?;
+        }
+
+        self.with_objects(|objs| {
+            let mut changed = PropMap::new();
+            if let Some(rssi) = rssi {
+                changed.insert("RSSI".to_string(), var(rssi));
+            }
+
+            let mut manufacturer_data = BTreeMap::new();
+            let mut service_data = BTreeMap::new();
+            for structure in &data.0 {
+                match structure {
+                    Structure::Flags(flags) => {
+                        changed.insert("AdvertisingFlags".to_string(), var(vec![u8::from(*flags)]));
+                    }
+                    Structure::LocalName { name, .. } => {
+                        changed.insert("Name".to_string(), var(name.clone()));
+                        changed.insert("Alias".to_string(), var(name.clone()));
+                    }
+                    Structure::TxPowerLevel(tx_power) => {
+                        changed.insert("TxPower".to_string(), var(i16::from(*tx_power)));
+                    }
+                    Structure::ClassOfDevice(class) => {
+                        changed.insert("Class".to_string(), var(u32::from(class.clone())));
+                    }
+                    Structure::Appearance(appearance) => {
+                        changed.insert("Appearance".to_string(), var(*appearance));
+                    }
+                    Structure::ServiceData16 { uuid, data } => {
+                        service_data.insert(Uuid::from_u16(*uuid), data.clone());
+                    }
+                    Structure::ServiceData32 { uuid, data } => {
+                        service_data.insert(Uuid::from_u32(*uuid), data.clone());
+                    }
+                    Structure::ServiceData128 { uuid, data } => {
+                        service_data.insert(*uuid, data.clone());
+                    }
+                    Structure::ManufacturerData { company, data } => {
+                        manufacturer_data.insert(*company, data.clone());
+                    }
+                    _ => (),
+                }
+            }
+
+            let service_uuids = data.service_uuids();
+            if !service_uuids.is_empty() {
+                let mut uuids: Vec<String> = objs.get(&path, device::INTERFACE, "UUIDs").unwrap_or_default();
+                for uuid in service_uuids {
+                    let uuid = uuid.to_string();
+                    if !uuids.contains(&uuid) {
+                        uuids.push(uuid);
+                    }
+                }
+                changed.insert("UUIDs".to_string(), var(uuids));
+            }
+            if !manufacturer_data.is_empty() {
+                changed.insert("ManufacturerData".to_string(), manufacturer_data_var(&manufacturer_data));
+            }
+            if !service_data.is_empty() {
+                changed.insert("ServiceData".to_string(), service_data_var(&service_data));
+            }
+
+            objs.set(&path, device::INTERFACE, changed);
+            Ok(())
+        })
+    }
+
+    /// Sets the battery level of a remote device in percent.
+    ///
+    /// If `None`, the battery interface is removed from the device.
+    /// Otherwise it is added, if the device does not have it yet.
+    pub fn set_battery_percentage(
+        &self, adapter_name: &str, address: Address, percentage: Option<u8>,
+    ) -> Result<()> {
+        let path = crate::Device::dbus_path(adapter_name, address)?;
+        self.with_objects(|objs| {
+            if !objs.contains(&path, device::INTERFACE) {
+                return Err(Error::new(ErrorKind::NotFound));
+            }
+            match percentage {
+                Some(percentage) if objs.contains(&path, device::BATTERY_INTERFACE) => {
+                    objs.set(&path, device::BATTERY_INTERFACE, props([("Percentage", var(percentage))]))
+                }
+                Some(percentage) => {
+                    objs.add_interface(&path, device::BATTERY_INTERFACE, battery_props(percentage))
+                }
+                None => objs.remove_interface(&path, device::BATTERY_INTERFACE),
+            }
+            Ok(())
+        })
+    }
+
+    /// Simulates the remote device terminating the connection.
+    pub fn disconnect_device(&self, adapter_name: &str, address: Address) -> Result<()> {
+        let path = crate::Device::dbus_path(adapter_name, address)?;
+        self.with_objects(|objs| {
+            if !objs.contains(&path, device::INTERFACE) {
+                return Err(Error::new(ErrorKind::NotFound));
+            }
+            objs.set(
+                &path,
+                device::INTERFACE,
+                props([("Connected", var(false)), ("ServicesResolved", var(false))]),
+            );
+            Ok(())
+        })
+    }
+
+    /// Sends a notification of a new characteristic value from a remote device.
+    ///
+    /// The value of the first characteristic with the specified UUID is changed.
+    /// The change is only reported to clients when notifications have been started.
+    /// The GATT services of the device must have been resolved by connecting it.
+    pub fn notify(
+        &self, adapter_name: &str, address: Address, characteristic: Uuid, value: Vec<u8>,
+    ) -> Result<()> {
+        let device_path = crate::Device::dbus_path(adapter_name, address)?;
+        self.with_objects(|objs| {
+            let path = objs.find_characteristic(&device_path, characteristic)?;
+            if objs.get(&path, gatt::CHARACTERISTIC_INTERFACE, "Notifying").unwrap_or_default() {
+                objs.set(&path, gatt::CHARACTERISTIC_INTERFACE, props([("Value", var(value))]));
+            } else {
+                objs.store_value(&path, gatt::CHARACTERISTIC_INTERFACE, value);
+            }
+            Ok(())
+        })
+    }
+
+    /// Simulates a restart of the Bluetooth daemon.
+    ///
+    /// The service name is released and acquired again, which sessions observe
+    /// as the daemon stopping and starting.
+    /// All agent registrations are lost.
+    /// Adapters and devices are kept and announced again, like a newly started daemon does.
+    pub async fn restart(&self) -> Result<()> {
+        self.shared.connection.release_name(SERVICE_NAME).await?;
+        self.shared.state.lock().unwrap().agents.clear();
+        self.shared.connection.request_name(SERVICE_NAME, false, true, true).await?;
+        self.with_objects(|objs| {
+            for (path, interfaces) in &objs.state.objects {
+                objs.announce(path, interfaces);
+            }
+            Ok(())
+        })
+    }
+
+    /// Number of registered authorization agents.
+    pub fn registered_agents(&self) -> usize {
+        self.shared.state.lock().unwrap().agents.len()
+    }
+
+    /// The current value of a characteristic of a remote device.
+    ///
+    /// The value of the first characteristic with the specified UUID is returned.
+    /// The GATT services of the device must have been resolved by connecting it.
+    pub fn characteristic_value(
+        &self, adapter_name: &str, address: Address, characteristic: Uuid,
+    ) -> Result<Vec<u8>> {
+        let device_path = crate::Device::dbus_path(adapter_name, address)?;
+        self.with_objects(|objs| {
+            let path = objs.find_characteristic(&device_path, characteristic)?;
+            Ok(objs.get(&path, gatt::CHARACTERISTIC_INTERFACE, "Value").unwrap_or_default())
+        })
+    }
+}
+
+/// Private D-Bus daemon, which is terminated when dropped.
+struct BusDaemon(Child);
+
+impl BusDaemon {
+    /// Starts the daemon and returns its address.
+    fn start() -> std::io::Result<(Self, String)> {
+        let mut child = Command::new("dbus-daemon")
+            .args(["--session", "--nofork", "--print-address", "--address=unix:tmpdir=/tmp"])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()?;
+        let stdout = child.stdout.take().unwrap();
+        let daemon = Self(child);
+
+        let mut address = String::new();
+        BufReader::new(stdout).read_line(&mut address)?;
+        let address = address.trim().to_string();
+        if address.is_empty() {
+            return Err(std::io::Error::other("dbus-daemon did not provide an address"));
+        }
+
+        Ok((daemon, address))
+    }
+}
+
+impl Drop for BusDaemon {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+fn var<T: RefArg + 'static>(value: T) -> Variant<Box<dyn RefArg + 'static>> {
+    Variant(Box::new(value))
+}
+
+fn props<const N: usize>(props: [(&str, Variant<Box<dyn RefArg + 'static>>); N]) -> PropMap {
+    props.into_iter().map(|(name, value)| (name.to_string(), value)).collect()
+}
+
+fn interfaces<const N: usize>(
+    interface: &str, properties: [(&str, Variant<Box<dyn RefArg + 'static>>); N],
+) -> Interfaces {
+    [(interface.to_string(), props(properties))].into()
+}
+
+fn clone_props(props: &PropMap) -> PropMap {
+    props.iter().map(|(name, value)| (name.clone(), Variant(value.0.box_clone()))).collect()
+}
+
+fn manufacturer_data_var(data: &BTreeMap<u16, Vec<u8>>) -> Variant<Box<dyn RefArg + 'static>> {
+    var(data.iter().map(|(id, data)| (*id, var(data.clone()))).collect::<HashMap<_, _>>())
+}
+
+fn service_data_var(data: &BTreeMap<Uuid, Vec<u8>>) -> Variant<Box<dyn RefArg + 'static>> {
+    var(data.iter().map(|(uuid, data)| (uuid.to_string(), var(data.clone()))).collect::<HashMap<_, _>>())
+}
+
+fn battery_props(percentage: u8) -> PropMap {
+    props([("Percentage", var(percentage))])
+}
+
+/// Converts an error kind into the corresponding BlueZ D-Bus error.
+fn method_err(kind: ErrorKind) -> MethodErr {
+    let name = format!("{kind:?}");
+    let name = if ErrorKind::from_str(&name).is_ok() { name } else { "Failed".to_string() };
+    (format!("{ERR_PREFIX}{name}"), kind.to_string()).into()
+}
+
+/// Reads the offset from the options of a read or write request.
+fn offset(options: &PropMap, len: usize) -> std::result::Result<usize, MethodErr> {
+    let offset = prop_cast::<u16>(options, "offset").copied().unwrap_or_default() as usize;
+    if offset > len {
+        return Err(method_err(ErrorKind::InvalidOffset));
+    }
+    Ok(offset)
+}
+
+fn register_interfaces(cr: &mut Crossroads, shared: &Arc<Shared>) -> HashMap<&'static str, IfaceToken<()>> {
+    let mut tokens = HashMap::new();
+
+    // Runs a method handler with access to the objects.
+    fn with<R>(
+        shared: &Shared, cr: &mut Crossroads, f: impl FnOnce(&mut Objects) -> std::result::Result<R, MethodErr>,
+    ) -> std::result::Result<R, MethodErr> {
+        let mut state = shared.state.lock().unwrap();
+        f(&mut Objects { cr, state: &mut state, connection: &shared.connection })
+    }
+
+    let s = shared.clone();
+    tokens.insert(
+        OBJECT_MANAGER_INTERFACE,
+        cr.register(OBJECT_MANAGER_INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            ib.method("GetManagedObjects", (), ("objects",), move |_ctx, _, ()| {
+                let state = s.state.lock().unwrap();
+                let objects: HashMap<Path<'static>, HashMap<String, PropMap>> = state
+                    .objects
+                    .iter()
+                    .map(|(path, interfaces)| {
+                        (
+                            path.clone(),
+                            interfaces.iter().map(|(name, props)| (name.clone(), clone_props(props))).collect(),
+                        )
+                    })
+                    .collect();
+                Ok((objects,))
+            });
+        }),
+    );
+
+    let s = shared.clone();
+    tokens.insert(
+        PROPERTIES_INTERFACE,
+        cr.register(PROPERTIES_INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            let s_get = s.clone();
+            ib.method(
+                "Get",
+                ("interface_name", "property_name"),
+                ("value",),
+                move |ctx, _, (interface, name): (String, String)| {
+                    let state = s_get.state.lock().unwrap();
+                    let value = state
+                        .objects
+                        .get(ctx.path())
+                        .and_then(|o| o.get(&interface))
+                        .and_then(|props| props.get(&name))
+                        .ok_or_else(|| MethodErr::invalid_arg(&name))?;
+                    Ok((Variant(value.0.box_clone()),))
+                },
+            );
+            let s_get_all = s.clone();
+            ib.method("GetAll", ("interface_name",), ("properties",), move |ctx, _, (interface,): (String,)| {
+                let state = s_get_all.state.lock().unwrap();
+                let props = state
+                    .objects
+                    .get(ctx.path())
+                    .and_then(|o| o.get(&interface))
+                    .ok_or_else(|| MethodErr::invalid_arg(&interface))?;
+                Ok((clone_props(props),))
+            });
+            ib.method_with_cr(
+                "Set",
+                ("interface_name", "property_name", "value"),
+                (),
+                move |ctx, cr, (interface, name, value): (String, String, Variant<Box<dyn RefArg + 'static>>)| {
+                    with(&s, cr, |objs| {
+                        let path = ctx.path().clone().into_static();
+                        let exists = objs
+                            .state
+                            .objects
+                            .get(&path)
+                            .and_then(|o| o.get(&interface))
+                            .is_some_and(|props| props.contains_key(&name));
+                        if !exists {
+                            return Err(MethodErr::invalid_arg(&name));
+                        }
+                        objs.set(&path, &interface, [(name, value)].into());
+                        Ok(())
+                    })
+                },
+            );
+        }),
+    );
+
+    let s = shared.clone();
+    tokens.insert(
+        agent::MANAGER_INTERFACE,
+        cr.register(agent::MANAGER_INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            let s_reg = s.clone();
+            ib.method(
+                "RegisterAgent",
+                ("agent", "capability"),
+                (),
+                move |_, _, (agent, _): (Path<'static>, String)| {
+                    if s_reg.state.lock().unwrap().agents.insert(agent) {
+                        Ok(())
+                    } else {
+                        Err(method_err(ErrorKind::AlreadyExists))
+                    }
+                },
+            );
+            ib.method("UnregisterAgent", ("agent",), (), move |_, _, (agent,): (Path<'static>,)| {
+                if s.state.lock().unwrap().agents.remove(&agent) {
+                    Ok(())
+                } else {
+                    Err(method_err(ErrorKind::DoesNotExist))
+                }
+            });
+            ib.method("RequestDefaultAgent", ("agent",), (), |_, _, (_,): (Path<'static>,)| Ok(()));
+        }),
+    );
+
+    let s = shared.clone();
+    tokens.insert(
+        adapter::INTERFACE,
+        cr.register(adapter::INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            let s_start = s.clone();
+            ib.method_with_cr("StartDiscovery", (), (), move |ctx, cr, ()| {
+                with(&s_start, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    if !objs.get::<bool>(&path, adapter::INTERFACE, "Powered").unwrap_or_default() {
+                        return Err(method_err(ErrorKind::NotReady));
+                    }
+                    objs.set(&path, adapter::INTERFACE, props([("Discovering", var(true))]));
+                    Ok(())
+                })
+            });
+            let s_stop = s.clone();
+            ib.method_with_cr("StopDiscovery", (), (), move |ctx, cr, ()| {
+                with(&s_stop, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    if !objs.get::<bool>(&path, adapter::INTERFACE, "Discovering").unwrap_or_default() {
+                        return Err(method_err(ErrorKind::Failed));
+                    }
+                    objs.set(&path, adapter::INTERFACE, props([("Discovering", var(false))]));
+
+                    // Like BlueZ, forget the signal strengths of the devices found.
+                    let prefix = format!("{path}/");
+                    let devices: Vec<_> =
+                        objs.state.objects.keys().filter(|p| p.starts_with(&prefix)).cloned().collect();
+                    for device in devices {
+                        objs.invalidate(&device, device::INTERFACE, &["RSSI", "TxPower"]);
+                    }
+                    Ok(())
+                })
+            });
+            ib.method("SetDiscoveryFilter", ("properties",), (), |_, _, (_,): (PropMap,)| Ok(()));
+            ib.method("GetDiscoveryFilters", (), ("filters",), |_, _, ()| {
+                let filters =
+                    ["UUIDs", "RSSI", "Pathloss", "Transport", "DuplicateData", "Discoverable", "Pattern"];
+                Ok((filters.map(String::from).to_vec(),))
+            });
+            ib.method_with_cr("RemoveDevice", ("device",), (), move |ctx, cr, (device,): (Path<'static>,)| {
+                with(&s, cr, |objs| {
+                    if !device.starts_with(&format!("{}/", ctx.path()))
+                        || !objs.contains(&device, device::INTERFACE)
+                    {
+                        return Err(method_err(ErrorKind::DoesNotExist));
+                    }
+                    objs.remove(&device);
+                    Ok(())
+                })
+            });
+        }),
+    );
+
+    let s = shared.clone();
+    tokens.insert(
+        device::INTERFACE,
+        cr.register(device::INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            let s_connect = s.clone();
+            ib.method_with_cr("Connect", (), (), move |ctx, cr, ()| {
+                with(&s_connect, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    if let Some(kind) = objs.state.devices.get(&path).and_then(|d| d.connect_error.clone()) {
+                        return Err(method_err(kind));
+                    }
+                    objs.set(&path, device::INTERFACE, props([("Connected", var(true))]));
+                    objs.publish_services(&path);
+                    objs.set(&path, device::INTERFACE, props([("ServicesResolved", var(true))]));
+                    Ok(())
+                })
+            });
+            let s_disconnect = s.clone();
+            ib.method_with_cr("Disconnect", (), (), move |ctx, cr, ()| {
+                with(&s_disconnect, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    objs.set(
+                        &path,
+                        device::INTERFACE,
+                        props([("Connected", var(false)), ("ServicesResolved", var(false))]),
+                    );
+                    Ok(())
+                })
+            });
+            ib.method("ConnectProfile", ("uuid",), (), |_, _, (_,): (String,)| Ok(()));
+            ib.method("DisconnectProfile", ("uuid",), (), |_, _, (_,): (String,)| Ok(()));
+            ib.method_with_cr("Pair", (), (), move |ctx, cr, ()| {
+                with(&s, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    if objs.get::<bool>(&path, device::INTERFACE, "Paired").unwrap_or_default() {
+                        return Err(method_err(ErrorKind::AlreadyExists));
+                    }
+                    if let Some(kind) = objs.state.devices.get(&path).and_then(|d| d.pairing_error.clone()) {
+                        return Err(method_err(kind));
+                    }
+                    objs.set(&path, device::INTERFACE, props([("Paired", var(true)), ("Bonded", var(true))]));
+                    Ok(())
+                })
+            });
+            ib.method("CancelPairing", (), (), |_, _, ()| Ok(()));
+        }),
+    );
+
+    tokens.insert(gatt::SERVICE_INTERFACE, cr.register(gatt::SERVICE_INTERFACE, |_: &mut IfaceBuilder<()>| {}));
+
+    let s = shared.clone();
+    tokens.insert(
+        gatt::CHARACTERISTIC_INTERFACE,
+        cr.register(gatt::CHARACTERISTIC_INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            const IFACE: &str = gatt::CHARACTERISTIC_INTERFACE;
+
+            let s_read = s.clone();
+            ib.method_with_cr("ReadValue", ("options",), ("value",), move |ctx, cr, (options,): (PropMap,)| {
+                with(&s_read, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    let flags = CharacteristicFlags::from_slice(
+                        &objs.get::<Vec<String>>(&path, IFACE, "Flags").unwrap_or_default(),
+                    );
+                    if !(flags.read
+                        || flags.encrypt_read
+                        || flags.encrypt_authenticated_read
+                        || flags.secure_read)
+                    {
+                        return Err(method_err(ErrorKind::NotPermitted));
+                    }
+                    let value: Vec<u8> = objs.get(&path, IFACE, "Value").unwrap_or_default();
+                    let offset = offset(&options, value.len())?;
+                    Ok((value[offset..].to_vec(),))
+                })
+            });
+            let s_write = s.clone();
+            ib.method_with_cr(
+                "WriteValue",
+                ("value", "options"),
+                (),
+                move |ctx, cr, (data, options): (Vec<u8>, PropMap)| {
+                    with(&s_write, cr, |objs| {
+                        let path = ctx.path().clone().into_static();
+                        let flags = CharacteristicFlags::from_slice(
+                            &objs.get::<Vec<String>>(&path, IFACE, "Flags").unwrap_or_default(),
+                        );
+                        if !(flags.write
+                            || flags.write_without_response
+                            || flags.encrypt_write
+                            || flags.encrypt_authenticated_write
+                            || flags.secure_write)
+                        {
+                            return Err(method_err(ErrorKind::NotPermitted));
+                        }
+                        let mut value: Vec<u8> = objs.get(&path, IFACE, "Value").unwrap_or_default();
+                        let offset = offset(&options, value.len())?;
+                        value.truncate(offset);
+                        value.extend(data);
+                        objs.store_value(&path, IFACE, value);
+                        Ok(())
+                    })
+                },
+            );
+            let s_start = s.clone();
+            ib.method_with_cr("StartNotify", (), (), move |ctx, cr, ()| {
+                with(&s_start, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    let flags = CharacteristicFlags::from_slice(
+                        &objs.get::<Vec<String>>(&path, IFACE, "Flags").unwrap_or_default(),
+                    );
+                    if !(flags.notify || flags.indicate) {
+                        return Err(method_err(ErrorKind::NotSupported));
+                    }
+                    objs.set(&path, IFACE, props([("Notifying", var(true))]));
+                    Ok(())
+                })
+            });
+            ib.method_with_cr("StopNotify", (), (), move |ctx, cr, ()| {
+                with(&s, cr, |objs| {
+                    let path = ctx.path().clone().into_static();
+                    objs.set(&path, IFACE, props([("Notifying", var(false))]));
+                    Ok(())
+                })
+            });
+        }),
+    );
+
+    let s = shared.clone();
+    tokens.insert(
+        gatt::DESCRIPTOR_INTERFACE,
+        cr.register(gatt::DESCRIPTOR_INTERFACE, |ib: &mut IfaceBuilder<()>| {
+            const IFACE: &str = gatt::DESCRIPTOR_INTERFACE;
+
+            let s_read = s.clone();
+            ib.method_with_cr("ReadValue", ("options",), ("value",), move |ctx, cr, (options,): (PropMap,)| {
+                with(&s_read, cr, |objs| {
+                    let value: Vec<u8> =
+                        objs.get(&ctx.path().clone().into_static(), IFACE, "Value").unwrap_or_default();
+                    let offset = offset(&options, value.len())?;
+                    Ok((value[offset..].to_vec(),))
+                })
+            });
+            ib.method_with_cr(
+                "WriteValue",
+                ("value", "options"),
+                (),
+                move |ctx, cr, (data, options): (Vec<u8>, PropMap)| {
+                    with(&s, cr, |objs| {
+                        let path = ctx.path().clone().into_static();
+                        let mut value: Vec<u8> = objs.get(&path, IFACE, "Value").unwrap_or_default();
+                        let offset = offset(&options, value.len())?;
+                        value.truncate(offset);
+                        value.extend(data);
+                        objs.store_value(&path, IFACE, value);
+                        Ok(())
+                    })
+                },
+            );
+        }),
+    );
+
+    tokens
+}
@@ -0,0 +1,207 @@
+//! Tests of advertising data parsing and serialization.
+
+use bluer::{
+    ad::{AdvertisingData, Flags, InvalidData, Structure},
+    class::{ClassOfDevice, DeviceClass, MajorServiceClass, PhoneClass},
+    Address,
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// LE advertising data containing most supported AD types.
+const LE_ADV: &[u8] = &[
+    // Flags: LE general discoverable, BR/EDR not supported.
+    0x02, 0x01, 0x06, //
+    // Complete list of 16-bit UUIDs: Battery Service, Heart Rate.
+    0x05, 0x03, 0x0f, 0x18, 0x0d, 0x18, //
+    // Incomplete list of 32-bit UUIDs.
+    0x05, 0x04, 0x78, 0x56, 0x34, 0x12, //
+    // Incomplete list of 128-bit UUIDs.
+    0x11, 0x06, 0xf0, 0xde, 0xbc, 0x9a, 0x78, 0x56, 0x34, 0x12, 0x34, 0x12, 0x34, 0x12, 0x78, 0x56, 0x34, 0x12,
+    // Shortened local name.
+    0x04, 0x08, b'B', b'l', b'u', //
+    // TX power level: -12 dBm.
+    0x02, 0x0a, 0xf4, //
+    // Peripheral connection interval range.
+    0x05, 0x12, 0x06, 0x00, 0xff, 0xff, //
+    // List of 16-bit solicitation UUIDs.
+    0x03, 0x14, 0x0a, 0x18, //
+    // Service data of Battery Service: 100 %.
+    0x04, 0x16, 0x0f, 0x18, 0x64, //
+    // Service data with 32-bit UUID.
+    0x06, 0x20, 0x78, 0x56, 0x34, 0x12, 0xaa, //
+    // Public target address.
+    0x07, 0x17, 0x66, 0x55, 0x44, 0x33, 0x22, 0x11, //
+    // Appearance: generic watch.
+    0x03, 0x19, 0xc0, 0x00, //
+    // Advertising interval: 100 ms.
+    0x03, 0x1a, 0xa0, 0x00, //
+    // LE Bluetooth device address, random.
+    0x08, 0x1b, 0x66, 0x55, 0x44, 0x33, 0x22, 0xc1, 0x01, //
+    // LE role: peripheral only.
+    0x02, 0x1c, 0x00, //
+    // URI: https://example.com
+    0x0f, 0x24, 0x17, b'/', b'/', b'e', b'x', b'a', b'm', b'p', b'l', b'e', b'.', b'c', b'o', b'm', //
+    // LE supported features.
+    0x03, 0x27, 0x01, 0x08, //
+    // Long advertising interval.
+    0x04, 0x2f, 0x00, 0x00, 0x01, //
+    // Broadcast name.
+    0x05, 0x30, b'L', b'i', b'v', b'e', //
+    // Mesh beacon, not decoded.
+    0x03, 0x2b, 0x00, 0x01, //
+    // Manufacturer specific data of Apple.
+    0x05, 0xff, 0x4c, 0x00, 0x12, 0x34,
+];
+
+fn le_adv_structures() -> Vec<Structure> {
+    vec![
+        Structure::Flags(Flags {
+            le_general_discoverable: true,
+            br_edr_not_supported: true,
+            ..Default::default()
+        }),
+        Structure::ServiceUuids16 { complete: true, uuids: vec![0x180f, 0x180d] },
+        Structure::ServiceUuids32 { complete: false, uuids: vec![0x12345678] },
+        Structure::ServiceUuids128 {
+            complete: false,
+            uuids: vec!["12345678-1234-1234-1234-56789abcdef0".parse().unwrap()],
+        },
+        Structure::LocalName { complete: false, name: "Blu".to_string() },
+        Structure::TxPowerLevel(-12),
+        Structure::ConnectionIntervalRange { min: 6, max: 0xffff },
+        Structure::SolicitUuids16(vec![0x180a]),
+        Structure::ServiceData16 { uuid: 0x180f, data: vec![100] },
+        Structure::ServiceData32 { uuid: 0x12345678, data: vec![0xaa] },
+        Structure::PublicTargetAddresses(vec![Address::new([0x11, 0x22, 0x33, 0x44, 0x55, 0x66])]),
+        Structure::Appearance(0x00c0),
+        Structure::AdvertisingInterval(160),
+        Structure::LeAddress { address: Address::new([0xc1, 0x22, 0x33, 0x44, 0x55, 0x66]), random: true },
+        Structure::LeRole(0),
+        Structure::Uri("https://example.com".to_string()),
+        Structure::LeSupportedFeatures(vec![0x01, 0x08]),
+        Structure::LongAdvertisingInterval(0x010000),
+        Structure::BroadcastName("Live".to_string()),
+        Structure::Other { ad_type: 0x2b, data: vec![0x00, 0x01] },
+        Structure::ManufacturerData { company: 0x004c, data: vec![0x12, 0x34] },
+    ]
+}
+
+#[test]
+fn parse_le_advertising_data() {
+    let data = AdvertisingData::parse(LE_ADV).unwrap();
+    assert_eq!(data.0, le_adv_structures());
+
+    assert_eq!(data.local_name(), Some("Blu"));
+    assert_eq!(
+        data.service_uuids(),
+        vec![
+            Uuid::from_u128(0x0000180f_0000_1000_8000_00805f9b34fb),
+            Uuid::from_u128(0x0000180d_0000_1000_8000_00805f9b34fb),
+            Uuid::from_u128(0x12345678_0000_1000_8000_00805f9b34fb),
+            Uuid::from_u128(0x12345678_1234_1234_1234_56789abcdef0),
+        ]
+    );
+    assert_eq!(data.0[12].advertising_interval(), Some(Duration::from_millis(100)));
+    assert_eq!(data.0[6].connection_interval_range(), Some((Some(Duration::from_micros(7500)), None)));
+}
+
+#[test]
+fn serialize_le_advertising_data() {
+    let data = AdvertisingData(le_adv_structures());
+    assert_eq!(data.to_bytes().unwrap(), LE_ADV);
+}
+
+#[test]
+fn round_trip_eir() {
+    // Extended inquiry response with class of device, complete local name and zero padding.
+    let mut eir = vec![0x04, 0x0d, 0x0c, 0x02, 0x5a, 0x06, 0x09, b'P', b'h', b'o', b'n', b'e'];
+    let len = eir.len();
+    eir.resize(240, 0);
+
+    let data = AdvertisingData::parse(&eir).unwrap();
+    let class = ClassOfDevice {
+        service_classes: [
+            MajorServiceClass::Networking,
+            MajorServiceClass::Capturing,
+            MajorServiceClass::ObjectTransfer,
+            MajorServiceClass::Telephony,
+        ]
+        .into_iter()
+        .collect(),
+        device_class: DeviceClass::Phone(PhoneClass::Smartphone),
+    };
+    assert_eq!(
+        data.0,
+        vec![Structure::ClassOfDevice(class), Structure::LocalName { complete: true, name: "Phone".to_string() }]
+    );
+    assert_eq!(data.to_bytes().unwrap(), &eir[..len]);
+}
+
+#[test]
+fn round_trip_malformed_structures() {
+    let raw: &[u8] = &[
+        // Flags with reserved bit set.
+        0x02, 0x01, 0x86, //
+        // Flags of wrong length.
+        0x03, 0x01, 0x06, 0x00, //
+        // List of 16-bit UUIDs of odd length.
+        0x04, 0x03, 0x0f, 0x18, 0x0d, //
+        // Local name with invalid UTF-8.
+        0x03, 0x09, 0xff, 0xfe, //
+        // URI with unknown scheme code.
+        0x04, 0x24, 0x02, b'/', b'/', //
+        // URI with https scheme not using scheme code.
+        0x09, 0x24, 0x01, b'h', b't', b't', b'p', b's', b':', b'x', //
+        // Long advertising interval in non-minimal encoding.
+        0x05, 0x2f, 0x01, 0x00, 0x00, 0x00, //
+        // Manufacturer data without company identifier.
+        0x02, 0xff, 0x4c,
+    ];
+
+    let data = AdvertisingData::parse(raw).unwrap();
+    assert_eq!(data.0.len(), 8);
+    assert!(data.0.iter().all(|s| matches!(s, Structure::Other { .. })));
+    assert_eq!(data.to_bytes().unwrap(), raw);
+}
+
+#[test]
+fn round_trip_uri_without_scheme_code() {
+    let data = AdvertisingData(vec![Structure::Uri("mailto:user@example.com".to_string())]);
+    let raw = data.to_bytes().unwrap();
+    assert_eq!(&raw[..3], &[0x19, 0x24, 0x01]);
+    assert_eq!(AdvertisingData::parse(&raw).unwrap(), data);
+}
+
+#[test]
+fn invalid_data() {
+    assert_eq!(AdvertisingData::parse(&[0x02, 0x01, 0x06, 0x05, 0x09, b'a']), Err(InvalidData::Truncated(3)));
+
+    let data = AdvertisingData(vec![Structure::ManufacturerData { company: 0xffff, data: vec![0; 253] }]);
+    assert_eq!(data.to_bytes(), Err(InvalidData::TooLong(0xff)));
+}
+
+#[cfg(feature = "bluetoothd")]
+#[test]
+fn into_advertisement() {
+    use bluer::adv::{Advertisement, Feature};
+
+    let adv: Advertisement = AdvertisingData::parse(LE_ADV).unwrap().into();
+
+    assert_eq!(adv.discoverable, Some(true));
+    assert_eq!(adv.local_name.as_deref(), Some("Blu"));
+    assert_eq!(adv.appearance, Some(0x00c0));
+    assert_eq!(adv.tx_power, Some(-12));
+    assert!(adv.system_includes.contains(&Feature::TxPower));
+    assert_eq!(adv.service_uuids.len(), 4);
+    assert_eq!(
+        adv.solicit_uuids.into_iter().collect::<Vec<_>>(),
+        vec![Uuid::from_u128(0x0000180a_0000_1000_8000_00805f9b34fb)]
+    );
+    assert_eq!(adv.service_data[&Uuid::from_u128(0x0000180f_0000_1000_8000_00805f9b34fb)], vec![100]);
+    assert_eq!(adv.manufacturer_data[&0x004c], vec![0x12, 0x34]);
+    assert_eq!(adv.advertising_data[&0x2b], vec![0x00, 0x01]);
+    assert_eq!(adv.advertising_data[&0x30], b"Live".to_vec());
+    assert!(!adv.advertising_data.contains_key(&0x01));
+    assert!(!adv.advertising_data.contains_key(&0xff));
+}
@@ -0,0 +1,184 @@
+//! Tests of beacon decoding and encoding using byte-level test vectors.
+
+use bluer::{
+    ad::AdvertisingData,
+    beacon::{AltBeacon, Beacon, Eddystone, IBeacon, IBEACON_COMPANY},
+};
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Manufacturer specific data of an iBeacon, excluding the company identifier.
+const IBEACON: &[u8] = &[
+    0x02, 0x15, 0xe2, 0xc5, 0x6d, 0xb5, 0xdf, 0xfb, 0x48, 0xd2, 0xb0, 0x60, 0xd0, 0xf5, 0xa7, 0x10, 0x96, 0xe0,
+    0x00, 0x01, 0x00, 0x02, 0xc5,
+];
+
+/// Service data of an Eddystone UID frame.
+const EDDYSTONE_UID: &[u8] = &[
+    0x00, 0xe7, 0xed, 0xd1, 0xeb, 0xea, 0xc0, 0x4e, 0x5d, 0xef, 0xa0, 0x17, 0x01, 0x23, 0x45, 0x67, 0x89, 0xab,
+    0x00, 0x00,
+];
+
+/// Service data of an Eddystone URL frame for `https://www.google.com/`.
+const EDDYSTONE_URL: &[u8] = &[0x10, 0xf4, 0x01, b'g', b'o', b'o', b'g', b'l', b'e', 0x00];
+
+/// Service data of an Eddystone TLM frame.
+const EDDYSTONE_TLM: &[u8] =
+    &[0x20, 0x00, 0x0b, 0xb8, 0x19, 0x80, 0x00, 0x00, 0x00, 0x0a, 0x00, 0x00, 0x00, 0x64];
+
+/// Service data of an Eddystone EID frame.
+const EDDYSTONE_EID: &[u8] = &[0x30, 0xf4, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88];
+
+/// Manufacturer specific data of an AltBeacon, excluding the company identifier.
+const ALTBEACON: &[u8] = &[
+    0xbe, 0xac, 0x2f, 0x23, 0x44, 0x54, 0xcf, 0x6d, 0x4a, 0x0f, 0xad, 0xf2, 0xf4, 0x91, 0x1b, 0xa9, 0xff, 0xa6,
+    0x00, 0x01, 0x00, 0x02, 0xc5, 0x00,
+];
+
+fn ibeacon() -> IBeacon {
+    IBeacon {
+        uuid: "e2c56db5-dffb-48d2-b060-d0f5a71096e0".parse().unwrap(),
+        major: 1,
+        minor: 2,
+        measured_power: -59,
+    }
+}
+
+fn altbeacon() -> AltBeacon {
+    AltBeacon {
+        manufacturer: 0x0118,
+        id: [
+            0x2f, 0x23, 0x44, 0x54, 0xcf, 0x6d, 0x4a, 0x0f, 0xad, 0xf2, 0xf4, 0x91, 0x1b, 0xa9, 0xff, 0xa6, 0x00,
+            0x01, 0x00, 0x02,
+        ],
+        reference_rssi: -59,
+        manufacturer_reserved: 0,
+    }
+}
+
+fn eddystone_tlm() -> Eddystone {
+    Eddystone::Tlm {
+        battery_voltage: 3000,
+        temperature: 0x1980,
+        advertising_count: 10,
+        uptime: Duration::from_secs(10),
+    }
+}
+
+#[test]
+fn ibeacon_vector() {
+    assert_eq!(IBeacon::from_manufacturer_data(IBEACON), Some(ibeacon()));
+    assert_eq!(ibeacon().to_manufacturer_data(), IBEACON);
+    assert_eq!(Beacon::from_manufacturer_data(IBEACON_COMPANY, IBEACON), Some(Beacon::IBeacon(ibeacon())));
+    assert_eq!(Beacon::from_manufacturer_data(0x0059, IBEACON), None);
+    assert_eq!(IBeacon::from_manufacturer_data(&IBEACON[..22]), None);
+}
+
+#[test]
+fn eddystone_uid_vector() {
+    let uid = Eddystone::Uid {
+        tx_power: -25,
+        namespace: [0xed, 0xd1, 0xeb, 0xea, 0xc0, 0x4e, 0x5d, 0xef, 0xa0, 0x17],
+        instance: [0x01, 0x23, 0x45, 0x67, 0x89, 0xab],
+    };
+    assert_eq!(Eddystone::from_service_data(EDDYSTONE_UID), Some(uid.clone()));
+    assert_eq!(Eddystone::from_service_data(&EDDYSTONE_UID[..18]), Some(uid.clone()));
+    assert_eq!(uid.to_service_data().unwrap(), EDDYSTONE_UID);
+}
+
+#[test]
+fn eddystone_url_vector() {
+    let url = Eddystone::Url { tx_power: -12, url: "https://www.google.com/".to_string() };
+    assert_eq!(Eddystone::from_service_data(EDDYSTONE_URL), Some(url.clone()));
+    assert_eq!(url.to_service_data().unwrap(), EDDYSTONE_URL);
+
+    let url = Eddystone::Url { tx_power: 0, url: "http://example.info/a.html".to_string() };
+    let data = url.to_service_data().unwrap();
+    assert_eq!(data, [&[0x10, 0x00, 0x02][..], b"example", &[0x04], b"a.html"].concat());
+    assert_eq!(Eddystone::from_service_data(&data), Some(url));
+}
+
+#[test]
+fn eddystone_url_invalid() {
+    for url in ["ftp://example.com", "https://example.com/with space", "https://example.com/much/too/long"] {
+        let url = Eddystone::Url { tx_power: 0, url: url.to_string() };
+        assert!(url.to_service_data().is_err(), "{url:?}");
+    }
+
+    // Invalid scheme prefix and reserved character.
+    assert_eq!(Eddystone::from_service_data(&[0x10, 0x00, 0x04, b'a']), None);
+    assert_eq!(Eddystone::from_service_data(&[0x10, 0x00, 0x03, 0x20]), None);
+}
+
+#[test]
+fn eddystone_tlm_vector() {
+    let tlm = eddystone_tlm();
+    assert_eq!(Eddystone::from_service_data(EDDYSTONE_TLM), Some(tlm.clone()));
+    assert_eq!(tlm.to_service_data().unwrap(), EDDYSTONE_TLM);
+    assert_eq!(tlm.temperature(), Some(25.5));
+
+    let mut unsupported = EDDYSTONE_TLM.to_vec();
+    unsupported[4..6].copy_from_slice(&[0x80, 0x00]);
+    assert_eq!(Eddystone::from_service_data(&unsupported).unwrap().temperature(), None);
+
+    // Encrypted telemetry is not supported.
+    let mut encrypted = EDDYSTONE_TLM.to_vec();
+    encrypted[1] = 0x01;
+    assert_eq!(Eddystone::from_service_data(&encrypted), None);
+}
+
+#[test]
+fn eddystone_eid_vector() {
+    let eid = Eddystone::Eid { tx_power: -12, eid: [0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88] };
+    assert_eq!(Eddystone::from_service_data(EDDYSTONE_EID), Some(eid.clone()));
+    assert_eq!(eid.to_service_data().unwrap(), EDDYSTONE_EID);
+    assert_eq!(
+        Beacon::from_service_data(&Uuid::from_u128(0x0000feaa_0000_1000_8000_00805f9b34fb), EDDYSTONE_EID),
+        Some(Beacon::Eddystone(eid))
+    );
+}
+
+#[test]
+fn altbeacon_vector() {
+    assert_eq!(AltBeacon::from_manufacturer_data(0x0118, ALTBEACON), Some(altbeacon()));
+    assert_eq!(altbeacon().to_manufacturer_data(), ALTBEACON);
+    assert_eq!(Beacon::from_manufacturer_data(0x0118, ALTBEACON), Some(Beacon::AltBeacon(altbeacon())));
+}
+
+#[test]
+fn ibeacon_advertising_data() {
+    let raw = [&[0x02, 0x01, 0x06, 0x1a, 0xff, 0x4c, 0x00][..], IBEACON].concat();
+
+    let beacon = Beacon::from(ibeacon());
+    assert_eq!(beacon.to_advertising_data().unwrap().to_bytes().unwrap(), raw);
+    assert_eq!(Beacon::from_advertising_data(&AdvertisingData::parse(&raw).unwrap()), vec![beacon]);
+}
+
+#[test]
+fn eddystone_advertising_data() {
+    let raw = [&[0x02, 0x01, 0x06, 0x03, 0x03, 0xaa, 0xfe, 0x11, 0x16, 0xaa, 0xfe][..], EDDYSTONE_TLM].concat();
+
+    let beacon = Beacon::from(eddystone_tlm());
+    assert_eq!(beacon.to_advertising_data().unwrap().to_bytes().unwrap(), raw);
+    assert_eq!(Beacon::from_advertising_data(&AdvertisingData::parse(&raw).unwrap()), vec![beacon]);
+}
+
+#[cfg(feature = "bluetoothd")]
+#[test]
+fn to_advertisement() {
+    use bluer::adv::Type;
+
+    let adv = Beacon::from(ibeacon()).to_advertisement().unwrap();
+    assert_eq!(adv.advertisement_type, Type::Broadcast);
+    assert_eq!(adv.manufacturer_data[&IBEACON_COMPANY], IBEACON);
+    assert!(adv.service_uuids.is_empty());
+
+    let adv = Beacon::from(eddystone_tlm()).to_advertisement().unwrap();
+    let uuid = Uuid::from_u128(0x0000feaa_0000_1000_8000_00805f9b34fb);
+    assert_eq!(adv.advertisement_type, Type::Broadcast);
+    assert!(adv.service_uuids.contains(&uuid));
+    assert_eq!(adv.service_data[&uuid], EDDYSTONE_TLM);
+
+    let adv = Beacon::from(altbeacon()).to_advertisement().unwrap();
+    assert_eq!(adv.manufacturer_data[&0x0118], ALTBEACON);
+}
@@ -0,0 +1,168 @@
+//! Tests of capture file reading and writing using fixture files.
+
+use bluer::capture::{btsnoop, pcap, ReadRecord, Record, WriteRecord};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+const MONITOR_BTSNOOP: &[u8] = include_bytes!("fixtures/monitor.btsnoop");
+const H4_BTSNOOP: &[u8] = include_bytes!("fixtures/h4.btsnoop");
+const MONITOR_PCAP: &[u8] = include_bytes!("fixtures/monitor.pcap");
+const H4_PCAP: &[u8] = include_bytes!("fixtures/h4.pcap");
+const H4_BE_NS_PCAP: &[u8] = include_bytes!("fixtures/h4_be_ns.pcap");
+
+fn ts(micros: u64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(1_704_067_200) + Duration::from_micros(micros)
+}
+
+fn record(index: u16, opcode: u16, micros: u64, payload: &[u8]) -> Record {
+    Record { index, opcode, timestamp: ts(micros), payload: payload.to_vec() }
+}
+
+/// HCI packets contained in all fixtures.
+fn hci_records() -> Vec<Record> {
+    vec![
+        // HCI_Reset command.
+        record(0, 2, 0, &[0x03, 0x0c, 0x00]),
+        // Command Complete event for HCI_Reset.
+        record(0, 3, 1500, &[0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00]),
+        // Received ACL data.
+        record(0, 5, 250_000, &[0x01, 0x00, 0x07, 0x00, 0x03, 0x00, 0x04, 0x00, 0xde, 0xad, 0xbe, 0xef]),
+    ]
+}
+
+/// Records contained in the monitor fixtures.
+fn monitor_records() -> Vec<Record> {
+    let mut records = hci_records();
+    // System note.
+    records.push(record(0xffff, 12, 250_001, b"capture\0"));
+    records
+}
+
+fn read_all(reader: impl Iterator<Item = std::io::Result<Record>>) -> Vec<Record> {
+    reader.collect::<std::io::Result<_>>().unwrap()
+}
+
+fn write_all(writer: &mut impl WriteRecord, records: &[Record]) {
+    for record in records {
+        writer.write_record(record).unwrap();
+    }
+    writer.flush().unwrap();
+}
+
+#[test]
+fn btsnoop_monitor() {
+    let reader = btsnoop::Reader::new(MONITOR_BTSNOOP).unwrap();
+    assert_eq!(reader.datalink(), btsnoop::Datalink::Monitor);
+    let records = read_all(reader);
+    assert_eq!(records, monitor_records());
+
+    let mut writer = btsnoop::Writer::new(Vec::new(), btsnoop::Datalink::Monitor).unwrap();
+    write_all(&mut writer, &records);
+    assert_eq!(writer.into_inner(), MONITOR_BTSNOOP);
+}
+
+#[test]
+fn btsnoop_h4() {
+    let reader = btsnoop::Reader::new(H4_BTSNOOP).unwrap();
+    assert_eq!(reader.datalink(), btsnoop::Datalink::H4);
+    let records = read_all(reader);
+    assert_eq!(records, hci_records());
+
+    let mut writer = btsnoop::Writer::new(Vec::new(), btsnoop::Datalink::H4).unwrap();
+    write_all(&mut writer, &records);
+    assert_eq!(writer.into_inner(), H4_BTSNOOP);
+}
+
+#[test]
+fn pcap_monitor() {
+    let reader = pcap::Reader::new(MONITOR_PCAP).unwrap();
+    assert_eq!(reader.link_type(), pcap::LinkType::BluetoothLinuxMonitor);
+    assert_eq!(reader.snaplen(), pcap::DEFAULT_SNAPLEN);
+    let records = read_all(reader);
+    assert_eq!(records, monitor_records());
+
+    let mut writer = pcap::Writer::new(Vec::new(), pcap::LinkType::BluetoothLinuxMonitor).unwrap();
+    write_all(&mut writer, &records);
+    assert_eq!(writer.into_inner(), MONITOR_PCAP);
+}
+
+#[test]
+fn pcap_h4() {
+    let reader = pcap::Reader::new(H4_PCAP).unwrap();
+    assert_eq!(reader.link_type(), pcap::LinkType::BluetoothHciH4WithPhdr);
+    let records = read_all(reader);
+    assert_eq!(records, hci_records());
+
+    let mut writer = pcap::Writer::new(Vec::new(), pcap::LinkType::BluetoothHciH4WithPhdr).unwrap();
+    write_all(&mut writer, &records);
+    assert_eq!(writer.into_inner(), H4_PCAP);
+}
+
+#[test]
+fn pcap_big_endian_nanos() {
+    let reader = pcap::Reader::new(H4_BE_NS_PCAP).unwrap();
+    assert_eq!(reader.link_type(), pcap::LinkType::BluetoothHciH4WithPhdr);
+    assert_eq!(read_all(reader), hci_records());
+}
+
+#[test]
+fn convert_btsnoop_to_pcap() {
+    let mut reader = btsnoop::Reader::new(MONITOR_BTSNOOP).unwrap();
+    let mut writer = pcap::Writer::new(Vec::new(), pcap::LinkType::BluetoothHciH4WithPhdr).unwrap();
+
+    let mut written = 0;
+    while let Some(record) = reader.read_record().unwrap() {
+        if writer.write_record(&record).unwrap() {
+            written += 1;
+        }
+    }
+
+    // The system note cannot be represented in H4 format.
+    assert_eq!(written, 3);
+    assert_eq!(writer.into_inner(), H4_PCAP);
+}
+
+#[test]
+fn invalid_header() {
+    assert!(btsnoop::Reader::new(MONITOR_PCAP).is_err());
+    assert!(pcap::Reader::new(MONITOR_BTSNOOP).is_err());
+}
+
+#[test]
+fn truncated_record() {
+    let reader = btsnoop::Reader::new(&MONITOR_BTSNOOP[..MONITOR_BTSNOOP.len() - 1]).unwrap();
+    let records: Vec<_> = reader.collect();
+    assert_eq!(records.len(), 4);
+    assert!(records[3].is_err());
+}
+
+#[test]
+fn oversized_record() {
+    let mut data = MONITOR_BTSNOOP[..16].to_vec();
+    data.extend_from_slice(&[0xff; 8]);
+    data.extend_from_slice(&[0; 16]);
+    let records: Vec<_> = btsnoop::Reader::new(&data[..]).unwrap().collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].as_ref().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+
+    let mut data = MONITOR_PCAP[..24].to_vec();
+    data.extend_from_slice(&[0; 8]);
+    data.extend_from_slice(&[0xff; 8]);
+    let records: Vec<_> = pcap::Reader::new(&data[..]).unwrap().collect();
+    assert_eq!(records.len(), 1);
+    assert_eq!(records[0].as_ref().unwrap_err().kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn replay() {
+    let rt = tokio::runtime::Builder::new_current_thread().enable_time().build().unwrap();
+    let records = rt.block_on(async {
+        let reader = pcap::Reader::new(MONITOR_PCAP).unwrap();
+        let mut replay = bluer::capture::Replay::new(reader, f64::INFINITY);
+        let mut records = Vec::new();
+        while let Some(record) = replay.next().await.unwrap() {
+            records.push(record);
+        }
+        records
+    });
+    assert_eq!(records, monitor_records());
+}
@@ -0,0 +1,112 @@
+//! Tests of Class of Device decoding and encoding.
+
+use bluer::class::{
+    AudioVideoClass, ClassOfDevice, ComputerClass, DeviceClass, ImagingClass, MajorDeviceClass,
+    MajorServiceClass, PeripheralClass, PeripheralKind, PhoneClass,
+};
+
+fn round_trip(value: u32, class: ClassOfDevice) {
+    assert_eq!(ClassOfDevice::from(value), class);
+    assert_eq!(u32::from(class), value);
+}
+
+#[test]
+fn headset() {
+    round_trip(
+        0x240404,
+        ClassOfDevice {
+            service_classes: [MajorServiceClass::Rendering, MajorServiceClass::Audio].into(),
+            device_class: DeviceClass::AudioVideo(AudioVideoClass::WearableHeadset),
+        },
+    );
+}
+
+#[test]
+fn smartphone() {
+    let class = ClassOfDevice {
+        service_classes: [
+            MajorServiceClass::Networking,
+            MajorServiceClass::Capturing,
+            MajorServiceClass::ObjectTransfer,
+            MajorServiceClass::Audio,
+            MajorServiceClass::Telephony,
+        ]
+        .into(),
+        device_class: DeviceClass::Phone(PhoneClass::Smartphone),
+    };
+    round_trip(0x7a020c, class.clone());
+    assert_eq!(class.device_class.major(), MajorDeviceClass::Phone);
+    assert_eq!(class.device_class.minor(), 0x03);
+}
+
+#[test]
+fn service_class_bits() {
+    for (sc, bit) in [
+        (MajorServiceClass::LimitedDiscoverableMode, 13),
+        (MajorServiceClass::LeAudio, 14),
+        (MajorServiceClass::Positioning, 16),
+        (MajorServiceClass::Information, 23),
+    ] {
+        round_trip(
+            1 << bit,
+            ClassOfDevice { service_classes: [sc].into(), device_class: DeviceClass::Miscellaneous(0) },
+        );
+    }
+}
+
+#[test]
+fn peripheral() {
+    let keyboard = PeripheralClass { keyboard: true, ..Default::default() };
+    round_trip(
+        0x002540,
+        ClassOfDevice {
+            service_classes: [MajorServiceClass::LimitedDiscoverableMode].into(),
+            device_class: DeviceClass::Peripheral(keyboard),
+        },
+    );
+
+    let mouse = PeripheralClass { pointing_device: true, ..Default::default() };
+    round_trip(0x000580, ClassOfDevice { device_class: DeviceClass::Peripheral(mouse), ..Default::default() });
+
+    let combo = PeripheralClass { keyboard: true, pointing_device: true, kind: PeripheralKind::Uncategorized };
+    round_trip(0x0005c0, ClassOfDevice { device_class: DeviceClass::Peripheral(combo), ..Default::default() });
+    assert_eq!(combo.to_string(), "Combo Keyboard/Pointing Device");
+
+    let gamepad = PeripheralClass { kind: PeripheralKind::Gamepad, ..Default::default() };
+    round_trip(0x000508, ClassOfDevice { device_class: DeviceClass::Peripheral(gamepad), ..Default::default() });
+
+    assert_eq!(u8::from(PeripheralClass::from(0x3f)), 0x3f);
+    assert_eq!(PeripheralClass::from(0x3f).kind, PeripheralKind::Other(0x0f));
+}
+
+#[test]
+fn imaging() {
+    let printer = ImagingClass { printer: true, scanner: true, ..Default::default() };
+    round_trip(0x0006c0, ClassOfDevice { device_class: DeviceClass::Imaging(printer), ..Default::default() });
+    assert_eq!(printer.to_string(), "Scanner/Printer");
+}
+
+#[test]
+fn reserved_values() {
+    round_trip(
+        0x000a08,
+        ClassOfDevice { device_class: DeviceClass::Other { major: 0x0a, minor: 0x02 }, ..Default::default() },
+    );
+    round_trip(
+        0x0001fc,
+        ClassOfDevice { device_class: DeviceClass::Computer(ComputerClass::Other(0x3f)), ..Default::default() },
+    );
+    round_trip(0x001f00, ClassOfDevice::default());
+
+    // Format type bits and bits outside the 24-bit value are ignored when decoding.
+    assert_eq!(
+        ClassOfDevice::from(0xff00_0003),
+        ClassOfDevice { device_class: DeviceClass::Miscellaneous(0), ..Default::default() }
+    );
+}
+
+#[test]
+fn display() {
+    assert_eq!(ClassOfDevice::from(0x240404).to_string(), "Audio/Video: Wearable Headset (Rendering, Audio)");
+    assert_eq!(ClassOfDevice::from(0x000508).to_string(), "Peripheral: Gamepad");
+}
@@ -0,0 +1,115 @@
+//! Tests of typed GATT characteristic values using byte-level test vectors.
+
+use bluer::gatt::value::{
+    AssignedCharacteristic, BatteryLevel, BloodPressureMeasurement, CrankRevolutions, CscMeasurement, DateTime,
+    Decode, DeviceName, Encode, Float, HeartRateMeasurement, SFloat, TemperatureMeasurement, WheelRevolutions,
+};
+use uuid::Uuid;
+
+#[test]
+fn sfloat() {
+    // 120.0 mmHg as 1200 * 10^-1.
+    let value = SFloat::decode(&[0xb0, 0xf4]).unwrap();
+    assert_eq!(value, SFloat::new(1200, -1));
+    assert_eq!(value.to_f64(), 120.0);
+    assert_eq!(value.encode(), [0xb0, 0xf4]);
+
+    assert_eq!(SFloat::decode(&[0xff, 0x0f]).unwrap(), SFloat::new(-1, 0));
+    assert_eq!(SFloat::from_f64(36.6), SFloat::new(366, -1));
+    assert_eq!(SFloat::from_f64(-0.5), SFloat::new(-500, -3));
+    assert_eq!(SFloat::from_f64(1e12), SFloat::INFINITY);
+    assert_eq!(SFloat::from_f64(f64::NAN), SFloat::NAN);
+    assert_eq!(SFloat::from_f64(0.0), SFloat::new(0, 0));
+
+    assert_eq!(SFloat::decode(&[0xff, 0x07]).unwrap(), SFloat::NAN);
+    assert!(SFloat::NAN.to_f64().is_nan());
+    assert!(SFloat::NRES.to_f64().is_nan());
+    assert_eq!(SFloat::NEG_INFINITY.encode(), [0x02, 0x08]);
+    assert_eq!(SFloat::NEG_INFINITY.to_f64(), f64::NEG_INFINITY);
+
+    assert!(SFloat::decode(&[0xb0]).is_err());
+}
+
+#[test]
+fn float() {
+    // 36.4 degrees as 364 * 10^-1.
+    let value = Float::decode(&[0x6c, 0x01, 0x00, 0xff]).unwrap();
+    assert_eq!(value, Float::new(364, -1));
+    assert_eq!(value.to_f64(), 36.4);
+    assert_eq!(value.encode(), [0x6c, 0x01, 0x00, 0xff]);
+
+    assert_eq!(Float::decode(&[0xfe, 0xff, 0xff, 0x02]).unwrap(), Float::new(-2, 2));
+    assert_eq!(Float::from_f64(-200.0).to_f64(), -200.0);
+    assert_eq!(Float::from_f64(f64::INFINITY), Float::INFINITY);
+    assert_eq!(Float::INFINITY.encode(), [0xfe, 0xff, 0x7f, 0x00]);
+    assert!(Float::NRES.to_f64().is_nan());
+}
+
+#[test]
+fn heart_rate_measurement() {
+    // 8-bit heart rate without optional fields.
+    let hr = HeartRateMeasurement { heart_rate: 72, ..Default::default() };
+    assert_eq!(HeartRateMeasurement::decode(&[0x00, 0x48]).unwrap(), hr);
+    assert_eq!(hr.encode(), [0x00, 0x48]);
+
+    // 16-bit heart rate with contact, energy expended and RR intervals.
+    let raw = [0x1f, 0x2c, 0x01, 0x10, 0x00, 0x00, 0x04, 0x80, 0x03];
+    let hr = HeartRateMeasurement {
+        heart_rate: 300,
+        sensor_contact: Some(true),
+        energy_expended: Some(16),
+        rr_intervals: vec![1024, 896],
+    };
+    assert_eq!(HeartRateMeasurement::decode(&raw).unwrap(), hr);
+    assert_eq!(hr.encode(), raw);
+
+    assert!(HeartRateMeasurement::decode(&[0x01, 0x48]).is_err());
+    assert_eq!(HeartRateMeasurement::UUID, Uuid::from_u128(0x00002a37_0000_1000_8000_00805f9b34fb));
+}
+
+#[test]
+fn temperature_measurement() {
+    let raw = [0x06, 0x6c, 0x01, 0x00, 0xff, 0xea, 0x07, 0x0a, 0x12, 0x08, 0x1e, 0x00, 0x02];
+    let measurement = TemperatureMeasurement {
+        temperature: Float::new(364, -1),
+        fahrenheit: false,
+        timestamp: Some(DateTime { year: 2026, month: 10, day: 18, hours: 8, minutes: 30, seconds: 0 }),
+        temperature_type: Some(2),
+    };
+    assert_eq!(TemperatureMeasurement::decode(&raw).unwrap(), measurement);
+    assert_eq!(measurement.encode(), raw);
+}
+
+#[test]
+fn blood_pressure_measurement() {
+    let raw = [0x04, 0x78, 0x00, 0x50, 0x00, 0x5d, 0x00, 0x48, 0x00];
+    let measurement = BloodPressureMeasurement {
+        systolic: SFloat::new(120, 0),
+        diastolic: SFloat::new(80, 0),
+        mean_arterial_pressure: SFloat::new(93, 0),
+        pulse_rate: Some(SFloat::new(72, 0)),
+        ..Default::default()
+    };
+    assert_eq!(BloodPressureMeasurement::decode(&raw).unwrap(), measurement);
+    assert_eq!(measurement.encode(), raw);
+}
+
+#[test]
+fn csc_measurement() {
+    let raw = [0x03, 0x10, 0x27, 0x00, 0x00, 0x00, 0x04, 0x64, 0x00, 0x00, 0x08];
+    let measurement = CscMeasurement {
+        wheel: Some(WheelRevolutions { cumulative_revolutions: 10000, last_event_time: 1024 }),
+        crank: Some(CrankRevolutions { cumulative_revolutions: 100, last_event_time: 2048 }),
+    };
+    assert_eq!(CscMeasurement::decode(&raw).unwrap(), measurement);
+    assert_eq!(measurement.encode(), raw);
+}
+
+#[test]
+fn simple_values() {
+    assert_eq!(BatteryLevel::decode(&[0x64]).unwrap(), BatteryLevel(100));
+    assert_eq!(DeviceName::decode(b"Sensor\0\0").unwrap(), DeviceName("Sensor".to_string()));
+    assert_eq!(DeviceName("Sensor".to_string()).encode(), b"Sensor");
+    assert!(DeviceName::decode(&[0xff]).is_err());
+    assert!(BatteryLevel::decode(&[]).is_err());
+}
@@ -0,0 +1,145 @@
+//! Tests of HCI packet and monitor record encoding and decoding.
+
+use bluer::hci::{
+    AclData, AclHeader, Command, CommandHeader, Event, EventHeader, MonitorOpcode, MonitorRecord, Opcode, Packet,
+    PacketType, SocketAddr,
+};
+use std::io::ErrorKind;
+
+#[test]
+fn opcode() {
+    // HCI_Reset
+    let opcode = Opcode::from(0x0c03);
+    assert_eq!(opcode, Opcode::new(0x03, 0x003));
+    assert_eq!(u16::from(opcode), 0x0c03);
+    assert_eq!(opcode.to_string(), "0x03|0x0003");
+
+    // LE Set Scan Enable
+    assert_eq!(u16::from(Opcode::new(0x08, 0x000c)), 0x200c);
+    assert_eq!(u16::from(Opcode::new(0xff, 0xffff)), 0xffff);
+}
+
+#[test]
+fn headers() {
+    let hdr = CommandHeader::decode(&[0x03, 0x0c, 0x00]).unwrap();
+    assert_eq!(hdr, CommandHeader { opcode: Opcode::new(0x03, 0x003), param_len: 0 });
+    assert_eq!(hdr.encode(), [0x03, 0x0c, 0x00]);
+
+    let hdr = EventHeader::decode(&[0x0e, 0x04, 0xff]).unwrap();
+    assert_eq!(hdr, EventHeader { code: 0x0e, param_len: 4 });
+    assert_eq!(hdr.encode(), [0x0e, 0x04]);
+
+    let hdr = AclHeader::decode(&[0x40, 0x20, 0x05, 0x00]).unwrap();
+    assert_eq!(hdr, AclHeader { handle: 0x040, packet_boundary: 2, broadcast: 0, data_len: 5 });
+    assert_eq!(hdr.encode(), [0x40, 0x20, 0x05, 0x00]);
+
+    let hdr = AclHeader { handle: 0xfff, packet_boundary: 1, broadcast: 3, data_len: 0x1234 };
+    assert_eq!(AclHeader::decode(&hdr.encode()).unwrap(), hdr);
+}
+
+#[test]
+fn packets() {
+    let cmd = Command { opcode: Opcode::new(0x08, 0x000c), params: vec![0x01, 0x00] };
+    let buf = cmd.encode().unwrap();
+    assert_eq!(buf, [0x0c, 0x20, 0x02, 0x01, 0x00]);
+    assert_eq!(Command::decode(&buf).unwrap(), cmd);
+
+    // Command Complete for HCI_Reset with status success.
+    let evt = Event::decode(&[0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00]).unwrap();
+    assert_eq!(evt, Event { code: Event::COMMAND_COMPLETE, params: vec![0x01, 0x03, 0x0c, 0x00] });
+    assert_eq!(evt.command_opcode(), Some(Opcode::new(0x03, 0x003)));
+
+    // Command Status for LE Create Connection.
+    let evt = Event { code: Event::COMMAND_STATUS, params: vec![0x00, 0x01, 0x0d, 0x20] };
+    assert_eq!(evt.command_opcode(), Some(Opcode::new(0x08, 0x000d)));
+    assert_eq!(Event { code: Event::LE_META, params: vec![0x02] }.command_opcode(), None);
+    assert_eq!(Event { code: Event::COMMAND_COMPLETE, params: vec![0x01] }.command_opcode(), None);
+
+    let acl = AclData { handle: 0x001, packet_boundary: 2, broadcast: 0, data: vec![1, 2, 3] };
+    let buf = Packet::AclData(acl.clone()).encode_h4().unwrap();
+    assert_eq!(buf, [0x02, 0x01, 0x20, 0x03, 0x00, 1, 2, 3]);
+    assert_eq!(Packet::decode_h4(&buf).unwrap(), Packet::AclData(acl));
+
+    let iso = Packet::decode_h4(&[0x05, 0xaa, 0xbb]).unwrap();
+    assert_eq!(iso, Packet::Other { ty: PacketType::IsoData, data: vec![0xaa, 0xbb] });
+    assert_eq!(iso.encode_h4().unwrap(), [0x05, 0xaa, 0xbb]);
+}
+
+fn invalid<T: std::fmt::Debug>(res: std::io::Result<T>) {
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn truncated_packets() {
+    invalid(CommandHeader::decode(&[0x03, 0x0c]));
+    invalid(EventHeader::decode(&[0x0e]));
+    invalid(AclHeader::decode(&[0x01, 0x20, 0x03]));
+    invalid(Command::decode(&[0x03, 0x0c, 0x02, 0x00]));
+    invalid(Event::decode(&[0x0e, 0x04, 0x01, 0x03, 0x0c]));
+    invalid(AclData::decode(&[0x01, 0x20, 0x03, 0x00, 1, 2]));
+    invalid(Packet::decode_h4(&[]));
+    invalid(Packet::decode_h4(&[0x00, 0x03, 0x0c, 0x00]));
+    invalid(Packet::decode_h4(&[0x04, 0x0e]));
+
+    assert!(Command { opcode: Opcode::default(), params: vec![0; 256] }.encode().is_err());
+    assert!(Event { code: 0x3e, params: vec![0; 256] }.encode().is_err());
+}
+
+#[test]
+fn monitor_opcodes() {
+    for value in 0..=20 {
+        assert_eq!(u16::from(MonitorOpcode::from(value)), value);
+    }
+    assert_eq!(MonitorOpcode::from(0x1234), MonitorOpcode::Unknown(0x1234));
+
+    assert_eq!(MonitorOpcode::from(2), MonitorOpcode::Command);
+    assert_eq!(MonitorOpcode::Command.packet_type(), Some(PacketType::Command));
+    assert!(MonitorOpcode::Command.is_sent());
+    assert_eq!(MonitorOpcode::from(3), MonitorOpcode::Event);
+    assert!(!MonitorOpcode::Event.is_sent());
+    assert_eq!(MonitorOpcode::AclRx.packet_type(), Some(PacketType::AclData));
+    assert_eq!(MonitorOpcode::IsoTx.packet_type(), Some(PacketType::IsoData));
+    assert_eq!(MonitorOpcode::NewIndex.packet_type(), None);
+}
+
+#[test]
+fn monitor_records() {
+    // Command Complete event of hci0 as delivered by the monitor channel.
+    let buf = [0x03, 0x00, 0x00, 0x00, 0x06, 0x00, 0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00];
+    let record = MonitorRecord::decode(&buf).unwrap();
+    assert_eq!(record.index, 0);
+    assert_eq!(record.opcode, MonitorOpcode::Event);
+    assert_eq!(record.timestamp, None);
+    assert_eq!(record.payload, buf[MonitorRecord::HEADER_SIZE..]);
+    assert_eq!(record.encode().unwrap(), buf);
+    match record.packet() {
+        Some(Ok(Packet::Event(evt))) => assert_eq!(evt.command_opcode(), Some(Opcode::new(0x03, 0x003))),
+        other => panic!("unexpected packet {other:?}"),
+    }
+
+    // Trailing data after the payload is ignored.
+    let mut long = buf.to_vec();
+    long.push(0xff);
+    assert_eq!(MonitorRecord::decode(&long).unwrap(), record);
+
+    let note = MonitorRecord {
+        index: SocketAddr::INDEX_NONE,
+        opcode: MonitorOpcode::SystemNote,
+        timestamp: None,
+        payload: b"note\0".to_vec(),
+    };
+    assert_eq!(MonitorRecord::decode(&note.encode().unwrap()).unwrap(), note);
+    assert!(note.packet().is_none());
+
+    // A record whose payload is not a valid packet of its type.
+    let bad = MonitorRecord { index: 0, opcode: MonitorOpcode::AclTx, timestamp: None, payload: vec![0x01] };
+    assert!(matches!(bad.packet(), Some(Err(_))));
+}
+
+#[test]
+fn truncated_monitor_records() {
+    let buf = [0x03, 0x00, 0x00, 0x00, 0x06, 0x00, 0x0e, 0x04, 0x01, 0x03, 0x0c, 0x00];
+    for len in 0..buf.len() {
+        invalid(MonitorRecord::decode(&buf[..len]));
+    }
+}
@@ -0,0 +1,46 @@
+//! Tests of the assigned appearance values and categories.
+
+use bluer::id::{Appearance, AppearanceCategory};
+
+#[test]
+fn appearances() {
+    let mut count = 0;
+    for value in 0..=u16::MAX {
+        let Ok(appearance) = Appearance::try_from(value) else { continue };
+        count += 1;
+
+        assert_eq!(u16::from(appearance), value);
+        assert_eq!(appearance.to_string().parse::<Appearance>().unwrap(), appearance);
+        assert_eq!(appearance.subcategory(), (value & 0x3f) as u8);
+
+        let category = appearance.category().unwrap_or_else(|| panic!("{appearance:?} has no category"));
+        assert_eq!(u16::from(category), value >> 6);
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn categories() {
+    let mut count = 0;
+    for value in 0..=u16::MAX {
+        let Ok(category) = AppearanceCategory::try_from(value) else { continue };
+        count += 1;
+
+        assert_eq!(u16::from(category), value);
+        assert_eq!(category.to_string().parse::<AppearanceCategory>().unwrap(), category);
+
+        let generic = Appearance::from(category);
+        assert_eq!(u16::from(generic), value << 6);
+        assert_eq!(generic.subcategory(), 0);
+        assert_eq!(generic.category(), Some(category));
+    }
+    assert!(count > 0);
+}
+
+#[test]
+fn known_values() {
+    assert_eq!(u16::from(Appearance::Unknown), 0x0000);
+    assert_eq!(Appearance::try_from(0x03c1).unwrap().category(), Some(AppearanceCategory::HumanInterfaceDevice));
+    assert_eq!(Appearance::try_from(0x03c1).unwrap().subcategory(), 1);
+    assert_eq!(Appearance::try_from(0x003f), Err(0x003f));
+}
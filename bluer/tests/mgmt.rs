@@ -0,0 +1,247 @@
+//! Tests of management command, reply and event encoding and decoding.
+
+use bluer::{
+    mgmt::{settings, Command, ControllerInfo, Event, LinkKey, LongTermKey, Reply, Status, Version, INDEX_NONE},
+    Address, AddressType,
+};
+use std::io::ErrorKind;
+
+/// Address 11:22:33:44:55:66 in little-endian byte order as sent by the kernel.
+const ADDR: [u8; 6] = [0x66, 0x55, 0x44, 0x33, 0x22, 0x11];
+
+fn address() -> Address {
+    Address::new([0x11, 0x22, 0x33, 0x44, 0x55, 0x66])
+}
+
+fn packet(code: u16, index: u16, params: &[u8]) -> Vec<u8> {
+    let mut buf = Vec::new();
+    buf.extend_from_slice(&code.to_le_bytes());
+    buf.extend_from_slice(&index.to_le_bytes());
+    buf.extend_from_slice(&(params.len() as u16).to_le_bytes());
+    buf.extend_from_slice(params);
+    buf
+}
+
+fn invalid<T: std::fmt::Debug>(res: std::io::Result<T>) {
+    assert_eq!(res.unwrap_err().kind(), ErrorKind::InvalidData);
+}
+
+#[test]
+fn commands() {
+    // Set Powered on hci0.
+    let cmd = Command { index: 0, opcode: 0x0005, params: vec![0x01] };
+    let buf = cmd.encode().unwrap();
+    assert_eq!(buf, [0x05, 0x00, 0x00, 0x00, 0x01, 0x00, 0x01]);
+    assert_eq!(Command::decode(&buf).unwrap(), cmd);
+
+    // Read Version is not related to a controller.
+    let cmd = Command { index: INDEX_NONE, opcode: 0x0001, params: vec![] };
+    assert_eq!(cmd.encode().unwrap(), [0x01, 0x00, 0xff, 0xff, 0x00, 0x00]);
+
+    let err = Command { params: vec![0; 0x10000], ..Default::default() }.encode().unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidInput);
+}
+
+#[test]
+fn replies() {
+    // Command Complete for Read Version.
+    let buf = packet(0x0001, INDEX_NONE, &[0x01, 0x00, 0x00, 0x01, 0x16, 0x00]);
+    let reply = Reply::decode(&buf).unwrap();
+    assert_eq!(
+        reply,
+        Reply { index: INDEX_NONE, opcode: 0x0001, status: Status::Success, params: vec![0x01, 0x16, 0x00] }
+    );
+    assert_eq!(Version::decode(&reply.params).unwrap(), Version { version: 1, revision: 22 });
+
+    // Command Status for Set Powered with status Busy.
+    let reply = Reply::decode(&packet(0x0002, 0, &[0x05, 0x00, 0x0a])).unwrap();
+    assert_eq!(reply, Reply { index: 0, opcode: 0x0005, status: Status::Busy, params: vec![] });
+
+    // Unknown status codes are reported as failure.
+    assert_eq!(Reply::decode(&packet(0x0001, 0, &[0x05, 0x00, 0xee])).unwrap().status, Status::Failed);
+
+    // Other events are not replies.
+    invalid(Reply::decode(&packet(0x0004, 0, &[])));
+}
+
+#[test]
+fn controller_info() {
+    let mut params = ADDR.to_vec();
+    params.push(0x0c);
+    params.extend_from_slice(&0x0002u16.to_le_bytes());
+    params.extend_from_slice(&0x00ff_ffffu32.to_le_bytes());
+    params.extend_from_slice(&(settings::POWERED | settings::LE).to_le_bytes());
+    params.extend_from_slice(&[0x0c, 0x01, 0x1c]);
+    let mut name = b"test".to_vec();
+    name.resize(249, 0);
+    params.extend_from_slice(&name);
+    let mut short_name = b"t".to_vec();
+    short_name.resize(11, 0);
+    params.extend_from_slice(&short_name);
+
+    let info = ControllerInfo::decode(&params).unwrap();
+    assert_eq!(info.address, address());
+    assert_eq!(info.bluetooth_version, 0x0c);
+    assert_eq!(info.manufacturer, 2);
+    assert_eq!(info.supported_settings, 0x00ff_ffff);
+    assert_eq!(info.current_settings, settings::POWERED | settings::LE);
+    assert_eq!(info.class, 0x1c010c);
+    assert_eq!(info.name, "test");
+    assert_eq!(info.short_name, "t");
+
+    for len in 0..params.len() {
+        invalid(ControllerInfo::decode(&params[..len]));
+    }
+    invalid(Version::decode(&[0x01, 0x16]));
+}
+
+#[test]
+fn events() {
+    assert_eq!(
+        Event::decode(&packet(0x0003, 1, &[0x42])).unwrap(),
+        Event::ControllerError { index: 1, code: 0x42 }
+    );
+    assert_eq!(Event::decode(&packet(0x0004, 1, &[])).unwrap(), Event::IndexAdded { index: 1 });
+    assert_eq!(Event::decode(&packet(0x0005, 1, &[])).unwrap(), Event::IndexRemoved { index: 1 });
+    assert_eq!(
+        Event::decode(&packet(0x0006, 0, &[0x01, 0x02, 0x00, 0x00])).unwrap(),
+        Event::NewSettings { index: 0, settings: settings::POWERED | settings::LE }
+    );
+    assert_eq!(
+        Event::decode(&packet(0x0007, 0, &[0x04, 0x04, 0x24])).unwrap(),
+        Event::ClassOfDeviceChanged { index: 0, class: 0x240404 }
+    );
+
+    let mut params = b"name".to_vec();
+    params.resize(249, 0);
+    params.extend_from_slice(b"short\0\0\0\0\0\0");
+    assert_eq!(
+        Event::decode(&packet(0x0008, 0, &params)).unwrap(),
+        Event::LocalNameChanged { index: 0, name: "name".to_string(), short_name: "short".to_string() }
+    );
+
+    let mut params = vec![0x01];
+    params.extend_from_slice(&ADDR);
+    params.extend_from_slice(&[0x00, 0x04]);
+    params.extend_from_slice(&[0xaa; 16]);
+    params.push(0x04);
+    assert_eq!(
+        Event::decode(&packet(0x0009, 0, &params)).unwrap(),
+        Event::NewLinkKey {
+            index: 0,
+            store_hint: true,
+            key: LinkKey {
+                address: address(),
+                address_type: AddressType::BrEdr,
+                key_type: 0x04,
+                value: [0xaa; 16],
+                pin_length: 4
+            },
+        }
+    );
+
+    let mut params = vec![0x00];
+    params.extend_from_slice(&ADDR);
+    params.extend_from_slice(&[0x02, 0x01, 0x01, 0x10, 0x34, 0x12]);
+    params.extend_from_slice(&0x0102_0304_0506_0708u64.to_le_bytes());
+    params.extend_from_slice(&[0xbb; 16]);
+    assert_eq!(
+        Event::decode(&packet(0x000a, 0, &params)).unwrap(),
+        Event::NewLongTermKey {
+            index: 0,
+            store_hint: false,
+            key: LongTermKey {
+                address: address(),
+                address_type: AddressType::LeRandom,
+                key_type: 0x01,
+                central: true,
+                encryption_size: 16,
+                ediv: 0x1234,
+                rand: 0x0102_0304_0506_0708,
+                value: [0xbb; 16],
+            },
+        }
+    );
+
+    let mut params = ADDR.to_vec();
+    params.push(0x01);
+    params.extend_from_slice(&0x08u32.to_le_bytes());
+    params.extend_from_slice(&3u16.to_le_bytes());
+    params.extend_from_slice(&[0x02, 0x01, 0x06]);
+    assert_eq!(
+        Event::decode(&packet(0x000b, 0, &params)).unwrap(),
+        Event::DeviceConnected {
+            index: 0,
+            address: address(),
+            address_type: AddressType::LePublic,
+            flags: 0x08,
+            eir: vec![0x02, 0x01, 0x06],
+        }
+    );
+
+    let mut params = ADDR.to_vec();
+    params.extend_from_slice(&[0x01, 0x03]);
+    assert_eq!(
+        Event::decode(&packet(0x000c, 0, &params)).unwrap(),
+        Event::DeviceDisconnected {
+            index: 0,
+            address: address(),
+            address_type: AddressType::LePublic,
+            reason: 3
+        }
+    );
+
+    assert_eq!(
+        Event::decode(&packet(0x0025, 0, &[1, 2])).unwrap(),
+        Event::Other { index: 0, code: 0x0025, params: vec![1, 2] }
+    );
+
+    // Command replies are decoded using Reply.
+    invalid(Event::decode(&packet(0x0001, 0, &[0x01, 0x00, 0x00])));
+
+    // Invalid address type.
+    let mut params = ADDR.to_vec();
+    params.extend_from_slice(&[0x07, 0x03]);
+    invalid(Event::decode(&packet(0x000c, 0, &params)));
+}
+
+#[test]
+fn truncated_packets() {
+    let buf = packet(0x0004, 0, &[0x01, 0x00]);
+    for len in 0..buf.len() {
+        invalid(Command::decode(&buf[..len]));
+        invalid(Event::decode(&buf[..len]));
+        invalid(Reply::decode(&buf[..len]));
+    }
+
+    // Command replies without opcode and status.
+    invalid(Reply::decode(&packet(0x0001, 0, &[0x01, 0x00])));
+}
+
+#[test]
+fn truncated_events() {
+    let mut device_connected = ADDR.to_vec();
+    device_connected.push(0x01);
+    device_connected.extend_from_slice(&0u32.to_le_bytes());
+    device_connected.extend_from_slice(&3u16.to_le_bytes());
+    device_connected.extend_from_slice(&[0x02, 0x01, 0x06]);
+
+    let mut device_disconnected = ADDR.to_vec();
+    device_disconnected.extend_from_slice(&[0x01, 0x03]);
+
+    for (code, params) in [
+        (0x0003, vec![0x42]),
+        (0x0006, vec![0; 4]),
+        (0x0007, vec![0; 3]),
+        (0x0008, vec![0; 260]),
+        (0x0009, vec![0; 26]),
+        (0x000a, vec![0; 37]),
+        (0x000b, device_connected),
+        (0x000c, device_disconnected),
+    ] {
+        Event::decode(&packet(code, 0, &params)).unwrap();
+        for len in 0..params.len() {
+            invalid(Event::decode(&packet(code, 0, &params[..len])));
+        }
+    }
+}
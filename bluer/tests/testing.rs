@@ -0,0 +1,449 @@
+//! Tests of the mock Bluetooth daemon.
+
+use bluer::{
+    ad::{AdvertisingData, Structure},
+    agent::Agent,
+    gatt::{value::BatteryLevel, CharacteristicFlags},
+    testing::{MockAdapter, MockAdvertisement, MockBluez, MockCharacteristic, MockDevice, MockService},
+    AdapterEvent, AdapterProperty, Address, DeviceEvent, DeviceInfo, DeviceProperty, DevicePropertyKind,
+    ErrorKind, Session, SessionEvent, SessionOptions,
+};
+use futures::{pin_mut, StreamExt};
+use std::time::Duration;
+use tokio::time::{sleep, timeout};
+use uuid::Uuid;
+
+const ADDRESS: Address = Address::new([0x12, 0x34, 0x56, 0x78, 0x9a, 0xbc]);
+const BATTERY_SERVICE: Uuid = Uuid::from_u128(0x0000180f_0000_1000_8000_00805f9b34fb);
+const BATTERY_LEVEL: Uuid = Uuid::from_u128(0x00002a19_0000_1000_8000_00805f9b34fb);
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+fn battery_device() -> MockDevice {
+    MockDevice {
+        address: ADDRESS,
+        name: Some("Sensor".to_string()),
+        services: vec![MockService {
+            uuid: BATTERY_SERVICE,
+            primary: true,
+            characteristics: vec![MockCharacteristic {
+                uuid: BATTERY_LEVEL,
+                flags: CharacteristicFlags { read: true, write: true, notify: true, ..Default::default() },
+                value: vec![80],
+                ..Default::default()
+            }],
+            ..Default::default()
+        }],
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn adapters() {
+    let mock = MockBluez::new().await.unwrap();
+    let session = mock.session().await.unwrap();
+    assert!(session.adapter_names().await.unwrap().is_empty());
+
+    let events = session.events().await.unwrap();
+    pin_mut!(events);
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    let evt = timeout(TIMEOUT, events.next()).await.unwrap();
+    assert_eq!(evt, Some(SessionEvent::AdapterAdded("hci0".to_string())));
+
+    let adapter = session.default_adapter().await.unwrap();
+    assert_eq!(adapter.name(), "hci0");
+    assert_eq!(adapter.address().await.unwrap(), MockAdapter::default().address);
+    assert!(adapter.is_powered().await.unwrap());
+    adapter.set_powered(false).await.unwrap();
+    assert!(!adapter.is_powered().await.unwrap());
+    assert_eq!(adapter.discover_devices().await.err().map(|err| err.kind), Some(ErrorKind::NotReady));
+
+    mock.remove_adapter("hci0").unwrap();
+    let evt = timeout(TIMEOUT, events.next()).await.unwrap();
+    assert_eq!(evt, Some(SessionEvent::AdapterRemoved("hci0".to_string())));
+}
+
+#[tokio::test]
+async fn discovery() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    let session = mock.session().await.unwrap();
+    let adapter = session.default_adapter().await.unwrap();
+
+    let discovery = adapter.discover_devices().await.unwrap();
+    pin_mut!(discovery);
+    assert!(mock.is_discovering("hci0").unwrap());
+
+    let data = AdvertisingData(vec![
+        Structure::LocalName { complete: true, name: "Beacon".to_string() },
+        Structure::ServiceUuids16 { complete: true, uuids: vec![0x180f] },
+        Structure::ManufacturerData { company: 0x004c, data: vec![0x12, 0x34] },
+    ]);
+    mock.receive_advertisement(
+        "hci0",
+        MockAdvertisement { address: ADDRESS, rssi: Some(-60), data, ..Default::default() },
+    )
+    .unwrap();
+
+    loop {
+        match timeout(TIMEOUT, discovery.next()).await.unwrap() {
+            Some(AdapterEvent::DeviceAdded(address)) => {
+                assert_eq!(address, ADDRESS);
+                break;
+            }
+            Some(_) => (),
+            None => panic!("discovery ended"),
+        }
+    }
+
+    let device = adapter.device(ADDRESS).unwrap();
+    assert_eq!(device.name().await.unwrap().as_deref(), Some("Beacon"));
+    assert_eq!(device.rssi().await.unwrap(), Some(-60));
+    assert_eq!(device.uuids().await.unwrap().unwrap().into_iter().collect::<Vec<_>>(), vec![BATTERY_SERVICE]);
+    assert_eq!(device.manufacturer_data().await.unwrap().unwrap()[&0x004c], vec![0x12, 0x34]);
+
+    adapter.remove_device(ADDRESS).await.unwrap();
+    assert!(adapter.device_addresses().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn pairing() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    mock.add_device("hci0", MockDevice { address: ADDRESS, ..Default::default() }).unwrap();
+    let other = Address::new([0x11, 0x22, 0x33, 0x44, 0x55, 0x66]);
+    mock.add_device(
+        "hci0",
+        MockDevice {
+            address: other,
+            pairing_error: Some(ErrorKind::AuthenticationFailed),
+            connect_error: Some(ErrorKind::ConnectionAttemptFailed),
+            ..Default::default()
+        },
+    )
+    .unwrap();
+
+    let session = mock.session().await.unwrap();
+    let adapter = session.default_adapter().await.unwrap();
+
+    let device = adapter.device(ADDRESS).unwrap();
+    assert!(!device.is_paired().await.unwrap());
+    device.pair().await.unwrap();
+    assert!(device.is_paired().await.unwrap());
+    assert_eq!(device.pair().await.unwrap_err().kind, ErrorKind::AlreadyExists);
+
+    let device = adapter.device(other).unwrap();
+    assert_eq!(device.pair().await.unwrap_err().kind, ErrorKind::AuthenticationFailed);
+    assert_eq!(device.connect().await.unwrap_err().kind, ErrorKind::ConnectionAttemptFailed);
+    assert!(!device.is_paired().await.unwrap());
+}
+
+#[tokio::test]
+async fn gatt() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    mock.add_device("hci0", battery_device()).unwrap();
+
+    let session = mock.session().await.unwrap();
+    let device = session.default_adapter().await.unwrap().device(ADDRESS).unwrap();
+    assert_eq!(device.services().await.unwrap_err().kind, ErrorKind::ServicesUnresolved);
+
+    device.connect().await.unwrap();
+    assert!(device.is_connected().await.unwrap());
+
+    let services = device.services().await.unwrap();
+    assert_eq!(services.len(), 1);
+    assert_eq!(services[0].uuid().await.unwrap(), BATTERY_SERVICE);
+    let characteristics = services[0].characteristics().await.unwrap();
+    assert_eq!(characteristics.len(), 1);
+    let characteristic = &characteristics[0];
+    assert_eq!(characteristic.uuid().await.unwrap(), BATTERY_LEVEL);
+
+    assert_eq!(characteristic.read().await.unwrap(), vec![80]);
+    assert_eq!(characteristic.read_typed::<BatteryLevel>().await.unwrap(), BatteryLevel(80));
+    characteristic.write(&[75]).await.unwrap();
+    assert_eq!(mock.characteristic_value("hci0", ADDRESS, BATTERY_LEVEL).unwrap(), vec![75]);
+
+    let notifications = characteristic.notify().await.unwrap();
+    pin_mut!(notifications);
+    mock.notify("hci0", ADDRESS, BATTERY_LEVEL, vec![70]).unwrap();
+    assert_eq!(timeout(TIMEOUT, notifications.next()).await.unwrap(), Some(vec![70]));
+
+    mock.disconnect_device("hci0", ADDRESS).unwrap();
+    assert!(!device.is_connected().await.unwrap());
+}
+
+#[tokio::test]
+async fn session_options() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+
+    let options = SessionOptions { timeout: Duration::from_secs(10), ..Default::default() };
+    let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+    assert_eq!(session.adapter_names().await.unwrap(), vec!["hci0".to_string()]);
+
+    let options = SessionOptions { service_name: "org.example.Missing".to_string(), ..Default::default() };
+    let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+    assert!(session.adapter_names().await.is_err());
+
+    let options = SessionOptions { service_name: "not a bus name".to_string(), ..Default::default() };
+    let err = Session::new_with_address(mock.bus_address(), options).await.unwrap_err();
+    assert_eq!(err.kind, ErrorKind::InvalidArguments);
+}
+
+#[tokio::test]
+async fn daemon_restart() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+
+    let options = SessionOptions { reregister: true, ..Default::default() };
+    let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+    let events = session.events().await.unwrap();
+    pin_mut!(events);
+    let agent = session.register_agent(Agent::default()).await.unwrap();
+    assert_eq!(mock.registered_agents(), 1);
+
+    mock.restart().await.unwrap();
+    assert_eq!(timeout(TIMEOUT, events.next()).await.unwrap(), Some(SessionEvent::DaemonStopped));
+    assert_eq!(timeout(TIMEOUT, events.next()).await.unwrap(), Some(SessionEvent::DaemonStarted));
+    assert_eq!(
+        timeout(TIMEOUT, events.next()).await.unwrap(),
+        Some(SessionEvent::AdapterAdded("hci0".to_string()))
+    );
+
+    timeout(TIMEOUT, async {
+        while mock.registered_agents() != 1 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    drop(agent);
+    timeout(TIMEOUT, async {
+        while mock.registered_agents() != 0 {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[tokio::test]
+async fn property_cache() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    let device = MockDevice {
+        address: ADDRESS,
+        name: Some("Sensor".to_string()),
+        class: Some(0x240404),
+        rssi: Some(-70),
+        service_uuids: [BATTERY_SERVICE].into(),
+        manufacturer_data: [(0x004c, vec![0x12, 0x34])].into(),
+        service_data: [(BATTERY_SERVICE, vec![80])].into(),
+        ..Default::default()
+    };
+    mock.add_device("hci0", device).unwrap();
+
+    let options = SessionOptions { cache_properties: true, ..Default::default() };
+    let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+    let uncached = mock.session().await.unwrap();
+    let adapter = session.default_adapter().await.unwrap();
+    assert_eq!(adapter.device_addresses().await.unwrap(), vec![ADDRESS]);
+
+    let device = adapter.device(ADDRESS).unwrap();
+    let uncached_device = uncached.default_adapter().await.unwrap().device(ADDRESS).unwrap();
+    assert_eq!(
+        format!("{:?}", device.all_properties().await.unwrap()),
+        format!("{:?}", uncached_device.all_properties().await.unwrap())
+    );
+    assert_eq!(
+        format!("{:?}", device.info().await.unwrap()),
+        format!("{:?}", uncached_device.info().await.unwrap())
+    );
+    assert_eq!(device.icon().await.unwrap(), None);
+
+    let events = device.events().await.unwrap();
+    pin_mut!(events);
+    mock.receive_advertisement(
+        "hci0",
+        MockAdvertisement { address: ADDRESS, rssi: Some(-50), ..Default::default() },
+    )
+    .unwrap();
+    loop {
+        match timeout(TIMEOUT, events.next()).await.unwrap() {
+            Some(DeviceEvent::PropertyChanged(DeviceProperty::Rssi(rssi))) => {
+                assert_eq!(rssi, -50);
+                break;
+            }
+            Some(_) => (),
+            None => panic!("device events ended"),
+        }
+    }
+    assert_eq!(device.rssi().await.unwrap(), Some(-50));
+
+    adapter.set_powered(false).await.unwrap();
+    assert!(!adapter.is_powered().await.unwrap());
+
+    mock.remove_device("hci0", ADDRESS).unwrap();
+    while timeout(TIMEOUT, events.next()).await.unwrap().is_some() {}
+    assert!(adapter.device_addresses().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn property_cache_restart() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+
+    let options = SessionOptions { cache_properties: true, ..Default::default() };
+    let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+    let events = session.events().await.unwrap();
+    pin_mut!(events);
+
+    mock.restart().await.unwrap();
+    assert_eq!(timeout(TIMEOUT, events.next()).await.unwrap(), Some(SessionEvent::DaemonStopped));
+    assert_eq!(timeout(TIMEOUT, events.next()).await.unwrap(), Some(SessionEvent::DaemonStarted));
+    mock.add_device("hci0", battery_device()).unwrap();
+    assert_eq!(
+        timeout(TIMEOUT, events.next()).await.unwrap(),
+        Some(SessionEvent::AdapterAdded("hci0".to_string()))
+    );
+
+    let adapter = session.default_adapter().await.unwrap();
+    timeout(TIMEOUT, async {
+        while adapter.device_addresses().await.unwrap() != vec![ADDRESS] {
+            sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let device = adapter.device(ADDRESS).unwrap();
+    assert_eq!(device.name().await.unwrap(), battery_device().name);
+}
+
+#[tokio::test]
+async fn info() {
+    let mock = MockBluez::new().await.unwrap();
+    mock.add_adapter(MockAdapter::default()).unwrap();
+    mock.add_device("hci0", battery_device()).unwrap();
+
+    let session = mock.session().await.unwrap();
+    let adapter = session.default_adapter().await.unwrap();
+    let adapter_info = adapter.info().await.unwrap();
+    assert_eq!(adapter_info.address, Some(MockAdapter::default().address));
+    assert_eq!(adapter_info.is_powered, Some(true));
+
+    let mut adapter_info = adapter_info;
+    adapter_info.apply(AdapterProperty::Powered(false));
+    assert_eq!(adapter_info.is_powered, Some(false));
+
+    let device = adapter.device(ADDRESS).unwrap();
+    let mut info = device.info().await.unwrap();
+    assert_eq!(info.remote_address, Some(ADDRESS));
+    assert_eq!(info.name.as_deref(), Some("Sensor"));
+    assert_eq!(info.is_paired, Some(false));
+    assert_eq!(info.battery_percentage, None);
+    assert_eq!(
+        format!("{:?}", info),
+        format!("{:?}", device.all_properties().await.unwrap().into_iter().collect::<DeviceInfo>())
+    );
+
+    let events = device.events().await.unwrap();
+    pin_mut!(events);
+    let update = |info: &mut DeviceInfo, event: Option<DeviceEvent>| match event {
+        Some(DeviceEvent::PropertyChanged(property)) => info.apply(property),
+        Some(DeviceEvent::PropertyInvalidated(kind)) => info.invalidate(kind),
+        Some(_) => (),
+        None => panic!("device events ended"),
+    };
+
+    device.pair().await.unwrap();
+    while info.is_paired != Some(true) {
+        update(&mut info, timeout(TIMEOUT, events.next()).await.unwrap());
+    }
+    assert_eq!(format!("{:?}", info), format!("{:?}", device.info().await.unwrap()));
+
+    let discovery = adapter.discover_devices().await.unwrap();
+    mock.receive_advertisement(
+        "hci0",
+        MockAdvertisement { address: ADDRESS, rssi: Some(-60), ..Default::default() },
+    )
+    .unwrap();
+    while info.rssi != Some(-60) {
+        update(&mut info, timeout(TIMEOUT, events.next()).await.unwrap());
+    }
+    drop(discovery);
+    while info.rssi.is_some() {
+        update(&mut info, timeout(TIMEOUT, events.next()).await.unwrap());
+    }
+    assert_eq!(device.rssi().await.unwrap(), None);
+    assert_eq!(format!("{:?}", info), format!("{:?}", device.info().await.unwrap()));
+}
+
+#[tokio::test]
+async fn interface_removed() {
+    for cache_properties in [false, true] {
+        let mock = MockBluez::new().await.unwrap();
+        mock.add_adapter(MockAdapter::default()).unwrap();
+        mock.add_device(
+            "hci0",
+            MockDevice { address: ADDRESS, battery_percentage: Some(80), ..Default::default() },
+        )
+        .unwrap();
+
+        let options = SessionOptions { cache_properties, ..Default::default() };
+        let session = Session::new_with_address(mock.bus_address(), options).await.unwrap();
+        let adapter = session.default_adapter().await.unwrap();
+        let device = adapter.device(ADDRESS).unwrap();
+        assert_eq!(device.battery_percentage().await.unwrap(), Some(80));
+
+        let adapter_events = adapter.events().await.unwrap();
+        pin_mut!(adapter_events);
+        let events = device.events().await.unwrap();
+        pin_mut!(events);
+
+        // Removing the battery interface invalidates its properties, but keeps the device.
+        mock.set_battery_percentage("hci0", ADDRESS, None).unwrap();
+        loop {
+            match timeout(TIMEOUT, events.next()).await.unwrap() {
+                Some(DeviceEvent::PropertyInvalidated(DevicePropertyKind::BatteryPercentage)) => break,
+                Some(_) => (),
+                None => panic!("device events ended"),
+            }
+        }
+        assert_eq!(device.battery_percentage().await.unwrap(), None);
+
+        mock.receive_advertisement(
+            "hci0",
+            MockAdvertisement { address: ADDRESS, rssi: Some(-50), ..Default::default() },
+        )
+        .unwrap();
+        loop {
+            match timeout(TIMEOUT, events.next()).await.unwrap() {
+                Some(DeviceEvent::PropertyChanged(DeviceProperty::Rssi(rssi))) => {
+                    assert_eq!(rssi, -50);
+                    break;
+                }
+                Some(_) => (),
+                None => panic!("device events ended"),
+            }
+        }
+
+        mock.set_battery_percentage("hci0", ADDRESS, Some(70)).unwrap();
+        timeout(TIMEOUT, async {
+            while device.battery_percentage().await.unwrap() != Some(70) {
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+
+        // Removing the device ends the stream.
+        mock.remove_device("hci0", ADDRESS).unwrap();
+        while timeout(TIMEOUT, events.next()).await.unwrap().is_some() {}
+
+        match timeout(TIMEOUT, adapter_events.next()).await.unwrap() {
+            Some(AdapterEvent::DeviceRemoved(address)) => assert_eq!(address, ADDRESS),
+            other => panic!("unexpected adapter event {other:?}"),
+        }
+    }
+}
@@ -0,0 +1,147 @@
+//! Tests of vCard parsing.
+
+use bluer::obex::vcard::{CallKind, Name, TypedValue, VCard};
+
+#[test]
+fn vcard_21() {
+    let card = VCard::parse(
+        "BEGIN:VCARD\r\n\
+         VERSION:2.1\r\n\
+         N:Doe;John;;Dr.;\r\n\
+         FN:Dr. John Doe\r\n\
+         TEL;CELL;PREF:+1 555 0100\r\n\
+         TEL;TYPE=HOME,VOICE:+1 555 0101\r\n\
+         item1.EMAIL;INTERNET:john@example.com\r\n\
+         ADR;WORK:;;1 Main St;Springfield;;12345;USA\r\n\
+         ORG:Example Inc.;Research\r\n\
+         X-IRMC-CALL-DATETIME;MISSED:20240101T120000Z\r\n\
+         END:VCARD\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(card.version.as_deref(), Some("2.1"));
+    assert_eq!(card.formatted_name.as_deref(), Some("Dr. John Doe"));
+    assert_eq!(
+        card.name,
+        Some(Name {
+            family: "Doe".to_string(),
+            given: "John".to_string(),
+            prefix: "Dr.".to_string(),
+            ..Default::default()
+        })
+    );
+    assert_eq!(
+        card.telephones,
+        vec![
+            TypedValue { value: "+1 555 0100".to_string(), types: vec!["CELL".to_string(), "PREF".to_string()] },
+            TypedValue { value: "+1 555 0101".to_string(), types: vec!["HOME".to_string(), "VOICE".to_string()] },
+        ]
+    );
+    assert_eq!(card.emails[0].value, "john@example.com");
+    assert_eq!(card.get("EMAIL").next().unwrap().group.as_deref(), Some("item1"));
+    assert_eq!(card.addresses[0].street, "1 Main St");
+    assert_eq!(card.addresses[0].country, "USA");
+    assert_eq!(card.addresses[0].types, vec!["WORK".to_string()]);
+    assert_eq!(card.organization.as_deref(), Some("Example Inc."));
+    let call = card.call.unwrap();
+    assert_eq!(call.kind, Some(CallKind::Missed));
+    assert_eq!(call.datetime, "20240101T120000Z");
+}
+
+#[test]
+fn folded_lines() {
+    let card = VCard::parse(
+        "BEGIN:VCARD\n\
+         VERSION:3.0\n\
+         FN:Jane\n \x20Doe\n\
+         NOTE:first line\\nsecond\n\
+         \tline\\, continued\n\
+         END:VCARD\n",
+    )
+    .unwrap();
+
+    assert_eq!(card.formatted_name.as_deref(), Some("Jane Doe"));
+    assert_eq!(card.note.as_deref(), Some("first line\nsecondline, continued"));
+}
+
+#[test]
+fn quoted_printable() {
+    let card = VCard::parse(
+        "BEGIN:VCARD\r\n\
+         VERSION:2.1\r\n\
+         FN;CHARSET=UTF-8;ENCODING=QUOTED-PRINTABLE:J=C3=BCrgen =\r\n\
+         M=C3=BCller\r\n\
+         NOTE;QUOTED-PRINTABLE;CHARSET=ISO-8859-1:Caf=E9=\r\n\
+         =20au lait\r\n\
+         END:VCARD\r\n",
+    )
+    .unwrap();
+
+    assert_eq!(card.formatted_name.as_deref(), Some("Jürgen Müller"));
+    assert_eq!(card.note.as_deref(), Some("Café au lait"));
+    let note = card.get("NOTE").next().unwrap();
+    assert_eq!(note.param("ENCODING").collect::<Vec<_>>(), vec!["QUOTED-PRINTABLE"]);
+    assert_eq!(note.param("CHARSET").collect::<Vec<_>>(), vec!["ISO-8859-1"]);
+}
+
+#[test]
+fn malformed_quoted_printable() {
+    let card = VCard::parse(
+        "BEGIN:VCARD\n\
+         FN;ENCODING=QUOTED-PRINTABLE:100=ZZ=4=3D\n\
+         NOTE;ENCODING=QUOTED-PRINTABLE:trailing=4\n\
+         END:VCARD\n",
+    )
+    .unwrap();
+
+    assert_eq!(card.formatted_name.as_deref(), Some("100=ZZ=4="));
+    assert_eq!(card.note.as_deref(), Some("trailing=4"));
+}
+
+#[test]
+fn quoted_parameters() {
+    let card = VCard::parse(
+        "BEGIN:VCARD\n\
+         VERSION:3.0\n\
+         TEL;TYPE=\"work,voice\";X-LABEL=\"a:b\":+1 555 0102\n\
+         END:VCARD\n",
+    )
+    .unwrap();
+
+    let tel = card.get("TEL").next().unwrap();
+    assert_eq!(tel.value, "+1 555 0102");
+    assert_eq!(tel.param("X-LABEL").collect::<Vec<_>>(), vec!["\"a:b\""]);
+    assert_eq!(card.telephones[0].types, vec!["WORK".to_string(), "VOICE".to_string()]);
+}
+
+#[test]
+fn multiple_cards() {
+    let cards = VCard::parse_all(
+        "BEGIN:VCARD\n\
+         VERSION:2.1\n\
+         FN:First\n\
+         END:VCARD\n\
+         \n\
+         BEGIN:VCARD\n\
+         VERSION:2.1\n\
+         FN:Second\n\
+         AGENT:\n\
+         BEGIN:VCARD\n\
+         FN:Nested\n\
+         END:VCARD\n\
+         END:VCARD\n",
+    )
+    .unwrap();
+
+    let names: Vec<_> = cards.iter().map(|c| c.formatted_name.as_deref().unwrap()).collect();
+    assert_eq!(names, vec!["First", "Second"]);
+    assert!(VCard::parse("BEGIN:VCARD\nFN:A\nEND:VCARD\nBEGIN:VCARD\nFN:B\nEND:VCARD\n").is_err());
+}
+
+#[test]
+fn invalid() {
+    assert!(VCard::parse("BEGIN:VCARD\nFN:Unterminated\n").is_err());
+    assert!(VCard::parse_all("FN:Orphan\nEND:VCARD\n").is_err());
+    assert!(VCard::parse("").is_err());
+    assert!(VCard::parse_all("").unwrap().is_empty());
+}